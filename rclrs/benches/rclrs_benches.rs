@@ -0,0 +1,86 @@
+//! The benchmark suite of `rclrs`, see the `bench_utils` module for how to run it.
+
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rclrs::bench_utils::{conversion_round_trip, dispatch_overhead, TopicBench, PAYLOAD_SIZES};
+use rclrs::testing::TestFixture;
+use std_msgs::msg::String as StringMsg;
+
+// The number of messages that are in flight at once in the throughput benchmark.
+const BATCH_SIZE: usize = 10;
+const MATCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn payload(size: usize) -> StringMsg {
+    StringMsg {
+        data: "x".repeat(size),
+    }
+}
+
+fn publish_take_throughput(c: &mut Criterion) {
+    let fixture = TestFixture::new("bench_throughput").unwrap();
+    let mut group = c.benchmark_group("publish_take_throughput");
+    for &size in PAYLOAD_SIZES {
+        let mut bench = TopicBench::new(
+            &fixture,
+            &format!("throughput_{}", size),
+            BATCH_SIZE,
+            MATCH_TIMEOUT,
+        )
+        .unwrap();
+        let message = payload(size);
+        group.throughput(Throughput::Bytes((size * BATCH_SIZE) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &message, |b, message| {
+            b.iter_custom(|iters| {
+                (0..iters)
+                    .map(|_| bench.throughput(message, BATCH_SIZE).unwrap())
+                    .sum()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn round_trip_latency(c: &mut Criterion) {
+    let fixture = TestFixture::new("bench_round_trip").unwrap();
+    let mut group = c.benchmark_group("round_trip_latency");
+    for &size in PAYLOAD_SIZES {
+        let mut bench =
+            TopicBench::new(&fixture, &format!("round_trip_{}", size), 1, MATCH_TIMEOUT).unwrap();
+        let message = payload(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &message, |b, message| {
+            b.iter_custom(|iters| bench.round_trip(message, iters as usize).unwrap().total())
+        });
+    }
+    group.finish();
+}
+
+fn executor_dispatch(c: &mut Criterion) {
+    let mut fixture = TestFixture::new("bench_dispatch").unwrap();
+    c.bench_function("executor_dispatch", |b| {
+        b.iter_custom(|iters| {
+            dispatch_overhead(&mut fixture.publisher_node, iters as usize).unwrap()
+        })
+    });
+}
+
+fn message_conversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("message_conversion");
+    for &size in PAYLOAD_SIZES {
+        let message = payload(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &message, |b, message| {
+            b.iter_custom(|iters| conversion_round_trip(message, iters as usize))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    publish_take_throughput,
+    round_trip_latency,
+    executor_dispatch,
+    message_conversion
+);
+criterion_main!(benches);
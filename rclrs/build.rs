@@ -1,10 +1,11 @@
 extern crate bindgen;
 
 use std::env;
-use std::fs::read_dir;
-use std::path::{Path, PathBuf};
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
 
 const AMENT_PREFIX_PATH: &str = "AMENT_PREFIX_PATH";
+const ROS_DISTRO: &str = "ROS_DISTRO";
 
 fn main() {
     let mut builder = bindgen::Builder::default()
@@ -41,47 +42,71 @@ fn main() {
     //
     // See REP 122 for more details: https://www.ros.org/reps/rep-0122.html#filesystem-layout
 
-    if let Ok(ament_prefix_paths) = env::var(AMENT_PREFIX_PATH) {
-        for ament_prefix_path in ament_prefix_paths.split(':').map(Path::new) {
-            // Locate the ament index
-            let ament_index = ament_prefix_path.join("share/ament_index/resource_index/packages");
-            if !ament_index.is_dir() {
-                continue;
-            }
+    let ament_prefix_paths: Vec<PathBuf> = env::var_os(AMENT_PREFIX_PATH)
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_else(|| {
+            panic!(
+                "{} environment variable not set - please source ROS 2 installation first.",
+                AMENT_PREFIX_PATH
+            )
+        });
+    for ament_prefix_path in &ament_prefix_paths {
+        // Locate the ament index
+        let ament_index = ament_prefix_path.join("share/ament_index/resource_index/packages");
+        if !ament_index.is_dir() {
+            continue;
+        }
 
-            // Old-style include directory
-            let include_dir = ament_prefix_path.join("include");
+        // Old-style include directory
+        let include_dir = ament_prefix_path.join("include");
 
-            // Including the old-style packages
-            builder = builder.clang_arg(format!("-isystem{}", include_dir.display()));
+        // Including the old-style packages
+        builder = builder.clang_arg(format!("-isystem{}", include_dir.display()));
 
-            // Search for and include new-style-converted package paths
-            for dir_entry in read_dir(&ament_index).unwrap().filter_map(|p| p.ok()) {
-                let package = dir_entry.file_name();
-                let package_include_dir = include_dir.join(&package);
+        // Search for and include new-style-converted package paths
+        for dir_entry in read_dir(&ament_index).unwrap().filter_map(|p| p.ok()) {
+            let package = dir_entry.file_name();
+            let package_include_dir = include_dir.join(&package);
 
-                if package_include_dir.is_dir() {
-                    let new_style_include_dir = package_include_dir.join(&package);
+            if package_include_dir.is_dir() {
+                let new_style_include_dir = package_include_dir.join(&package);
 
-                    // CycloneDDS is a special case - it needs to be included as if it were a new-style path, but
-                    // doesn't actually have a secondary folder within it called "CycloneDDS"
-                    // TODO(jhdcs): if this changes in future, remove this check
-                    if package == "CycloneDDS" || new_style_include_dir.is_dir() {
-                        builder =
-                            builder.clang_arg(format!("-isystem{}", package_include_dir.display()));
-                    }
+                // CycloneDDS is a special case - it needs to be included as if it were a new-style path, but
+                // doesn't actually have a secondary folder within it called "CycloneDDS"
+                // TODO(jhdcs): if this changes in future, remove this check
+                if package == "CycloneDDS" || new_style_include_dir.is_dir() {
+                    builder =
+                        builder.clang_arg(format!("-isystem{}", package_include_dir.display()));
                 }
             }
+        }
 
-            // Link the native libraries
-            let library_path = ament_prefix_path.join("lib");
-            println!("cargo:rustc-link-search=native={}", library_path.display());
+        // Link the native libraries
+        let library_path = ament_prefix_path.join("lib");
+        println!("cargo:rustc-link-search=native={}", library_path.display());
+    }
+
+    // Some rcl/rmw functions are not available in all distros. The `ros_distro` cfg allows
+    // gating them, e.g. with `#[cfg(not(ros_distro = "foxy"))]`. See the compat module.
+    println!("cargo:rustc-check-cfg=cfg(ros_distro, values(any()))");
+    println!("cargo:rerun-if-env-changed={}", ROS_DISTRO);
+    println!("cargo:rerun-if-env-changed={}", AMENT_PREFIX_PATH);
+    let detected_distro = detect_ros_distro(&ament_prefix_paths);
+    match (env::var(ROS_DISTRO).ok(), detected_distro) {
+        (Some(ros_distro), detected) => {
+            // Rolling has no fixed rcl version, so only released distros can be told apart.
+            if let Some(detected) =
+                detected.filter(|detected| *detected != "rolling" && *detected != ros_distro)
+            {
+                println!(
+                    "cargo:warning={} is set to '{}', but the sourced rcl is from '{}'",
+                    ROS_DISTRO, ros_distro, detected
+                );
+            }
+            println!("cargo:rustc-cfg=ros_distro=\"{}\"", ros_distro);
         }
-    } else {
-        panic!(
-            "{} environment variable not set - please source ROS 2 installation first.",
-            AMENT_PREFIX_PATH
-        );
+        (None, Some(detected)) => println!("cargo:rustc-cfg=ros_distro=\"{}\"", detected),
+        (None, None) => {}
     }
 
     println!("cargo:rustc-link-lib=dylib=rcl");
@@ -96,3 +121,27 @@ fn main() {
         .write_to_file(out_path.join("rcl_bindings_generated.rs"))
         .expect("Couldn't write bindings!");
 }
+
+// Determines the distro from the version of the first rcl package found in the ament prefixes.
+// rcl bumps its major version in every distro, except that Rolling runs ahead of the next
+// release, so unknown newer versions are treated as Rolling.
+fn detect_ros_distro(ament_prefix_paths: &[PathBuf]) -> Option<&'static str> {
+    let package_xml = ament_prefix_paths
+        .iter()
+        .map(|prefix| prefix.join("share/rcl/package.xml"))
+        .find_map(|path| read_to_string(path).ok())?;
+    let version = package_xml
+        .split("<version>")
+        .nth(1)?
+        .split("</version>")
+        .next()?;
+    let major: u32 = version.trim().split('.').next()?.parse().ok()?;
+    Some(match major {
+        0..=1 => "foxy",
+        2..=3 => "galactic",
+        4..=5 => "humble",
+        6..=8 => "iron",
+        9 => "jazzy",
+        _ => "rolling",
+    })
+}
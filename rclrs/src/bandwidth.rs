@@ -0,0 +1,218 @@
+use crate::error::{RclReturnCode, SubscriberErrorCode};
+use crate::qos::QoSProfile;
+use crate::{Node, SerializedMessage, Subscription, SubscriptionBase, SubscriptionHandle};
+
+use std::borrow::Borrow;
+use std::collections::VecDeque;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use rosidl_runtime_rs::Message;
+
+/// The rate and bandwidth of a topic, as measured by a [`BandwidthMonitor`].
+///
+/// The values are computed over the window of the most recent messages, like `ros2 topic hz` and
+/// `ros2 topic bw`. Sizes are those of the serialized messages, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BandwidthStats {
+    /// The mean number of messages per second.
+    pub messages_per_second: f64,
+    /// The mean number of serialized bytes per second.
+    pub bytes_per_second: f64,
+    /// The mean size of a message.
+    pub mean_size: f64,
+    /// The size of the smallest message.
+    pub min_size: usize,
+    /// The size of the largest message.
+    pub max_size: usize,
+    /// The number of messages the values were computed from.
+    pub sample_count: usize,
+}
+
+/// Measures the message rate and bandwidth of a topic.
+///
+/// The monitor takes the messages in their serialized form, so it neither deserializes them nor
+/// needs to know their content, and the measured sizes are the ones sent by the middleware.
+/// Like a [`Relay`][1], the monitor is executed by [`spin_once`][2] and [`spin`][3] like a
+/// subscription, and runs as long as it is alive.
+///
+/// # Example
+/// ```ignore
+/// let monitor = BandwidthMonitor::<sensor_msgs::msg::PointCloud2>::new(
+///     &mut node,
+///     "points",
+///     QOS_PROFILE_SENSOR_DATA,
+///     100,
+/// )?;
+/// rclrs::spin_once(&node, Some(Duration::from_secs(1)))?;
+/// if let Some(stats) = monitor.stats() {
+///     println!("{:.1} Hz, {:.0} B/s", stats.messages_per_second, stats.bytes_per_second);
+/// }
+/// ```
+///
+/// [1]: crate::Relay
+/// [2]: crate::spin_once
+/// [3]: crate::spin
+pub struct BandwidthMonitor<T>
+where
+    T: Message,
+{
+    subscription: Subscription<T>,
+    buffer: Mutex<SerializedMessage>,
+    window: Mutex<SampleWindow>,
+}
+
+impl<T> BandwidthMonitor<T>
+where
+    T: Message,
+{
+    /// Creates a monitor for `topic`, which computes its statistics over the last `window`
+    /// messages.
+    ///
+    /// `ros2 topic hz` and `ros2 topic bw` use a window of 10000 and 100 messages, respectively.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes, or when `window` is smaller than 2.
+    pub fn new(
+        node: &mut Node,
+        topic: &str,
+        qos: QoSProfile,
+        window: usize,
+    ) -> Result<Arc<Self>, RclReturnCode> {
+        assert!(window >= 2, "the window must contain at least two messages");
+        // Messages of bounded types fit into the buffer without reallocating.
+        let capacity = T::serialized_size_bound().unwrap_or(0);
+        // The subscription is only used for taking serialized messages, so its callback is never
+        // invoked.
+        let monitor = Arc::new(Self {
+            subscription: Subscription::new(node, topic, qos, |_msg: T| {})?,
+            buffer: Mutex::new(SerializedMessage::with_capacity(capacity)?),
+            window: Mutex::new(SampleWindow::new(window)),
+        });
+        node.subscriptions
+            .push(Arc::downgrade(&monitor) as Weak<dyn SubscriptionBase>);
+        Ok(monitor)
+    }
+
+    /// Returns the statistics over the current window, or `None` if fewer than two messages have
+    /// been received since the monitor was created or reset.
+    pub fn stats(&self) -> Option<BandwidthStats> {
+        self.window.lock().stats()
+    }
+
+    /// Forgets the received messages, e.g. after the publisher changed its rate.
+    pub fn reset(&self) {
+        self.window.lock().clear();
+    }
+
+    /// Returns the subscription to the monitored topic.
+    pub fn subscription(&self) -> &Subscription<T> {
+        &self.subscription
+    }
+}
+
+impl<T> SubscriptionBase for BandwidthMonitor<T>
+where
+    T: Message,
+{
+    fn handle(&self) -> &SubscriptionHandle {
+        self.subscription.handle.borrow()
+    }
+
+    fn execute(&self) -> Result<(), RclReturnCode> {
+        let buffer = &mut *self.buffer.lock();
+        match self.subscription.take_serialized(buffer) {
+            Ok(()) => {
+                self.window.lock().add(Instant::now(), buffer.len());
+                Ok(())
+            }
+            // Spurious wakeup, as in `Subscription::execute()`.
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// The arrival times and sizes of the most recent messages.
+struct SampleWindow {
+    capacity: usize,
+    samples: VecDeque<(Instant, usize)>,
+}
+
+impl SampleWindow {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    fn add(&mut self, time: Instant, size: usize) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((time, size));
+    }
+
+    fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    fn stats(&self) -> Option<BandwidthStats> {
+        let (first, _) = *self.samples.front()?;
+        let (last, _) = *self.samples.back()?;
+        let elapsed = last.saturating_duration_since(first);
+        if self.samples.len() < 2 || elapsed == Duration::ZERO {
+            return None;
+        }
+        let sizes = self.samples.iter().map(|&(_, size)| size);
+        let total: usize = sizes.clone().sum();
+        // The first message marks the start of the measured time span, so it is not counted in
+        // the rates.
+        let (_, first_size) = self.samples[0];
+        let seconds = elapsed.as_secs_f64();
+        Some(BandwidthStats {
+            messages_per_second: (self.samples.len() - 1) as f64 / seconds,
+            bytes_per_second: (total - first_size) as f64 / seconds,
+            mean_size: total as f64 / self.samples.len() as f64,
+            min_size: sizes.clone().min()?,
+            max_size: sizes.max()?,
+            sample_count: self.samples.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_window() {
+        let start = Instant::now();
+        let mut window = SampleWindow::new(3);
+        window.add(start, 100);
+        assert_eq!(window.stats(), None);
+
+        window.add(start + Duration::from_millis(100), 200);
+        window.add(start + Duration::from_millis(200), 300);
+        let stats = window.stats().unwrap();
+        assert!((stats.messages_per_second - 10.0).abs() < 1e-9);
+        assert!((stats.bytes_per_second - 2500.0).abs() < 1e-9);
+        assert_eq!(stats.mean_size, 200.0);
+        assert_eq!((stats.min_size, stats.max_size), (100, 300));
+        assert_eq!(stats.sample_count, 3);
+
+        // The oldest message is dropped from a full window.
+        window.add(start + Duration::from_millis(400), 400);
+        let stats = window.stats().unwrap();
+        assert!((stats.messages_per_second - 2.0 / 0.3).abs() < 1e-9);
+        assert_eq!(stats.min_size, 200);
+
+        window.clear();
+        assert_eq!(window.stats(), None);
+    }
+}
@@ -0,0 +1,305 @@
+//! Building blocks for benchmarking `rclrs` and the middleware it runs on.
+//!
+//! This module is only available with the `bench-utils` feature. The benchmarks in the
+//! `benches/` directory of this crate are built from these functions, so that their numbers can
+//! be reproduced, or extended with custom message types, on other hardware. Each function returns
+//! the raw time it measured, which fits Criterion's `iter_custom()`, but can also be used without
+//! Criterion.
+//!
+//! To run the suite, use `cargo bench --features bench-utils`. As a regression gate, save a
+//! baseline on a known good revision with
+//! `cargo bench --features bench-utils -- --save-baseline main`, and compare against it with
+//! `cargo bench --features bench-utils -- --baseline main`. For gates without Criterion, see
+//! [`LatencyStats::regressed_from`].
+//!
+//! # Example
+//! ```
+//! use rclrs::bench_utils::LatencyStats;
+//! use std::time::Duration;
+//!
+//! let baseline = LatencyStats::from_samples(vec![Duration::from_micros(100); 10]);
+//! let current = LatencyStats::from_samples(vec![Duration::from_micros(130); 10]);
+//! assert!(current.regressed_from(&baseline, 0.2));
+//! ```
+
+use crate::error::SubscriberErrorCode;
+use crate::qos::{QoSHistoryPolicy, QoSProfile, QoSReliabilityPolicy, QOS_PROFILE_DEFAULT};
+use crate::testing::TestFixture;
+use crate::{
+    spin_once, GuardCondition, Node, Publisher, RclReturnCode, Subscription, WaitSet, Waitable,
+};
+
+use std::borrow::Cow;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rosidl_runtime_rs::Message;
+
+/// The payload sizes in bytes that the benchmark suite uses.
+///
+/// They range from small control messages to large point clouds and images.
+pub const PAYLOAD_SIZES: &[usize] = &[64, 1024, 64 * 1024, 1024 * 1024];
+
+/// Statistics of a set of latency samples.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencyStats {
+    // Sorted in ascending order
+    samples: Vec<Duration>,
+}
+
+impl LatencyStats {
+    /// Creates statistics from samples in any order.
+    ///
+    /// # Panics
+    /// When there are no samples.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        assert!(
+            !samples.is_empty(),
+            "LatencyStats needs at least one sample"
+        );
+        samples.sort_unstable();
+        Self { samples }
+    }
+
+    /// Returns the samples in ascending order.
+    pub fn samples(&self) -> &[Duration] {
+        &self.samples
+    }
+
+    /// Returns the sum of all samples.
+    pub fn total(&self) -> Duration {
+        self.samples.iter().sum()
+    }
+
+    /// Returns the smallest sample.
+    pub fn min(&self) -> Duration {
+        self.samples[0]
+    }
+
+    /// Returns the largest sample.
+    pub fn max(&self) -> Duration {
+        self.samples[self.samples.len() - 1]
+    }
+
+    /// Returns the arithmetic mean of the samples.
+    pub fn mean(&self) -> Duration {
+        self.total() / self.samples.len() as u32
+    }
+
+    /// Returns the median of the samples.
+    pub fn median(&self) -> Duration {
+        self.percentile(50.0)
+    }
+
+    /// Returns the sample below which the given percentage of samples lies, e.g. `99.0` for the
+    /// 99th percentile.
+    ///
+    /// This uses the nearest-rank method, so the result is always one of the samples.
+    pub fn percentile(&self, percent: f64) -> Duration {
+        let rank = (percent.clamp(0.0, 100.0) / 100.0 * self.samples.len() as f64).ceil() as usize;
+        self.samples[rank.clamp(1, self.samples.len()) - 1]
+    }
+
+    /// Returns true when the median is more than `tolerance` (e.g. `0.1` for 10%) above the
+    /// median of the baseline.
+    pub fn regressed_from(&self, baseline: &LatencyStats, tolerance: f64) -> bool {
+        self.median().as_secs_f64() > baseline.median().as_secs_f64() * (1.0 + tolerance)
+    }
+}
+
+/// A pair of topics between the two nodes of a [`TestFixture`], for measuring the transport of
+/// messages of type `T`.
+///
+/// Messages are published on `<topic>` by the publisher node and echoed back on
+/// `<topic>_echo` by the subscriber node. Both directions are reliable and keep as many messages
+/// as the `depth` passed to [`new`][1], so no messages are lost as long as no more than `depth`
+/// messages are in flight.
+///
+/// [1]: TopicBench::new
+pub struct TopicBench<T: Message> {
+    publisher: Publisher<T>,
+    subscription: Arc<Subscription<T>>,
+    echo_publisher: Publisher<T>,
+    echo_subscription: Arc<Subscription<T>>,
+    wait_set: WaitSet,
+}
+
+impl<T: Message> TopicBench<T> {
+    /// Creates the publishers and subscriptions, and waits until they are matched.
+    ///
+    /// Returns [`RclReturnCode::Timeout`] when they are not matched within the `timeout`.
+    pub fn new(
+        fixture: &TestFixture,
+        topic: &str,
+        depth: usize,
+        timeout: Duration,
+    ) -> Result<Self, RclReturnCode> {
+        let qos = QoSProfile {
+            history: QoSHistoryPolicy::KeepLast {
+                depth: depth.max(1) as u32,
+            },
+            reliability: QoSReliabilityPolicy::Reliable,
+            ..QOS_PROFILE_DEFAULT
+        };
+        let echo_topic = format!("{}_echo", topic);
+        let bench = Self {
+            publisher: Publisher::new(&fixture.publisher_node, topic, qos)?,
+            subscription: Arc::new(Subscription::new(
+                &fixture.subscriber_node,
+                topic,
+                qos,
+                |_| {},
+            )?),
+            echo_publisher: Publisher::new(&fixture.subscriber_node, &echo_topic, qos)?,
+            echo_subscription: Arc::new(Subscription::new(
+                &fixture.publisher_node,
+                &echo_topic,
+                qos,
+                |_| {},
+            )?),
+            wait_set: WaitSet::new(1, &fixture.context)?,
+        };
+        let deadline = Instant::now() + timeout;
+        bench.publisher.wait_for_subscribers(1, Some(timeout))?;
+        bench
+            .echo_publisher
+            .wait_for_subscribers(1, Some(deadline.saturating_duration_since(Instant::now())))?;
+        Ok(bench)
+    }
+
+    /// Publishes `count` copies of the message in one direction and takes all of them.
+    ///
+    /// Returns the time from the first publish to the last take. For a correct result, `count`
+    /// must not exceed the `depth` of the bench.
+    pub fn throughput(&mut self, message: &T, count: usize) -> Result<Duration, RclReturnCode> {
+        let start = Instant::now();
+        for _ in 0..count {
+            self.publisher.publish(message)?;
+        }
+        for _ in 0..count {
+            black_box(take_blocking(&mut self.wait_set, &self.subscription)?);
+        }
+        Ok(start.elapsed())
+    }
+
+    /// Sends the message to the subscriber node and back, `iterations` times.
+    ///
+    /// Returns one sample per round trip, measured from publishing the message until taking the
+    /// echo.
+    pub fn round_trip(
+        &mut self,
+        message: &T,
+        iterations: usize,
+    ) -> Result<LatencyStats, RclReturnCode> {
+        let mut samples = Vec::with_capacity(iterations.max(1));
+        for _ in 0..iterations.max(1) {
+            let start = Instant::now();
+            self.publisher.publish(message)?;
+            let received = take_blocking(&mut self.wait_set, &self.subscription)?;
+            self.echo_publisher.publish(received)?;
+            black_box(take_blocking(&mut self.wait_set, &self.echo_subscription)?);
+            samples.push(start.elapsed());
+        }
+        Ok(LatencyStats::from_samples(samples))
+    }
+}
+
+// How long a take waits before giving up, so that lost messages make benchmarks fail instead of
+// hang.
+const TAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Waits until the subscription has a message and takes it.
+fn take_blocking<T: Message>(
+    wait_set: &mut WaitSet,
+    subscription: &Arc<Subscription<T>>,
+) -> Result<T, RclReturnCode> {
+    let deadline = Instant::now() + TAKE_TIMEOUT;
+    loop {
+        match subscription.take() {
+            Ok(message) => return Ok(message),
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {
+                if Instant::now() >= deadline {
+                    return Err(RclReturnCode::Timeout);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        wait_set.clear();
+        wait_set.add_subscription(subscription.clone())?;
+        wait_set.wait(Some(deadline.saturating_duration_since(Instant::now())))?;
+    }
+}
+
+// A waitable that does nothing, for measuring the overhead of the executor itself.
+struct NoopWaitable {
+    guard_condition: GuardCondition,
+}
+
+impl Waitable for NoopWaitable {
+    fn guard_condition(&self) -> &GuardCondition {
+        &self.guard_condition
+    }
+
+    fn execute(&self) -> Result<(), RclReturnCode> {
+        Ok(())
+    }
+}
+
+/// Measures how long [`spin_once`] takes to dispatch a single ready entity, `iterations` times.
+///
+/// This adds a waitable with an empty callback to the node and triggers it before each spin, so
+/// the result is the cost of building the wait set, waiting and dispatching, without any
+/// middleware transport. The other entities of the node are spun as well, so it should not have
+/// any subscriptions with pending messages.
+pub fn dispatch_overhead(node: &mut Node, iterations: usize) -> Result<Duration, RclReturnCode> {
+    let waitable = Arc::new(NoopWaitable {
+        guard_condition: GuardCondition::new_for_context_handle(&node.context)?,
+    });
+    node.add_waitable(&waitable)?;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        waitable.guard_condition.trigger()?;
+        spin_once(node, Some(Duration::ZERO))?;
+    }
+    Ok(start.elapsed())
+}
+
+/// Converts the message to its RMW representation and back, `iterations` times.
+///
+/// For message types whose idiomatic representation uses standard Rust containers, this is the
+/// conversion cost that publishing and taking add on top of the middleware.
+pub fn conversion_round_trip<T: Message>(message: &T, iterations: usize) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let rmw_message = T::into_rmw_message(Cow::Borrowed(black_box(message))).into_owned();
+        black_box(T::from_rmw_message(rmw_message));
+    }
+    start.elapsed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        let stats = LatencyStats::from_samples(
+            [5, 1, 4, 2, 3]
+                .iter()
+                .map(|&ms| Duration::from_millis(ms))
+                .collect(),
+        );
+        assert_eq!(stats.min(), Duration::from_millis(1));
+        assert_eq!(stats.max(), Duration::from_millis(5));
+        assert_eq!(stats.mean(), Duration::from_millis(3));
+        assert_eq!(stats.median(), Duration::from_millis(3));
+        assert_eq!(stats.percentile(80.0), Duration::from_millis(4));
+        assert_eq!(stats.percentile(100.0), Duration::from_millis(5));
+        assert_eq!(stats.percentile(0.0), Duration::from_millis(1));
+
+        let baseline = LatencyStats::from_samples(vec![Duration::from_millis(2)]);
+        assert!(stats.regressed_from(&baseline, 0.1));
+        assert!(!stats.regressed_from(&baseline, 0.5));
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Estimates the offset between the local clock and the clock of a remote time source.
+///
+/// Robots in a multi-robot system each stamp their messages with their own clock, so their
+/// stamps must be aligned before they can be compared. The estimator collects measurements of
+/// the remote clock, and uses the most accurate recent one, like the clock filter of NTP:
+///
+/// - Round trips, added with [`add_round_trip`][1], measure the offset NTP-style: the local
+///   clock is read when a request is sent and when the response arrives, and the remote side
+///   stamps when it received the request and sent the response, e.g. in a ping service. The
+///   measurement with the shortest round trip in the window is used, since it was least affected
+///   by network delays.
+/// - Stamps, added with [`add_stamp`][2], are one-way measurements from messages that carry the
+///   time of the remote clock, e.g. `/clock` or the header of any message. Since the message
+///   delay cannot be measured, these only give a lower bound for the offset, and the largest one
+///   in the window is used. They are only used when there are no round trips in the window.
+///
+/// The offset is the remote time minus the local time, so that
+/// `remote time = local time + offset`.
+///
+/// # Example
+/// ```
+/// # use rclrs::ClockOffsetEstimator;
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// let t = |millis| UNIX_EPOCH + Duration::from_millis(millis);
+/// let mut estimator = ClockOffsetEstimator::new(8);
+/// // The remote clock is 500 ms ahead, and the request and the response take 10 ms each.
+/// estimator.add_round_trip(t(1000), t(1510), t(1511), t(1021));
+/// assert_eq!(estimator.offset_nanos(), Some(500_000_000));
+/// assert_eq!(estimator.to_remote(t(2000)), Some(t(2500)));
+/// ```
+///
+/// [1]: ClockOffsetEstimator::add_round_trip
+/// [2]: ClockOffsetEstimator::add_stamp
+#[derive(Clone, Debug)]
+pub struct ClockOffsetEstimator {
+    window: usize,
+    samples: VecDeque<OffsetSample>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct OffsetSample {
+    offset_nanos: i64,
+    // `None` for one-way measurements.
+    round_trip_delay: Option<Duration>,
+}
+
+impl ClockOffsetEstimator {
+    /// Creates an estimator that uses the last `window` measurements.
+    ///
+    /// A larger window is more robust against network delays, but takes longer to follow a drift
+    /// of the clocks. A window of `0` is treated like `1`.
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            samples: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Adds a round trip to the remote time source.
+    ///
+    /// The request was sent at `request_sent` and the response received at `response_received`,
+    /// both according to the local clock. The remote side received the request at
+    /// `remote_received` and sent the response at `remote_sent`, according to the remote clock.
+    pub fn add_round_trip(
+        &mut self,
+        request_sent: SystemTime,
+        remote_received: SystemTime,
+        remote_sent: SystemTime,
+        response_received: SystemTime,
+    ) {
+        let outbound = nanos_between(request_sent, remote_received);
+        let inbound = nanos_between(response_received, remote_sent);
+        let round_trip = nanos_between(request_sent, response_received)
+            - nanos_between(remote_received, remote_sent);
+        self.add_sample(OffsetSample {
+            offset_nanos: ((outbound + inbound) / 2) as i64,
+            // A negative round trip can only come from a clock jump.
+            round_trip_delay: Some(Duration::from_nanos(round_trip.max(0) as u64)),
+        });
+    }
+
+    /// Adds a message stamp of the remote clock, which was received at `received` according to
+    /// the local clock.
+    pub fn add_stamp(&mut self, remote_stamp: SystemTime, received: SystemTime) {
+        self.add_sample(OffsetSample {
+            offset_nanos: nanos_between(received, remote_stamp) as i64,
+            round_trip_delay: None,
+        });
+    }
+
+    fn add_sample(&mut self, sample: OffsetSample) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    // Returns the most accurate sample in the window.
+    fn best_sample(&self) -> Option<&OffsetSample> {
+        self.samples
+            .iter()
+            .filter(|sample| sample.round_trip_delay.is_some())
+            .min_by_key(|sample| sample.round_trip_delay)
+            .or_else(|| self.samples.iter().max_by_key(|sample| sample.offset_nanos))
+    }
+
+    /// Returns the estimated offset of the remote clock in nanoseconds, or `None` if no
+    /// measurements were added yet.
+    pub fn offset_nanos(&self) -> Option<i64> {
+        self.best_sample().map(|sample| sample.offset_nanos)
+    }
+
+    /// Returns the round trip delay of the measurement that the offset is based on.
+    ///
+    /// Half of it is the maximum error of the offset, as long as the clocks do not drift. This is
+    /// `None` if the offset is based on stamps.
+    pub fn round_trip_delay(&self) -> Option<Duration> {
+        self.best_sample()?.round_trip_delay
+    }
+
+    /// Converts a time of the local clock to the remote clock.
+    pub fn to_remote(&self, local: SystemTime) -> Option<SystemTime> {
+        Some(shift(local, self.offset_nanos()?))
+    }
+
+    /// Converts a time of the remote clock to the local clock.
+    pub fn to_local(&self, remote: SystemTime) -> Option<SystemTime> {
+        Some(shift(remote, -self.offset_nanos()?))
+    }
+
+    /// Returns the current time of the remote clock, estimated from the local system clock.
+    pub fn now(&self) -> Option<SystemTime> {
+        self.to_remote(SystemTime::now())
+    }
+
+    /// Returns the number of measurements in the window.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns `true` if no measurements were added yet.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Removes all measurements, e.g. after the remote clock jumped.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+// Returns `to - from` in nanoseconds.
+fn nanos_between(from: SystemTime, to: SystemTime) -> i128 {
+    match to.duration_since(from) {
+        Ok(duration) => duration.as_nanos() as i128,
+        Err(error) => -(error.duration().as_nanos() as i128),
+    }
+}
+
+fn shift(time: SystemTime, nanos: i64) -> SystemTime {
+    let duration = Duration::from_nanos(nanos.unsigned_abs());
+    let shifted = if nanos >= 0 {
+        time.checked_add(duration)
+    } else {
+        time.checked_sub(duration)
+    };
+    // Only times far outside the range of ROS times overflow.
+    shifted.unwrap_or(UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_clock_offset_estimator() {
+        let mut estimator = ClockOffsetEstimator::new(2);
+        assert_eq!(estimator.offset_nanos(), None);
+
+        // The remote clock is 200 ms behind, and the messages take 30 ms and 10 ms.
+        estimator.add_stamp(t(800), t(1030));
+        estimator.add_stamp(t(900), t(1110));
+        assert_eq!(estimator.offset_nanos(), Some(-210_000_000));
+        assert_eq!(estimator.round_trip_delay(), None);
+
+        // A round trip of 100 ms with a 20 ms delay on the way out, and 80 ms on the way back.
+        estimator.add_round_trip(t(2000), t(1820), t(1820), t(2100));
+        assert_eq!(estimator.offset_nanos(), Some(-230_000_000));
+        // A more accurate round trip replaces it, and the stamp falls out of the window.
+        estimator.add_round_trip(t(3000), t(2805), t(2805), t(3010));
+        assert_eq!(estimator.len(), 2);
+        assert_eq!(estimator.offset_nanos(), Some(-200_000_000));
+        assert_eq!(
+            estimator.round_trip_delay(),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(estimator.to_local(t(2800)), Some(t(3000)));
+    }
+}
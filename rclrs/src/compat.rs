@@ -0,0 +1,52 @@
+//! Shims over the differences between the rcl and rmw APIs of the supported distros.
+//!
+//! The distro is selected with the `ros_distro` cfg, which the build script derives from the
+//! `ROS_DISTRO` environment variable or, if that is not set, from the version of rcl in the
+//! sourced installation. The gates name the distros that lack a feature, so that newer distros
+//! like Rolling get the full API without being listed.
+//!
+//! Code that only differs in whether a field or function exists belongs here, so that the rest
+//! of the crate can use it without repeating the gates.
+//!
+//! APIs that do not exist at all in older distros have nothing to fall back to, and are gated
+//! where they are defined instead, together with their private helpers and imports. These are
+//! - QoS compatibility checks, network flow endpoints and domain IDs, which were added in
+//!   Galactic,
+//! - type hash mismatches between endpoints, which were added in Iron.
+
+use crate::rcl_bindings::*;
+use crate::TypeHash;
+
+/// Returns the sequence number of a received message, if the middleware supports it.
+///
+/// Sequence numbers were added in Humble. Middlewares that don't support them leave them at zero.
+#[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic")))]
+pub(crate) fn publication_sequence_number(message_info: &rmw_message_info_t) -> Option<u64> {
+    Some(message_info.publication_sequence_number).filter(|&n| n != 0)
+}
+
+#[cfg(any(ros_distro = "foxy", ros_distro = "galactic"))]
+pub(crate) fn publication_sequence_number(_message_info: &rmw_message_info_t) -> Option<u64> {
+    None
+}
+
+/// Returns the type hash that an endpoint advertises, if it is known.
+///
+/// Type hashes were added in Iron. A version of zero means that the middleware does not know the
+/// hash.
+#[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+pub(crate) fn endpoint_type_hash(endpoint: &rmw_topic_endpoint_info_t) -> Option<TypeHash> {
+    let type_hash = &endpoint.topic_type_hash;
+    if type_hash.version == 0 {
+        return None;
+    }
+    Some(TypeHash {
+        version: type_hash.version,
+        value: type_hash.value,
+    })
+}
+
+#[cfg(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble"))]
+pub(crate) fn endpoint_type_hash(_endpoint: &rmw_topic_endpoint_info_t) -> Option<TypeHash> {
+    None
+}
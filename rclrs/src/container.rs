@@ -0,0 +1,157 @@
+use crate::rcl_bindings::*;
+use crate::{spin_entities_once, Context, Node, RclReturnCode, SpinOptions};
+
+use std::time::Duration;
+use std::vec::Vec;
+
+/// Owns several nodes that share a context and are spun together.
+///
+/// This is the common pattern for composing multiple nodes, e.g. the drivers and algorithms of a
+/// robot, into one process. All nodes are created in the container's [`Context`], and
+/// [`spin_once`][1] waits on the subscriptions of all nodes at once, so that a single thread
+/// serves all of them.
+///
+/// Nodes can be added and removed at any time between spins. When the container is dropped, the
+/// nodes are dropped in the reverse order of their creation, and the context is dropped last.
+///
+/// # Example
+/// ```ignore
+/// let mut container = NodeContainer::new(Context::new(std::env::args())?);
+/// let camera = container.create_node("camera", "/sensors")?;
+/// let _image_sub = camera.create_subscription::<Image, _>("image_raw", QOS_PROFILE_SENSOR_DATA, |msg| { /* ... */ })?;
+/// container.create_node("planner", "")?;
+/// container.spin()?;
+/// ```
+///
+/// [1]: NodeContainer::spin_once
+pub struct NodeContainer {
+    // The nodes, in the order of their creation. They must be dropped before the context.
+    nodes: Vec<Node>,
+    context: Context,
+}
+
+impl Drop for NodeContainer {
+    fn drop(&mut self) {
+        while let Some(node) = self.nodes.pop() {
+            drop(node);
+        }
+    }
+}
+
+impl NodeContainer {
+    /// Creates an empty container that creates its nodes in the given context.
+    pub fn new(context: Context) -> Self {
+        Self {
+            nodes: Vec::new(),
+            context,
+        }
+    }
+
+    /// Returns the context of the nodes.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Creates a new node in the container, see [`Node::new_with_namespace`].
+    ///
+    /// The returned reference can be used to create the publishers and subscriptions of the
+    /// node.
+    pub fn create_node(
+        &mut self,
+        node_name: &str,
+        node_namespace: &str,
+    ) -> Result<&mut Node, RclReturnCode> {
+        let node = Node::new_with_namespace(node_name, node_namespace, &self.context)?;
+        self.nodes.push(node);
+        Ok(self.nodes.last_mut().unwrap())
+    }
+
+    /// Returns the nodes of the container, in the order of their creation.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node with the given fully qualified name, e.g. `/sensors/camera`.
+    pub fn node(&self, fully_qualified_name: &str) -> Option<&Node> {
+        self.nodes
+            .iter()
+            .find(|node| node.fully_qualified_name() == fully_qualified_name)
+    }
+
+    /// Returns the node with the given fully qualified name, for creating entities.
+    pub fn node_mut(&mut self, fully_qualified_name: &str) -> Option<&mut Node> {
+        self.nodes
+            .iter_mut()
+            .find(|node| node.fully_qualified_name() == fully_qualified_name)
+    }
+
+    /// Removes the node with the given fully qualified name from the container and returns it.
+    ///
+    /// The subscriptions of the node are no longer executed by the container. The node itself
+    /// is destroyed once the returned value and all its publishers and subscriptions are dropped.
+    pub fn remove_node(&mut self, fully_qualified_name: &str) -> Option<Node> {
+        let index = self
+            .nodes
+            .iter()
+            .position(|node| node.fully_qualified_name() == fully_qualified_name)?;
+        Some(self.nodes.remove(index))
+    }
+
+    /// Polls all nodes for new messages and executes the corresponding callbacks.
+    ///
+    /// This behaves like [`spin_once`][1] for a single node.
+    ///
+    /// [1]: crate::spin_once
+    pub fn spin_once(&self, timeout: Option<Duration>) -> Result<(), RclReturnCode> {
+        self.spin_once_with_options(timeout, &SpinOptions::default())
+    }
+
+    /// Like [`spin_once`][1], but with non-default [`SpinOptions`].
+    ///
+    /// [1]: NodeContainer::spin_once
+    pub fn spin_once_with_options(
+        &self,
+        timeout: Option<Duration>,
+        options: &SpinOptions,
+    ) -> Result<(), RclReturnCode> {
+        let live_subscriptions = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.live_subscriptions())
+            .collect();
+        let live_waitables = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.live_waitables())
+            .collect();
+        spin_entities_once(
+            &self.context.handle,
+            &self.context.interrupt,
+            live_subscriptions,
+            live_waitables,
+            timeout,
+            options,
+        )
+    }
+
+    /// Convenience function for calling [`spin_once`][1] in a loop while the context is valid.
+    ///
+    /// [1]: NodeContainer::spin_once
+    pub fn spin(&self) -> Result<(), RclReturnCode> {
+        self.spin_with_options(&SpinOptions::default())
+    }
+
+    /// Like [`spin`][1], but with non-default [`SpinOptions`].
+    ///
+    /// [1]: NodeContainer::spin
+    pub fn spin_with_options(&self, options: &SpinOptions) -> Result<(), RclReturnCode> {
+        // SAFETY: No preconditions for this function.
+        while unsafe { rcl_context_is_valid(&mut *self.context.handle.lock() as *mut _) } {
+            match self.spin_once_with_options(None, options) {
+                Ok(()) | Err(RclReturnCode::Timeout) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
 use crate::rcl_bindings::*;
-use crate::{Node, RclReturnCode, ToResult};
+use crate::{GuardCondition, Node, RclReturnCode, ToResult, Waitable};
 
+use std::collections::HashSet;
 use std::ffi::CString;
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
 use std::string::String;
 use std::sync::Arc;
 use std::vec::Vec;
@@ -22,8 +23,12 @@ impl Drop for rcl_context_t {
 /// Shared state between nodes and similar entities.
 ///
 /// It is possible, but not usually necessary, to have several contexts in an application.
+/// Entities are bound to the context they were created in: nodes belong to the context they were
+/// created from, and a [`WaitSet`][1] only accepts subscriptions of nodes in its own context.
 ///
 /// Ownership of the context is shared by the `Context` itself and all nodes created from it.
+/// Cloning a `Context` is cheap, and the clone refers to the same context. See also
+/// [`default_context`][2] for a process-wide context.
 ///
 /// # Details
 /// A context stores, among other things
@@ -31,8 +36,50 @@ impl Drop for rcl_context_t {
 /// - middleware-specific data, e.g. the domain participant in DDS
 /// - the allocator used (left as the default by `rclrs`)
 ///
+/// [1]: crate::WaitSet
+/// [2]: crate::default_context
+#[derive(Clone)]
 pub struct Context {
     pub(crate) handle: Arc<Mutex<rcl_context_t>>,
+    // Wakes up the spin loops in this context when it is shut down.
+    pub(crate) interrupt: Arc<Interrupt>,
+    // The command line arguments that the context was created with.
+    args: Vec<String>,
+}
+
+// A waitable that wakes up the spin loops of a context when the context is shut down.
+pub(crate) struct Interrupt {
+    guard_condition: GuardCondition,
+}
+
+impl Waitable for Interrupt {
+    fn guard_condition(&self) -> &GuardCondition {
+        &self.guard_condition
+    }
+
+    fn execute(&self) -> Result<(), RclReturnCode> {
+        // The spin loop checks whether the context is still valid by itself.
+        Ok(())
+    }
+}
+
+/// The command line arguments of a [`Context`], split up by [`Context::arguments`].
+///
+/// The remapping rules and parameter overrides are given in their command line syntax, e.g.
+/// `my_node:chatter:=other_chatter` or `use_sim_time:=true`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContextArguments {
+    /// The arguments outside of `--ros-args` sections, including the program name if it was
+    /// passed in. These are meant for the application itself.
+    pub non_ros_arguments: Vec<String>,
+    /// Arguments inside of `--ros-args` sections that were not recognized as ROS arguments.
+    pub unknown_ros_arguments: Vec<String>,
+    /// The remapping rules, given with `-r` or `--remap`.
+    pub remap_rules: Vec<String>,
+    /// The parameter overrides, given with `-p` or `--param`.
+    pub parameter_overrides: Vec<String>,
+    /// The parameter files, given with `--params-file`.
+    pub parameter_files: Vec<String>,
 }
 
 impl Context {
@@ -54,19 +101,49 @@ impl Context {
     /// # Panics
     /// When there is an interior null byte in any of the args.
     pub fn new(args: impl IntoIterator<Item = String>) -> Result<Self, RclReturnCode> {
-        let context = Self {
-            // SAFETY: Getting a zero-initialized value is always safe
-            handle: Arc::new(Mutex::new(unsafe { rcl_get_zero_initialized_context() })),
-        };
+        Self::new_with_init_options(args, |_| Ok(()))
+    }
+
+    /// Creates a new context that uses the given [ROS domain ID][1].
+    ///
+    /// Nodes only communicate with nodes in the same domain, so using different domain IDs
+    /// isolates the contexts within one process from each other, e.g. in tests that run in
+    /// parallel. Without an explicit domain ID, the `ROS_DOMAIN_ID` environment variable is used.
+    ///
+    /// This is not available in Foxy.
+    ///
+    /// # Panics
+    /// When there is an interior null byte in any of the args.
+    ///
+    /// [1]: https://docs.ros.org/en/rolling/Concepts/About-Domain-ID.html
+    #[cfg(not(ros_distro = "foxy"))]
+    pub fn new_with_domain_id(
+        args: impl IntoIterator<Item = String>,
+        domain_id: usize,
+    ) -> Result<Self, RclReturnCode> {
+        Self::new_with_init_options(args, |init_options| {
+            // SAFETY: The init options are initialized.
+            unsafe { rcl_init_options_set_domain_id(init_options as *mut _, domain_id).ok() }
+        })
+    }
+
+    // Initializes a context, with a hook for modifying the default init options.
+    fn new_with_init_options(
+        args: impl IntoIterator<Item = String>,
+        modify_init_options: impl FnOnce(&mut rcl_init_options_t) -> Result<(), RclReturnCode>,
+    ) -> Result<Self, RclReturnCode> {
+        let args: Vec<String> = args.into_iter().collect();
         let cstring_args: Vec<CString> = args
-            .into_iter()
-            .map(|arg| CString::new(arg).unwrap())
+            .iter()
+            .map(|arg| CString::new(arg.as_str()).unwrap())
             .collect();
+        // SAFETY: Getting a zero-initialized value is always safe
+        let handle = Arc::new(Mutex::new(unsafe { rcl_get_zero_initialized_context() }));
         // Vector of pointers into cstring_args
         let c_args: Vec<*const c_char> = cstring_args.iter().map(|arg| arg.as_ptr()).collect();
         // Scope for the handle
         {
-            let handle = &mut *context.handle.lock();
+            let handle = &mut *handle.lock();
             unsafe {
                 // SAFETY: No preconditions for this function.
                 let allocator = rcutils_get_default_allocator();
@@ -75,26 +152,36 @@ impl Context {
                 // SAFETY: Passing in a zero-initialized value is expected.
                 // In the case where this returns not ok, there's nothing to clean up.
                 rcl_init_options_init(&mut init_options as *mut _, allocator).ok()?;
-                // SAFETY: This function does not store the ephemeral init_options and c_args
-                // pointers. Passing in a zero-initialized handle is expected.
-                let ret = rcl_init(
-                    c_args.len() as i32,
-                    if c_args.is_empty() {
-                        std::ptr::null()
-                    } else {
-                        c_args.as_ptr()
-                    },
-                    &init_options as *const _,
-                    handle as *mut _,
-                );
+                let ret = modify_init_options(&mut init_options).and_then(|()| {
+                    // SAFETY: This function does not store the ephemeral init_options and c_args
+                    // pointers. Passing in a zero-initialized handle is expected.
+                    rcl_init(
+                        c_args.len() as i32,
+                        if c_args.is_empty() {
+                            std::ptr::null()
+                        } else {
+                            c_args.as_ptr()
+                        },
+                        &init_options as *const _,
+                        handle as *mut _,
+                    )
+                    .ok()
+                });
                 // SAFETY: It's safe to pass in an initialized object.
                 // Early return will not leak memory, because this is the last fini function.
                 rcl_init_options_fini(&mut init_options as *mut _).ok()?;
                 // Move the check after the last fini()
-                ret.ok()?;
+                ret?;
             }
         }
-        Ok(context)
+        let interrupt = Arc::new(Interrupt {
+            guard_condition: GuardCondition::new_for_context_handle(&handle)?,
+        });
+        Ok(Self {
+            handle,
+            interrupt,
+            args,
+        })
     }
 
     /// Creates a node.
@@ -114,15 +201,265 @@ impl Context {
         Node::new(node_name, self)
     }
 
+    /// Returns the ROS domain ID of the context.
+    ///
+    /// This is not available in Foxy.
+    #[cfg(not(ros_distro = "foxy"))]
+    pub fn domain_id(&self) -> Result<usize, RclReturnCode> {
+        let mut domain_id = 0;
+        // SAFETY: The context handle is valid, and the domain ID is a valid pointer.
+        unsafe {
+            rcl_context_get_domain_id(&mut *self.handle.lock() as *mut _, &mut domain_id as *mut _)
+                .ok()?;
+        }
+        Ok(domain_id)
+    }
+
+    /// Returns the command line arguments of the context, split up into ROS and non-ROS arguments.
+    ///
+    /// The ROS arguments have already been applied by `rclrs`, e.g. remapping rules are applied
+    /// to all nodes of this context. The non-ROS arguments can be passed on to the argument parser
+    /// of the application.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, RclReturnCode};
+    /// let args = ["my_program", "--verbose", "--ros-args", "-r", "chatter:=talk", "--", "input.txt"];
+    /// let context = Context::new(args.map(String::from))?;
+    /// let arguments = context.arguments()?;
+    /// assert_eq!(arguments.non_ros_arguments, ["my_program", "--verbose", "input.txt"]);
+    /// assert_eq!(arguments.remap_rules, ["chatter:=talk"]);
+    /// # Ok::<(), RclReturnCode>(())
+    /// ```
+    pub fn arguments(&self) -> Result<ContextArguments, RclReturnCode> {
+        let handle = &*self.handle.lock();
+        let non_ros_indices = get_argument_indices(
+            &handle.global_arguments,
+            rcl_arguments_get_count_unparsed,
+            rcl_arguments_get_unparsed,
+        )?;
+        let unknown_ros_indices = get_argument_indices(
+            &handle.global_arguments,
+            rcl_arguments_get_count_unparsed_ros,
+            rcl_arguments_get_unparsed_ros,
+        )?;
+        let mut arguments = ContextArguments {
+            non_ros_arguments: non_ros_indices
+                .iter()
+                .map(|&index| self.args[index].clone())
+                .collect(),
+            unknown_ros_arguments: unknown_ros_indices
+                .iter()
+                .map(|&index| self.args[index].clone())
+                .collect(),
+            ..Default::default()
+        };
+        // rcl has no API for listing the parsed rules, but since rcl_init() has already validated
+        // the ROS arguments, each flag can simply be paired with the argument following it.
+        let skipped_indices: HashSet<usize> = non_ros_indices
+            .into_iter()
+            .chain(unknown_ros_indices)
+            .collect();
+        let mut ros_args = (0..self.args.len())
+            .filter(|index| !skipped_indices.contains(index))
+            .map(|index| self.args[index].as_str());
+        while let Some(arg) = ros_args.next() {
+            let list = match arg {
+                "-r" | "--remap" => &mut arguments.remap_rules,
+                "-p" | "--param" => &mut arguments.parameter_overrides,
+                "--params-file" => &mut arguments.parameter_files,
+                _ => continue,
+            };
+            list.extend(ros_args.next().map(String::from));
+        }
+        Ok(arguments)
+    }
+
+    /// Returns the command line arguments of the context that are not ROS arguments.
+    ///
+    /// These are the arguments outside of `--ros-args` sections, including the program name if
+    /// it was passed in, in their original order. Since they start with the program name, they
+    /// can be passed directly to an argument parser like `clap`, which would otherwise fail on
+    /// `--ros-args`:
+    ///
+    /// ```ignore
+    /// let context = Context::new(std::env::args())?;
+    /// let cli = Cli::parse_from(context.non_ros_arguments()?);
+    /// ```
+    ///
+    /// This is the same as the `non_ros_arguments` of [`Context::arguments`].
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, RclReturnCode};
+    /// let args = ["my_program", "-v", "--ros-args", "-r", "chatter:=talk", "--", "input.txt"];
+    /// let context = Context::new(args.map(String::from))?;
+    /// assert_eq!(context.non_ros_arguments()?, ["my_program", "-v", "input.txt"]);
+    /// # Ok::<(), RclReturnCode>(())
+    /// ```
+    pub fn non_ros_arguments(&self) -> Result<Vec<String>, RclReturnCode> {
+        let handle = &*self.handle.lock();
+        let indices = get_argument_indices(
+            &handle.global_arguments,
+            rcl_arguments_get_count_unparsed,
+            rcl_arguments_get_unparsed,
+        )?;
+        Ok(indices
+            .into_iter()
+            .map(|index| self.args[index].clone())
+            .collect())
+    }
+
     /// Checks if the context is still valid.
     ///
-    /// This will return `false` when a signal has caused the context to shut down (currently
-    /// unimplemented).
+    /// This will return `false` after the context has been shut down, e.g. by the signal handler
+    /// of the [`default_context`][1].
+    ///
+    /// [1]: crate::default_context
     pub fn ok(&self) -> bool {
-        // This will currently always return true, but once we have a signal handler, the signal
-        // handler could call `rcl_shutdown()`, hence making the context invalid.
         let handle = &mut *self.handle.lock();
         // SAFETY: No preconditions for this function.
         unsafe { rcl_context_is_valid(handle as *mut _) }
     }
+
+    /// Shuts down the context.
+    ///
+    /// Afterwards, [`ok`][1] returns `false`, [`spin`][2] wakes up and returns, and no more
+    /// nodes can be created in the context. The context is shared by all of its clones and its
+    /// nodes, so they are all affected.
+    ///
+    /// Returns [`AlreadyShutdown`][3] if the context has already been shut down.
+    ///
+    /// [1]: Context::ok
+    /// [2]: crate::spin
+    /// [3]: crate::RclErrorCode::AlreadyShutdown
+    pub fn shutdown(&self) -> Result<(), RclReturnCode> {
+        {
+            let handle = &mut *self.handle.lock();
+            // SAFETY: The context is initialized. Shutting it down twice is caught by rcl.
+            unsafe { rcl_shutdown(handle as *mut _).ok()? };
+        }
+        self.interrupt.guard_condition.trigger()
+    }
+}
+
+// Returns the indices into the args for one of the rcl_arguments_get_unparsed* functions.
+fn get_argument_indices(
+    arguments: &rcl_arguments_t,
+    get_count: unsafe extern "C" fn(*const rcl_arguments_t) -> c_int,
+    get_indices: unsafe extern "C" fn(
+        *const rcl_arguments_t,
+        rcl_allocator_t,
+        *mut *mut c_int,
+    ) -> rcl_ret_t,
+) -> Result<Vec<usize>, RclReturnCode> {
+    // SAFETY: The arguments have been initialized by rcl_init().
+    let count = unsafe { get_count(arguments as *const _) };
+    if count < 0 {
+        return Err(RclReturnCode::InvalidArgument);
+    }
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let mut indices_ptr: *mut c_int = std::ptr::null_mut();
+    // SAFETY: The arguments have been initialized, and the output is a valid pointer to a null
+    // pointer. On success, the output is an array of `count` indices that was allocated with the
+    // given allocator, and it is not used after being deallocated.
+    unsafe {
+        get_indices(
+            arguments as *const _,
+            rcutils_get_default_allocator(),
+            &mut indices_ptr as *mut _,
+        )
+        .ok()?;
+        let indices = std::slice::from_raw_parts(indices_ptr, count as usize)
+            .iter()
+            .map(|&index| index as usize)
+            .collect();
+        let allocator = rcutils_get_default_allocator();
+        if let Some(deallocate) = allocator.deallocate {
+            deallocate(indices_ptr as *mut _, allocator.state);
+        }
+        Ok(indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{spin_once, RclErrorCode, WaitSet, QOS_PROFILE_DEFAULT};
+
+    use std::time::{Duration, Instant};
+
+    // Moves a context to the thread that shuts it down.
+    struct SendContext(Context);
+
+    // SAFETY: The rcl context is only accessed through its mutex, and is not bound to the thread
+    // that created it.
+    unsafe impl Send for SendContext {}
+
+    impl SendContext {
+        fn shutdown(&self) -> Result<(), RclReturnCode> {
+            self.0.shutdown()
+        }
+    }
+
+    #[test]
+    fn test_shutdown() -> Result<(), RclReturnCode> {
+        let context = Context::new([])?;
+        assert!(context.ok());
+        context.clone().shutdown()?;
+        assert!(!context.ok());
+        assert!(matches!(
+            context.shutdown(),
+            Err(RclReturnCode::RclError(RclErrorCode::AlreadyShutdown))
+        ));
+        assert!(context.create_node("my_node").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_shutdown_wakes_up_spin() -> Result<(), RclReturnCode> {
+        let context = Context::new([])?;
+        let node = context.create_node("my_node")?;
+        let shutdown_context = SendContext(context.clone());
+        let start = Instant::now();
+        let shutdown_thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            shutdown_context.shutdown()
+        });
+        spin_once(&node, Some(Duration::from_secs(10)))?;
+        assert!(start.elapsed() < Duration::from_secs(10));
+        shutdown_thread.join().unwrap()?;
+        assert!(!context.ok());
+        // Spinning in a context that was shut down returns right away.
+        spin_once(&node, None)
+    }
+
+    #[test]
+    #[cfg(not(ros_distro = "foxy"))]
+    fn test_domain_id_is_per_context() -> Result<(), RclReturnCode> {
+        let context_a = Context::new_with_domain_id([], 42)?;
+        let context_b = Context::new_with_domain_id([], 43)?;
+        assert_eq!(context_a.domain_id()?, 42);
+        assert_eq!(context_b.domain_id()?, 43);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_set_rejects_subscription_of_other_context() -> Result<(), RclReturnCode> {
+        let context = Context::new([])?;
+        let other_context = Context::new([])?;
+        let mut node = other_context.create_node("my_node")?;
+        let subscription =
+            node.create_subscription("topic", QOS_PROFILE_DEFAULT, |_: std_msgs::msg::Empty| {})?;
+        let mut wait_set = WaitSet::new(1, &context)?;
+        assert!(matches!(
+            wait_set.add_subscription(subscription.clone()),
+            Err(RclReturnCode::InvalidArgument)
+        ));
+        // The subscription can still be added to a wait set of its own context.
+        let mut wait_set = WaitSet::new(1, &other_context)?;
+        wait_set.add_subscription(subscription)
+    }
 }
@@ -0,0 +1,150 @@
+use crate::qos::QoSProfile;
+use crate::{Node, RclReturnCode, Subscription};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use rosidl_runtime_rs::Message;
+
+/// A subscription for data that must arrive within a deadline, with a fallback for when it is
+/// late.
+///
+/// This is a common safety pattern in motion control: when e.g. velocity commands stop arriving,
+/// the fallback can bring the robot to a safe state based on the last known command.
+///
+/// Missed deadlines are detected by the [message timeout][1] of the subscription, so they are
+/// reported even when the middleware does not support deadline events, and the [`QoSProfile`]
+/// does not need a deadline. This way, the subscription also matches publishers with the default
+/// QoS profile. Whenever no message has been received for the duration of the deadline, the
+/// fallback is invoked with a copy of the last received message, or `None` if no message has been
+/// received yet.
+///
+/// To also require publishers to offer the deadline, set the deadline of the QoS profile to
+/// [`QoSDuration::Custom`][4] with the same duration. Then only publishers that offer a deadline
+/// that is at least as short are matched.
+///
+/// As with all subscriptions, messages are received and missed deadlines are detected by
+/// [`spin_once`][2] and [`spin`][3].
+///
+/// [1]: Subscription::set_message_timeout
+/// [2]: crate::spin_once
+/// [3]: crate::spin
+/// [4]: crate::QoSDuration::Custom
+pub struct DeadlineSubscriber<T>
+where
+    T: Message,
+{
+    subscription: Arc<Subscription<T>>,
+    last_message: Arc<Mutex<Option<T>>>,
+    missed_deadlines: Arc<AtomicUsize>,
+}
+
+impl<T> DeadlineSubscriber<T>
+where
+    T: Message,
+{
+    /// Creates a new `DeadlineSubscriber`.
+    ///
+    /// The `qos` profile is used as it is. The `callback` is invoked for every received message,
+    /// and the `fallback` for every missed deadline.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new<F, G>(
+        node: &mut Node,
+        topic: &str,
+        qos: QoSProfile,
+        deadline: Duration,
+        mut callback: F,
+        mut fallback: G,
+    ) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut(T) + 'static,
+        G: FnMut(Option<T>) + 'static,
+    {
+        let last_message = Arc::new(Mutex::new(None));
+        let missed_deadlines = Arc::new(AtomicUsize::new(0));
+        let callback_last_message = Arc::clone(&last_message);
+        let subscription = node.create_subscription(topic, qos, move |msg: T| {
+            *callback_last_message.lock() = Some(msg.clone());
+            callback(msg);
+        })?;
+        let fallback_last_message = Arc::clone(&last_message);
+        let fallback_missed_deadlines = Arc::clone(&missed_deadlines);
+        subscription.set_message_timeout(deadline, move || {
+            fallback_missed_deadlines.fetch_add(1, Ordering::Relaxed);
+            // The message is copied, so that the lock is not held while running user code.
+            let last_message = fallback_last_message.lock().clone();
+            fallback(last_message);
+        });
+        Ok(Self {
+            subscription,
+            last_message,
+            missed_deadlines,
+        })
+    }
+
+    /// Returns the last received message, if any.
+    pub fn last_message(&self) -> Option<T> {
+        self.last_message.lock().clone()
+    }
+
+    /// Returns the number of times the fallback has been invoked.
+    pub fn missed_deadlines(&self) -> usize {
+        self.missed_deadlines.load(Ordering::Relaxed)
+    }
+
+    /// Returns the underlying subscription.
+    pub fn subscription(&self) -> &Arc<Subscription<T>> {
+        &self.subscription
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestFixture;
+    use crate::{Publisher, QOS_PROFILE_DEFAULT};
+
+    use std::time::Instant;
+
+    use std_msgs::msg::Float64;
+
+    #[test]
+    fn test_default_publisher_matches_and_fallback_gets_last_message() -> Result<(), RclReturnCode>
+    {
+        let mut fixture = TestFixture::new("deadline")?;
+        let fallbacks = Arc::new(Mutex::new(Vec::new()));
+        let fallback_values = Arc::clone(&fallbacks);
+        let subscriber = DeadlineSubscriber::<Float64>::new(
+            &mut fixture.subscriber_node,
+            "velocity",
+            QOS_PROFILE_DEFAULT,
+            Duration::from_millis(200),
+            |_| {},
+            move |msg: Option<Float64>| fallback_values.lock().push(msg.map(|msg| msg.data)),
+        )?;
+        // A publisher that offers no deadline is matched.
+        let publisher =
+            Publisher::<Float64>::new(&fixture.publisher_node, "velocity", QOS_PROFILE_DEFAULT)?;
+        publisher.wait_for_subscribers(1, Some(Duration::from_secs(10)))?;
+
+        publisher.publish(Float64 { data: 0.5 })?;
+        let start = Instant::now();
+        while subscriber.last_message().is_none() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            crate::spin_once(&fixture.subscriber_node, Some(Duration::from_secs(10)))?;
+        }
+        // Without further messages, the spin wakes up when the deadline has passed.
+        let missed_before_message = subscriber.missed_deadlines();
+        while subscriber.missed_deadlines() == missed_before_message {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            crate::spin_once(&fixture.subscriber_node, Some(Duration::from_secs(10)))?;
+        }
+        assert_eq!(fallbacks.lock().last(), Some(&Some(0.5)));
+        Ok(())
+    }
+}
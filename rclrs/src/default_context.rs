@@ -0,0 +1,208 @@
+use crate::error::{RclErrorCode, RclReturnCode};
+use crate::Context;
+
+use parking_lot::{const_mutex, Mutex};
+
+// The process-wide context, which is created on first use.
+static DEFAULT_CONTEXT: Mutex<Option<DefaultContext>> = const_mutex(None);
+
+struct DefaultContext {
+    context: Context,
+    // Whether `default_context()` installs the signal handler, which is not the case when the
+    // default context was initialized without it.
+    signal_handler: bool,
+}
+
+// SAFETY: The rcl context is only accessed through its mutex, and is not bound to the thread
+// that created it, like the context of a `GuardCondition`.
+unsafe impl Send for DefaultContext {}
+
+/// Returns the process-wide default context, creating it on first use.
+///
+/// The default context spares libraries and deeply nested code from passing a [`Context`]
+/// around: everything that uses the default context shares the same middleware resources and
+/// command line arguments. [`NodeBuilder`][1] uses it when no context is set explicitly.
+///
+/// On first use, the context is created from `std::env::args()`. On Unix, this function also
+/// installs a handler for `SIGINT` and `SIGTERM` that shuts the default context down. This makes
+/// [`spin`][2] return on Ctrl-C, also while it is waiting. The handler is only run once, so
+/// a second Ctrl-C terminates the process as usual. Use [`init_default_context`] instead for
+/// other arguments, or to do without the signal handler. [`NodeBuilder`][1] does not install
+/// the signal handler, so it is only installed when this function is called.
+///
+/// Since the returned context shares its state with all other copies, shutting it down shuts
+/// down the default context for the rest of the process. Other contexts, created with
+/// [`Context::new`], are independent of the default context.
+///
+/// # Example
+/// ```
+/// # use rclrs::{default_context, Node};
+/// let node = Node::builder().name("my_node").build()?;
+/// assert!(default_context()?.ok());
+/// # Ok::<(), rclrs::NodeBuildError>(())
+/// ```
+///
+/// [1]: crate::NodeBuilder
+/// [2]: crate::spin
+pub fn default_context() -> Result<Context, RclReturnCode> {
+    get_default_context(true)
+}
+
+// Returns the default context, creating it on first use. The signal handler is installed if it
+// was requested and the default context was not initialized without it.
+pub(crate) fn get_default_context(install_signal_handler: bool) -> Result<Context, RclReturnCode> {
+    let mut default_context = DEFAULT_CONTEXT.lock();
+    let default_context = match &mut *default_context {
+        Some(default_context) => default_context,
+        slot @ None => slot.insert(DefaultContext {
+            context: Context::new(std::env::args())?,
+            signal_handler: true,
+        }),
+    };
+    #[cfg(unix)]
+    if install_signal_handler && default_context.signal_handler {
+        signal_handler::install()?;
+    }
+    #[cfg(not(unix))]
+    let _ = install_signal_handler;
+    Ok(default_context.context.clone())
+}
+
+/// Creates the default context from the given arguments, see [`default_context`].
+///
+/// This must be called before anything uses the default context, e.g. at the start of `main()`,
+/// and returns [`AlreadyInit`][1] otherwise. The signal handler is only installed when
+/// `install_signal_handler` is `true`, which has no effect on other platforms than Unix.
+///
+/// # Panics
+/// When there is an interior null byte in any of the args.
+///
+/// [1]: crate::RclErrorCode::AlreadyInit
+pub fn init_default_context(
+    args: impl IntoIterator<Item = String>,
+    install_signal_handler: bool,
+) -> Result<Context, RclReturnCode> {
+    let default_context = &mut *DEFAULT_CONTEXT.lock();
+    if default_context.is_some() {
+        return Err(RclReturnCode::RclError(RclErrorCode::AlreadyInit));
+    }
+    let context = Context::new(args)?;
+    #[cfg(unix)]
+    if install_signal_handler {
+        signal_handler::install()?;
+    }
+    *default_context = Some(DefaultContext {
+        context: context.clone(),
+        signal_handler: install_signal_handler,
+    });
+    Ok(context)
+}
+
+// Shuts down the default context, which also wakes up the spin loops that are waiting in it.
+fn shutdown_default_context() {
+    if let Some(DefaultContext { context, .. }) = &*DEFAULT_CONTEXT.lock() {
+        let _ = context.shutdown();
+    }
+}
+
+#[cfg(unix)]
+mod signal_handler {
+    use super::shutdown_default_context;
+    use crate::RclReturnCode;
+
+    use std::io::Read;
+    use std::os::raw::c_int;
+    use std::os::unix::io::IntoRawFd;
+    use std::os::unix::net::UnixStream;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    // The socket that the signal handler writes to, or -1 before the handler is installed.
+    static WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+
+    // Installs the signal handler once per process. Since only async-signal-safe functions may
+    // be called in a signal handler, the handler merely wakes up a thread that does the actual
+    // shutdown.
+    pub(super) fn install() -> Result<(), RclReturnCode> {
+        if is_installed() {
+            return Ok(());
+        }
+        let (wake_sender, mut wake_receiver) =
+            UnixStream::pair().map_err(|_| RclReturnCode::Error)?;
+        std::thread::Builder::new()
+            .name(String::from("rclrs_signal_handler"))
+            .spawn(move || {
+                let mut buffer = [0];
+                if let Ok(1) = wake_receiver.read(&mut buffer) {
+                    shutdown_default_context();
+                }
+            })
+            .map_err(|_| RclReturnCode::Error)?;
+        // The socket is intentionally leaked, since the handler may run at any time.
+        WAKE_FD.store(wake_sender.into_raw_fd(), Ordering::Release);
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            // SAFETY: The action is fully initialized, and the handler only calls
+            // async-signal-safe functions.
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = handle_signal as extern "C" fn(c_int) as libc::sighandler_t;
+                libc::sigemptyset(&mut action.sa_mask as *mut _);
+                // Restore the default action after the first signal.
+                action.sa_flags = libc::SA_RESTART | libc::SA_RESETHAND;
+                if libc::sigaction(signal, &action as *const _, std::ptr::null_mut()) != 0 {
+                    return Err(RclReturnCode::Error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn is_installed() -> bool {
+        WAKE_FD.load(Ordering::Acquire) != -1
+    }
+
+    extern "C" fn handle_signal(_signal: c_int) {
+        let fd = WAKE_FD.load(Ordering::Acquire);
+        // SAFETY: write() is async-signal-safe, and the buffer is valid.
+        unsafe { libc::write(fd, [0u8].as_ptr() as *const _, 1) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Node;
+
+    use std::sync::Arc;
+
+    // The default context is shared by the whole process, so it is tested in a single test.
+    #[test]
+    fn test_default_context() -> Result<(), RclReturnCode> {
+        let node = Node::builder().name("my_node").build().unwrap();
+        #[cfg(unix)]
+        assert!(!signal_handler::is_installed());
+        let context = default_context()?;
+        assert!(Arc::ptr_eq(&context.handle, &node.context));
+        assert!(Arc::ptr_eq(&default_context()?.handle, &context.handle));
+        assert!(matches!(
+            init_default_context([], true),
+            Err(RclReturnCode::RclError(RclErrorCode::AlreadyInit))
+        ));
+        assert!(context.ok());
+
+        // The signal handler shuts the default context down, which wakes up the spin loop.
+        #[cfg(unix)]
+        {
+            use crate::spin_once;
+            use std::time::{Duration, Instant};
+
+            assert!(signal_handler::is_installed());
+            let start = Instant::now();
+            // SAFETY: No preconditions for this function.
+            unsafe { libc::raise(libc::SIGINT) };
+            spin_once(&node, Some(Duration::from_secs(10)))?;
+            assert!(start.elapsed() < Duration::from_secs(10));
+            assert!(!context.ok());
+        }
+        Ok(())
+    }
+}
@@ -435,6 +435,10 @@ pub enum RclReturnCode {
     LifecycleError(LifecycleErrorCode),
     /// Unrecognized/unimplemented error code
     UnknownError(i32),
+    /// A fallible callback returned an error, see [`CallbackErrorPolicy::StopSpinning`][1]
+    ///
+    /// [1]: crate::CallbackErrorPolicy::StopSpinning
+    CallbackError(String),
 }
 
 impl From<i32> for RclReturnCode {
@@ -583,6 +587,9 @@ impl Display for RclReturnCode {
             Self::UnknownError(unknown_err) => {
                 write!(f, "RclReturnCode: Unknown error code -> `{}`", unknown_err)
             }
+            Self::CallbackError(callback_err) => {
+                write!(f, "RclReturnCode: Callback failed -> `{}`", callback_err)
+            }
         }
     }
 }
@@ -0,0 +1,105 @@
+//! Hooks for making calls into `rcl` fail, for testing error handling.
+//!
+//! This module is only available with the `fault-injection` feature, which is meant to be enabled
+//! in tests only.
+//!
+//! Faults are registered per thread, so tests that run in parallel do not affect each other.
+//! A call that has a fault injected returns the given error code without calling into `rcl`.
+//!
+//! # Example
+//! ```ignore
+//! use rclrs::fault_injection::{inject_fault, FaultPoint};
+//! use rclrs::{RclReturnCode, SubscriberErrorCode};
+//!
+//! // Simulate a spurious wakeup on the first take.
+//! inject_fault(FaultPoint::Take, 1, SubscriberErrorCode::SubscriptionTakeFailed as i32);
+//! assert!(subscription.take().is_err());
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A call into `rcl` whose result can be replaced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FaultPoint {
+    /// `rcl_take()`, used when taking a message from a subscription.
+    Take,
+    /// `rcl_publish()`, used when publishing a message.
+    Publish,
+    /// `rcl_wait()`, used when waiting on a wait set.
+    Wait,
+}
+
+#[derive(Default)]
+struct FaultState {
+    call_count: usize,
+    // Maps call numbers to the error codes they return.
+    faults: HashMap<usize, i32>,
+}
+
+thread_local! {
+    static FAULT_STATES: RefCell<HashMap<FaultPoint, FaultState>> = RefCell::new(HashMap::new());
+}
+
+/// Makes a call on the current thread return the given error code.
+///
+/// Calls are numbered starting from 1 for each fault point, and counted since the last
+/// [`reset`]. The error code is a raw `rcl` return code, e.g. `RclReturnCode::Timeout` is `2`,
+/// and the codes of the more specific error enums can be obtained by casting them to `i32`.
+///
+/// # Panics
+/// When the error code is `0`, i.e. `RCL_RET_OK`.
+pub fn inject_fault(point: FaultPoint, call_number: usize, error_code: i32) {
+    assert_ne!(error_code, 0, "Injected faults must be errors");
+    FAULT_STATES.with(|states| {
+        states
+            .borrow_mut()
+            .entry(point)
+            .or_default()
+            .faults
+            .insert(call_number, error_code);
+    });
+}
+
+/// Returns the number of calls on the current thread since the last [`reset`].
+pub fn call_count(point: FaultPoint) -> usize {
+    FAULT_STATES.with(|states| {
+        states
+            .borrow()
+            .get(&point)
+            .map_or(0, |state| state.call_count)
+    })
+}
+
+/// Removes all injected faults and resets the call counts of the current thread.
+pub fn reset() {
+    FAULT_STATES.with(|states| states.borrow_mut().clear());
+}
+
+/// Counts a call, and returns the error code to return instead of calling into `rcl`, if any.
+pub(crate) fn intercept(point: FaultPoint) -> Option<i32> {
+    FAULT_STATES.with(|states| {
+        let mut states = states.borrow_mut();
+        let state = states.entry(point).or_default();
+        state.call_count += 1;
+        state.faults.remove(&state.call_count)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_fault() {
+        reset();
+        inject_fault(FaultPoint::Take, 2, 2);
+        assert_eq!(intercept(FaultPoint::Take), None);
+        assert_eq!(intercept(FaultPoint::Publish), None);
+        assert_eq!(intercept(FaultPoint::Take), Some(2));
+        assert_eq!(intercept(FaultPoint::Take), None);
+        assert_eq!(call_count(FaultPoint::Take), 3);
+        reset();
+        assert_eq!(call_count(FaultPoint::Take), 0);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::error::RclReturnCode;
+use crate::rcl_bindings::rcl_context_t;
+use crate::{Context, GuardCondition, Node, Waitable};
+
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use parking_lot::Mutex;
+
+/// A [`Waitable`] that runs a callback when a file descriptor becomes readable.
+///
+/// This integrates the I/O of e.g. serial ports, CAN sockets or network sockets into the spin
+/// loop: a background thread polls the file descriptor and triggers a guard condition when it
+/// is readable, and the callback runs on the thread that spins the node. The callback is
+/// expected to read the available data. It also runs when the file descriptor reports an error
+/// or a hang-up, which can be detected by the read failing. An error returned by the callback is
+/// returned by [`spin_once`][1] and [`spin`][2]. Until the callback has run, the file
+/// descriptor is not polled again, so the callback runs once per readiness even though `poll()`
+/// is level-triggered.
+///
+/// The file descriptor is not owned, i.e. it must stay open for as long as the waitable exists
+/// and is not closed when the waitable is dropped. Dropping the waitable stops the background
+/// thread.
+///
+/// # Example
+/// ```ignore
+/// let port = File::open("/dev/ttyUSB0")?;
+/// let fd = port.as_raw_fd();
+/// let waitable = Arc::new(FdWaitable::new(&context, fd, move || {
+///     let mut buffer = [0; 256];
+///     let n = (&port)
+///         .read(&mut buffer)
+///         .map_err(|error| RclReturnCode::CallbackError(error.to_string()))?;
+///     println!("Read {:?}", &buffer[..n]);
+///     Ok(())
+/// })?);
+/// node.add_waitable(&waitable)?;
+/// rclrs::spin(&node)?;
+/// ```
+///
+/// [1]: crate::spin_once
+/// [2]: crate::spin
+pub struct FdWaitable {
+    guard_condition: Arc<GuardCondition>,
+    callback: Mutex<Box<dyn FnMut() -> Result<(), RclReturnCode> + 'static>>,
+    shared: Arc<PollerState>,
+    wake_sender: UnixStream,
+    poller: Option<JoinHandle<()>>,
+}
+
+// The state that is shared with the poller thread.
+struct PollerState {
+    // Whether the file descriptor should be polled, i.e. the callback has run since the last
+    // trigger.
+    armed: AtomicBool,
+    stop: AtomicBool,
+}
+
+impl FdWaitable {
+    /// Creates a waitable for a file descriptor and starts polling it.
+    pub fn new<F>(context: &Context, fd: RawFd, callback: F) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut() -> Result<(), RclReturnCode> + 'static,
+    {
+        Self::new_for_context_handle(&context.handle, fd, callback)
+    }
+
+    /// Creates a waitable in the context of the node, for adding it to that node.
+    pub fn new_for_node<F>(node: &Node, fd: RawFd, callback: F) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut() -> Result<(), RclReturnCode> + 'static,
+    {
+        Self::new_for_context_handle(&node.context, fd, callback)
+    }
+
+    fn new_for_context_handle<F>(
+        context_handle: &Arc<Mutex<rcl_context_t>>,
+        fd: RawFd,
+        callback: F,
+    ) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut() -> Result<(), RclReturnCode> + 'static,
+    {
+        let guard_condition = Arc::new(GuardCondition::new_for_context_handle(context_handle)?);
+        let (wake_sender, wake_receiver) = UnixStream::pair().map_err(|_| RclReturnCode::Error)?;
+        wake_receiver
+            .set_nonblocking(true)
+            .map_err(|_| RclReturnCode::Error)?;
+        let shared = Arc::new(PollerState {
+            armed: AtomicBool::new(true),
+            stop: AtomicBool::new(false),
+        });
+        let poller = {
+            let guard_condition = Arc::clone(&guard_condition);
+            let shared = Arc::clone(&shared);
+            std::thread::Builder::new()
+                .name(String::from("rclrs_fd_poller"))
+                .spawn(move || poll_fd(fd, wake_receiver, &guard_condition, &shared))
+                .map_err(|_| RclReturnCode::Error)?
+        };
+        Ok(Self {
+            guard_condition,
+            callback: Mutex::new(Box::new(callback)),
+            shared,
+            wake_sender,
+            poller: Some(poller),
+        })
+    }
+
+    // Wakes up the poller thread, so that it notices a change of the shared state.
+    fn wake_poller(&self) {
+        // A full socket buffer means the poller has pending wake-ups already.
+        let _ = (&self.wake_sender).write(&[0]);
+    }
+}
+
+impl Waitable for FdWaitable {
+    fn guard_condition(&self) -> &GuardCondition {
+        &self.guard_condition
+    }
+
+    fn execute(&self) -> Result<(), RclReturnCode> {
+        if self.shared.armed.load(Ordering::Acquire) {
+            // The guard condition was not triggered by the poller.
+            return Ok(());
+        }
+        let result = (*self.callback.lock())();
+        self.shared.armed.store(true, Ordering::Release);
+        self.wake_poller();
+        result
+    }
+}
+
+impl Drop for FdWaitable {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Release);
+        self.wake_poller();
+        if let Some(poller) = self.poller.take() {
+            let _ = poller.join();
+        }
+    }
+}
+
+// The loop of the poller thread.
+fn poll_fd(
+    fd: RawFd,
+    mut wake_receiver: UnixStream,
+    guard_condition: &GuardCondition,
+    shared: &PollerState,
+) {
+    let mut pollfds = [
+        libc::pollfd {
+            fd: wake_receiver.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    while !shared.stop.load(Ordering::Acquire) {
+        // Only the wake-up socket is polled while the callback has not run yet.
+        let n_fds = if shared.armed.load(Ordering::Acquire) {
+            2
+        } else {
+            1
+        };
+        // SAFETY: The pointer and length describe a valid array of pollfd structs.
+        let ret = unsafe { libc::poll(pollfds.as_mut_ptr(), n_fds, -1) };
+        if ret < 0 {
+            if std::io::Error::last_os_error().kind() == ErrorKind::Interrupted {
+                continue;
+            }
+            return;
+        }
+        if pollfds[0].revents != 0 {
+            let mut buffer = [0; 64];
+            while let Ok(1..) = wake_receiver.read(&mut buffer) {}
+        }
+        if n_fds == 2 && pollfds[1].revents != 0 {
+            shared.armed.store(false, Ordering::Release);
+            if guard_condition.trigger().is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{spin_once, RclReturnCode};
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_fd_waitable_executes_when_readable() -> Result<(), RclReturnCode> {
+        let context = Context::new([])?;
+        let mut node = context.create_node("fd_waitable")?;
+        let (mut sender, mut receiver) = UnixStream::pair().unwrap();
+        receiver.set_nonblocking(true).unwrap();
+        let fd = receiver.as_raw_fd();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let callback_received = Arc::clone(&received);
+        let waitable = Arc::new(FdWaitable::new(&context, fd, move || {
+            let mut buffer = [0; 16];
+            while let Ok(n @ 1..) = receiver.read(&mut buffer) {
+                callback_received.lock().extend_from_slice(&buffer[..n]);
+            }
+            Ok(())
+        })?);
+        node.add_waitable(&waitable)?;
+
+        sender.write_all(b"ping").unwrap();
+        spin_once(&node, Some(Duration::from_secs(10)))?;
+        assert_eq!(*received.lock(), b"ping");
+
+        // The callback read all data, so the file descriptor is not readable anymore.
+        assert!(matches!(
+            spin_once(&node, Some(Duration::from_millis(100))),
+            Err(RclReturnCode::Timeout)
+        ));
+
+        // The waitable is armed again after its callback has run.
+        sender.write_all(b"pong").unwrap();
+        spin_once(&node, Some(Duration::from_secs(10)))?;
+        assert_eq!(*received.lock(), b"pingpong");
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::error::ToResult;
+use crate::rcl_bindings::rcl_ret_t;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// The number of rcl entities of one kind that have been initialized, but not finalized yet.
+// See `testing::live_handles()`.
+pub(crate) struct HandleCounter(AtomicUsize);
+
+pub(crate) static LIVE_NODES: HandleCounter = HandleCounter::new();
+pub(crate) static LIVE_PUBLISHERS: HandleCounter = HandleCounter::new();
+pub(crate) static LIVE_SUBSCRIPTIONS: HandleCounter = HandleCounter::new();
+
+impl HandleCounter {
+    const fn new() -> Self {
+        Self(AtomicUsize::new(0))
+    }
+
+    pub(crate) fn initialized(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn finalized(&self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Checks the result of a `rcl_*_fini()` call in a `Drop` impl.
+//
+// Errors can't be returned from `drop()`, so they only fail a debug assertion. This is skipped
+// while the thread is panicking, since panicking again would abort the process.
+pub(crate) fn check_fini(ret: rcl_ret_t, entity: &str) {
+    if cfg!(debug_assertions) && !std::thread::panicking() {
+        if let Err(e) = ret.ok() {
+            panic!("Failed to finalize {}: {}", entity, e);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::error::{RclReturnCode, SubscriberErrorCode};
+use crate::qos::QoSProfile;
+use crate::rcl_bindings::*;
+use crate::{
+    Node, PublisherHandle, SerializedMessage, SubscriptionBase, SubscriptionHandle,
+    SubscriptionOptions,
+};
+
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+
+// Looks up the type support of a message type in the registry of `rosidl_runtime_rs`.
+fn get_type_support(
+    type_name: &str,
+) -> Result<*const rosidl_message_type_support_t, RclReturnCode> {
+    rosidl_runtime_rs::get_message_type_support(type_name)
+        .map(|type_support| type_support as *const rosidl_message_type_support_t)
+        .ok_or(RclReturnCode::InvalidArgument)
+}
+
+/// A publisher for a message type that is only known at runtime.
+///
+/// This is the counterpart of [`GenericSubscription`], for tools such as relays that are
+/// configured with the message type when they are launched. Messages are published in the
+/// serialized format of the middleware, see [`SerializedMessage`].
+///
+/// The message type is looked up by its full name, e.g. `std_msgs/msg/String`, in the registry of
+/// `rosidl_runtime_rs`. This requires the `registry` feature, and the crate of the message type
+/// must be linked into the binary with its `registry` feature enabled.
+pub struct GenericPublisher {
+    handle: PublisherHandle,
+    type_name: String,
+}
+
+impl GenericPublisher {
+    /// Creates a new `GenericPublisher`.
+    ///
+    /// Returns [`RclReturnCode::InvalidArgument`] when the message type is not registered.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new(
+        node: &Node,
+        topic: &str,
+        type_name: &str,
+        qos: QoSProfile,
+    ) -> Result<Self, RclReturnCode> {
+        let type_support = get_type_support(type_name)?;
+        Ok(Self {
+            handle: PublisherHandle::new(node, topic, type_support, qos)?,
+            type_name: type_name.to_owned(),
+        })
+    }
+
+    /// Publishes a message in the serialized format of the middleware.
+    ///
+    /// The message is not checked to be of the type of the publisher.
+    pub fn publish(&self, message: &SerializedMessage) -> Result<(), RclReturnCode> {
+        self.handle.publish_serialized(message)
+    }
+
+    /// Returns the full name of the message type, e.g. `std_msgs/msg/String`.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the fully qualified topic name of the publisher, after remapping.
+    pub fn topic_name(&self) -> String {
+        self.handle.topic_name()
+    }
+
+    /// Returns the number of subscriptions that are currently matched with this publisher.
+    pub fn get_subscription_count(&self) -> Result<usize, RclReturnCode> {
+        self.handle.get_subscription_count()
+    }
+}
+
+type GenericCallback = Box<dyn FnMut(&SerializedMessage) -> Result<(), RclReturnCode> + 'static>;
+
+/// A subscription for a message type that is only known at runtime.
+///
+/// The callback receives the messages in the serialized format of the middleware, see
+/// [`SerializedMessage`]. An error returned by the callback is returned by [`spin_once`][1] and
+/// [`spin`][2].
+///
+/// The message type is looked up in the same way as for a [`GenericPublisher`].
+///
+/// [1]: crate::spin_once
+/// [2]: crate::spin
+pub struct GenericSubscription {
+    handle: Arc<SubscriptionHandle>,
+    callback: Mutex<GenericCallback>,
+    // The buffer is reused for all messages.
+    buffer: Mutex<SerializedMessage>,
+    type_name: String,
+}
+
+impl GenericSubscription {
+    /// Creates a new `GenericSubscription`, which is spun by the node as long as it is alive.
+    ///
+    /// Returns [`RclReturnCode::InvalidArgument`] when the message type is not registered.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new<F>(
+        node: &mut Node,
+        topic: &str,
+        type_name: &str,
+        qos: QoSProfile,
+        callback: F,
+    ) -> Result<Arc<Self>, RclReturnCode>
+    where
+        F: FnMut(&SerializedMessage) -> Result<(), RclReturnCode> + 'static,
+    {
+        let type_support = get_type_support(type_name)?;
+        let subscription = Arc::new(Self {
+            handle: SubscriptionHandle::new(
+                node,
+                topic,
+                type_support,
+                qos,
+                &SubscriptionOptions::default(),
+            )?,
+            callback: Mutex::new(Box::new(callback)),
+            buffer: Mutex::new(SerializedMessage::new()?),
+            type_name: type_name.to_owned(),
+        });
+        node.subscriptions
+            .push(Arc::downgrade(&subscription) as Weak<dyn SubscriptionBase>);
+        Ok(subscription)
+    }
+
+    /// Returns the full name of the message type, e.g. `std_msgs/msg/String`.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the fully qualified topic name of the subscription, after remapping.
+    pub fn topic_name(&self) -> String {
+        self.handle.topic_name()
+    }
+}
+
+impl SubscriptionBase for GenericSubscription {
+    fn handle(&self) -> &SubscriptionHandle {
+        &self.handle
+    }
+
+    fn execute(&self) -> Result<(), RclReturnCode> {
+        let buffer = &mut *self.buffer.lock();
+        match self.handle.take_serialized(buffer) {
+            Ok(()) => (*self.callback.lock())(buffer),
+            // Spurious wakeup, as in `Subscription::execute()`.
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
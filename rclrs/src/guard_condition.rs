@@ -0,0 +1,131 @@
+use crate::error::{RclReturnCode, ToResult};
+use crate::rcl_bindings::*;
+use crate::Context;
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+/// A condition that user code can trigger to wake up a [`WaitSet`][1].
+///
+/// Guard conditions connect event sources outside of ROS, e.g. a thread that reads from a serial
+/// port, to the spin loop. They are usually owned by a [`Waitable`][2]. Triggering a guard
+/// condition makes the next wait on a wait set that contains it return, even when it is already
+/// waiting. The trigger is consumed by that wait, so several triggers before a wait only wake
+/// it up once.
+///
+/// Guard conditions can be shared with and triggered from other threads.
+///
+/// [1]: crate::WaitSet
+/// [2]: crate::Waitable
+pub struct GuardCondition {
+    pub(crate) handle: Mutex<rcl_guard_condition_t>,
+    // Used to ensure the context is alive while the guard condition is alive.
+    pub(crate) context_handle: Arc<Mutex<rcl_context_t>>,
+}
+
+// SAFETY: rcl guard conditions are not bound to the thread that created them, and the handle is
+// only accessed through the mutex. The context is only used to keep it alive.
+unsafe impl Send for GuardCondition {}
+// SAFETY: See above.
+unsafe impl Sync for GuardCondition {}
+
+impl Drop for GuardCondition {
+    fn drop(&mut self) {
+        // SAFETY: No preconditions for this function (besides passing in a valid guard condition).
+        unsafe {
+            rcl_guard_condition_fini(self.handle.get_mut() as *mut _);
+        }
+    }
+}
+
+impl GuardCondition {
+    /// Creates a new guard condition in the given context.
+    pub fn new(context: &Context) -> Result<Self, RclReturnCode> {
+        Self::new_for_context_handle(&context.handle)
+    }
+
+    // Creates a guard condition for the context of a node, which does not have a `Context` of its
+    // own.
+    pub(crate) fn new_for_context_handle(
+        context_handle: &Arc<Mutex<rcl_context_t>>,
+    ) -> Result<Self, RclReturnCode> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut guard_condition = unsafe { rcl_get_zero_initialized_guard_condition() };
+        unsafe {
+            // SAFETY: The guard condition is zero-initialized as expected by this function, and
+            // the context is valid. The context is kept alive because it is co-owned by the
+            // guard condition.
+            rcl_guard_condition_init(
+                &mut guard_condition as *mut _,
+                &mut *context_handle.lock() as *mut _,
+                rcl_guard_condition_get_default_options(),
+            )
+            .ok()?;
+        }
+        Ok(Self {
+            handle: Mutex::new(guard_condition),
+            context_handle: context_handle.clone(),
+        })
+    }
+
+    /// Wakes up the wait sets that contain this guard condition.
+    pub fn trigger(&self) -> Result<(), RclReturnCode> {
+        // SAFETY: No preconditions for this function (besides passing in a valid guard condition).
+        unsafe { rcl_trigger_guard_condition(&mut *self.handle.lock() as *mut _) }.ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{spin_once, Waitable};
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    // Counts how often it was executed.
+    struct CountingWaitable {
+        guard_condition: GuardCondition,
+        executions: AtomicUsize,
+    }
+
+    impl Waitable for CountingWaitable {
+        fn guard_condition(&self) -> &GuardCondition {
+            &self.guard_condition
+        }
+
+        fn execute(&self) -> Result<(), RclReturnCode> {
+            self.executions.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trigger_wakes_up_spin() -> Result<(), RclReturnCode> {
+        let context = Context::new([])?;
+        let mut node = context.create_node("guard_condition")?;
+        let waitable = Arc::new(CountingWaitable {
+            guard_condition: GuardCondition::new(&context)?,
+            executions: AtomicUsize::new(0),
+        });
+        node.add_waitable(&waitable)?;
+
+        assert!(matches!(
+            spin_once(&node, Some(Duration::from_millis(100))),
+            Err(RclReturnCode::Timeout)
+        ));
+        assert_eq!(waitable.executions.load(Ordering::Relaxed), 0);
+
+        // Trigger the guard condition while the spin is waiting.
+        let trigger_waitable = Arc::clone(&waitable);
+        let trigger_thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            trigger_waitable.guard_condition.trigger()
+        });
+        spin_once(&node, Some(Duration::from_secs(10)))?;
+        trigger_thread.join().unwrap()?;
+        assert_eq!(waitable.executions.load(Ordering::Relaxed), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::qos::{
+    QoSDuration, QoSHistoryPolicy, QoSLivelinessPolicy, QoSProfile, QoSReliabilityPolicy,
+    QOS_PROFILE_DEFAULT,
+};
+use crate::{Node, Publisher, RclReturnCode, Subscription};
+
+use std::boxed::Box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use rosidl_runtime_rs::Message;
+
+/// Publishes heartbeat messages that show that a node is still working.
+///
+/// Unlike a heartbeat that is sent from a dedicated thread, [`beat`][1] is meant to be called from
+/// the node's own processing loop or callbacks. This way, a [`HeartbeatMonitor`] can detect a node
+/// that has stopped working even though its process is still alive.
+///
+/// Any message type can be used as the heartbeat, e.g. `std_msgs::msg::Empty`. The published
+/// messages are default-constructed.
+///
+/// The publisher uses a QoS profile with a deadline and lifespan equal to the heartbeat period,
+/// and manual-by-topic liveliness, so that the middleware can also report missed heartbeats.
+///
+/// [1]: HeartbeatPublisher::beat
+pub struct HeartbeatPublisher<T>
+where
+    T: Message,
+{
+    publisher: Publisher<T>,
+}
+
+impl<T> HeartbeatPublisher<T>
+where
+    T: Message,
+{
+    /// Creates a new heartbeat publisher, which is expected to call [`beat`][1] at least once
+    /// every `period`.
+    ///
+    /// [1]: HeartbeatPublisher::beat
+    pub fn new(node: &Node, topic: &str, period: Duration) -> Result<Self, RclReturnCode> {
+        let qos = QoSProfile {
+            lifespan: QoSDuration::Custom(period),
+            reliability: QoSReliabilityPolicy::Reliable,
+            ..heartbeat_qos(period)
+        };
+        Ok(Self {
+            publisher: node.create_publisher(topic, qos)?,
+        })
+    }
+
+    /// Publishes a heartbeat.
+    pub fn beat(&self) -> Result<(), RclReturnCode> {
+        self.publisher.publish(T::default())
+    }
+}
+
+/// Monitors the heartbeats published by a [`HeartbeatPublisher`].
+///
+/// The monitor considers the remote node alive while it receives heartbeats at least once every
+/// `timeout`. Whenever this status changes, the monitor's callback is invoked with the new status.
+///
+/// Heartbeats are received and missing heartbeats are detected by [`spin_once`][1] and
+/// [`spin`][2], which wake up when the timeout expires, through the [message timeout][3] of the
+/// monitor's subscription. The status can also be checked in between with [`check`][4].
+///
+/// [1]: crate::spin_once
+/// [2]: crate::spin
+/// [3]: Subscription::set_message_timeout
+/// [4]: HeartbeatMonitor::check
+pub struct HeartbeatMonitor<T>
+where
+    T: Message,
+{
+    _subscription: Arc<Subscription<T>>,
+    state: Arc<Mutex<HeartbeatState>>,
+    timeout: Duration,
+}
+
+struct HeartbeatState {
+    last_heartbeat: Option<Instant>,
+    alive: bool,
+    callback: Box<dyn FnMut(bool) + 'static>,
+}
+
+impl<T> HeartbeatMonitor<T>
+where
+    T: Message,
+{
+    /// Creates a new heartbeat monitor.
+    ///
+    /// The remote node is considered dead until the first heartbeat has been received.
+    ///
+    /// The callback is called with `true` when the remote node becomes alive, and with `false`
+    /// when it stops sending heartbeats. It must not call any methods of the monitor.
+    pub fn new<F>(
+        node: &mut Node,
+        topic: &str,
+        timeout: Duration,
+        callback: F,
+    ) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut(bool) + 'static,
+    {
+        let state = Arc::new(Mutex::new(HeartbeatState {
+            last_heartbeat: None,
+            alive: false,
+            callback: Box::new(callback),
+        }));
+        let subscription_state = Arc::clone(&state);
+        let subscription =
+            node.create_subscription(topic, heartbeat_qos(timeout), move |_msg: T| {
+                let state = &mut *subscription_state.lock();
+                state.last_heartbeat = Some(Instant::now());
+                if !state.alive {
+                    state.alive = true;
+                    (state.callback)(true);
+                }
+            })?;
+        let timeout_state = Arc::clone(&state);
+        subscription.set_message_timeout(timeout, move || {
+            let state = &mut *timeout_state.lock();
+            if state.alive {
+                state.alive = false;
+                (state.callback)(false);
+            }
+        });
+        Ok(Self {
+            _subscription: subscription,
+            state,
+            timeout,
+        })
+    }
+
+    /// Checks whether the remote node is alive, and invokes the callback if it just stopped
+    /// sending heartbeats.
+    ///
+    /// Spinning the node does this as well, so this is only needed for an up-to-date status
+    /// between spins.
+    pub fn check(&self) -> bool {
+        let state = &mut *self.state.lock();
+        let timed_out = match state.last_heartbeat {
+            Some(last_heartbeat) => last_heartbeat.elapsed() > self.timeout,
+            None => true,
+        };
+        if state.alive && timed_out {
+            state.alive = false;
+            (state.callback)(false);
+        }
+        state.alive
+    }
+
+    /// Returns the time at which the last heartbeat was received, if any.
+    pub fn last_heartbeat(&self) -> Option<Instant> {
+        self.state.lock().last_heartbeat
+    }
+}
+
+fn heartbeat_qos(period: Duration) -> QoSProfile {
+    QoSProfile {
+        history: QoSHistoryPolicy::KeepLast { depth: 1 },
+        reliability: QoSReliabilityPolicy::BestEffort,
+        deadline: QoSDuration::Custom(period),
+        liveliness: QoSLivelinessPolicy::ManualByTopic,
+        liveliness_lease_duration: QoSDuration::Custom(period),
+        ..QOS_PROFILE_DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestFixture;
+
+    use std_msgs::msg::Empty;
+
+    #[test]
+    fn test_spin_reports_liveliness_changes() -> Result<(), RclReturnCode> {
+        let mut fixture = TestFixture::new("heartbeat")?;
+        let heartbeat = HeartbeatPublisher::<Empty>::new(
+            &fixture.publisher_node,
+            "heartbeat",
+            Duration::from_millis(100),
+        )?;
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let callback_statuses = Arc::clone(&statuses);
+        let _monitor = HeartbeatMonitor::<Empty>::new(
+            &mut fixture.subscriber_node,
+            "heartbeat",
+            Duration::from_millis(300),
+            move |alive| callback_statuses.lock().push(alive),
+        )?;
+        heartbeat
+            .publisher
+            .wait_for_subscribers(1, Some(Duration::from_secs(10)))?;
+
+        heartbeat.beat()?;
+        crate::spin_once(&fixture.subscriber_node, Some(Duration::from_secs(10)))?;
+        assert_eq!(*statuses.lock(), [true]);
+
+        // Without further heartbeats, the spin wakes up when the timeout expires, and reports the
+        // loss of liveliness without any call to check().
+        let start = Instant::now();
+        while statuses.lock().len() < 2 {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            crate::spin_once(&fixture.subscriber_node, Some(Duration::from_secs(10)))?;
+        }
+        assert_eq!(*statuses.lock(), [true, false]);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        Ok(())
+    }
+}
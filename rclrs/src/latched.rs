@@ -0,0 +1,89 @@
+use crate::qos::QoSProfile;
+use crate::{MessageCow, Node, Publisher, RclReturnCode};
+
+use parking_lot::Mutex;
+
+use rosidl_runtime_rs::Message;
+
+/// A publisher that makes its last message available to late-joining subscriptions.
+///
+/// The publisher uses [`QoSProfile::transient_local_latched`], so with middlewares that support
+/// transient local durability, late joiners that also request it receive the last message
+/// automatically.
+///
+/// As a fallback for middlewares or subscriptions without durability support, the last message
+/// is also kept in software. Calling [`republish_to_new_subscribers`][1] periodically, e.g. after
+/// each call to [`spin_once`][2], publishes it again whenever the number of matched
+/// subscriptions has increased. Note that existing subscriptions receive the message again too.
+///
+/// [1]: LatchedPublisher::republish_to_new_subscribers
+/// [2]: crate::spin_once
+pub struct LatchedPublisher<T>
+where
+    T: Message,
+{
+    publisher: Publisher<T>,
+    state: Mutex<LatchedState<T>>,
+}
+
+struct LatchedState<T> {
+    last_message: Option<T>,
+    subscription_count: usize,
+}
+
+impl<T> LatchedPublisher<T>
+where
+    T: Message,
+{
+    /// Creates a new `LatchedPublisher`.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new(node: &Node, topic: &str) -> Result<Self, RclReturnCode> {
+        Ok(Self {
+            publisher: node.create_publisher(topic, QoSProfile::transient_local_latched(1))?,
+            state: Mutex::new(LatchedState {
+                last_message: None,
+                subscription_count: 0,
+            }),
+        })
+    }
+
+    /// Publishes a message and keeps a copy of it for late joiners.
+    pub fn publish<'a, M: MessageCow<'a, T>>(&self, message: M) -> Result<(), RclReturnCode> {
+        let message = message.into_cow().into_owned();
+        self.publisher.publish(&message)?;
+        let state = &mut *self.state.lock();
+        state.last_message = Some(message);
+        state.subscription_count = self.publisher.get_subscription_count()?;
+        Ok(())
+    }
+
+    /// Publishes the last message again if new subscriptions have been matched since the last
+    /// publish.
+    ///
+    /// Returns `true` if the message was published again.
+    pub fn republish_to_new_subscribers(&self) -> Result<bool, RclReturnCode> {
+        let state = &mut *self.state.lock();
+        let subscription_count = self.publisher.get_subscription_count()?;
+        let has_new_subscribers = subscription_count > state.subscription_count;
+        state.subscription_count = subscription_count;
+        match &state.last_message {
+            Some(message) if has_new_subscribers => {
+                self.publisher.publish(message)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Returns the last published message, if any.
+    pub fn last_message(&self) -> Option<T> {
+        self.state.lock().last_message.clone()
+    }
+
+    /// Returns the underlying publisher.
+    pub fn publisher(&self) -> &Publisher<T> {
+        &self.publisher
+    }
+}
@@ -9,22 +9,82 @@ extern crate parking_lot;
 extern crate rosidl_runtime_rs;
 extern crate std;
 
+mod bandwidth;
+#[cfg(feature = "bench-utils")]
+pub mod bench_utils;
+mod clock_offset;
+mod compat;
+mod container;
 mod context;
+mod deadline;
+mod default_context;
 mod error;
+#[cfg(feature = "fault-injection")]
+pub mod fault_injection;
+#[cfg(unix)]
+mod fd_waitable;
+mod finalization;
+#[cfg(feature = "registry")]
+mod generic;
+mod guard_condition;
+mod heartbeat;
+mod latched;
+#[cfg(feature = "log")]
+mod log_bridge;
+mod logging;
+mod metrics;
 mod node;
+mod pipeline;
+mod provenance;
 mod qos;
+mod robot_namespace;
+mod serialized;
+mod spin_options;
+pub mod testing;
+mod timed_buffer;
+mod topic;
+mod type_hash;
 mod wait;
+mod watchdog;
 
 mod rcl_bindings;
 
+pub use bandwidth::*;
+pub use clock_offset::*;
+pub use container::*;
 pub use context::*;
+pub use deadline::*;
+pub use default_context::*;
 pub use error::*;
+#[cfg(unix)]
+pub use fd_waitable::*;
+#[cfg(feature = "registry")]
+pub use generic::*;
+pub use guard_condition::*;
+pub use heartbeat::*;
+pub use latched::*;
+#[cfg(feature = "log")]
+pub use log_bridge::*;
+pub use metrics::*;
 pub use node::*;
+pub use pipeline::*;
+pub use provenance::*;
 pub use qos::*;
+pub use robot_namespace::*;
+pub use serialized::*;
+pub use spin_options::*;
+pub use timed_buffer::*;
+pub use topic::*;
+pub use type_hash::*;
 pub use wait::*;
 
-use rcl_bindings::rcl_context_is_valid;
-use std::time::Duration;
+use context::Interrupt;
+use rcl_bindings::{rcl_context_is_valid, rcl_context_t};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use watchdog::WatchGuard;
+
+use parking_lot::Mutex;
 
 /// Polls the node for new messages and executes the corresponding callbacks.
 ///
@@ -36,31 +96,197 @@ use std::time::Duration;
 ///
 /// [1]: crate::SubscriberErrorCode
 pub fn spin_once(node: &Node, timeout: Option<Duration>) -> Result<(), RclReturnCode> {
-    let live_subscriptions = node.live_subscriptions();
-    let ctx = Context {
-        handle: node.context.clone(),
-    };
-    let mut wait_set = WaitSet::new(live_subscriptions.len(), &ctx)?;
+    spin_once_with_options(node, timeout, &SpinOptions::default())
+}
+
+/// Like [`spin_once`], but with non-default [`SpinOptions`].
+pub fn spin_once_with_options(
+    node: &Node,
+    timeout: Option<Duration>,
+    options: &SpinOptions,
+) -> Result<(), RclReturnCode> {
+    spin_entities_once(
+        &node.context,
+        &node.interrupt,
+        node.live_subscriptions(),
+        node.live_waitables(),
+        timeout,
+        options,
+    )
+}
+
+// Waits on the given entities and executes the ready ones, see `spin_once()`.
+pub(crate) fn spin_entities_once(
+    context: &Arc<Mutex<rcl_context_t>>,
+    interrupt: &Arc<Interrupt>,
+    live_subscriptions: Vec<Arc<dyn SubscriptionBase>>,
+    live_waitables: Vec<Arc<dyn Waitable>>,
+    timeout: Option<Duration>,
+    options: &SpinOptions,
+) -> Result<(), RclReturnCode> {
+    // SAFETY: No preconditions for this function.
+    if !unsafe { rcl_context_is_valid(&mut *context.lock() as *mut _) } {
+        // No wait set can be created in a context that was shut down, e.g. by a signal handler
+        // after `spin()` checked the context.
+        return Ok(());
+    }
+    let mut wait_set = WaitSet::new_for_context_handle(
+        live_subscriptions.len(),
+        live_waitables.len() + 1,
+        context,
+    )?;
 
     for live_subscription in &live_subscriptions {
         wait_set.add_subscription(live_subscription.clone())?;
     }
+    for live_waitable in live_waitables {
+        wait_set.add_waitable(live_waitable)?;
+    }
+    // The interrupt wakes up the wait when the context is shut down.
+    wait_set.add_waitable(interrupt.clone())?;
 
-    let ready_entities = wait_set.wait(timeout)?;
-    for ready_subscription in ready_entities.subscriptions {
-        ready_subscription.execute()?;
+    // Wake up in time for the earliest message timeout of the subscriptions.
+    let wait_timeout = match live_subscriptions
+        .iter()
+        .filter_map(|subscription| subscription.handle().message_timeout_deadline())
+        .min()
+    {
+        Some(deadline) => {
+            let until_deadline = deadline.saturating_duration_since(Instant::now());
+            Some(timeout.map_or(until_deadline, |timeout| timeout.min(until_deadline)))
+        }
+        None => timeout,
+    };
+    // Do not block while there are queued messages to process.
+    let queued_subscriptions = subscriptions_with_queued_messages(&live_subscriptions);
+    let wait_timeout = if queued_subscriptions.is_empty() {
+        wait_timeout
+    } else {
+        Some(Duration::ZERO)
+    };
+    let wait_result = wait_set.wait(wait_timeout);
+    let mut ready_time = Instant::now();
+    let mut message_timeout_expired = false;
+    for live_subscription in &live_subscriptions {
+        message_timeout_expired |= live_subscription.handle().check_message_timeout(ready_time);
+    }
+    let (mut ready_subscriptions, ready_waitables) = match wait_result {
+        Ok(ready_entities) => (ready_entities.subscriptions, ready_entities.waitables),
+        Err(RclReturnCode::Timeout) if message_timeout_expired => return Ok(()),
+        Err(RclReturnCode::Timeout) if !queued_subscriptions.is_empty() => (Vec::new(), Vec::new()),
+        Err(e) => return Err(e),
+    };
+    for ready_waitable in ready_waitables {
+        let _watch = options.callback_timeout.map(|timeout| {
+            WatchGuard::new(
+                timeout,
+                CallbackEntity::Waitable,
+                options.warning_handler.clone(),
+            )
+        });
+        ready_waitable.execute()?;
+    }
+    merge_subscriptions(&mut ready_subscriptions, queued_subscriptions);
+    let budget = options.budget_per_entity.max(1);
+    for round in 1..=budget {
+        // Run the subscriptions whose callback has run least recently first, so that they take
+        // turns across spins.
+        ready_subscriptions
+            .sort_by_key(|subscription| *subscription.handle().last_execution.lock());
+        for ready_subscription in &ready_subscriptions {
+            execute_subscription(ready_subscription, ready_time, options)?;
+        }
+        if round == budget || ready_subscriptions.is_empty() {
+            break;
+        }
+        // Give the subscriptions that still have messages another turn.
+        wait_set.clear();
+        for ready_subscription in &ready_subscriptions {
+            wait_set.add_subscription(ready_subscription.clone())?;
+        }
+        let queued_subscriptions = subscriptions_with_queued_messages(&ready_subscriptions);
+        ready_subscriptions = match wait_set.wait(Some(Duration::ZERO)) {
+            Ok(ready_entities) => ready_entities.subscriptions,
+            Err(RclReturnCode::Timeout) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        merge_subscriptions(&mut ready_subscriptions, queued_subscriptions);
+        if ready_subscriptions.is_empty() {
+            break;
+        }
+        ready_time = Instant::now();
     }
 
     Ok(())
 }
 
+fn subscriptions_with_queued_messages(
+    subscriptions: &[Arc<dyn SubscriptionBase>],
+) -> Vec<Arc<dyn SubscriptionBase>> {
+    subscriptions
+        .iter()
+        .filter(|subscription| subscription.has_queued_messages())
+        .cloned()
+        .collect()
+}
+
+// Adds the subscriptions that are not in the list yet.
+fn merge_subscriptions(
+    subscriptions: &mut Vec<Arc<dyn SubscriptionBase>>,
+    others: Vec<Arc<dyn SubscriptionBase>>,
+) {
+    for other in others {
+        if !subscriptions
+            .iter()
+            .any(|subscription| Arc::ptr_eq(subscription, &other))
+        {
+            subscriptions.push(other);
+        }
+    }
+}
+
+// Runs the callback of a ready subscription and records its metrics.
+fn execute_subscription(
+    subscription: &Arc<dyn SubscriptionBase>,
+    ready_time: Instant,
+    options: &SpinOptions,
+) -> Result<(), RclReturnCode> {
+    let start_time = Instant::now();
+    let wait_age = start_time - ready_time;
+    if let Some(threshold) = options.starvation_warning_threshold {
+        if wait_age > threshold {
+            options.warn(SpinWarning::Starvation {
+                topic_name: subscription.handle().topic_name(),
+                wait_time: wait_age,
+            });
+        }
+    }
+    let watch = options.callback_timeout.map(|timeout| {
+        let entity = CallbackEntity::Subscription {
+            topic_name: subscription.handle().topic_name(),
+        };
+        WatchGuard::new(timeout, entity, options.warning_handler.clone())
+    });
+    let result = subscription.execute();
+    drop(watch);
+    let handle = subscription.handle();
+    handle.metrics.lock().record(wait_age, start_time.elapsed());
+    *handle.last_execution.lock() = Some(start_time);
+    result
+}
+
 /// Convenience function for calling [`spin_once`] in a loop.
 ///
 /// This function additionally checks that the context is still valid.
 pub fn spin(node: &Node) -> Result<(), RclReturnCode> {
+    spin_with_options(node, &SpinOptions::default())
+}
+
+/// Like [`spin`], but with non-default [`SpinOptions`].
+pub fn spin_with_options(node: &Node, options: &SpinOptions) -> Result<(), RclReturnCode> {
     // SAFETY: No preconditions for this function.
     while unsafe { rcl_context_is_valid(&mut *node.context.lock() as *mut _) } {
-        if let Some(error) = spin_once(node, None).err() {
+        if let Some(error) = spin_once_with_options(node, None, options).err() {
             match error {
                 RclReturnCode::Timeout => continue,
                 error => return Err(error),
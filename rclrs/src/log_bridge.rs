@@ -0,0 +1,97 @@
+use crate::error::{RclErrorCode, RclReturnCode, ToResult};
+use crate::rcl_bindings::*;
+use crate::Context;
+
+use std::ffi::CString;
+use std::os::raw::c_int;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Routes the records of the [`log`] crate into the ROS logging system.
+///
+/// After this has been called, records logged with e.g. `log::info!()`, including those of
+/// third-party libraries, are handled like log messages of `rclcpp` nodes: they are printed to the
+/// console, written to the log file and published on `/rosout`, according to the logging
+/// settings in the ROS arguments of the context, e.g. `--ros-args --log-level debug`.
+///
+/// The target of a record is used as the logger name, with `::` replaced by `.`, so that e.g.
+/// records from the module `my_driver::serial` are logged by the logger `my_driver.serial`.
+/// Since `log` has no fatal level, `Trace` and `Debug` both map to the ROS debug severity.
+///
+/// This should be called once, before creating any nodes, since nodes only get a `/rosout`
+/// publisher when logging has been configured at the time they are created. Calling it again
+/// returns [`RclErrorCode::AlreadyInit`].
+pub fn init_log_bridge(context: &Context, max_level: LevelFilter) -> Result<(), RclReturnCode> {
+    static LOG_BRIDGE: LogBridge = LogBridge;
+    log::set_logger(&LOG_BRIDGE).map_err(|_| RclErrorCode::AlreadyInit)?;
+    log::set_max_level(max_level);
+    let handle = &*context.handle.lock();
+    // SAFETY: The global arguments have been initialized by rcl_init(). Neither argument needs
+    // to outlive this call.
+    unsafe {
+        rcl_logging_configure(
+            &handle.global_arguments as *const _,
+            &rcutils_get_default_allocator() as *const _,
+        )
+        .ok()
+    }
+}
+
+struct LogBridge;
+
+impl Log for LogBridge {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let logger_name = to_c_string(&metadata.target().replace("::", "."));
+        // SAFETY: The logger name is a valid null-terminated string.
+        unsafe {
+            rcutils_logging_logger_is_enabled_for(
+                logger_name.as_ptr(),
+                to_severity(metadata.level()),
+            )
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let logger_name = to_c_string(&record.target().replace("::", "."));
+        let message = to_c_string(&record.args().to_string());
+        let function_name = to_c_string(record.module_path().unwrap_or_default());
+        let file_name = to_c_string(record.file().unwrap_or_default());
+        let format = to_c_string("%s");
+        let location = rcutils_log_location_t {
+            function_name: function_name.as_ptr(),
+            file_name: file_name.as_ptr(),
+            line_number: record.line().unwrap_or_default() as usize,
+        };
+        // SAFETY: All strings are valid and null-terminated, and the message is passed as an
+        // argument to a constant format string, so it is not interpreted as a format itself.
+        unsafe {
+            rcutils_log(
+                &location as *const _,
+                to_severity(record.level()),
+                logger_name.as_ptr(),
+                format.as_ptr(),
+                message.as_ptr(),
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn to_severity(level: Level) -> c_int {
+    let severity = match level {
+        Level::Error => RCUTILS_LOG_SEVERITY::RCUTILS_LOG_SEVERITY_ERROR,
+        Level::Warn => RCUTILS_LOG_SEVERITY::RCUTILS_LOG_SEVERITY_WARN,
+        Level::Info => RCUTILS_LOG_SEVERITY::RCUTILS_LOG_SEVERITY_INFO,
+        Level::Debug | Level::Trace => RCUTILS_LOG_SEVERITY::RCUTILS_LOG_SEVERITY_DEBUG,
+    };
+    severity as c_int
+}
+
+// Interior null bytes would truncate the string, so they are removed.
+fn to_c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap()
+}
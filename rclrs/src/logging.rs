@@ -0,0 +1,34 @@
+use crate::rcl_bindings::*;
+
+use std::ffi::CString;
+use std::os::raw::c_int;
+
+// The logger for the warnings of rclrs itself.
+const LOGGER_NAME: &str = "rclrs";
+
+// Logs a warning through the ROS logging system, so that it is handled like the warnings of
+// rclcpp, e.g. written to the log file, according to the logging settings of the process.
+pub(crate) fn log_warning(message: &str) {
+    let severity = RCUTILS_LOG_SEVERITY::RCUTILS_LOG_SEVERITY_WARN as c_int;
+    // Neither string contains null bytes.
+    let logger_name = CString::new(LOGGER_NAME).unwrap();
+    let format = CString::new("%s").unwrap();
+    // SAFETY: The logger name is a valid null-terminated string.
+    if !unsafe { rcutils_logging_logger_is_enabled_for(logger_name.as_ptr(), severity) } {
+        return;
+    }
+    // Interior null bytes would truncate the message, so they are removed.
+    let message = CString::new(message.replace('\0', "")).unwrap();
+    // SAFETY: All strings are valid and null-terminated, and the message is passed as an argument
+    // to a constant format string, so it is not interpreted as a format itself. The location is
+    // allowed to be null.
+    unsafe {
+        rcutils_log(
+            std::ptr::null(),
+            severity,
+            logger_name.as_ptr(),
+            format.as_ptr(),
+            message.as_ptr(),
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+/// The upper bounds of the buckets of a [`LatencyHistogram`], excluding the implicit infinite
+/// bucket.
+pub const LATENCY_BUCKET_BOUNDS: [Duration; 10] = [
+    Duration::from_micros(10),
+    Duration::from_micros(50),
+    Duration::from_micros(100),
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+];
+
+/// A histogram of durations with the fixed buckets in [`LATENCY_BUCKET_BOUNDS`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// The number of samples per bucket. The last entry counts the samples that are larger than
+    /// all bucket bounds.
+    pub bucket_counts: [u64; LATENCY_BUCKET_BOUNDS.len() + 1],
+    /// The total number of samples.
+    pub count: u64,
+    /// The sum of all samples.
+    pub sum: Duration,
+    /// The largest sample.
+    pub max: Duration,
+}
+
+impl LatencyHistogram {
+    /// Adds a sample to the histogram.
+    pub fn record(&mut self, sample: Duration) {
+        let bucket = LATENCY_BUCKET_BOUNDS
+            .iter()
+            .position(|&bound| sample <= bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS.len());
+        self.bucket_counts[bucket] += 1;
+        self.count += 1;
+        self.sum += sample;
+        self.max = self.max.max(sample);
+    }
+
+    /// Returns the average of all samples, or `None` if there are no samples.
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            self.sum.as_secs_f64() / self.count as f64,
+        ))
+    }
+}
+
+/// Timing statistics for the callback of a single entity, returned by
+/// [`Node::callback_metrics`][1].
+///
+/// [1]: crate::Node::callback_metrics
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallbackMetrics {
+    /// The fully qualified topic name of the entity.
+    pub topic: String,
+    /// The time from the wait set returning the entity as ready until its callback was started.
+    pub latency: LatencyHistogram,
+    /// The time taken to take the message and run the callback.
+    pub duration: LatencyHistogram,
+}
+
+impl CallbackMetrics {
+    pub(crate) fn record(&mut self, latency: Duration, duration: Duration) {
+        self.latency.record(latency);
+        self.duration.record(duration);
+    }
+}
+
+/// Formats callback metrics in the Prometheus text exposition format.
+///
+/// Two histograms are produced, `rclrs_callback_latency_seconds` and
+/// `rclrs_callback_duration_seconds`, both with a `topic` label.
+pub fn metrics_to_prometheus_text(metrics: &[CallbackMetrics]) -> String {
+    let mut text = String::new();
+    write_prometheus_histogram(
+        &mut text,
+        "rclrs_callback_latency_seconds",
+        "Time from an entity becoming ready until its callback started.",
+        metrics.iter().map(|m| (m.topic.as_str(), &m.latency)),
+    );
+    write_prometheus_histogram(
+        &mut text,
+        "rclrs_callback_duration_seconds",
+        "Time taken to execute the callback of an entity.",
+        metrics.iter().map(|m| (m.topic.as_str(), &m.duration)),
+    );
+    text
+}
+
+fn write_prometheus_histogram<'a>(
+    text: &mut String,
+    name: &str,
+    help: &str,
+    histograms: impl Iterator<Item = (&'a str, &'a LatencyHistogram)>,
+) {
+    // Writing to a String cannot fail.
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} histogram", name);
+    for (topic, histogram) in histograms {
+        let topic = escape_label_value(topic);
+        let bounds = LATENCY_BUCKET_BOUNDS
+            .iter()
+            .map(|bound| bound.as_secs_f64().to_string())
+            .chain(std::iter::once(String::from("+Inf")));
+        let mut cumulative_count = 0;
+        for (bound, bucket_count) in bounds.zip(histogram.bucket_counts) {
+            cumulative_count += bucket_count;
+            let _ = writeln!(
+                text,
+                "{}_bucket{{topic=\"{}\",le=\"{}\"}} {}",
+                name, topic, bound, cumulative_count
+            );
+        }
+        let _ = writeln!(
+            text,
+            "{}_sum{{topic=\"{}\"}} {}",
+            name,
+            topic,
+            histogram.sum.as_secs_f64()
+        );
+        let _ = writeln!(
+            text,
+            "{}_count{{topic=\"{}\"}} {}",
+            name, topic, histogram.count
+        );
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.mean(), None);
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_millis(2));
+        histogram.record(Duration::from_secs(1));
+        assert_eq!(histogram.bucket_counts[0], 1);
+        assert_eq!(histogram.bucket_counts[5], 1);
+        assert_eq!(histogram.bucket_counts[LATENCY_BUCKET_BOUNDS.len()], 1);
+        assert_eq!(histogram.count, 3);
+        assert_eq!(histogram.max, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_prometheus_text() {
+        let mut metrics = CallbackMetrics {
+            topic: String::from("/chatter"),
+            ..Default::default()
+        };
+        metrics.record(Duration::from_micros(20), Duration::from_secs(2));
+        let text = metrics_to_prometheus_text(&[metrics]);
+        assert!(text.contains("# TYPE rclrs_callback_latency_seconds histogram"));
+        assert!(text.contains(
+            "rclrs_callback_latency_seconds_bucket{topic=\"/chatter\",le=\"0.00001\"} 0"
+        ));
+        assert!(text.contains(
+            "rclrs_callback_latency_seconds_bucket{topic=\"/chatter\",le=\"0.00005\"} 1"
+        ));
+        assert!(text
+            .contains("rclrs_callback_duration_seconds_bucket{topic=\"/chatter\",le=\"+Inf\"} 1"));
+        assert!(text.contains("rclrs_callback_duration_seconds_count{topic=\"/chatter\"} 1"));
+    }
+}
@@ -0,0 +1,454 @@
+use crate::default_context::get_default_context;
+use crate::error::{NodeErrorCode, RclReturnCode, ToResult};
+use crate::node::NodeHandle;
+use crate::rcl_bindings::*;
+use crate::{Context, Node};
+
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use std::sync::Arc;
+
+/// Marker for a [`NodeBuilder`] that has no name yet.
+#[derive(Clone, Copy, Debug)]
+pub struct Unnamed;
+
+/// Marker for a [`NodeBuilder`] that has a name, and can therefore build a node.
+#[derive(Clone, Debug)]
+pub struct Named(String);
+
+/// A builder for [`Node`]s.
+///
+/// A node builder is created with [`Node::builder`]. The name of the node is required, and
+/// [`build`][1] is only available after it has been set with [`name`][2], so forgetting it is a
+/// compile error instead of a runtime error:
+///
+/// ```compile_fail
+/// # use rclrs::Node;
+/// let node = Node::builder().namespace("/ns").build();
+/// ```
+///
+/// All other settings are optional. Without a [`context`][3], the node is created in the
+/// [`default_context`][4]. This does not install the signal handler of the default context, for
+/// that, call [`default_context`][4] explicitly.
+///
+/// # Example
+/// ```
+/// # use rclrs::{Context, Node, NodeBuildError};
+/// let context = Context::new([])?;
+/// let node = Node::builder()
+///     .name("my_node")
+///     .namespace("/my_ns")
+///     .context(&context)
+///     .build()?;
+/// assert_eq!(node.fully_qualified_name(), "/my_ns/my_node");
+///
+/// let error = Node::builder().name("my node").context(&context).build();
+/// assert!(matches!(error, Err(NodeBuildError::InvalidName { position: 2, .. })));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [1]: NodeBuilder::build
+/// [2]: NodeBuilder::name
+/// [3]: NodeBuilder::context
+/// [4]: crate::default_context
+#[derive(Clone)]
+#[must_use = "the builder does nothing until `build` is called"]
+pub struct NodeBuilder<'a, N> {
+    name: N,
+    namespace: String,
+    context: Option<&'a Context>,
+    options: NodeOptions,
+}
+
+/// Options for creating a [`Node`], which are passed to [`NodeBuilder::options`].
+///
+/// The options are built with chained method calls, starting from [`NodeOptions::new`]:
+///
+/// ```
+/// # use rclrs::NodeOptions;
+/// let args = ["--ros-args", "-r", "chatter:=talk"].map(String::from);
+/// let options = NodeOptions::new()
+///     .arguments(args)
+///     .use_global_arguments(false)
+///     .enable_rosout(false);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeOptions {
+    arguments: Vec<String>,
+    use_global_arguments: bool,
+    enable_rosout: bool,
+}
+
+/// The reason why a [`NodeBuilder`] could not build a node.
+#[derive(Debug, PartialEq)]
+pub enum NodeBuildError {
+    /// The node name is not valid.
+    InvalidName {
+        /// The invalid node name.
+        name: String,
+        /// The index of the first invalid character in the node name.
+        position: usize,
+        /// A description of the problem.
+        reason: String,
+    },
+    /// The node namespace is not valid.
+    InvalidNamespace {
+        /// The invalid namespace.
+        namespace: String,
+        /// The index of the first invalid character in the namespace.
+        position: usize,
+        /// A description of the problem.
+        reason: String,
+    },
+    /// An argument in the [`NodeOptions`] contains a null byte.
+    InvalidArgument {
+        /// The invalid argument.
+        argument: String,
+    },
+    /// An error from rcl, e.g. when the context was shut down or the node arguments are not
+    /// valid ROS arguments.
+    Rcl(RclReturnCode),
+}
+
+impl Node {
+    /// Returns a builder for a new node.
+    ///
+    /// See [`NodeBuilder`].
+    pub fn builder() -> NodeBuilder<'static, Unnamed> {
+        NodeBuilder {
+            name: Unnamed,
+            namespace: String::new(),
+            context: None,
+            options: NodeOptions::new(),
+        }
+    }
+}
+
+impl<'a, N> NodeBuilder<'a, N> {
+    /// Sets the name of the node.
+    ///
+    /// The name must not contain a namespace, i.e. no forward slashes.
+    pub fn name(self, name: &str) -> NodeBuilder<'a, Named> {
+        NodeBuilder {
+            name: Named(name.to_owned()),
+            namespace: self.namespace,
+            context: self.context,
+            options: self.options,
+        }
+    }
+
+    /// Sets the namespace of the node.
+    ///
+    /// A namespace without a leading forward slash is automatically changed to have a leading
+    /// forward slash. The default is the root namespace.
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = namespace.to_owned();
+        self
+    }
+
+    /// Sets the context in which the node is created.
+    pub fn context<'b>(self, context: &'b Context) -> NodeBuilder<'b, N> {
+        NodeBuilder {
+            name: self.name,
+            namespace: self.namespace,
+            context: Some(context),
+            options: self.options,
+        }
+    }
+
+    /// Sets the options of the node.
+    pub fn options(mut self, options: NodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl NodeBuilder<'_, Named> {
+    /// Creates the node.
+    ///
+    /// The name and namespace are validated before the node is created, and the error describes
+    /// what is wrong with them.
+    pub fn build(self) -> Result<Node, NodeBuildError> {
+        let name = self.name.0;
+        validate_node_name(&name)?;
+        let namespace = normalize_namespace(&self.namespace);
+        validate_namespace(&self.namespace, &namespace)?;
+        let owned_context;
+        let context = match self.context {
+            Some(context) => context,
+            None => {
+                owned_context = get_default_context(false)?;
+                &owned_context
+            }
+        };
+        let arguments = self
+            .options
+            .arguments
+            .iter()
+            .map(|argument| {
+                CString::new(argument.as_str()).map_err(|_| NodeBuildError::InvalidArgument {
+                    argument: argument.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let c_arguments: Vec<*const c_char> = arguments.iter().map(|arg| arg.as_ptr()).collect();
+        // Both strings were validated above, so they don't contain null bytes.
+        let raw_node_name = CString::new(name).unwrap();
+        let raw_node_ns = CString::new(namespace).unwrap();
+
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut node_handle = unsafe { rcl_get_zero_initialized_node() };
+        // SAFETY: No preconditions for this function.
+        let mut node_options = unsafe { rcl_node_get_default_options() };
+        node_options.use_global_arguments = self.options.use_global_arguments;
+        node_options.enable_rosout = self.options.enable_rosout;
+        if !c_arguments.is_empty() {
+            // SAFETY: The arguments are valid null-terminated strings, and the node options
+            // contain zero-initialized arguments as expected by this function. The strings are
+            // copied, so they don't need to outlive the node options.
+            unsafe {
+                rcl_parse_arguments(
+                    c_arguments.len() as c_int,
+                    c_arguments.as_ptr(),
+                    rcutils_get_default_allocator(),
+                    &mut node_options.arguments as *mut _,
+                )
+                .ok()?;
+            }
+        }
+        let init_result = unsafe {
+            // SAFETY: The node handle is zero-initialized as expected by this function.
+            // The strings and node options are copied by this function, so we don't need
+            // to keep them alive.
+            // The context handle is kept alive because it is co-owned by the node.
+            rcl_node_init(
+                &mut node_handle as *mut _,
+                raw_node_name.as_ptr(),
+                raw_node_ns.as_ptr(),
+                &mut *context.handle.lock() as *mut _,
+                &node_options as *const _,
+            )
+        };
+        // SAFETY: The node options were initialized above and are not used afterwards. This also
+        // finalizes the parsed arguments.
+        unsafe { rcl_node_options_fini(&mut node_options as *mut _) };
+        init_result.ok()?;
+
+        Ok(Node {
+            handle: Arc::new(NodeHandle::new(node_handle, context.handle.clone())),
+            context: context.handle.clone(),
+            interrupt: context.interrupt.clone(),
+            subscriptions: std::vec![],
+            waitables: std::vec![],
+        })
+    }
+}
+
+impl NodeOptions {
+    /// Creates the default options.
+    ///
+    /// By default, the node has no arguments of its own, uses the global arguments of its
+    /// context, and publishes its log messages on `/rosout`.
+    pub fn new() -> Self {
+        Self {
+            arguments: Vec::new(),
+            use_global_arguments: true,
+            enable_rosout: true,
+        }
+    }
+
+    /// Sets the command line arguments that only apply to this node.
+    ///
+    /// As for [`Context::new`], ROS arguments must follow a `--ros-args` flag. Rules in these
+    /// arguments take precedence over the global arguments of the context.
+    pub fn arguments(mut self, arguments: impl IntoIterator<Item = String>) -> Self {
+        self.arguments = arguments.into_iter().collect();
+        self
+    }
+
+    /// Sets whether the global arguments of the context apply to this node.
+    ///
+    /// This is `true` by default. Nodes that shouldn't be affected by e.g. remapping rules on
+    /// the command line can disable it.
+    pub fn use_global_arguments(mut self, use_global_arguments: bool) -> Self {
+        self.use_global_arguments = use_global_arguments;
+        self
+    }
+
+    /// Sets whether the node publishes its log messages on the `/rosout` topic.
+    ///
+    /// This is `true` by default. When it is disabled, the node doesn't create the `/rosout`
+    /// publisher, which saves a publisher and its discovery traffic on constrained systems. Log
+    /// messages are still printed to the console.
+    pub fn enable_rosout(mut self, enable_rosout: bool) -> Self {
+        self.enable_rosout = enable_rosout;
+        self
+    }
+}
+
+impl Default for NodeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for NodeBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName {
+                name,
+                position,
+                reason,
+            } => write!(
+                f,
+                "Invalid node name '{}' at position {}: {}",
+                name, position, reason
+            ),
+            Self::InvalidNamespace {
+                namespace,
+                position,
+                reason,
+            } => write!(
+                f,
+                "Invalid node namespace '{}' at position {}: {}",
+                namespace, position, reason
+            ),
+            Self::InvalidArgument { argument } => {
+                write!(f, "Node argument {:?} contains a null byte", argument)
+            }
+            Self::Rcl(err) => write!(f, "Failed to create node: {}", err),
+        }
+    }
+}
+
+impl Error for NodeBuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Rcl(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<RclReturnCode> for NodeBuildError {
+    fn from(err: RclReturnCode) -> Self {
+        Self::Rcl(err)
+    }
+}
+
+impl From<NodeBuildError> for RclReturnCode {
+    fn from(err: NodeBuildError) -> Self {
+        match err {
+            NodeBuildError::InvalidName { .. } => NodeErrorCode::NodeInvalidName.into(),
+            NodeBuildError::InvalidNamespace { .. } => NodeErrorCode::NodeInvalidNamespace.into(),
+            NodeBuildError::InvalidArgument { .. } => RclReturnCode::InvalidArgument,
+            NodeBuildError::Rcl(err) => err,
+        }
+    }
+}
+
+// Adds the leading forward slash that rcl_node_init() adds to relative namespaces.
+fn normalize_namespace(namespace: &str) -> String {
+    if namespace.starts_with('/') {
+        namespace.to_owned()
+    } else {
+        format!("/{}", namespace)
+    }
+}
+
+fn validate_node_name(name: &str) -> Result<(), NodeBuildError> {
+    let invalid_name = |position, reason: &str| NodeBuildError::InvalidName {
+        name: name.to_owned(),
+        position,
+        reason: reason.to_owned(),
+    };
+    let c_name = CString::new(name)
+        .map_err(|err| invalid_name(err.nul_position(), "node name must not contain null bytes"))?;
+    let mut validation_result: c_int = 0;
+    let mut invalid_index: usize = 0;
+    // SAFETY: All pointers are valid for the duration of the call. This function can only fail
+    // when passed null pointers.
+    unsafe {
+        rmw_validate_node_name(
+            c_name.as_ptr(),
+            &mut validation_result as *mut _,
+            &mut invalid_index as *mut _,
+        )
+        .ok()?;
+    }
+    // SAFETY: No preconditions for this function.
+    let reason = unsafe { rmw_node_name_validation_result_string(validation_result) };
+    // A null pointer means that the name is valid.
+    if reason.is_null() {
+        return Ok(());
+    }
+    // SAFETY: The reason is a static null-terminated string.
+    Err(invalid_name(
+        invalid_index,
+        &unsafe { CStr::from_ptr(reason) }.to_string_lossy(),
+    ))
+}
+
+// Validates the normalized namespace, but reports errors for the namespace as it was given.
+fn validate_namespace(namespace: &str, normalized: &str) -> Result<(), NodeBuildError> {
+    let invalid_namespace = |position, reason: &str| NodeBuildError::InvalidNamespace {
+        namespace: namespace.to_owned(),
+        position,
+        reason: reason.to_owned(),
+    };
+    let c_namespace = CString::new(normalized).map_err(|err| {
+        invalid_namespace(
+            err.nul_position() - (normalized.len() - namespace.len()),
+            "namespace must not contain null bytes",
+        )
+    })?;
+    match find_invalid_namespace(&c_namespace)? {
+        None => Ok(()),
+        Some((invalid_index, reason)) => Err(invalid_namespace(
+            invalid_index.saturating_sub(normalized.len() - namespace.len()),
+            &reason,
+        )),
+    }
+}
+
+// Validates an absolute namespace with rmw. Returns the index at which the namespace is invalid
+// and the reason, or `None` if the namespace is valid.
+pub(crate) fn find_invalid_namespace(
+    namespace: &CStr,
+) -> Result<Option<(usize, String)>, RclReturnCode> {
+    let mut validation_result: c_int = 0;
+    let mut invalid_index: usize = 0;
+    // SAFETY: All pointers are valid for the duration of the call. This function can only fail
+    // when passed null pointers.
+    unsafe {
+        rmw_validate_namespace(
+            namespace.as_ptr(),
+            &mut validation_result as *mut _,
+            &mut invalid_index as *mut _,
+        )
+        .ok()?;
+    }
+    // SAFETY: No preconditions for this function.
+    let reason = unsafe { rmw_namespace_validation_result_string(validation_result) };
+    // A null pointer means that the namespace is valid.
+    if reason.is_null() {
+        return Ok(None);
+    }
+    // SAFETY: The reason is a static null-terminated string.
+    let reason = unsafe { CStr::from_ptr(reason) }.to_string_lossy();
+    Ok(Some((invalid_index, reason.into_owned())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_namespace() {
+        assert_eq!(normalize_namespace(""), "/");
+        assert_eq!(normalize_namespace("foo/bar"), "/foo/bar");
+        assert_eq!(normalize_namespace("/foo"), "/foo");
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+/// A filter that recognizes keys which were seen recently, for suppressing duplicate messages.
+///
+/// The filter remembers the last `window` distinct keys. Subscriptions use it with the
+/// [`dedup_window`][1] option, keyed on the GID of the publisher and the sequence number of the
+/// message, which suppresses messages that the middleware delivered more than once.
+///
+/// Redundant publishers in a high-availability setup have different GIDs, so their copies of a
+/// sample can only be recognized by the content. For that, the filter can be used with a key
+/// derived from the message, e.g. its timestamp:
+///
+/// ```ignore
+/// let mut filter = DedupFilter::new(100);
+/// let subscription = node.create_subscription("pose", QOS_PROFILE_DEFAULT, move |msg: PoseStamped| {
+///     if filter.insert((msg.header.stamp.sec, msg.header.stamp.nanosec)) {
+///         handle_pose(msg);
+///     }
+/// })?;
+/// ```
+///
+/// [1]: crate::SubscriptionOptions::dedup_window
+#[derive(Clone, Debug)]
+pub struct DedupFilter<K> {
+    window: usize,
+    seen: HashSet<K>,
+    // The keys in `seen`, oldest first.
+    order: VecDeque<K>,
+}
+
+impl<K> DedupFilter<K>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Creates a filter that remembers the last `window` keys.
+    ///
+    /// A filter with a window of `0` lets everything through.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            seen: HashSet::with_capacity(window),
+            order: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Records the key, and returns whether it is new.
+    ///
+    /// Returns `false` when the key is among the last `window` keys, i.e. when the message it
+    /// belongs to is a duplicate.
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.window == 0 {
+            return true;
+        }
+        if self.seen.contains(&key) {
+            return false;
+        }
+        if self.order.len() == self.window {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.clone());
+        self.seen.insert(key);
+        true
+    }
+
+    /// Forgets all keys.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_filter() {
+        let mut filter = DedupFilter::new(2);
+        assert!(filter.insert(1));
+        assert!(filter.insert(2));
+        assert!(!filter.insert(1));
+        assert!(filter.insert(3));
+        // 1 has been pushed out of the window by 3
+        assert!(filter.insert(1));
+        assert!(!filter.insert(3));
+        filter.clear();
+        assert!(filter.insert(3));
+
+        let mut disabled = DedupFilter::new(0);
+        assert!(disabled.insert(1));
+        assert!(disabled.insert(1));
+    }
+}
@@ -0,0 +1,507 @@
+use crate::compat::endpoint_type_hash;
+use crate::error::{NodeErrorCode, RclReturnCode, ToResult};
+use crate::node::NodeHandle;
+use crate::qos::QoSProfile;
+use crate::rcl_bindings::*;
+use crate::{Node, TypeHash};
+
+use std::collections::BTreeSet;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use parking_lot::Mutex;
+
+/// A change in the set of nodes in the ROS graph, returned by [`NodeWatcher`].
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum NodeEvent {
+    /// A node with the given fully qualified name appeared.
+    Appeared(String),
+    /// A node with the given fully qualified name disappeared.
+    Disappeared(String),
+}
+
+/// The kind of an entity of a node, see [`EntityInfo`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EntityKind {
+    /// A publisher.
+    Publisher,
+    /// A subscription.
+    Subscription,
+}
+
+/// Information about a publisher or subscription of a node, returned by [`Node::entities`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntityInfo {
+    /// Whether this is a publisher or a subscription.
+    pub kind: EntityKind,
+    /// The fully qualified name of the topic.
+    pub topic_name: String,
+    /// The message type of the topic, e.g. `std_msgs/msg/String`.
+    pub topic_type: String,
+    /// The QoS profile of the entity, as reported by the middleware.
+    pub qos: QoSProfile,
+    /// The hash of the message type definition, as advertised by the entity.
+    ///
+    /// This is `None` when the middleware does not know it, and always before Iron.
+    pub type_hash: Option<TypeHash>,
+}
+
+/// Keeps track of the nodes in the ROS graph.
+///
+/// The watcher remembers the set of nodes it has seen, and reports the differences to that set
+/// as [`NodeEvent`]s. This is useful e.g. for supervisors that restart crashed nodes.
+///
+/// Node names are always fully qualified, e.g. `/my_ns/my_node`.
+pub struct NodeWatcher {
+    node_handle: Arc<NodeHandle>,
+    context_handle: Arc<Mutex<rcl_context_t>>,
+    known_nodes: BTreeSet<String>,
+}
+
+impl NodeWatcher {
+    /// Creates a new watcher that uses `node` to observe the ROS graph.
+    ///
+    /// The nodes that exist at this point are not reported as events.
+    pub fn new(node: &Node) -> Result<Self, RclReturnCode> {
+        let known_nodes = get_fully_qualified_node_names(&node.handle)?
+            .into_iter()
+            .collect();
+        Ok(Self {
+            node_handle: node.handle.clone(),
+            context_handle: node.context.clone(),
+            known_nodes,
+        })
+    }
+
+    /// Returns the fully qualified names of the nodes that are currently known to exist.
+    pub fn known_nodes(&self) -> impl Iterator<Item = &str> {
+        self.known_nodes.iter().map(String::as_str)
+    }
+
+    /// Returns the changes to the ROS graph since the last call, without blocking.
+    pub fn poll(&mut self) -> Result<Vec<NodeEvent>, RclReturnCode> {
+        let current_nodes: BTreeSet<String> = get_fully_qualified_node_names(&self.node_handle)?
+            .into_iter()
+            .collect();
+        let events = current_nodes
+            .difference(&self.known_nodes)
+            .cloned()
+            .map(NodeEvent::Appeared)
+            .chain(
+                self.known_nodes
+                    .difference(&current_nodes)
+                    .cloned()
+                    .map(NodeEvent::Disappeared),
+            )
+            .collect();
+        self.known_nodes = current_nodes;
+        Ok(events)
+    }
+
+    /// Blocks until nodes appear or disappear, or until the timeout has been exceeded.
+    ///
+    /// See [`WaitSet::wait`][1] for the meaning of the `timeout` parameter. When the timeout is
+    /// exceeded, an empty list of events is returned.
+    ///
+    /// [1]: crate::WaitSet::wait
+    pub fn wait(&mut self, timeout: Option<Duration>) -> Result<Vec<NodeEvent>, RclReturnCode> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            // Graph changes also include e.g. new publishers, so this loop continues until there
+            // is a change to the set of nodes.
+            let events = self.poll()?;
+            if !events.is_empty() {
+                return Ok(events);
+            }
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            match wait_for_graph_change(&self.node_handle, &self.context_handle, remaining) {
+                Ok(()) => {}
+                Err(RclReturnCode::Timeout) => return self.poll(),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Blocks until a node with the given name exists in the ROS graph, or until the timeout has been
+/// exceeded.
+///
+/// The name can be fully qualified, such as `/my_ns/my_node`. Otherwise, it matches a node with
+/// that name in any namespace.
+///
+/// See [`WaitSet::wait`][1] for the meaning of the `timeout` parameter. When the timeout is
+/// exceeded, [`RclReturnCode::Timeout`] is returned.
+///
+/// [1]: crate::WaitSet::wait
+pub fn wait_for_node(
+    node: &Node,
+    node_name: &str,
+    timeout: Option<Duration>,
+) -> Result<(), RclReturnCode> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let matches = |fully_qualified_name: &str| {
+        if node_name.starts_with('/') {
+            fully_qualified_name == node_name
+        } else {
+            fully_qualified_name.rsplit('/').next() == Some(node_name)
+        }
+    };
+    loop {
+        if get_fully_qualified_node_names(&node.handle)?
+            .iter()
+            .any(|name| matches(name))
+        {
+            return Ok(());
+        }
+        let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        wait_for_graph_change(&node.handle, &node.context, remaining)?;
+    }
+}
+
+/// Returns the names and namespaces of all nodes in the ROS graph.
+pub(crate) fn get_node_names_with_namespaces(
+    node_handle: &NodeHandle,
+) -> Result<Vec<(String, String)>, RclReturnCode> {
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut node_names = unsafe { rcutils_get_zero_initialized_string_array() };
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut node_namespaces = unsafe { rcutils_get_zero_initialized_string_array() };
+    unsafe {
+        // SAFETY: The node handle is valid, and the string arrays are zero-initialized as
+        // expected by this function.
+        rcl_get_node_names(
+            &*node_handle.lock() as *const _,
+            rcutils_get_default_allocator(),
+            &mut node_names as *mut _,
+            &mut node_namespaces as *mut _,
+        )
+        .ok()?;
+    }
+    // SAFETY: The string arrays were initialized by rcl_get_node_names().
+    let names_with_namespaces = unsafe { string_array_to_vec(&node_names) }
+        .into_iter()
+        .zip(unsafe { string_array_to_vec(&node_namespaces) })
+        .collect();
+    // SAFETY: The string arrays were initialized by rcl_get_node_names(), and are not used
+    // afterwards.
+    unsafe {
+        rcutils_string_array_fini(&mut node_names as *mut _).ok()?;
+        rcutils_string_array_fini(&mut node_namespaces as *mut _).ok()?;
+    }
+    Ok(names_with_namespaces)
+}
+
+/// Returns the publishers or subscriptions of a node, as seen in the ROS graph.
+pub(crate) fn get_node_entities(
+    node_handle: &NodeHandle,
+    kind: EntityKind,
+) -> Result<Vec<EntityInfo>, RclReturnCode> {
+    let node_handle = &*node_handle.lock();
+    // SAFETY: The node handle is valid. The returned strings are owned by the node and are
+    // copied before the lock is released.
+    let (node_name, node_namespace) = unsafe {
+        let node_name = rcl_node_get_name(node_handle as *const _);
+        let node_namespace = rcl_node_get_namespace(node_handle as *const _);
+        if node_name.is_null() || node_namespace.is_null() {
+            return Err(RclReturnCode::NodeError(NodeErrorCode::NodeInvalid));
+        }
+        (
+            CStr::from_ptr(node_name).to_owned(),
+            CStr::from_ptr(node_namespace).to_owned(),
+        )
+    };
+    // SAFETY: No preconditions for this function.
+    let mut allocator = unsafe { rcutils_get_default_allocator() };
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut names_and_types = unsafe { rcl_get_zero_initialized_names_and_types() };
+    unsafe {
+        // SAFETY: The node handle and the strings are valid, and the names and types are
+        // zero-initialized as expected by these functions.
+        match kind {
+            EntityKind::Publisher => rcl_get_publisher_names_and_types_by_node(
+                node_handle as *const _,
+                &mut allocator as *mut _,
+                false,
+                node_name.as_ptr(),
+                node_namespace.as_ptr(),
+                &mut names_and_types as *mut _,
+            ),
+            EntityKind::Subscription => rcl_get_subscriber_names_and_types_by_node(
+                node_handle as *const _,
+                &mut allocator as *mut _,
+                false,
+                node_name.as_ptr(),
+                node_namespace.as_ptr(),
+                &mut names_and_types as *mut _,
+            ),
+        }
+        .ok()?;
+    }
+    // SAFETY: The names were initialized by the function above.
+    let topic_names = unsafe { string_array_to_vec(&names_and_types.names) };
+    // SAFETY: The names and types were initialized by the function above, and are not used
+    // afterwards.
+    unsafe { rcl_names_and_types_fini(&mut names_and_types as *mut _).ok()? };
+
+    let mut entities = Vec::new();
+    for topic_name in topic_names {
+        // Topic names returned by rcl never contain null bytes.
+        let topic_name_c_string = CString::new(topic_name.as_str()).unwrap();
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut endpoints_info = unsafe { rmw_get_zero_initialized_topic_endpoint_info_array() };
+        unsafe {
+            // SAFETY: The node handle and topic name are valid, and the endpoint info array is
+            // zero-initialized as expected by these functions.
+            match kind {
+                EntityKind::Publisher => rcl_get_publishers_info_by_topic(
+                    node_handle as *const _,
+                    &mut allocator as *mut _,
+                    topic_name_c_string.as_ptr(),
+                    false,
+                    &mut endpoints_info as *mut _,
+                ),
+                EntityKind::Subscription => rcl_get_subscriptions_info_by_topic(
+                    node_handle as *const _,
+                    &mut allocator as *mut _,
+                    topic_name_c_string.as_ptr(),
+                    false,
+                    &mut endpoints_info as *mut _,
+                ),
+            }
+            .ok()?;
+        }
+        let endpoints = if endpoints_info.info_array.is_null() {
+            &[]
+        } else {
+            // SAFETY: The info array contains `size` initialized elements.
+            unsafe { std::slice::from_raw_parts(endpoints_info.info_array, endpoints_info.size) }
+        };
+        for endpoint in endpoints {
+            // SAFETY: The strings in the endpoint info are valid and null-terminated.
+            let (endpoint_node_name, endpoint_node_namespace, topic_type) = unsafe {
+                (
+                    CStr::from_ptr(endpoint.node_name),
+                    CStr::from_ptr(endpoint.node_namespace),
+                    CStr::from_ptr(endpoint.topic_type),
+                )
+            };
+            if endpoint_node_name == node_name.as_c_str()
+                && endpoint_node_namespace == node_namespace.as_c_str()
+            {
+                entities.push(EntityInfo {
+                    kind,
+                    topic_name: topic_name.clone(),
+                    topic_type: topic_type.to_string_lossy().into_owned(),
+                    qos: QoSProfile::from(&endpoint.qos_profile),
+                    type_hash: endpoint_type_hash(endpoint),
+                });
+            }
+        }
+        // SAFETY: The endpoint info array was initialized by the function above, and is not
+        // used afterwards.
+        unsafe {
+            rmw_topic_endpoint_info_array_fini(
+                &mut endpoints_info as *mut _,
+                &mut allocator as *mut _,
+            )
+        }
+        .ok()?;
+    }
+    Ok(entities)
+}
+
+/// A publisher or subscription on a topic, see [`get_topic_endpoints`].
+#[cfg(not(ros_distro = "foxy"))]
+pub(crate) struct TopicEndpoint {
+    /// The fully qualified name of the node of the endpoint.
+    pub(crate) node_name: String,
+    pub(crate) qos: QoSProfile,
+    pub(crate) type_hash: Option<TypeHash>,
+}
+
+/// Returns all publishers or subscriptions on the topic, as seen in the ROS graph.
+#[cfg(not(ros_distro = "foxy"))]
+pub(crate) fn get_topic_endpoints(
+    node_handle: &NodeHandle,
+    topic_name: &str,
+    kind: EntityKind,
+) -> Result<Vec<TopicEndpoint>, RclReturnCode> {
+    let node_handle = &*node_handle.lock();
+    // Topic names returned by rcl never contain null bytes.
+    let topic_name_c_string = CString::new(topic_name).unwrap();
+    // SAFETY: No preconditions for this function.
+    let mut allocator = unsafe { rcutils_get_default_allocator() };
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut endpoints_info = unsafe { rmw_get_zero_initialized_topic_endpoint_info_array() };
+    unsafe {
+        // SAFETY: The node handle and topic name are valid, and the endpoint info array is
+        // zero-initialized as expected by these functions.
+        match kind {
+            EntityKind::Publisher => rcl_get_publishers_info_by_topic(
+                node_handle as *const _,
+                &mut allocator as *mut _,
+                topic_name_c_string.as_ptr(),
+                false,
+                &mut endpoints_info as *mut _,
+            ),
+            EntityKind::Subscription => rcl_get_subscriptions_info_by_topic(
+                node_handle as *const _,
+                &mut allocator as *mut _,
+                topic_name_c_string.as_ptr(),
+                false,
+                &mut endpoints_info as *mut _,
+            ),
+        }
+        .ok()?;
+    }
+    let endpoints = if endpoints_info.info_array.is_null() {
+        &[]
+    } else {
+        // SAFETY: The info array contains `size` initialized elements.
+        unsafe { std::slice::from_raw_parts(endpoints_info.info_array, endpoints_info.size) }
+    };
+    let topic_endpoints = endpoints
+        .iter()
+        .map(|endpoint| {
+            // SAFETY: The strings in the endpoint info are valid and null-terminated.
+            let (name, namespace) = unsafe {
+                (
+                    CStr::from_ptr(endpoint.node_name).to_string_lossy(),
+                    CStr::from_ptr(endpoint.node_namespace).to_string_lossy(),
+                )
+            };
+            TopicEndpoint {
+                node_name: fully_qualified_name(&name, &namespace),
+                qos: QoSProfile::from(&endpoint.qos_profile),
+                type_hash: endpoint_type_hash(endpoint),
+            }
+        })
+        .collect();
+    // SAFETY: The endpoint info array was initialized by the function above, and is not used
+    // afterwards.
+    unsafe {
+        rmw_topic_endpoint_info_array_fini(&mut endpoints_info as *mut _, &mut allocator as *mut _)
+    }
+    .ok()?;
+    Ok(topic_endpoints)
+}
+
+/// Returns the fully qualified names of the nodes that have publishers or subscriptions on the
+/// topic, whose type hash is known and differs from `type_hash`.
+#[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+pub(crate) fn get_mismatched_endpoints(
+    node_handle: &NodeHandle,
+    topic_name: &str,
+    kind: EntityKind,
+    type_hash: TypeHash,
+) -> Result<Vec<String>, RclReturnCode> {
+    Ok(get_topic_endpoints(node_handle, topic_name, kind)?
+        .into_iter()
+        .filter(|endpoint| matches!(endpoint.type_hash, Some(hash) if hash != type_hash))
+        .map(|endpoint| endpoint.node_name)
+        .collect())
+}
+
+/// Combines a node name and a namespace into a fully qualified name.
+pub(crate) fn fully_qualified_name(name: &str, namespace: &str) -> String {
+    if namespace.ends_with('/') {
+        format!("{}{}", namespace, name)
+    } else {
+        format!("{}/{}", namespace, name)
+    }
+}
+
+fn get_fully_qualified_node_names(node_handle: &NodeHandle) -> Result<Vec<String>, RclReturnCode> {
+    Ok(get_node_names_with_namespaces(node_handle)?
+        .into_iter()
+        .map(|(name, namespace)| fully_qualified_name(&name, &namespace))
+        .collect())
+}
+
+/// Converts a `rcutils_string_array_t` into owned strings.
+///
+/// # Safety
+/// The string array must be initialized, and all its entries must be valid null-terminated
+/// strings.
+unsafe fn string_array_to_vec(string_array: &rcutils_string_array_t) -> Vec<String> {
+    if string_array.data.is_null() {
+        return Vec::new();
+    }
+    std::slice::from_raw_parts(string_array.data, string_array.size)
+        .iter()
+        .map(|&s| {
+            CStr::from_ptr(s as *const c_char)
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect()
+}
+
+/// Blocks until the graph guard condition of the node is triggered, or until the timeout has been
+/// exceeded.
+fn wait_for_graph_change(
+    node_handle: &NodeHandle,
+    context_handle: &Mutex<rcl_context_t>,
+    timeout: Option<Duration>,
+) -> Result<(), RclReturnCode> {
+    let timeout_ns = match timeout.map(|d| d.as_nanos()) {
+        None => -1,
+        Some(ns) if ns <= i64::MAX as u128 => ns as i64,
+        _ => {
+            return Err(RclReturnCode::InvalidArgument);
+        }
+    };
+    // SAFETY: The node handle is valid. The guard condition is owned by the node, which is kept
+    // alive by the caller for the duration of this function.
+    let graph_guard_condition =
+        unsafe { rcl_node_get_graph_guard_condition(&*node_handle.lock() as *const _) };
+    if graph_guard_condition.is_null() {
+        return Err(RclReturnCode::NodeError(crate::NodeErrorCode::NodeInvalid));
+    }
+    let mut wait_set = unsafe {
+        // SAFETY: Getting a zero-initialized value is always safe
+        let mut wait_set = rcl_get_zero_initialized_wait_set();
+        // SAFETY: We're passing in a zero-initialized wait set and a valid context.
+        // There are no other preconditions.
+        rcl_wait_set_init(
+            &mut wait_set as *mut _,
+            0,
+            1,
+            0,
+            0,
+            0,
+            0,
+            &mut *context_handle.lock() as *mut _,
+            rcutils_get_default_allocator(),
+        )
+        .ok()?;
+        wait_set
+    };
+    unsafe {
+        // SAFETY: The wait set and guard condition are valid, and the guard condition outlives
+        // the wait set. Passing in a null pointer for the third argument is explicitly allowed.
+        rcl_wait_set_add_guard_condition(
+            &mut wait_set as *mut _,
+            graph_guard_condition,
+            std::ptr::null_mut(),
+        )
+        .ok()?;
+        // SAFETY: The wait set is valid and only used in this thread.
+        rcl_wait(&mut wait_set as *mut _, timeout_ns).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fully_qualified_name;
+
+    #[test]
+    fn test_fully_qualified_name() {
+        assert_eq!(fully_qualified_name("my_node", "/"), "/my_node");
+        assert_eq!(fully_qualified_name("my_node", "/my_ns"), "/my_ns/my_node");
+    }
+}
@@ -0,0 +1,198 @@
+use crate::RclReturnCode;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Once;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use parking_lot::{const_mutex, Condvar, Mutex};
+
+// rcl has no event for matched endpoints in all supported distros, so the count is polled.
+// This is also the interval in which futures that can't be woken up by rcl are polled.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// The tasks of pending futures that are woken up at a deadline, shared by all futures.
+static WAKEUPS: Mutex<Vec<(Instant, Waker)>> = const_mutex(Vec::new());
+// Notified when a wakeup with a possibly earlier deadline is added.
+static WAKEUPS_CHANGED: Condvar = Condvar::new();
+static START_TIMER: Once = Once::new();
+
+// Blocks until the count reaches `n`, or the timeout has passed.
+pub(crate) fn wait_for_count<F>(
+    mut count: F,
+    n: usize,
+    timeout: Option<Duration>,
+) -> Result<(), RclReturnCode>
+where
+    F: FnMut() -> Result<usize, RclReturnCode>,
+{
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    loop {
+        if count()? >= n {
+            return Ok(());
+        }
+        let sleep_time = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(RclReturnCode::Timeout);
+                }
+                remaining.min(POLL_INTERVAL)
+            }
+            None => POLL_INTERVAL,
+        };
+        std::thread::sleep(sleep_time);
+    }
+}
+
+/// A future that resolves when a publisher or subscription has been matched with a number of
+/// endpoints.
+///
+/// Created by [`Publisher::wait_for_subscribers_async`][1] and
+/// [`Subscription::wait_for_publishers_async`][2]. It resolves to [`RclReturnCode::Timeout`]
+/// when the timeout passes first.
+///
+/// The future does not depend on a specific async runtime. While it is pending, the match count
+/// is checked every few milliseconds.
+///
+/// [1]: crate::Publisher::wait_for_subscribers_async
+/// [2]: crate::Subscription::wait_for_publishers_async
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct MatchFuture<'a> {
+    count: Box<dyn FnMut() -> Result<usize, RclReturnCode> + 'a>,
+    n: usize,
+    deadline: Option<Instant>,
+}
+
+impl<'a> MatchFuture<'a> {
+    pub(crate) fn new<F>(count: F, n: usize, timeout: Option<Duration>) -> Self
+    where
+        F: FnMut() -> Result<usize, RclReturnCode> + 'a,
+    {
+        Self {
+            count: Box::new(count),
+            n,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+}
+
+impl Future for MatchFuture<'_> {
+    type Output = Result<(), RclReturnCode>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match (self.count)() {
+            Ok(count) if count >= self.n => return Poll::Ready(Ok(())),
+            Ok(_) => {}
+            Err(e) => return Poll::Ready(Err(e)),
+        }
+        let sleep_time = match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Poll::Ready(Err(RclReturnCode::Timeout));
+                }
+                remaining.min(POLL_INTERVAL)
+            }
+            None => POLL_INTERVAL,
+        };
+        wake_after(cx, sleep_time);
+        Poll::Pending
+    }
+}
+
+// Makes the task of a pending future poll it again after the given time.
+//
+// The wakeups of all futures are run by a single timer thread, which is started on first use. A
+// task that is already scheduled keeps its earlier wakeup.
+pub(crate) fn wake_after(cx: &mut Context<'_>, duration: Duration) {
+    START_TIMER.call_once(|| {
+        std::thread::Builder::new()
+            .name(String::from("rclrs_timer"))
+            .spawn(run_timer)
+            .expect("Failed to start the timer thread");
+    });
+    let deadline = Instant::now() + duration;
+    let wakeups = &mut *WAKEUPS.lock();
+    match wakeups
+        .iter_mut()
+        .find(|(_, waker)| waker.will_wake(cx.waker()))
+    {
+        Some((scheduled, _)) if *scheduled <= deadline => return,
+        Some(wakeup) => wakeup.0 = deadline,
+        None => wakeups.push((deadline, cx.waker().clone())),
+    }
+    WAKEUPS_CHANGED.notify_one();
+}
+
+// The loop of the timer thread, which wakes each task whose deadline has passed once.
+fn run_timer() {
+    let mut wakeups = WAKEUPS.lock();
+    loop {
+        let now = Instant::now();
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut *wakeups)
+            .into_iter()
+            .partition(|(deadline, _)| *deadline <= now);
+        *wakeups = pending;
+        if !due.is_empty() {
+            // Do not hold the lock while waking, since that may poll the future right away.
+            drop(wakeups);
+            due.into_iter().for_each(|(_, waker)| waker.wake());
+            wakeups = WAKEUPS.lock();
+            continue;
+        }
+        match wakeups.iter().map(|(deadline, _)| *deadline).min() {
+            Some(deadline) => {
+                WAKEUPS_CHANGED.wait_until(&mut wakeups, deadline);
+            }
+            None => WAKEUPS_CHANGED.wait(&mut wakeups),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::sync::{mpsc, Arc};
+    use std::task::Wake;
+
+    #[test]
+    fn test_wait_for_count() {
+        let calls = Cell::new(0);
+        let count = || {
+            calls.set(calls.get() + 1);
+            Ok(calls.get())
+        };
+        assert_eq!(wait_for_count(count, 3, None), Ok(()));
+        assert_eq!(calls.get(), 3);
+        assert_eq!(
+            wait_for_count(|| Ok(0), 1, Some(Duration::from_millis(20))),
+            Err(RclReturnCode::Timeout)
+        );
+    }
+
+    struct SendOnWake(Mutex<mpsc::Sender<Instant>>);
+
+    impl Wake for SendOnWake {
+        fn wake(self: Arc<Self>) {
+            let _ = self.0.lock().send(Instant::now());
+        }
+    }
+
+    #[test]
+    fn test_wake_after() {
+        let (sender, receiver) = mpsc::channel();
+        let waker = Waker::from(Arc::new(SendOnWake(Mutex::new(sender))));
+        let mut cx = Context::from_waker(&waker);
+        let start = Instant::now();
+        // Repeated polls of the same task schedule a single wakeup.
+        for _ in 0..3 {
+            wake_after(&mut cx, Duration::from_millis(20));
+        }
+        let woken_at = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(woken_at - start >= Duration::from_millis(20));
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+    }
+}
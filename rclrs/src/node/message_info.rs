@@ -0,0 +1,130 @@
+use crate::compat::publication_sequence_number;
+use crate::rcl_bindings::*;
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// The largest GID of any supported distro. Newer distros use only the first 16 bytes.
+const MAX_GID_SIZE: usize = 24;
+
+/// The globally unique identifier of a publisher.
+///
+/// GIDs are assigned by the middleware, and are unique among all publishers in the ROS graph.
+/// They can be compared with the [`publisher_gid`][1] in the [`MessageInfo`] of a received
+/// message to tell which publisher sent it, e.g. to vote between redundant publishers of the
+/// same topic.
+///
+/// GIDs are displayed as dot-separated hex bytes, e.g. `01.0f.d3.6a…`, like in the output of
+/// `ros2 topic info --verbose`.
+///
+/// [1]: MessageInfo::publisher_gid
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Gid {
+    data: [u8; MAX_GID_SIZE],
+    len: usize,
+}
+
+impl Gid {
+    /// Returns the raw bytes of the GID.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+impl From<&rmw_gid_t> for Gid {
+    fn from(gid: &rmw_gid_t) -> Self {
+        let len = gid.data.len().min(MAX_GID_SIZE);
+        let mut data = [0; MAX_GID_SIZE];
+        data[..len].copy_from_slice(&gid.data[..len]);
+        Self { data, len }
+    }
+}
+
+impl fmt::Display for Gid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.as_bytes().iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Gid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Gid({})", self)
+    }
+}
+
+/// Information about a received message, returned by [`Subscription::take_with_info`][1].
+///
+/// [1]: crate::Subscription::take_with_info
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageInfo {
+    /// The GID of the publisher that sent the message, see [`Publisher::gid`][1].
+    ///
+    /// [1]: crate::Publisher::gid
+    pub publisher_gid: Gid,
+    /// When the message was published, according to the clock of the publisher.
+    ///
+    /// This is `None` when the middleware does not support it.
+    pub source_timestamp: Option<SystemTime>,
+    /// When the message was received by the middleware.
+    ///
+    /// This is `None` when the middleware does not support it.
+    pub received_timestamp: Option<SystemTime>,
+    /// The sequence number of the message among the messages of its publisher.
+    ///
+    /// This is `None` when the middleware does not support it, and always in Foxy and Galactic.
+    pub publication_sequence_number: Option<u64>,
+    /// Whether the message was sent by a publisher in the same process, through the intra-process
+    /// transport of the middleware.
+    pub from_intra_process: bool,
+}
+
+impl From<&rmw_message_info_t> for MessageInfo {
+    fn from(message_info: &rmw_message_info_t) -> Self {
+        Self {
+            publisher_gid: Gid::from(&message_info.publisher_gid),
+            source_timestamp: time_point_to_system_time(message_info.source_timestamp),
+            received_timestamp: time_point_to_system_time(message_info.received_timestamp),
+            publication_sequence_number: publication_sequence_number(message_info),
+            from_intra_process: message_info.from_intra_process,
+        }
+    }
+}
+
+// Converts nanoseconds since the epoch to a system time. Middlewares that don't support a
+// timestamp leave it at zero.
+fn time_point_to_system_time(nanoseconds: rmw_time_point_value_t) -> Option<SystemTime> {
+    match u64::try_from(nanoseconds) {
+        Ok(0) | Err(_) => None,
+        Ok(nanoseconds) => Some(UNIX_EPOCH + Duration::from_nanos(nanoseconds)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gid_display() {
+        let mut data = [0; MAX_GID_SIZE];
+        data[..3].copy_from_slice(&[0x01, 0x0f, 0xd3]);
+        let gid = Gid { data, len: 3 };
+        assert_eq!(gid.to_string(), "01.0f.d3");
+        assert_eq!(gid.as_bytes(), &[0x01, 0x0f, 0xd3]);
+    }
+
+    #[test]
+    fn test_time_point_to_system_time() {
+        assert_eq!(time_point_to_system_time(0), None);
+        assert_eq!(time_point_to_system_time(-1), None);
+        assert_eq!(
+            time_point_to_system_time(1_500_000_000),
+            Some(UNIX_EPOCH + Duration::from_millis(1500))
+        );
+    }
+}
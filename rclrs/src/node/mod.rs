@@ -1,25 +1,86 @@
-use crate::error::{RclReturnCode, ToResult};
+use crate::context::Interrupt;
+use crate::error::RclReturnCode;
+use crate::finalization::{check_fini, LIVE_NODES};
+use crate::metrics::CallbackMetrics;
 use crate::qos::QoSProfile;
 use crate::rcl_bindings::*;
-use crate::Context;
+use crate::{Context, Waitable};
 
+mod builder;
+mod dedup;
+mod graph;
+mod matching;
+mod message_info;
+#[cfg(not(ros_distro = "foxy"))]
+mod network_flow;
 mod publisher;
+#[cfg(not(ros_distro = "foxy"))]
+mod qos_audit;
+mod scope;
+#[cfg(feature = "futures-core")]
+mod stream;
 mod subscription;
+pub use self::builder::*;
+pub use self::dedup::*;
+pub use self::graph::*;
+pub use self::matching::MatchFuture;
+pub use self::message_info::*;
+#[cfg(not(ros_distro = "foxy"))]
+pub use self::network_flow::*;
 pub use self::publisher::*;
+#[cfg(not(ros_distro = "foxy"))]
+pub use self::qos_audit::*;
+pub use self::scope::*;
+#[cfg(feature = "futures-core")]
+pub use self::stream::*;
 pub use self::subscription::*;
 
-use std::ffi::CString;
+use std::ffi::CStr;
+use std::fmt::Display;
+use std::os::raw::c_char;
 use std::sync::{Arc, Weak};
 use std::vec::Vec;
 
-use parking_lot::Mutex;
+use parking_lot::{Mutex, MutexGuard};
 
 use rosidl_runtime_rs::Message;
 
-impl Drop for rcl_node_t {
+// The rcl node, together with the context it was created in.
+//
+// Publishers and subscriptions co-own this handle, and the handle co-owns the context, so
+// entities are always finalized before their node, and nodes before their context, no matter in
+// which order the user-facing structs are dropped.
+pub(crate) struct NodeHandle {
+    rcl_node: Mutex<rcl_node_t>,
+    pub(crate) context_handle: Arc<Mutex<rcl_context_t>>,
+}
+
+impl NodeHandle {
+    // Takes ownership of a node that was initialized in the given context.
+    pub(crate) fn new(rcl_node: rcl_node_t, context_handle: Arc<Mutex<rcl_context_t>>) -> Self {
+        LIVE_NODES.initialized();
+        Self {
+            rcl_node: Mutex::new(rcl_node),
+            context_handle,
+        }
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, rcl_node_t> {
+        self.rcl_node.lock()
+    }
+}
+
+impl Drop for NodeHandle {
     fn drop(&mut self) {
-        // SAFETY: No preconditions for this function
-        unsafe { rcl_node_fini(self as *mut _).unwrap() };
+        debug_assert!(
+            !self.context_handle.lock().impl_.is_null(),
+            "The context was finalized before its node"
+        );
+        // SAFETY: The node is valid, and its context is still alive since it is co-owned by the
+        // handle. No other entities of the node exist anymore, since they co-own the handle.
+        let ret = unsafe { rcl_node_fini(self.rcl_node.get_mut() as *mut _) };
+        check_fini(ret, "node");
+        LIVE_NODES.finalized();
     }
 }
 
@@ -32,15 +93,37 @@ impl Drop for rcl_node_t {
 /// That means that even after the node itself is dropped, it will continue to exist and be
 /// displayed by e.g. `ros2 topic` as long as its publishers and subscriptions are not dropped.
 ///
+/// # Remapping
+/// The name and namespace of a node can be changed at launch time with the `__node` and `__ns`
+/// remapping rules in the ROS arguments of its [`Context`], as in `rclcpp`. A rule that is
+/// prefixed with a node name, e.g. `talker:__node:=speaker`, only applies to the nodes with that
+/// name. Topic remapping rules, e.g. `talker:chatter:=speech`, are applied to the publishers and
+/// subscriptions of the node in the same way.
+///
+/// ```
+/// # use rclrs::{Context, Node, RclReturnCode};
+/// let args = ["--ros-args", "-r", "talker:__node:=speaker", "-r", "listener:__ns:=/foo"];
+/// let context = Context::new(args.map(String::from))?;
+/// let talker = Node::new("talker", &context)?;
+/// let listener = Node::new("listener", &context)?;
+/// assert_eq!(talker.fully_qualified_name(), "/speaker");
+/// assert_eq!(listener.fully_qualified_name(), "/foo/listener");
+/// # Ok::<(), RclReturnCode>(())
+/// ```
+///
 /// [1]: https://docs.ros.org/en/rolling/Tutorials/Understanding-ROS2-Nodes.html
 pub struct Node {
-    handle: Arc<Mutex<rcl_node_t>>,
+    handle: Arc<NodeHandle>,
     pub(crate) context: Arc<Mutex<rcl_context_t>>,
+    pub(crate) interrupt: Arc<Interrupt>,
     pub(crate) subscriptions: Vec<Weak<dyn SubscriptionBase>>,
+    pub(crate) waitables: Vec<Weak<dyn Waitable>>,
 }
 
 impl Node {
     /// Creates a new node in the empty namespace.
+    ///
+    /// See [`Node::builder`] for more options, and for detailed errors.
     #[allow(clippy::new_ret_no_self)]
     pub fn new(node_name: &str, context: &Context) -> Result<Node, RclReturnCode> {
         Self::new_with_namespace(node_name, "", context)
@@ -50,42 +133,75 @@ impl Node {
     ///
     /// A namespace without a leading forward slash is automatically changed to have a leading
     /// forward slash.
+    ///
+    /// See [`Node::builder`] for more options, and for detailed errors.
     pub fn new_with_namespace(
         node_name: &str,
         node_ns: &str,
         context: &Context,
     ) -> Result<Node, RclReturnCode> {
-        let raw_node_name = CString::new(node_name).unwrap();
-        let raw_node_ns = CString::new(node_ns).unwrap();
+        Self::builder()
+            .name(node_name)
+            .namespace(node_ns)
+            .context(context)
+            .build()
+            .map_err(RclReturnCode::from)
+    }
+
+    /// Returns the name of the node.
+    ///
+    /// This returns the name after remapping, so it is not necessarily the same as the name that
+    /// was used to create the node.
+    pub fn name(&self) -> String {
+        self.get_string(rcl_node_get_name)
+    }
 
-        // SAFETY: Getting a zero-initialized value is always safe.
-        let mut node_handle = unsafe { rcl_get_zero_initialized_node() };
-        let context_handle = &mut *context.handle.lock();
+    /// Returns the namespace of the node.
+    ///
+    /// This returns the namespace after remapping, so it is not necessarily the same as the
+    /// namespace that was used to create the node.
+    pub fn namespace(&self) -> String {
+        self.get_string(rcl_node_get_namespace)
+    }
+
+    /// Returns the fully qualified name of the node, e.g. `/my_ns/my_node`.
+    ///
+    /// The fully qualified name is the combination of the namespace and name of the node.
+    pub fn fully_qualified_name(&self) -> String {
+        self.get_string(rcl_node_get_fully_qualified_name)
+    }
 
+    // Helper for name(), namespace() and fully_qualified_name()
+    fn get_string(
+        &self,
+        getter: unsafe extern "C" fn(*const rcl_node_t) -> *const c_char,
+    ) -> String {
+        // SAFETY: The node handle is valid. The returned string is owned by the node and is
+        // copied before the lock is released.
         unsafe {
-            // SAFETY: No preconditions for this function.
-            let node_options = rcl_node_get_default_options();
-            // SAFETY: The node handle is zero-initialized as expected by this function.
-            // The strings and node options are copied by this function, so we don't need
-            // to keep them alive.
-            // The context handle is kept alive because it is co-owned by the node.
-            rcl_node_init(
-                &mut node_handle as *mut _,
-                raw_node_name.as_ptr(),
-                raw_node_ns.as_ptr(),
-                context_handle as *mut _,
-                &node_options as *const _,
-            )
-            .ok()?;
+            let char_ptr = getter(&*self.handle.lock() as *const _);
+            if char_ptr.is_null() {
+                return String::new();
+            }
+            CStr::from_ptr(char_ptr).to_string_lossy().into_owned()
         }
+    }
 
-        let handle = Arc::new(Mutex::new(node_handle));
+    /// Returns the names and namespaces of all nodes in the ROS graph, including this node.
+    pub fn get_node_names_with_namespaces(&self) -> Result<Vec<(String, String)>, RclReturnCode> {
+        get_node_names_with_namespaces(&self.handle)
+    }
 
-        Ok(Node {
-            handle,
-            context: context.handle.clone(),
-            subscriptions: std::vec![],
-        })
+    /// Returns the publishers and subscriptions of this node, with their topics, message types and
+    /// QoS profiles.
+    ///
+    /// The information is taken from the ROS graph, as seen by the middleware. It therefore also
+    /// contains entities that are created internally, such as the `/rosout` publisher, and new
+    /// entities might not be listed immediately.
+    pub fn entities(&self) -> Result<Vec<EntityInfo>, RclReturnCode> {
+        let mut entities = get_node_entities(&self.handle, EntityKind::Publisher)?;
+        entities.extend(get_node_entities(&self.handle, EntityKind::Subscription)?);
+        Ok(entities)
     }
 
     /// Creates a [`Publisher`][1].
@@ -117,12 +233,134 @@ impl Node {
         T: Message,
         F: FnMut(T) + Sized + 'static,
     {
-        let subscription = Arc::new(Subscription::<T>::new(self, topic, qos, callback)?);
+        self.create_subscription_with_options(topic, qos, SubscriptionOptions::default(), callback)
+    }
+
+    /// Creates a [`Subscription`][1] with non-default [`SubscriptionOptions`][2].
+    ///
+    /// [1]: crate::Subscription
+    /// [2]: crate::SubscriptionOptions
+    pub fn create_subscription_with_options<T, F>(
+        &mut self,
+        topic: &str,
+        qos: QoSProfile,
+        options: SubscriptionOptions,
+        callback: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        F: FnMut(T) + Sized + 'static,
+    {
+        let subscription = Arc::new(Subscription::<T>::new_with_options(
+            self, topic, qos, options, callback,
+        )?);
         self.subscriptions
             .push(Arc::downgrade(&subscription) as Weak<dyn SubscriptionBase>);
         Ok(subscription)
     }
 
+    /// Creates a [`Subscription`] with a callback that can fail.
+    ///
+    /// See [`Subscription::new_fallible`] for how errors are handled.
+    pub fn create_fallible_subscription<T, E, F>(
+        &mut self,
+        topic: &str,
+        qos: QoSProfile,
+        error_policy: CallbackErrorPolicy<E>,
+        callback: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        E: Display + 'static,
+        F: FnMut(T) -> Result<(), E> + Sized + 'static,
+    {
+        self.create_fallible_subscription_with_options(
+            topic,
+            qos,
+            SubscriptionOptions::default(),
+            error_policy,
+            callback,
+        )
+    }
+
+    /// Creates a [`Subscription`] with a callback that can fail, and with non-default
+    /// [`SubscriptionOptions`].
+    pub fn create_fallible_subscription_with_options<T, E, F>(
+        &mut self,
+        topic: &str,
+        qos: QoSProfile,
+        options: SubscriptionOptions,
+        error_policy: CallbackErrorPolicy<E>,
+        callback: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        E: Display + 'static,
+        F: FnMut(T) -> Result<(), E> + Sized + 'static,
+    {
+        let subscription = Arc::new(Subscription::<T>::new_fallible(
+            self,
+            topic,
+            qos,
+            options,
+            error_policy,
+            callback,
+        )?);
+        self.subscriptions
+            .push(Arc::downgrade(&subscription) as Weak<dyn SubscriptionBase>);
+        Ok(subscription)
+    }
+
+    /// Returns a scope in which the topics of new publishers and subscriptions are prefixed with
+    /// the given namespace.
+    ///
+    /// See [`NamespaceScope`].
+    pub fn scoped_namespace(&mut self, namespace: &str) -> NamespaceScope<'_> {
+        NamespaceScope::new(self, namespace.trim_matches('/').to_owned())
+    }
+
+    /// Returns timing statistics for the callbacks of all subscriptions of this node.
+    ///
+    /// The statistics are collected by [`spin_once`][1] and [`spin`][2], and cover the whole
+    /// lifetime of each subscription. Subscriptions that have been dropped are not included.
+    ///
+    /// See [`metrics_to_prometheus_text`][3] for exporting them.
+    ///
+    /// [1]: crate::spin_once
+    /// [2]: crate::spin
+    /// [3]: crate::metrics_to_prometheus_text
+    pub fn callback_metrics(&self) -> Vec<CallbackMetrics> {
+        self.live_subscriptions()
+            .iter()
+            .map(|subscription| {
+                let handle = subscription.handle();
+                CallbackMetrics {
+                    topic: handle.topic_name(),
+                    ..handle.metrics.lock().clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Adds a waitable, which is then spun together with the subscriptions of this node.
+    ///
+    /// Like subscriptions, the waitable is only spun as long as it is alive, i.e. the node does
+    /// not keep it alive.
+    ///
+    /// This will return [`RclReturnCode::InvalidArgument`] if the guard condition of the waitable
+    /// belongs to a different [`Context`] than the node.
+    pub fn add_waitable<W>(&mut self, waitable: &Arc<W>) -> Result<(), RclReturnCode>
+    where
+        W: Waitable + 'static,
+    {
+        if !Arc::ptr_eq(&waitable.guard_condition().context_handle, &self.context) {
+            return Err(RclReturnCode::InvalidArgument);
+        }
+        self.waitables
+            .push(Arc::downgrade(waitable) as Weak<dyn Waitable>);
+        Ok(())
+    }
+
     /// Returns the subscriptions that have not been dropped yet.
     pub(crate) fn live_subscriptions(&self) -> Vec<Arc<dyn SubscriptionBase>> {
         self.subscriptions
@@ -130,4 +368,9 @@ impl Node {
             .filter_map(Weak::upgrade)
             .collect()
     }
+
+    /// Returns the waitables that have not been dropped yet.
+    pub(crate) fn live_waitables(&self) -> Vec<Arc<dyn Waitable>> {
+        self.waitables.iter().filter_map(Weak::upgrade).collect()
+    }
 }
@@ -0,0 +1,104 @@
+use crate::error::{RclReturnCode, ToResult};
+use crate::rcl_bindings::*;
+
+use std::ffi::CStr;
+use std::vec::Vec;
+
+/// The transport protocol of a [`NetworkFlowEndpoint`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TransportProtocol {
+    /// The protocol is not known to the middleware.
+    Unknown,
+    /// User Datagram Protocol.
+    Udp,
+    /// Transmission Control Protocol.
+    Tcp,
+}
+
+/// The internet protocol of a [`NetworkFlowEndpoint`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InternetProtocol {
+    /// The protocol is not known to the middleware.
+    Unknown,
+    /// Internet Protocol version 4.
+    Ipv4,
+    /// Internet Protocol version 6.
+    Ipv6,
+}
+
+/// A local endpoint of the network traffic of a publisher or subscription.
+///
+/// Network flow endpoints can be used to configure traffic shaping in the network, e.g. to give
+/// the packets of a critical topic a higher priority based on their address and port. They are
+/// returned by [`Publisher::network_flow_endpoints`][1] and
+/// [`Subscription::network_flow_endpoints`][2].
+///
+/// [1]: crate::Publisher::network_flow_endpoints
+/// [2]: crate::Subscription::network_flow_endpoints
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct NetworkFlowEndpoint {
+    /// The transport protocol, e.g. UDP.
+    pub transport_protocol: TransportProtocol,
+    /// The internet protocol, e.g. IPv4.
+    pub internet_protocol: InternetProtocol,
+    /// The port of the transport protocol.
+    pub transport_port: u16,
+    /// The IPv6 flow label, or `0` if there is none.
+    pub flow_label: u32,
+    /// The internet address, e.g. `192.168.1.10`.
+    pub internet_address: String,
+}
+
+impl From<&rmw_network_flow_endpoint_t> for NetworkFlowEndpoint {
+    fn from(endpoint: &rmw_network_flow_endpoint_t) -> Self {
+        let transport_protocol = match endpoint.transport_protocol {
+            rmw_transport_protocol_t::RMW_TRANSPORT_PROTOCOL_UDP => TransportProtocol::Udp,
+            rmw_transport_protocol_t::RMW_TRANSPORT_PROTOCOL_TCP => TransportProtocol::Tcp,
+            _ => TransportProtocol::Unknown,
+        };
+        let internet_protocol = match endpoint.internet_protocol {
+            rmw_internet_protocol_t::RMW_INTERNET_PROTOCOL_IPV4 => InternetProtocol::Ipv4,
+            rmw_internet_protocol_t::RMW_INTERNET_PROTOCOL_IPV6 => InternetProtocol::Ipv6,
+            _ => InternetProtocol::Unknown,
+        };
+        // SAFETY: The address is a null-terminated string in a fixed-size array.
+        let internet_address = unsafe { CStr::from_ptr(endpoint.internet_address.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        Self {
+            transport_protocol,
+            internet_protocol,
+            transport_port: endpoint.transport_port,
+            flow_label: endpoint.flow_label,
+            internet_address,
+        }
+    }
+}
+
+/// Calls a `rcl_*_get_network_flow_endpoints()` function and converts the resulting array.
+pub(crate) fn get_network_flow_endpoints<F>(
+    get_endpoints: F,
+) -> Result<Vec<NetworkFlowEndpoint>, RclReturnCode>
+where
+    F: FnOnce(*mut rcutils_allocator_t, *mut rcl_network_flow_endpoint_array_t) -> rcl_ret_t,
+{
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut endpoint_array = unsafe { rmw_get_zero_initialized_network_flow_endpoint_array() };
+    // SAFETY: No preconditions for this function.
+    let mut allocator = unsafe { rcutils_get_default_allocator() };
+    get_endpoints(&mut allocator as *mut _, &mut endpoint_array as *mut _).ok()?;
+    let endpoints = if endpoint_array.network_flow_endpoint.is_null() {
+        Vec::new()
+    } else {
+        // SAFETY: The array contains `size` initialized elements.
+        unsafe {
+            std::slice::from_raw_parts(endpoint_array.network_flow_endpoint, endpoint_array.size)
+        }
+        .iter()
+        .map(NetworkFlowEndpoint::from)
+        .collect()
+    };
+    // SAFETY: The array was initialized by the function above, and stores its allocator.
+    unsafe { rmw_network_flow_endpoint_array_fini(&mut endpoint_array as *mut _) }.ok()?;
+    Ok(endpoints)
+}
@@ -1,12 +1,26 @@
 use crate::error::{RclReturnCode, ToResult};
+#[cfg(feature = "fault-injection")]
+use crate::fault_injection::FaultPoint;
+use crate::finalization::{check_fini, LIVE_PUBLISHERS};
+#[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+use crate::node::get_mismatched_endpoints;
+use crate::node::matching::{wait_for_count, MatchFuture};
+#[cfg(not(ros_distro = "foxy"))]
+use crate::node::network_flow::{get_network_flow_endpoints, NetworkFlowEndpoint};
+use crate::node::{Gid, NodeHandle};
+use crate::provenance::PublishedProvenance;
 use crate::qos::QoSProfile;
 use crate::rcl_bindings::*;
+use crate::serialized::SerializedMessage;
 use crate::Node;
+#[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+use crate::{EntityKind, TypeHash};
 
 use std::borrow::Cow;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 
 use parking_lot::{Mutex, MutexGuard};
 
@@ -14,23 +28,149 @@ use rosidl_runtime_rs::{Message, RmwMessage};
 
 pub(crate) struct PublisherHandle {
     handle: Mutex<rcl_publisher_t>,
-    node_handle: Arc<Mutex<rcl_node_t>>,
+    node_handle: Arc<NodeHandle>,
+    gid: Gid,
+    // The provenance of the last message, for the propagation of provenance IDs. This is `None`
+    // until the GID of the publisher is known.
+    provenance: Option<Arc<PublishedProvenance>>,
 }
 
 impl PublisherHandle {
+    // Creates a publisher with the given type support, which must stay valid for as long as the
+    // publisher exists.
+    //
+    // # Panics
+    // When the topic contains interior null bytes.
+    pub(crate) fn new(
+        node: &Node,
+        topic: &str,
+        type_support: *const rosidl_message_type_support_t,
+        qos: QoSProfile,
+    ) -> Result<Self, RclReturnCode> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut publisher_handle = unsafe { rcl_get_zero_initialized_publisher() };
+        let topic_c_string = CString::new(topic).unwrap();
+
+        // SAFETY: No preconditions for this function.
+        let mut publisher_options = unsafe { rcl_publisher_get_default_options() };
+        publisher_options.qos = qos.into();
+        unsafe {
+            // SAFETY: The publisher handle is zero-initialized as expected by this function.
+            // The node handle is kept alive because it is co-owned by the publisher.
+            // The topic name and the options are copied by this function, so they can be dropped
+            // afterwards.
+            // TODO: type support?
+            rcl_publisher_init(
+                &mut publisher_handle as *mut _,
+                &mut *node.handle.lock() as *mut _,
+                type_support,
+                topic_c_string.as_ptr(),
+                &publisher_options as *const _,
+            )
+            .ok()?;
+        }
+        LIVE_PUBLISHERS.initialized();
+
+        // SAFETY: The GID is plain data, for which all zeroes is a valid value. It is filled in
+        // by rmw_get_gid_for_publisher().
+        let mut gid: rmw_gid_t = unsafe { std::mem::zeroed() };
+        // The handle takes ownership right away, so that the publisher is finalized if anything
+        // below fails.
+        let mut handle = PublisherHandle {
+            handle: Mutex::new(publisher_handle),
+            node_handle: node.handle.clone(),
+            gid: Gid::from(&gid),
+            provenance: None,
+        };
+        unsafe {
+            // SAFETY: The publisher handle has been initialized above, so its rmw handle is valid.
+            rmw_get_gid_for_publisher(
+                rcl_publisher_get_rmw_handle(&*handle.lock() as *const _),
+                &mut gid as *mut _,
+            )
+            .ok()?;
+        }
+        handle.gid = Gid::from(&gid);
+        handle.provenance = Some(PublishedProvenance::register(&gid));
+        Ok(handle)
+    }
+
     fn lock(&self) -> MutexGuard<rcl_publisher_t> {
         self.handle.lock()
     }
+
+    pub(crate) fn publish_serialized(
+        &self,
+        message: &SerializedMessage,
+    ) -> Result<(), RclReturnCode> {
+        #[cfg(feature = "fault-injection")]
+        if let Some(error_code) = crate::fault_injection::intercept(FaultPoint::Publish) {
+            return Err(RclReturnCode::from(error_code));
+        }
+        let handle = &*self.lock();
+        // SAFETY: The publisher handle and the serialized message are valid, and the message
+        // does not need to be valid beyond the duration of this function call.
+        // The third argument is explicitly allowed to be NULL.
+        unsafe {
+            rcl_publish_serialized_message(
+                handle as *const _,
+                &message.handle as *const _,
+                std::ptr::null_mut(),
+            )
+        }
+        .ok()?;
+        self.record_published();
+        Ok(())
+    }
+
+    pub(crate) fn topic_name(&self) -> String {
+        // SAFETY: The handle is valid. The returned string is owned by the publisher and is
+        // copied before the lock is released.
+        unsafe {
+            let topic_name = rcl_publisher_get_topic_name(&*self.lock() as *const _);
+            if topic_name.is_null() {
+                return String::new();
+            }
+            CStr::from_ptr(topic_name).to_string_lossy().into_owned()
+        }
+    }
+
+    pub(crate) fn get_subscription_count(&self) -> Result<usize, RclReturnCode> {
+        let mut subscription_count = 0;
+        // SAFETY: The publisher handle is valid, and the count is a valid pointer.
+        unsafe {
+            rcl_publisher_get_subscription_count(
+                &*self.lock() as *const _,
+                &mut subscription_count as *mut _,
+            )
+            .ok()?;
+        }
+        Ok(subscription_count)
+    }
+
+    fn record_published(&self) {
+        if let Some(provenance) = &self.provenance {
+            provenance.record();
+        }
+    }
 }
 
 impl Drop for PublisherHandle {
     fn drop(&mut self) {
+        if let Some(provenance) = &self.provenance {
+            provenance.unregister();
+        }
         let handle = self.handle.get_mut();
         let node_handle = &mut *self.node_handle.lock();
-        // SAFETY: No preconditions for this function (besides the arguments being valid).
-        unsafe {
-            rcl_publisher_fini(handle as *mut _, node_handle as *mut _);
-        }
+        debug_assert!(
+            !node_handle.impl_.is_null(),
+            "The node was finalized before its publisher"
+        );
+        // SAFETY: No preconditions for this function (besides the arguments being valid). The
+        // node is still valid, since it is co-owned by the publisher.
+        let ret = unsafe { rcl_publisher_fini(handle as *mut _, node_handle as *mut _) };
+        check_fini(ret, "publisher");
+        LIVE_PUBLISHERS.finalized();
     }
 }
 
@@ -64,36 +204,9 @@ where
     where
         T: Message,
     {
-        // SAFETY: Getting a zero-initialized value is always safe.
-        let mut publisher_handle = unsafe { rcl_get_zero_initialized_publisher() };
         let type_support =
             <T as Message>::RmwMsg::get_type_support() as *const rosidl_message_type_support_t;
-        let topic_c_string = CString::new(topic).unwrap();
-        let node_handle = &mut *node.handle.lock();
-
-        // SAFETY: No preconditions for this function.
-        let mut publisher_options = unsafe { rcl_publisher_get_default_options() };
-        publisher_options.qos = qos.into();
-        unsafe {
-            // SAFETY: The publisher handle is zero-initialized as expected by this function.
-            // The node handle is kept alive because it is co-owned by the subscription.
-            // The topic name and the options are copied by this function, so they can be dropped
-            // afterwards.
-            // TODO: type support?
-            rcl_publisher_init(
-                &mut publisher_handle as *mut _,
-                node_handle as *mut _,
-                type_support,
-                topic_c_string.as_ptr(),
-                &publisher_options as *const _,
-            )
-            .ok()?;
-        }
-
-        let handle = Arc::new(PublisherHandle {
-            handle: Mutex::new(publisher_handle),
-            node_handle: node.handle.clone(),
-        });
+        let handle = Arc::new(PublisherHandle::new(node, topic, type_support, qos)?);
 
         Ok(Self {
             handle,
@@ -118,7 +231,25 @@ where
     ///
     /// [1]: https://github.com/ros2/ros2/issues/255
     pub fn publish<'a, M: MessageCow<'a, T>>(&self, message: M) -> Result<(), RclReturnCode> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "publish",
+            topic = %self.topic_name(),
+            provenance = ?crate::current_provenance()
+        )
+        .entered();
         let rmw_message = T::into_rmw_message(message.into_cow());
+        self.publish_rmw_message(rmw_message.as_ref())
+    }
+
+    fn publish_rmw_message(
+        &self,
+        rmw_message: &<T as Message>::RmwMsg,
+    ) -> Result<(), RclReturnCode> {
+        #[cfg(feature = "fault-injection")]
+        if let Some(error_code) = crate::fault_injection::intercept(FaultPoint::Publish) {
+            return Err(RclReturnCode::from(error_code));
+        }
         let handle = &mut *self.handle.lock();
         let ret = unsafe {
             // SAFETY: The message type is guaranteed to match the publisher type by the type system.
@@ -126,11 +257,195 @@ where
             // The third argument is explictly allowed to be NULL.
             rcl_publish(
                 handle as *mut _,
-                rmw_message.as_ref() as *const <T as Message>::RmwMsg as *mut _,
+                rmw_message as *const <T as Message>::RmwMsg as *mut _,
                 std::ptr::null_mut(),
             )
         };
-        ret.ok()
+        ret.ok()?;
+        self.handle.record_published();
+        Ok(())
+    }
+
+    /// Publishes a message that is already in the serialized format of the middleware.
+    ///
+    /// The message is not checked to be a serialized `T`. See also
+    /// [`Subscription::take_serialized`][1].
+    ///
+    /// [1]: crate::Subscription::take_serialized
+    pub fn publish_serialized(&self, message: &SerializedMessage) -> Result<(), RclReturnCode> {
+        self.handle.publish_serialized(message)
+    }
+
+    /// Returns the number of subscriptions that are currently matched with this publisher.
+    pub fn get_subscription_count(&self) -> Result<usize, RclReturnCode> {
+        self.handle.get_subscription_count()
+    }
+
+    /// Blocks until at least `n` subscriptions are matched with this publisher.
+    ///
+    /// This is useful when the first messages must not be lost, e.g. in tests and at startup.
+    /// When the subscriptions are not matched within the `timeout`, [`RclReturnCode::Timeout`]
+    /// is returned. A `timeout` of `None` waits forever.
+    pub fn wait_for_subscribers(
+        &self,
+        n: usize,
+        timeout: Option<Duration>,
+    ) -> Result<(), RclReturnCode> {
+        wait_for_count(|| self.get_subscription_count(), n, timeout)
+    }
+
+    /// Like [`wait_for_subscribers`][1], but returns a future instead of blocking.
+    ///
+    /// [1]: Publisher::wait_for_subscribers
+    pub fn wait_for_subscribers_async(
+        &self,
+        n: usize,
+        timeout: Option<Duration>,
+    ) -> MatchFuture<'_> {
+        MatchFuture::new(move || self.get_subscription_count(), n, timeout)
+    }
+
+    /// Returns the QoS profile that is actually used by the middleware.
+    ///
+    /// This can differ from the requested profile when it contained system default values, which
+    /// have been replaced by the middleware's defaults.
+    pub fn actual_qos(&self) -> Result<QoSProfile, RclReturnCode> {
+        // SAFETY: The handle is valid. The returned profile is owned by the publisher and is
+        // copied before the lock is released.
+        unsafe {
+            let qos = rcl_publisher_get_actual_qos(&*self.handle.lock() as *const _);
+            if qos.is_null() {
+                return Err(RclReturnCode::PublisherInvalid);
+            }
+            Ok(QoSProfile::from(&*qos))
+        }
+    }
+
+    /// Returns the globally unique identifier of the publisher.
+    ///
+    /// Subscribers receive it as the [`publisher_gid`][1] of the [`MessageInfo`][2] of each
+    /// message from this publisher.
+    ///
+    /// [1]: crate::MessageInfo::publisher_gid
+    /// [2]: crate::MessageInfo
+    pub fn gid(&self) -> Gid {
+        self.handle.gid
+    }
+
+    /// Returns the fully qualified topic name of the publisher, after remapping.
+    pub fn topic_name(&self) -> String {
+        self.handle.topic_name()
+    }
+
+    /// Returns the fully qualified names of the nodes with subscriptions on the topic, whose
+    /// message type has the same name but a different definition than `T`.
+    ///
+    /// Such subscriptions never receive messages from this publisher. The check compares the
+    /// [`TypeHash`][1] of `T` with the type hashes that the subscriptions advertise, so it
+    /// always returns an empty list when either is unknown.
+    ///
+    /// This is only available since Iron.
+    ///
+    /// [1]: crate::TypeHash
+    #[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+    pub fn mismatched_subscriptions(&self) -> Result<Vec<String>, RclReturnCode> {
+        match TypeHash::of::<T>() {
+            Some(type_hash) => get_mismatched_endpoints(
+                &self.handle.node_handle,
+                &self.topic_name(),
+                EntityKind::Subscription,
+                type_hash,
+            ),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the local network flow endpoints of the publisher.
+    ///
+    /// Not all middleware implementations support this, in which case an error is returned.
+    /// This is not available in Foxy.
+    #[cfg(not(ros_distro = "foxy"))]
+    pub fn network_flow_endpoints(&self) -> Result<Vec<NetworkFlowEndpoint>, RclReturnCode> {
+        let handle = &*self.handle.lock();
+        get_network_flow_endpoints(|allocator, endpoint_array| {
+            // SAFETY: The publisher handle is valid, and the endpoint array is zero-initialized
+            // as expected by this function.
+            unsafe {
+                rcl_publisher_get_network_flow_endpoints(
+                    handle as *const _,
+                    allocator,
+                    endpoint_array,
+                )
+            }
+        })
+    }
+}
+
+/// A [`Publisher`] that owns a persistent RMW-compatible message.
+///
+/// Publishing an idiomatic message allocates a new RMW-compatible message for each call. For
+/// high-rate topics, it is more efficient to modify the buffer returned by
+/// [`buffer_mut`][1] in place and then call [`publish`][2]. Strings and sequences in the buffer
+/// keep their allocations between publishes as long as their size does not change.
+///
+/// [1]: PublisherWithBuffer::buffer_mut
+/// [2]: PublisherWithBuffer::publish
+pub struct PublisherWithBuffer<T>
+where
+    T: Message,
+{
+    publisher: Publisher<T>,
+    buffer: <T as Message>::RmwMsg,
+}
+
+impl<T> PublisherWithBuffer<T>
+where
+    T: Message,
+{
+    /// Creates a new `PublisherWithBuffer`, whose buffer is a default-initialized message.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new(node: &Node, topic: &str, qos: QoSProfile) -> Result<Self, RclReturnCode> {
+        Ok(Self::from(Publisher::new(node, topic, qos)?))
+    }
+
+    /// Returns the buffer that is sent by [`publish`][1].
+    ///
+    /// [1]: PublisherWithBuffer::publish
+    pub fn buffer(&self) -> &<T as Message>::RmwMsg {
+        &self.buffer
+    }
+
+    /// Returns the buffer that is sent by [`publish`][1], for modifying it in place.
+    ///
+    /// [1]: PublisherWithBuffer::publish
+    pub fn buffer_mut(&mut self) -> &mut <T as Message>::RmwMsg {
+        &mut self.buffer
+    }
+
+    /// Publishes the current contents of the buffer.
+    ///
+    /// The buffer is left unchanged.
+    pub fn publish(&self) -> Result<(), RclReturnCode> {
+        self.publisher.publish_rmw_message(&self.buffer)
+    }
+
+    /// Returns the underlying publisher.
+    pub fn publisher(&self) -> &Publisher<T> {
+        &self.publisher
+    }
+}
+
+impl<T> From<Publisher<T>> for PublisherWithBuffer<T>
+where
+    T: Message,
+{
+    fn from(publisher: Publisher<T>) -> Self {
+        Self {
+            publisher,
+            buffer: Default::default(),
+        }
     }
 }
 
@@ -0,0 +1,334 @@
+use crate::error::RclReturnCode;
+use crate::node::graph::{get_node_entities, get_topic_endpoints};
+use crate::qos::{check_rmw_qos_compatible, QoSCompatibility, QoSProfile};
+use crate::{EntityInfo, EntityKind, GuardCondition, Node, NodeHandle, Waitable};
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+type AuditCallback = Box<dyn FnMut(&QoSAuditReport) + 'static>;
+
+// Topics of internal entities, which are expected to have no peers most of the time.
+const INTERNAL_TOPICS: &[&str] = &["/rosout"];
+
+/// The result of auditing the endpoints of a node, returned by [`Node::audit_qos`].
+///
+/// Like `ros2 doctor --report`, the report lists the publishers and subscriptions of the node
+/// that cannot communicate with their peers: those whose QoS profile is incompatible with a peer,
+/// those whose message type differs from a peer, and those without any compatible peer.
+///
+/// The report is formatted as a human-readable list of problems with `Display`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QoSAuditReport {
+    /// The fully qualified name of the audited node.
+    pub node_name: String,
+    /// The audit of each publisher and subscription of the node.
+    pub endpoints: Vec<EndpointAudit>,
+}
+
+/// The audit of a single publisher or subscription, see [`QoSAuditReport`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndpointAudit {
+    /// The audited publisher or subscription.
+    pub entity: EntityInfo,
+    /// The number of peers, i.e. subscriptions for a publisher and publishers for a subscription,
+    /// that are compatible with the entity.
+    pub compatible_peers: usize,
+    /// The peers whose QoS profile is or may be incompatible with the entity.
+    pub qos_mismatches: Vec<QoSMismatch>,
+    /// The fully qualified names of the nodes of peers that advertise a different type hash.
+    ///
+    /// This is always empty before Iron.
+    pub type_mismatches: Vec<String>,
+}
+
+/// A peer whose QoS profile is incompatible with an audited entity, see [`EndpointAudit`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct QoSMismatch {
+    /// The fully qualified name of the node of the peer.
+    pub node_name: String,
+    /// The QoS profile of the peer.
+    pub qos: QoSProfile,
+    /// Whether the profiles are incompatible or only possibly incompatible, with the reason.
+    pub compatibility: QoSCompatibility,
+}
+
+impl QoSAuditReport {
+    /// Returns `true` if no endpoint has mismatches or is without peers.
+    pub fn is_healthy(&self) -> bool {
+        self.endpoints.iter().all(EndpointAudit::is_healthy)
+    }
+
+    /// Returns the endpoints that have no compatible peers.
+    pub fn unmatched(&self) -> impl Iterator<Item = &EndpointAudit> {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.compatible_peers == 0)
+    }
+}
+
+impl EndpointAudit {
+    /// Returns `true` if the entity has compatible peers, and no incompatible ones.
+    pub fn is_healthy(&self) -> bool {
+        self.compatible_peers > 0
+            && self.qos_mismatches.is_empty()
+            && self.type_mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for QoSAuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "QoS audit of node '{}': ", self.node_name)?;
+        if self.is_healthy() {
+            return write!(f, "no problems found");
+        }
+        write!(f, "problems found")?;
+        for endpoint in self.endpoints.iter().filter(|e| !e.is_healthy()) {
+            let kind = match endpoint.entity.kind {
+                EntityKind::Publisher => "publisher",
+                EntityKind::Subscription => "subscription",
+            };
+            write!(
+                f,
+                "\n  {} on '{}' [{}]:",
+                kind, endpoint.entity.topic_name, endpoint.entity.topic_type
+            )?;
+            if endpoint.compatible_peers == 0 {
+                write!(f, "\n    no compatible peers")?;
+            }
+            for mismatch in &endpoint.qos_mismatches {
+                let (severity, reason) = match &mismatch.compatibility {
+                    QoSCompatibility::Ok => continue,
+                    QoSCompatibility::Warning(reason) => ("may be incompatible", reason),
+                    QoSCompatibility::Error(reason) => ("incompatible", reason),
+                };
+                write!(
+                    f,
+                    "\n    QoS {} with '{}': {}",
+                    severity, mismatch.node_name, reason
+                )?;
+            }
+            for node_name in &endpoint.type_mismatches {
+                write!(f, "\n    different message definition in '{}'", node_name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Node {
+    /// Checks the publishers and subscriptions of this node against their peers in the ROS graph.
+    ///
+    /// See [`QoSAuditReport`] for what is checked, and [`QoSAuditor`] for running the audit
+    /// periodically.
+    ///
+    /// This is not available in Foxy.
+    pub fn audit_qos(&self) -> Result<QoSAuditReport, RclReturnCode> {
+        audit_node(&self.handle, self.fully_qualified_name())
+    }
+}
+
+fn audit_node(
+    node_handle: &NodeHandle,
+    node_name: String,
+) -> Result<QoSAuditReport, RclReturnCode> {
+    let mut entities = get_node_entities(node_handle, EntityKind::Publisher)?;
+    entities.extend(get_node_entities(node_handle, EntityKind::Subscription)?);
+    let endpoints = entities
+        .into_iter()
+        .filter(|entity| !INTERNAL_TOPICS.contains(&entity.topic_name.as_str()))
+        .map(|entity| audit_entity(node_handle, entity))
+        .collect::<Result<_, _>>()?;
+    Ok(QoSAuditReport {
+        node_name,
+        endpoints,
+    })
+}
+
+fn audit_entity(
+    node_handle: &NodeHandle,
+    entity: EntityInfo,
+) -> Result<EndpointAudit, RclReturnCode> {
+    let peer_kind = match entity.kind {
+        EntityKind::Publisher => EntityKind::Subscription,
+        EntityKind::Subscription => EntityKind::Publisher,
+    };
+    let mut audit = EndpointAudit {
+        compatible_peers: 0,
+        qos_mismatches: Vec::new(),
+        type_mismatches: Vec::new(),
+        entity,
+    };
+    for peer in get_topic_endpoints(node_handle, &audit.entity.topic_name, peer_kind)? {
+        let (publisher_qos, subscription_qos) = match audit.entity.kind {
+            EntityKind::Publisher => (audit.entity.qos, peer.qos),
+            EntityKind::Subscription => (peer.qos, audit.entity.qos),
+        };
+        let compatibility =
+            check_rmw_qos_compatible(publisher_qos.into(), subscription_qos.into())?;
+        let type_matches = match (audit.entity.type_hash, peer.type_hash) {
+            (Some(own), Some(other)) => own == other,
+            _ => true,
+        };
+        if !type_matches {
+            audit.type_mismatches.push(peer.node_name.clone());
+        }
+        match compatibility {
+            QoSCompatibility::Ok if type_matches => audit.compatible_peers += 1,
+            QoSCompatibility::Ok => {}
+            compatibility => audit.qos_mismatches.push(QoSMismatch {
+                node_name: peer.node_name,
+                qos: peer.qos,
+                compatibility,
+            }),
+        }
+    }
+    Ok(audit)
+}
+
+/// Periodically audits the endpoints of a node, and passes the report to a callback.
+///
+/// This is a background check for misconfigured systems, which e.g. logs the report, or
+/// publishes it for a monitoring tool. A timer thread wakes up the spin loop once per period,
+/// and the audit and the callback run on the thread that spins the node, like subscription
+/// callbacks. The auditor is a [`Waitable`] that must be added to the node with
+/// [`Node::add_waitable`]. Dropping it stops the timer thread.
+///
+/// Since peers are discovered asynchronously, endpoints typically have no peers for a short
+/// time after they were created. The internal `/rosout` publisher is not audited.
+///
+/// This is not available in Foxy.
+///
+/// # Example
+/// ```ignore
+/// let auditor = Arc::new(QoSAuditor::new(&node, Duration::from_secs(10), |report| {
+///     if !report.is_healthy() {
+///         eprintln!("{}", report);
+///     }
+/// })?);
+/// node.add_waitable(&auditor)?;
+/// rclrs::spin(&node)?;
+/// ```
+pub struct QoSAuditor {
+    guard_condition: Arc<GuardCondition>,
+    node_handle: Arc<NodeHandle>,
+    node_name: String,
+    callback: Mutex<AuditCallback>,
+    due: Arc<AtomicBool>,
+    // Dropping the sender stops the timer thread.
+    stop_sender: Option<Sender<()>>,
+    timer: Option<JoinHandle<()>>,
+}
+
+impl QoSAuditor {
+    /// Creates an auditor that audits the node once per `period`, starting after one period.
+    pub fn new<F>(node: &Node, period: Duration, callback: F) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut(&QoSAuditReport) + 'static,
+    {
+        let guard_condition = Arc::new(GuardCondition::new_for_context_handle(&node.context)?);
+        let due = Arc::new(AtomicBool::new(false));
+        let (stop_sender, stop_receiver) = mpsc::channel::<()>();
+        let timer = {
+            let guard_condition = Arc::clone(&guard_condition);
+            let due = Arc::clone(&due);
+            std::thread::Builder::new()
+                .name(String::from("rclrs_qos_audit"))
+                .spawn(move || {
+                    while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(period) {
+                        due.store(true, Ordering::Release);
+                        if guard_condition.trigger().is_err() {
+                            break;
+                        }
+                    }
+                })
+                .map_err(|_| RclReturnCode::Error)?
+        };
+        Ok(Self {
+            guard_condition,
+            node_handle: Arc::clone(&node.handle),
+            node_name: node.fully_qualified_name(),
+            callback: Mutex::new(Box::new(callback)),
+            due,
+            stop_sender: Some(stop_sender),
+            timer: Some(timer),
+        })
+    }
+}
+
+impl Waitable for QoSAuditor {
+    fn guard_condition(&self) -> &GuardCondition {
+        &self.guard_condition
+    }
+
+    fn execute(&self) -> Result<(), RclReturnCode> {
+        if !self.due.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let report = audit_node(&self.node_handle, self.node_name.clone())?;
+        (*self.callback.lock())(&report);
+        Ok(())
+    }
+}
+
+impl Drop for QoSAuditor {
+    fn drop(&mut self) {
+        self.stop_sender.take();
+        if let Some(timer) = self.timer.take() {
+            let _ = timer.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QOS_PROFILE_DEFAULT;
+
+    #[test]
+    fn test_report_display() {
+        let endpoint = EndpointAudit {
+            entity: EntityInfo {
+                kind: EntityKind::Subscription,
+                topic_name: String::from("/scan"),
+                topic_type: String::from("sensor_msgs/msg/LaserScan"),
+                qos: QOS_PROFILE_DEFAULT,
+                type_hash: None,
+            },
+            compatible_peers: 1,
+            qos_mismatches: Vec::new(),
+            type_mismatches: Vec::new(),
+        };
+        let mut report = QoSAuditReport {
+            node_name: String::from("/ns/node"),
+            endpoints: vec![endpoint],
+        };
+        assert!(report.is_healthy());
+        assert_eq!(
+            report.to_string(),
+            "QoS audit of node '/ns/node': no problems found"
+        );
+
+        report.endpoints[0].compatible_peers = 0;
+        report.endpoints[0].qos_mismatches.push(QoSMismatch {
+            node_name: String::from("/lidar"),
+            qos: QOS_PROFILE_DEFAULT,
+            compatibility: QoSCompatibility::Error(String::from("Best effort publisher")),
+        });
+        assert!(!report.is_healthy());
+        assert_eq!(report.unmatched().count(), 1);
+        assert_eq!(
+            report.to_string(),
+            "QoS audit of node '/ns/node': problems found\n  \
+             subscription on '/scan' [sensor_msgs/msg/LaserScan]:\n    \
+             no compatible peers\n    \
+             QoS incompatible with '/lidar': Best effort publisher"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::qos::QoSProfile;
+use crate::{
+    CallbackErrorPolicy, Node, Publisher, RclReturnCode, Subscription, SubscriptionOptions,
+};
+
+use std::fmt::Display;
+use std::sync::Arc;
+
+use rosidl_runtime_rs::Message;
+
+/// A scope in which the topics of new publishers and subscriptions get a namespace prefix.
+///
+/// This is returned by [`Node::scoped_namespace`]. Relative topic names are prefixed with the
+/// namespace of the scope, e.g. `joint_states` becomes `arm_left/joint_states`, which is then
+/// expanded relative to the namespace of the node as usual. Absolute topic names, which start
+/// with `/`, and private topic names, which start with `~`, are not changed.
+///
+/// # Example
+/// ```ignore
+/// for side in ["left", "right"] {
+///     let mut scope = node.scoped_namespace(&format!("arm_{}", side));
+///     // Creates a publisher on `arm_left/joint_states` and `arm_right/joint_states`.
+///     publishers.push(scope.create_publisher::<JointState>("joint_states", QOS_PROFILE_DEFAULT)?);
+/// }
+/// ```
+pub struct NamespaceScope<'a> {
+    node: &'a mut Node,
+    namespace: String,
+}
+
+impl<'a> NamespaceScope<'a> {
+    pub(crate) fn new(node: &'a mut Node, namespace: String) -> Self {
+        Self { node, namespace }
+    }
+
+    /// Returns a scope for a nested namespace, e.g. `arm_left/gripper`.
+    pub fn scoped_namespace(&mut self, namespace: &str) -> NamespaceScope<'_> {
+        let namespace = prefix_topic(&self.namespace, namespace.trim_matches('/'));
+        NamespaceScope::new(self.node, namespace)
+    }
+
+    /// Returns the namespace prefix of the scope, without leading and trailing slashes.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the topic name that is used for the given topic in this scope.
+    pub fn topic_name(&self, topic: &str) -> String {
+        prefix_topic(&self.namespace, topic)
+    }
+
+    /// Returns the node that the entities are created with.
+    pub fn node(&mut self) -> &mut Node {
+        self.node
+    }
+
+    /// Creates a [`Publisher`] in this scope, see [`Node::create_publisher`].
+    pub fn create_publisher<T>(
+        &self,
+        topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Publisher<T>, RclReturnCode>
+    where
+        T: Message,
+    {
+        self.node.create_publisher(&self.topic_name(topic), qos)
+    }
+
+    /// Creates a [`Subscription`] in this scope, see [`Node::create_subscription`].
+    pub fn create_subscription<T, F>(
+        &mut self,
+        topic: &str,
+        qos: QoSProfile,
+        callback: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        F: FnMut(T) + Sized + 'static,
+    {
+        let topic = self.topic_name(topic);
+        self.node.create_subscription(&topic, qos, callback)
+    }
+
+    /// Creates a [`Subscription`] with non-default [`SubscriptionOptions`] in this scope, see
+    /// [`Node::create_subscription_with_options`].
+    pub fn create_subscription_with_options<T, F>(
+        &mut self,
+        topic: &str,
+        qos: QoSProfile,
+        options: SubscriptionOptions,
+        callback: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        F: FnMut(T) + Sized + 'static,
+    {
+        let topic = self.topic_name(topic);
+        self.node
+            .create_subscription_with_options(&topic, qos, options, callback)
+    }
+
+    /// Creates a [`Subscription`] with a callback that can fail in this scope, see
+    /// [`Node::create_fallible_subscription`].
+    pub fn create_fallible_subscription<T, E, F>(
+        &mut self,
+        topic: &str,
+        qos: QoSProfile,
+        error_policy: CallbackErrorPolicy<E>,
+        callback: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        E: Display + 'static,
+        F: FnMut(T) -> Result<(), E> + Sized + 'static,
+    {
+        let topic = self.topic_name(topic);
+        self.node
+            .create_fallible_subscription(&topic, qos, error_policy, callback)
+    }
+}
+
+// Prefixes a relative topic name with a namespace.
+fn prefix_topic(namespace: &str, topic: &str) -> String {
+    if namespace.is_empty() || topic.starts_with('/') || topic.starts_with('~') {
+        topic.to_owned()
+    } else if topic.is_empty() {
+        namespace.to_owned()
+    } else {
+        format!("{}/{}", namespace, topic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prefix_topic;
+
+    #[test]
+    fn test_prefix_topic() {
+        assert_eq!(
+            prefix_topic("arm_left", "joint_states"),
+            "arm_left/joint_states"
+        );
+        assert_eq!(prefix_topic("arm_left", "/joint_states"), "/joint_states");
+        assert_eq!(prefix_topic("arm_left", "~/status"), "~/status");
+        assert_eq!(prefix_topic("", "joint_states"), "joint_states");
+        assert_eq!(prefix_topic("arm_left", ""), "arm_left");
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{RclReturnCode, Subscription};
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::{FusedStream, Stream};
+use rosidl_runtime_rs::Message;
+
+/// A stream of the messages of a subscription.
+///
+/// Created by [`Subscription::stream`]. Since it implements [`FusedStream`], it can be used
+/// directly in `futures::select!` and `tokio::select!`, which makes it possible to handle several
+/// topics, together with timers and other events of the async runtime, in a single task.
+///
+/// The stream yields an error when taking a message fails, and continues afterwards. It never
+/// ends, because the subscription is alive for at least as long as the stream.
+///
+/// The task of the stream is woken up when [`spin_once`][1] or [`spin`][2] executes the
+/// subscription, so the node of the subscription must be spun, e.g. in another branch of the
+/// `select!`. Once the stream has waited for a message, new messages are passed to it instead of
+/// the callback, until it is dropped.
+///
+/// # Example
+/// ```ignore
+/// let mut temperatures = temperature_subscription.stream();
+/// let mut commands = command_subscription.stream();
+/// let mut interval = tokio::time::interval(Duration::from_secs(1));
+/// loop {
+///     tokio::select! {
+///         Some(temperature) = temperatures.next() => handle_temperature(temperature?),
+///         Some(command) = commands.next() => handle_command(command?),
+///         _ = interval.tick() => publish_status(),
+///     }
+/// }
+/// ```
+///
+/// [1]: crate::spin_once
+/// [2]: crate::spin
+#[must_use = "streams do nothing unless polled"]
+pub struct MessageStream<'a, T>
+where
+    T: Message,
+{
+    subscription: &'a Subscription<T>,
+    waiter_id: Option<u64>,
+}
+
+impl<T> Subscription<T>
+where
+    T: Message,
+{
+    /// Returns a stream of the messages of this subscription, bypassing the callback.
+    ///
+    /// Like with [`next`][1], messages that are received through the stream are not passed to
+    /// the callback. See [`MessageStream`] for when the stream is woken up.
+    ///
+    /// This needs the `futures-core` feature.
+    ///
+    /// [1]: Subscription::next
+    pub fn stream(&self) -> MessageStream<'_, T> {
+        MessageStream {
+            subscription: self,
+            waiter_id: None,
+        }
+    }
+}
+
+impl<T> Stream for MessageStream<'_, T>
+where
+    T: Message,
+{
+    type Item = Result<T, RclReturnCode>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        // The stream stays registered after a message, to receive the following ones.
+        this.subscription
+            .poll_next_message(cx, &mut this.waiter_id)
+            .map(Some)
+    }
+}
+
+impl<T> Drop for MessageStream<'_, T>
+where
+    T: Message,
+{
+    fn drop(&mut self) {
+        self.subscription.remove_waiter(self.waiter_id.take());
+    }
+}
+
+impl<T> FusedStream for MessageStream<'_, T>
+where
+    T: Message,
+{
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
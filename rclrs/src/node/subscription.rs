@@ -1,13 +1,37 @@
 use crate::error::{SubscriberErrorCode, ToResult};
+#[cfg(feature = "fault-injection")]
+use crate::fault_injection::FaultPoint;
+use crate::finalization::{check_fini, LIVE_SUBSCRIPTIONS};
+use crate::logging::log_warning;
+use crate::metrics::CallbackMetrics;
+#[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+use crate::node::get_mismatched_endpoints;
+use crate::node::matching::{wait_for_count, MatchFuture};
+#[cfg(not(ros_distro = "foxy"))]
+use crate::node::network_flow::{get_network_flow_endpoints, NetworkFlowEndpoint};
+use crate::node::{DedupFilter, Gid, MessageInfo, NodeHandle};
+use crate::provenance::{received_provenance, with_provenance, ProvenanceId};
 use crate::qos::QoSProfile;
-use crate::Node;
+#[cfg(not(ros_distro = "foxy"))]
+use crate::qos::{check_rmw_qos_compatible, QoSCompatibility};
+use crate::serialized::SerializedMessage;
 use crate::{rcl_bindings::*, RclReturnCode};
+#[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+use crate::{EntityKind, TypeHash};
+use crate::{Node, WaitSet};
 
 use std::borrow::Borrow;
 use std::boxed::Box;
-use std::ffi::CString;
+use std::collections::VecDeque;
+use std::ffi::{c_void, CStr, CString};
+use std::fmt::Display;
+use std::future::Future;
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::ptr::NonNull;
 use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime};
 
 use rosidl_runtime_rs::{Message, RmwMessage};
 
@@ -16,23 +40,175 @@ use parking_lot::{Mutex, MutexGuard};
 /// Internal struct used by subscriptions.
 pub struct SubscriptionHandle {
     handle: Mutex<rcl_subscription_t>,
-    node_handle: Arc<Mutex<rcl_node_t>>,
+    pub(crate) node_handle: Arc<NodeHandle>,
+    // Used for waiting on the subscription on its own, see `Subscription::recv_timeout()`.
+    pub(crate) context_handle: Arc<Mutex<rcl_context_t>>,
+    pub(crate) metrics: Mutex<CallbackMetrics>,
+    // When the executor last ran the subscription, used to execute subscriptions fairly.
+    pub(crate) last_execution: Mutex<Option<Instant>>,
+    message_timeout: Mutex<Option<MessageTimeout>>,
+}
+
+// A callback for when a subscription has not received a message for some time.
+struct MessageTimeout {
+    timeout: Duration,
+    deadline: Instant,
+    callback: Box<dyn FnMut() + 'static>,
 }
 
 impl SubscriptionHandle {
+    // Creates a subscription with the given type support, which must stay valid for as long as
+    // the subscription exists.
+    //
+    // # Panics
+    // When the topic contains interior null bytes.
+    pub(crate) fn new(
+        node: &Node,
+        topic: &str,
+        type_support: *const rosidl_message_type_support_t,
+        qos: QoSProfile,
+        options: &SubscriptionOptions,
+    ) -> Result<Arc<Self>, RclReturnCode> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut subscription_handle = unsafe { rcl_get_zero_initialized_subscription() };
+        let topic_c_string = CString::new(topic).unwrap();
+
+        // SAFETY: No preconditions for this function.
+        let mut subscription_options = unsafe { rcl_subscription_get_default_options() };
+        subscription_options.qos = qos.into();
+        subscription_options
+            .rmw_subscription_options
+            .rmw_specific_subscription_payload = options
+            .rmw_specific_payload
+            .map_or(std::ptr::null_mut(), |payload| payload.as_ptr());
+        unsafe {
+            // SAFETY: The subscription handle is zero-initialized as expected by this function.
+            // The node handle is kept alive because it is co-owned by the subscription.
+            // The topic name and the options are copied by this function, so they can be dropped
+            // afterwards. The creator of the middleware-specific payload guarantees that it is
+            // valid.
+            // TODO: type support?
+            rcl_subscription_init(
+                &mut subscription_handle as *mut _,
+                &mut *node.handle.lock() as *mut _,
+                type_support,
+                topic_c_string.as_ptr(),
+                &subscription_options as *const _,
+            )
+            .ok()?;
+        }
+        LIVE_SUBSCRIPTIONS.initialized();
+
+        // The handle takes ownership right away, so that the subscription is finalized if
+        // anything below fails.
+        let handle = Arc::new(SubscriptionHandle {
+            handle: Mutex::new(subscription_handle),
+            node_handle: node.handle.clone(),
+            context_handle: node.context.clone(),
+            metrics: Mutex::new(CallbackMetrics::default()),
+            last_execution: Mutex::new(None),
+            message_timeout: Mutex::new(None),
+        });
+
+        #[cfg(not(ros_distro = "foxy"))]
+        if options.warn_on_incompatible_qos {
+            warn_about_incompatible_publishers(&handle.lock(), &node.handle.lock(), qos)?;
+        }
+        Ok(handle)
+    }
+
     pub(crate) fn lock(&self) -> MutexGuard<rcl_subscription_t> {
         self.handle.lock()
     }
+
+    pub(crate) fn topic_name(&self) -> String {
+        // SAFETY: The handle is valid. The returned string is owned by the subscription and is
+        // copied before the lock is released.
+        unsafe {
+            let topic_name = rcl_subscription_get_topic_name(&*self.lock() as *const _);
+            if topic_name.is_null() {
+                return String::new();
+            }
+            CStr::from_ptr(topic_name).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Returns the time at which the message timeout expires, if one is set.
+    pub(crate) fn message_timeout_deadline(&self) -> Option<Instant> {
+        self.message_timeout
+            .lock()
+            .as_ref()
+            .map(|message_timeout| message_timeout.deadline)
+    }
+
+    pub(crate) fn take_serialized(
+        &self,
+        message: &mut SerializedMessage,
+    ) -> Result<(), RclReturnCode> {
+        #[cfg(feature = "fault-injection")]
+        if let Some(error_code) = crate::fault_injection::intercept(FaultPoint::Take) {
+            return Err(RclReturnCode::from(error_code));
+        }
+        let handle = &*self.lock();
+        unsafe {
+            // SAFETY: The subscription handle and the serialized message are valid. The message
+            // is resized with its own allocator.
+            // The latter two pointers are explicitly allowed to be NULL.
+            rcl_take_serialized_message(
+                handle as *const _,
+                &mut message.handle as *mut _,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        }
+        .ok()?;
+        self.reset_message_timeout();
+        Ok(())
+    }
+
+    fn reset_message_timeout(&self) {
+        if let Some(message_timeout) = &mut *self.message_timeout.lock() {
+            message_timeout.deadline = Instant::now() + message_timeout.timeout;
+        }
+    }
+
+    /// Invokes the message timeout callback if the deadline has passed, and restarts the timeout.
+    ///
+    /// Returns `true` if the callback was invoked.
+    pub(crate) fn check_message_timeout(&self, now: Instant) -> bool {
+        let mut slot = self.message_timeout.lock();
+        let mut message_timeout = match slot.take() {
+            Some(message_timeout) if now >= message_timeout.deadline => message_timeout,
+            other => {
+                *slot = other;
+                return false;
+            }
+        };
+        drop(slot);
+        // The lock is not held while invoking the callback, so that it can replace the timeout.
+        (message_timeout.callback)();
+        message_timeout.deadline = now + message_timeout.timeout;
+        let slot = &mut *self.message_timeout.lock();
+        if slot.is_none() {
+            *slot = Some(message_timeout);
+        }
+        true
+    }
 }
 
 impl Drop for SubscriptionHandle {
     fn drop(&mut self) {
         let handle = self.handle.get_mut();
         let node_handle = &mut *self.node_handle.lock();
-        // SAFETY: No preconditions for this function (besides the arguments being valid).
-        unsafe {
-            rcl_subscription_fini(handle as *mut _, node_handle as *mut _);
-        }
+        debug_assert!(
+            !node_handle.impl_.is_null(),
+            "The node was finalized before its subscription"
+        );
+        // SAFETY: No preconditions for this function (besides the arguments being valid). The
+        // node is still valid, since it is co-owned by the subscription.
+        let ret = unsafe { rcl_subscription_fini(handle as *mut _, node_handle as *mut _) };
+        check_fini(ret, "subscription");
+        LIVE_SUBSCRIPTIONS.finalized();
     }
 }
 
@@ -42,6 +218,236 @@ pub trait SubscriptionBase {
     fn handle(&self) -> &SubscriptionHandle;
     /// Tries to take a new message and run the callback with it.
     fn execute(&self) -> Result<(), RclReturnCode>;
+    /// Returns `true` if there are messages in the executor-side queue of the subscription.
+    ///
+    /// See [`SubscriptionOptions::queue_capacity`].
+    fn has_queued_messages(&self) -> bool {
+        false
+    }
+}
+
+/// Determines what happens when a message is received while the executor-side queue of a
+/// subscription is full.
+///
+/// See [`SubscriptionOptions::queue_capacity`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum QueueOverflowPolicy {
+    /// Discard the oldest message in the queue to make room for the new message.
+    #[default]
+    DropOldest,
+    /// Discard the new message.
+    DropNewest,
+    /// Stop taking messages from the middleware until there is room in the queue.
+    ///
+    /// The messages that are not taken stay in the history of the subscription, where they are
+    /// subject to the [`QoSProfile`].
+    Block,
+}
+
+impl QueueOverflowPolicy {
+    // Appends a message to a queue with the given capacity, and makes room for it according to
+    // the policy when the queue is full. With `Block`, the message is discarded, since the
+    // caller should not have taken it in the first place.
+    pub(crate) fn push<T>(self, queue: &mut VecDeque<T>, capacity: usize, msg: T) {
+        if queue.len() >= capacity {
+            match self {
+                Self::DropOldest => {
+                    queue.pop_front();
+                }
+                Self::DropNewest | Self::Block => return,
+            }
+        }
+        queue.push_back(msg);
+    }
+}
+
+/// An opaque pointer to middleware-specific options, see
+/// [`SubscriptionOptions::rmw_specific_payload`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RmwSpecificPayload(NonNull<c_void>);
+
+// SAFETY: The pointer is only passed on to the middleware, and the creator of the payload
+// guarantees that it can be used from any thread, see `RmwSpecificPayload::new`.
+unsafe impl Send for RmwSpecificPayload {}
+// SAFETY: See above.
+unsafe impl Sync for RmwSpecificPayload {}
+
+impl RmwSpecificPayload {
+    /// Wraps a pointer to a middleware-specific options struct.
+    ///
+    /// Returns `None` if the pointer is null.
+    ///
+    /// # Safety
+    /// The pointer must point to the options struct that the RMW implementation in use expects,
+    /// and it must stay valid until every subscription created with it has been constructed. The
+    /// RMW implementation may read and write through the pointer while creating a subscription.
+    ///
+    /// Since the payload is `Send` and `Sync`, subscriptions may be created with it on any
+    /// thread. The options struct must therefore not be bound to the thread that created it, and
+    /// must not be accessed by other code while a subscription is being created with it.
+    pub unsafe fn new(payload: *mut c_void) -> Option<Self> {
+        NonNull::new(payload).map(Self)
+    }
+
+    /// Returns the wrapped pointer.
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+}
+
+/// Options for creating a [`Subscription`].
+///
+/// These are settings of `rclrs` itself, as opposed to the [`QoSProfile`], which is passed on to
+/// the middleware.
+///
+/// # Example
+/// ```
+/// # use rclrs::SubscriptionOptions;
+/// let options = SubscriptionOptions {
+///     keep_latest_only: true,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SubscriptionOptions {
+    /// Only process the newest message when the subscription is ready.
+    ///
+    /// When this is `true`, executing the subscription takes messages until there are no more
+    /// messages available, and then invokes the callback once with the last of these messages.
+    /// Older messages are discarded without converting them to the idiomatic type.
+    ///
+    /// This is useful when only the freshest state matters, e.g. in control loops.
+    pub keep_latest_only: bool,
+    /// Print a warning for every publisher with an incompatible [`QoSProfile`].
+    ///
+    /// The check is performed against the publishers that have been discovered at the time the
+    /// subscription is created. See also [`qos_check_compatible`][1].
+    ///
+    /// This has no effect in Foxy.
+    ///
+    /// [1]: crate::qos_check_compatible
+    pub warn_on_incompatible_qos: bool,
+    /// The number of received messages to keep for [`Subscription::history`].
+    ///
+    /// The default of `0` disables the history. Messages that are discarded because of
+    /// `keep_latest_only` are not added to the history.
+    pub history_depth: usize,
+    /// The capacity of the executor-side queue of incoming messages.
+    ///
+    /// When this is non-zero, executing the subscription first takes all available messages from
+    /// the middleware and puts them into a queue, and then invokes the callback with the oldest
+    /// message in the queue. The remaining messages are processed by the following calls to
+    /// [`spin_once`][1], which does not block while the queue is not empty.
+    ///
+    /// This frees up the history of the subscription in the middleware while the callback is
+    /// slow, independently of the history depth of the [`QoSProfile`]. What happens when the
+    /// queue is full is determined by the `queue_overflow_policy`.
+    ///
+    /// The default of `0` disables the queue. It is also not used with `keep_latest_only`.
+    ///
+    /// [1]: crate::spin_once
+    pub queue_capacity: usize,
+    /// What happens when a message is received while the queue is full.
+    ///
+    /// This has no effect when the `queue_capacity` is `0`.
+    pub queue_overflow_policy: QueueOverflowPolicy,
+    /// The number of recent messages to remember for suppressing duplicates.
+    ///
+    /// When this is non-zero, a message is discarded when a message with the same publisher GID
+    /// and [`publication_sequence_number`][1] is among the last `dedup_window` received
+    /// messages. This suppresses messages that the middleware delivered more than once, e.g.
+    /// over several network interfaces. See [`DedupFilter`] for suppressing the copies of
+    /// redundant publishers.
+    ///
+    /// Messages without a sequence number are never discarded, so this has no effect in Foxy
+    /// and Galactic, or with middlewares that do not support sequence numbers. Messages taken
+    /// with [`Subscription::take_serialized`] are not checked.
+    ///
+    /// The default of `0` disables the check.
+    ///
+    /// [1]: crate::MessageInfo::publication_sequence_number
+    pub dedup_window: usize,
+    /// Middleware-specific options, which are passed on to the RMW implementation as the
+    /// `rmw_specific_subscription_payload` of the `rmw_subscription_options_t`.
+    ///
+    /// This is an escape hatch for features that only exist in a particular middleware, and
+    /// that are not covered by the [`QoSProfile`]. Whether and how the payload is interpreted is
+    /// up to the RMW implementation, and many implementations ignore it.
+    ///
+    /// The default of `None` passes a null pointer.
+    pub rmw_specific_payload: Option<RmwSpecificPayload>,
+}
+
+/// A future that resolves to the next message of a subscription.
+///
+/// Created by [`Subscription::next`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct NextMessage<'a, T>
+where
+    T: Message,
+{
+    subscription: &'a Subscription<T>,
+    waiter_id: Option<u64>,
+}
+
+impl<T> Future for NextMessage<'_, T>
+where
+    T: Message,
+{
+    type Output = Result<T, RclReturnCode>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let poll = this.subscription.poll_next_message(cx, &mut this.waiter_id);
+        if poll.is_ready() {
+            this.subscription.remove_waiter(this.waiter_id.take());
+        }
+        poll
+    }
+}
+
+impl<T> Drop for NextMessage<'_, T>
+where
+    T: Message,
+{
+    fn drop(&mut self) {
+        self.subscription.remove_waiter(self.waiter_id.take());
+    }
+}
+
+// The futures and streams that wait for a message of a subscription.
+#[derive(Default)]
+struct Waiters {
+    next_id: u64,
+    wakers: Vec<(u64, Waker)>,
+}
+
+/// A message together with the time it was received, returned by [`Subscription::history`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceivedMessage<T> {
+    /// The message.
+    pub message: T,
+    /// The time at which the message was taken from the middleware.
+    pub received_at: SystemTime,
+}
+
+type SubscriptionCallback<T> = Box<dyn FnMut(T) + 'static>;
+
+/// Determines what happens when a fallible subscription callback returns an error.
+///
+/// See [`Subscription::new_fallible`].
+#[derive(Default)]
+pub enum CallbackErrorPolicy<E> {
+    /// Log the error as a warning through the ROS logging system and continue spinning.
+    #[default]
+    LogAndContinue,
+    /// Make [`spin_once`][1] and [`spin`][2] return [`RclReturnCode::CallbackError`].
+    ///
+    /// [1]: crate::spin_once
+    /// [2]: crate::spin
+    StopSpinning,
+    /// Pass the error to the given handler and continue spinning.
+    Handle(Box<dyn FnMut(E) + 'static>),
 }
 
 /// Struct for receiving messages of type `T`.
@@ -60,8 +466,24 @@ where
     T: Message,
 {
     pub(crate) handle: Arc<SubscriptionHandle>,
-    /// The callback function that runs when a message was received.
-    pub callback: Mutex<Box<dyn FnMut(T) + 'static>>,
+    // The callback function that runs when a message was received. It is moved out while it is
+    // running, so that no lock is held while executing user code.
+    callback: Mutex<Option<SubscriptionCallback<T>>>,
+    options: SubscriptionOptions,
+    // An error returned by a fallible callback, to be returned from `execute()`.
+    callback_error: Arc<Mutex<Option<RclReturnCode>>>,
+    // The last received messages, oldest first, if enabled in the options.
+    history: Mutex<VecDeque<ReceivedMessage<T>>>,
+    // Messages that have been taken but not yet processed, oldest first, if enabled in the
+    // options.
+    queue: Mutex<VecDeque<(T, ProvenanceId)>>,
+    // The publisher GIDs and sequence numbers of recent messages, if enabled in the options.
+    dedup_filter: Mutex<DedupFilter<(Gid, u64)>>,
+    // The tasks of pending `next()` futures and message streams. While there are any, executing
+    // the subscription hands the messages over to them instead of running the callback.
+    waiters: Mutex<Waiters>,
+    // Messages that have been taken for the waiters, oldest first.
+    handed_over: Mutex<VecDeque<T>>,
     message: PhantomData<T>,
 }
 
@@ -83,44 +505,87 @@ where
         T: Message,
         F: FnMut(T) + Sized + 'static,
     {
-        // SAFETY: Getting a zero-initialized value is always safe.
-        let mut subscription_handle = unsafe { rcl_get_zero_initialized_subscription() };
+        Self::new_with_options(node, topic, qos, SubscriptionOptions::default(), callback)
+    }
+
+    /// Creates a new subscription with non-default [`SubscriptionOptions`].
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new_with_options<F>(
+        node: &Node,
+        topic: &str,
+        qos: QoSProfile,
+        options: SubscriptionOptions,
+        callback: F,
+    ) -> Result<Self, RclReturnCode>
+    where
+        T: Message,
+        F: FnMut(T) + Sized + 'static,
+    {
         let type_support =
             <T as Message>::RmwMsg::get_type_support() as *const rosidl_message_type_support_t;
-        let topic_c_string = CString::new(topic).unwrap();
-        let node_handle = &mut *node.handle.lock();
-
-        // SAFETY: No preconditions for this function.
-        let mut subscription_options = unsafe { rcl_subscription_get_default_options() };
-        subscription_options.qos = qos.into();
-        unsafe {
-            // SAFETY: The subscription handle is zero-initialized as expected by this function.
-            // The node handle is kept alive because it is co-owned by the subscription.
-            // The topic name and the options are copied by this function, so they can be dropped
-            // afterwards.
-            // TODO: type support?
-            rcl_subscription_init(
-                &mut subscription_handle as *mut _,
-                node_handle as *mut _,
-                type_support,
-                topic_c_string.as_ptr(),
-                &subscription_options as *const _,
-            )
-            .ok()?;
-        }
-
-        let handle = Arc::new(SubscriptionHandle {
-            handle: Mutex::new(subscription_handle),
-            node_handle: node.handle.clone(),
-        });
+        let handle = SubscriptionHandle::new(node, topic, type_support, qos, &options)?;
 
         Ok(Self {
             handle,
-            callback: Mutex::new(Box::new(callback)),
+            callback: Mutex::new(Some(Box::new(callback))),
+            options,
+            callback_error: Arc::new(Mutex::new(None)),
+            history: Mutex::new(VecDeque::with_capacity(options.history_depth)),
+            queue: Mutex::new(VecDeque::with_capacity(options.queue_capacity)),
+            dedup_filter: Mutex::new(DedupFilter::new(options.dedup_window)),
+            waiters: Mutex::new(Waiters::default()),
+            handed_over: Mutex::new(VecDeque::new()),
             message: PhantomData,
         })
     }
 
+    /// Creates a new subscription with a callback that can fail.
+    ///
+    /// Errors returned by the callback are dealt with according to the `error_policy`.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new_fallible<F, E>(
+        node: &Node,
+        topic: &str,
+        qos: QoSProfile,
+        options: SubscriptionOptions,
+        mut error_policy: CallbackErrorPolicy<E>,
+        mut callback: F,
+    ) -> Result<Self, RclReturnCode>
+    where
+        T: Message,
+        F: FnMut(T) -> Result<(), E> + Sized + 'static,
+        E: Display + 'static,
+    {
+        let callback_error = Arc::new(Mutex::new(None));
+        let callback_error_slot = Arc::clone(&callback_error);
+        let topic_name = topic.to_owned();
+        let infallible_callback = move |msg: T| {
+            let error = match callback(msg) {
+                Ok(()) => return,
+                Err(error) => error,
+            };
+            match &mut error_policy {
+                CallbackErrorPolicy::LogAndContinue => log_warning(&format!(
+                    "Error in the callback of the subscription on topic '{}': {}",
+                    topic_name, error
+                )),
+                CallbackErrorPolicy::StopSpinning => {
+                    *callback_error_slot.lock() =
+                        Some(RclReturnCode::CallbackError(error.to_string()))
+                }
+                CallbackErrorPolicy::Handle(handler) => handler(error),
+            }
+        };
+        Ok(Self {
+            callback_error,
+            ..Self::new_with_options(node, topic, qos, options, infallible_callback)?
+        })
+    }
+
     /// Fetches a new message.
     ///
     /// When there is no new message, this will return a
@@ -145,21 +610,546 @@ where
     // +-------------+
     // ```
     pub fn take(&self) -> Result<T, RclReturnCode> {
-        let mut rmw_message = <T as Message>::RmwMsg::default();
-        let handle = &mut *self.handle.lock();
-        let ret = unsafe {
-            // SAFETY: The first two pointers are valid/initialized, and do not need to be valid
-            // beyond the function call.
-            // The latter two pointers are explicitly allowed to be NULL.
-            rcl_take(
-                handle as *const _,
-                &mut rmw_message as *mut <T as Message>::RmwMsg as *mut _,
-                std::ptr::null_mut(),
-                std::ptr::null_mut(),
+        let (msg, _) = self.take_with_provenance()?;
+        Ok(msg)
+    }
+
+    /// Like [`take`][1], but also returns information about the message, such as the GID of the
+    /// publisher that sent it.
+    ///
+    /// Messages in the executor-side queue are not returned by this function, since their
+    /// information is not stored.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (reading, info) = subscription.take_with_info()?;
+    /// if info.publisher_gid == primary_sensor.gid() {
+    ///     votes.primary = Some(reading);
+    /// }
+    /// ```
+    ///
+    /// [1]: Subscription::take
+    pub fn take_with_info(&self) -> Result<(T, MessageInfo), RclReturnCode> {
+        let (rmw_message, message_info) = self.take_rmw_message()?;
+        Ok((
+            T::from_rmw_message(rmw_message),
+            MessageInfo::from(&message_info),
+        ))
+    }
+
+    /// Like [`take`][1], but returns the message in its RMW representation, without converting
+    /// it to `T`.
+    ///
+    /// The conversion copies every string and sequence field into an owned `String` or `Vec`.
+    /// The RMW message instead keeps the memory that the middleware filled in, and its string and
+    /// sequence fields can be read in place, e.g. with
+    /// [`rosidl_runtime_rs::String::as_str`] or by slicing a [`rosidl_runtime_rs::Sequence`].
+    /// For messages with large strings or blobs, this avoids most allocations.
+    ///
+    /// Messages in the executor-side queue are not returned by this function, since they have
+    /// already been converted.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let log = subscription.take_rmw()?;
+    /// if log.msg.as_str().is_ok_and(|text| text.contains("error")) {
+    ///     errors += 1;
+    /// }
+    /// ```
+    ///
+    /// [1]: Subscription::take
+    pub fn take_rmw(&self) -> Result<<T as Message>::RmwMsg, RclReturnCode> {
+        let (rmw_message, _) = self.take_rmw_message()?;
+        Ok(rmw_message)
+    }
+
+    /// Fetches all available messages and returns the newest one.
+    ///
+    /// Only the newest message is converted to the idiomatic message type.
+    ///
+    /// When there is no new message, this will return a
+    /// [`SubscriptionTakeFailed`][1] wrapped in an [`RclReturnCode`][2].
+    ///
+    /// [1]: crate::SubscriberErrorCode
+    /// [2]: crate::RclReturnCode
+    pub fn take_latest(&self) -> Result<T, RclReturnCode> {
+        let (msg, _) = self.take_latest_with_provenance()?;
+        Ok(msg)
+    }
+
+    /// Waits for the next message and returns it, bypassing the callback.
+    ///
+    /// This is convenient when only a single message is needed, e.g. one reading from a sensor in
+    /// a script. Only this subscription is waited on, so no other callbacks are executed. A
+    /// message that is already available, or in the executor-side queue, is returned right away.
+    /// Messages that are received this way are not passed to the callback, so the subscription
+    /// can be created with an empty callback for this purpose.
+    ///
+    /// When no message is received within the `timeout`, [`RclReturnCode::Timeout`] is returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let subscription = node.create_subscription("temperature", QOS_PROFILE_DEFAULT, |_: Temperature| {})?;
+    /// let reading = subscription.recv_timeout(Duration::from_secs(1))?;
+    /// ```
+    pub fn recv_timeout(self: &Arc<Self>, timeout: Duration) -> Result<T, RclReturnCode> {
+        let deadline = Instant::now() + timeout;
+        let mut wait_set = WaitSet::new_for_context_handle(1, 0, &self.handle.context_handle)?;
+        loop {
+            match self.take_next() {
+                Ok(message) => return Ok(message),
+                Err(RclReturnCode::SubscriberError(
+                    SubscriberErrorCode::SubscriptionTakeFailed,
+                )) => {}
+                Err(e) => return Err(e),
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(RclReturnCode::Timeout);
+            }
+            wait_set.clear();
+            wait_set.add_subscription(self.clone())?;
+            wait_set.wait(Some(remaining))?;
+        }
+    }
+
+    /// Returns a future that resolves to the next message, bypassing the callback.
+    ///
+    /// This is the non-blocking counterpart of [`recv_timeout`][1], without a timeout. The future
+    /// does not depend on a specific async runtime. A message that is already available is
+    /// returned on the first poll. Otherwise, the future is woken up when [`spin_once`][2] or
+    /// [`spin`][3] executes the subscription, so the subscription must have been created with
+    /// [`Node::create_subscription`] or a similar function, and its node must be spun. While
+    /// the future is pending, new messages are passed to it instead of the callback.
+    ///
+    /// [1]: Subscription::recv_timeout
+    /// [2]: crate::spin_once
+    /// [3]: crate::spin
+    pub fn next(&self) -> NextMessage<'_, T> {
+        NextMessage {
+            subscription: self,
+            waiter_id: None,
+        }
+    }
+
+    // Takes a message that was handed over to the waiters, or else from the executor-side
+    // queue, or else from the middleware.
+    pub(crate) fn take_next(&self) -> Result<T, RclReturnCode> {
+        if let Some(message) = self.handed_over.lock().pop_front() {
+            return Ok(message);
+        }
+        self.take_for_waiters()
+    }
+
+    // Takes a message from the executor-side queue, or else from the middleware.
+    fn take_for_waiters(&self) -> Result<T, RclReturnCode> {
+        if let Some((message, _)) = self.queue.lock().pop_front() {
+            return Ok(message);
+        }
+        self.take()
+    }
+
+    // Returns the next message for a future or stream, or else registers its task to be woken
+    // up when the subscription is executed. The ID of the registration is assigned on the first
+    // call, and stays registered until it is removed with `remove_waiter()`.
+    pub(crate) fn poll_next_message(
+        &self,
+        cx: &mut Context<'_>,
+        waiter_id: &mut Option<u64>,
+    ) -> Poll<Result<T, RclReturnCode>> {
+        // The lock is held while checking for a message, so that a message that arrives in the
+        // meantime is handed over to this task and not passed to the callback.
+        let waiters = &mut *self.waiters.lock();
+        match self.take_next() {
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {}
+            result => return Poll::Ready(result),
+        }
+        let id = *waiter_id.get_or_insert_with(|| {
+            waiters.next_id += 1;
+            waiters.next_id
+        });
+        match waiters.wakers.iter_mut().find(|(waiter, _)| *waiter == id) {
+            Some((_, waker)) => waker.clone_from(cx.waker()),
+            None => waiters.wakers.push((id, cx.waker().clone())),
+        }
+        Poll::Pending
+    }
+
+    // Stops handing messages over to a future or stream, e.g. when it is dropped.
+    pub(crate) fn remove_waiter(&self, waiter_id: Option<u64>) {
+        if let Some(id) = waiter_id {
+            self.waiters
+                .lock()
+                .wakers
+                .retain(|(waiter, _)| *waiter != id);
+        }
+    }
+
+    // Takes a message for the waiting futures and streams and wakes them up. Returns `false`
+    // if there are no waiters.
+    fn hand_over_to_waiters(&self) -> Result<bool, RclReturnCode> {
+        let waiters = self.waiters.lock();
+        if waiters.wakers.is_empty() {
+            return Ok(false);
+        }
+        match self.take_for_waiters() {
+            Ok(message) => self.handed_over.lock().push_back(message),
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {
+                return Ok(true)
+            }
+            Err(e) => return Err(e),
+        }
+        let wakers: Vec<Waker> = waiters
+            .wakers
+            .iter()
+            .map(|(_, waker)| waker.clone())
+            .collect();
+        // Do not hold the lock while waking, since that may poll the futures right away.
+        drop(waiters);
+        self.handle.reset_message_timeout();
+        wakers.into_iter().for_each(Waker::wake);
+        Ok(true)
+    }
+
+    fn take_with_provenance(&self) -> Result<(T, ProvenanceId), RclReturnCode> {
+        let (rmw_message, message_info) = self.take_rmw_message()?;
+        Ok((
+            T::from_rmw_message(rmw_message),
+            received_provenance(&message_info),
+        ))
+    }
+
+    fn take_latest_with_provenance(&self) -> Result<(T, ProvenanceId), RclReturnCode> {
+        let (mut latest, message_info) = self.take_rmw_message()?;
+        let mut latest_provenance = received_provenance(&message_info);
+        loop {
+            match self.take_rmw_message() {
+                Ok((rmw_message, message_info)) => {
+                    latest = rmw_message;
+                    latest_provenance = received_provenance(&message_info);
+                }
+                Err(RclReturnCode::SubscriberError(
+                    SubscriberErrorCode::SubscriptionTakeFailed,
+                )) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok((T::from_rmw_message(latest), latest_provenance))
+    }
+
+    /// Fetches a new message in the serialized format of the middleware, without converting it
+    /// to `T`.
+    ///
+    /// The message is written into the given buffer, which is resized as needed. Together with
+    /// [`Publisher::publish_serialized`][1], this allows forwarding messages without
+    /// deserializing them.
+    ///
+    /// When there is no new message, this will return a
+    /// [`SubscriptionTakeFailed`][2] wrapped in an [`RclReturnCode`][3].
+    ///
+    /// [1]: crate::Publisher::publish_serialized
+    /// [2]: crate::SubscriberErrorCode
+    /// [3]: crate::RclReturnCode
+    pub fn take_serialized(&self, message: &mut SerializedMessage) -> Result<(), RclReturnCode> {
+        self.handle.take_serialized(message)
+    }
+
+    /// Returns the last received messages, oldest first.
+    ///
+    /// Messages are only recorded when [`SubscriptionOptions::history_depth`] is non-zero, in
+    /// which case at most that many messages are returned. The history is updated by
+    /// [`spin_once`][1] and [`spin`][2] before the callback is invoked, so the callback sees the
+    /// current message as the last entry.
+    ///
+    /// [1]: crate::spin_once
+    /// [2]: crate::spin
+    pub fn history(&self) -> Vec<ReceivedMessage<T>> {
+        self.history.lock().iter().cloned().collect()
+    }
+
+    /// Replaces the callback that is invoked with received messages.
+    ///
+    /// This allows a node to change how it handles messages, e.g. when switching between the
+    /// modes of a state machine, without re-creating the subscription and losing its matched
+    /// publishers and history. It can be called from anywhere, including from within the current
+    /// callback.
+    ///
+    /// The replacement is atomic with respect to [`spin_once`][1] and [`spin`][2]: each message
+    /// is passed to either the old or the new callback, and none is lost. If the old callback is
+    /// currently running, it finishes with its message, and is dropped once it returns. Otherwise
+    /// it is dropped immediately. Either way, everything it captured is released.
+    ///
+    /// For subscriptions created with [`new_fallible`][3], the new callback replaces the
+    /// fallible callback together with its error policy.
+    ///
+    /// [1]: crate::spin_once
+    /// [2]: crate::spin
+    /// [3]: Subscription::new_fallible
+    pub fn set_callback<F>(&self, callback: F)
+    where
+        F: FnMut(T) + 'static,
+    {
+        let old_callback = self.callback.lock().replace(Box::new(callback));
+        // Dropped after releasing the lock, since dropping the captured state runs user code.
+        drop(old_callback);
+    }
+
+    /// Invokes the `callback` when no message has been received for the duration of `timeout`.
+    ///
+    /// This is a watchdog for detecting a silent publisher, e.g. a sensor driver that stopped
+    /// working. It is independent of the deadline [`QoSProfile`] policy, and works with every
+    /// middleware.
+    ///
+    /// The timeout starts now and restarts whenever a message is received. After the callback
+    /// has been invoked, it is invoked again after every further `timeout` without messages.
+    /// Setting a new timeout replaces the previous one.
+    ///
+    /// The timeout is checked by [`spin_once`][1] and [`spin`][2], which wake up in time for it,
+    /// so it only works for subscriptions that were created through a [`Node`].
+    ///
+    /// [1]: crate::spin_once
+    /// [2]: crate::spin
+    pub fn set_message_timeout<F>(&self, timeout: Duration, callback: F)
+    where
+        F: FnMut() + 'static,
+    {
+        *self.handle.message_timeout.lock() = Some(MessageTimeout {
+            timeout,
+            deadline: Instant::now() + timeout,
+            callback: Box::new(callback),
+        });
+    }
+
+    /// Removes the message timeout set by [`set_message_timeout`][1].
+    ///
+    /// [1]: Subscription::set_message_timeout
+    pub fn clear_message_timeout(&self) {
+        *self.handle.message_timeout.lock() = None;
+    }
+
+    /// Returns the QoS profile that is actually used by the middleware.
+    ///
+    /// This can differ from the requested profile when it contained system default values, which
+    /// have been replaced by the middleware's defaults.
+    pub fn actual_qos(&self) -> Result<QoSProfile, RclReturnCode> {
+        // SAFETY: The handle is valid. The returned profile is owned by the subscription and is
+        // copied before the lock is released.
+        unsafe {
+            let qos = rcl_subscription_get_actual_qos(&*self.handle.lock() as *const _);
+            if qos.is_null() {
+                return Err(RclReturnCode::SubscriberError(
+                    SubscriberErrorCode::SubscriptionInvalid,
+                ));
+            }
+            Ok(QoSProfile::from(&*qos))
+        }
+    }
+
+    /// Returns the fully qualified topic name of the subscription, after remapping.
+    pub fn topic_name(&self) -> String {
+        self.handle.topic_name()
+    }
+
+    /// Returns the number of publishers that are currently matched with this subscription.
+    pub fn get_publisher_count(&self) -> Result<usize, RclReturnCode> {
+        let mut publisher_count = 0;
+        // SAFETY: The subscription handle is valid, and the count is a valid pointer.
+        unsafe {
+            rcl_subscription_get_publisher_count(
+                &*self.handle.lock() as *const _,
+                &mut publisher_count as *mut _,
             )
+            .ok()?;
+        }
+        Ok(publisher_count)
+    }
+
+    /// Blocks until at least `n` publishers are matched with this subscription.
+    ///
+    /// When the publishers are not matched within the `timeout`, [`RclReturnCode::Timeout`] is
+    /// returned. A `timeout` of `None` waits forever.
+    pub fn wait_for_publishers(
+        &self,
+        n: usize,
+        timeout: Option<Duration>,
+    ) -> Result<(), RclReturnCode> {
+        wait_for_count(|| self.get_publisher_count(), n, timeout)
+    }
+
+    /// Like [`wait_for_publishers`][1], but returns a future instead of blocking.
+    ///
+    /// [1]: Subscription::wait_for_publishers
+    pub fn wait_for_publishers_async(
+        &self,
+        n: usize,
+        timeout: Option<Duration>,
+    ) -> MatchFuture<'_> {
+        MatchFuture::new(move || self.get_publisher_count(), n, timeout)
+    }
+
+    /// Returns the fully qualified names of the nodes with publishers on the topic, whose
+    /// message type has the same name but a different definition than `T`.
+    ///
+    /// Messages from such publishers are never received. The check compares the
+    /// [`TypeHash`][1] of `T` with the type hashes that the publishers advertise, so it always
+    /// returns an empty list when either is unknown.
+    ///
+    /// This is only available since Iron.
+    ///
+    /// [1]: crate::TypeHash
+    #[cfg(not(any(ros_distro = "foxy", ros_distro = "galactic", ros_distro = "humble")))]
+    pub fn mismatched_publishers(&self) -> Result<Vec<String>, RclReturnCode> {
+        match TypeHash::of::<T>() {
+            Some(type_hash) => get_mismatched_endpoints(
+                &self.handle.node_handle,
+                &self.topic_name(),
+                EntityKind::Publisher,
+                type_hash,
+            ),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the local network flow endpoints of the subscription.
+    ///
+    /// Not all middleware implementations support this, in which case an error is returned.
+    /// This is not available in Foxy.
+    #[cfg(not(ros_distro = "foxy"))]
+    pub fn network_flow_endpoints(&self) -> Result<Vec<NetworkFlowEndpoint>, RclReturnCode> {
+        let handle = &*self.handle.lock();
+        get_network_flow_endpoints(|allocator, endpoint_array| {
+            // SAFETY: The subscription handle is valid, and the endpoint array is
+            // zero-initialized as expected by this function.
+            unsafe {
+                rcl_subscription_get_network_flow_endpoints(
+                    handle as *const _,
+                    allocator,
+                    endpoint_array,
+                )
+            }
+        })
+    }
+
+    // Whether messages go through the executor-side queue.
+    fn uses_queue(&self) -> bool {
+        self.options.queue_capacity > 0 && !self.options.keep_latest_only
+    }
+
+    // Moves the available messages from the middleware into the queue, according to the
+    // overflow policy.
+    fn fill_queue(&self) -> Result<(), RclReturnCode> {
+        let queue = &mut *self.queue.lock();
+        let policy = self.options.queue_overflow_policy;
+        loop {
+            let full = queue.len() >= self.options.queue_capacity;
+            if full && policy == QueueOverflowPolicy::Block {
+                return Ok(());
+            }
+            let msg = match self.take_with_provenance() {
+                Ok(msg) => msg,
+                Err(RclReturnCode::SubscriberError(
+                    SubscriberErrorCode::SubscriptionTakeFailed,
+                )) => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            policy.push(queue, self.options.queue_capacity, msg);
+        }
+    }
+
+    // Takes a message and runs the callback with it.
+    fn take_and_run(&self, callback: &mut dyn FnMut(T)) -> Result<(), RclReturnCode> {
+        let msg = if self.uses_queue() {
+            self.fill_queue()?;
+            self.queue
+                .lock()
+                .pop_front()
+                .ok_or(RclReturnCode::SubscriberError(
+                    SubscriberErrorCode::SubscriptionTakeFailed,
+                ))
+        } else if self.options.keep_latest_only {
+            self.take_latest_with_provenance()
+        } else {
+            self.take_with_provenance()
+        };
+        let (msg, provenance) = match msg {
+            Ok(msg) => msg,
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {
+                // Spurious wakeup – this may happen even when a waitset indicated that this
+                // subscription was ready, so it shouldn't be an error.
+                return Ok(());
+            }
+            Err(e) => return Err(e),
         };
-        ret.ok()?;
-        Ok(T::from_rmw_message(rmw_message))
+        self.handle.reset_message_timeout();
+        if self.options.history_depth > 0 {
+            let history = &mut *self.history.lock();
+            if history.len() == self.options.history_depth {
+                history.pop_front();
+            }
+            history.push_back(ReceivedMessage {
+                message: msg.clone(),
+                received_at: SystemTime::now(),
+            });
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "callback",
+            topic = %self.topic_name(),
+            provenance = %provenance
+        )
+        .entered();
+        with_provenance(provenance, || callback(msg));
+        match self.callback_error.lock().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn take_rmw_message(
+        &self,
+    ) -> Result<(<T as Message>::RmwMsg, rmw_message_info_t), RclReturnCode> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("take", topic = %self.topic_name()).entered();
+        #[cfg(feature = "fault-injection")]
+        if let Some(error_code) = crate::fault_injection::intercept(FaultPoint::Take) {
+            return Err(RclReturnCode::from(error_code));
+        }
+        let handle = &mut *self.handle.lock();
+        loop {
+            let mut rmw_message = <T as Message>::RmwMsg::default();
+            // SAFETY: Getting a zero-initialized value is always safe.
+            let mut message_info = unsafe { rmw_get_zero_initialized_message_info() };
+            let ret = unsafe {
+                // SAFETY: The first three pointers are valid/initialized, and do not need to be
+                // valid beyond the function call.
+                // The last pointer is explicitly allowed to be NULL.
+                rcl_take(
+                    handle as *const _,
+                    &mut rmw_message as *mut <T as Message>::RmwMsg as *mut _,
+                    &mut message_info as *mut _,
+                    std::ptr::null_mut(),
+                )
+            };
+            ret.ok()?;
+            if !self.is_duplicate(&message_info) {
+                return Ok((rmw_message, message_info));
+            }
+        }
+    }
+
+    // Records the message in the dedup filter, and returns whether it was seen already.
+    fn is_duplicate(&self, message_info: &rmw_message_info_t) -> bool {
+        if self.options.dedup_window == 0 {
+            return false;
+        }
+        let info = MessageInfo::from(message_info);
+        match info.publication_sequence_number {
+            Some(sequence_number) => !self
+                .dedup_filter
+                .lock()
+                .insert((info.publisher_gid, sequence_number)),
+            None => false,
+        }
     }
 }
 
@@ -172,16 +1162,102 @@ where
     }
 
     fn execute(&self) -> Result<(), RclReturnCode> {
-        let msg = match self.take() {
-            Ok(msg) => msg,
-            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {
-                // Spurious wakeup – this may happen even when a waitset indicated that this
-                // subscription was ready, so it shouldn't be an error.
-                return Ok(());
+        if self.hand_over_to_waiters()? {
+            return Ok(());
+        }
+        let callback = self.callback.lock().take();
+        let mut callback = match callback {
+            Some(callback) => callback,
+            // The subscription is being executed from within its own callback. The message is
+            // left for a later call.
+            None => return Ok(()),
+        };
+        let result = self.take_and_run(&mut callback);
+        let slot = &mut *self.callback.lock();
+        // Keep a callback that was set while this one was running.
+        if slot.is_none() {
+            *slot = Some(callback);
+        }
+        result
+    }
+
+    fn has_queued_messages(&self) -> bool {
+        self.uses_queue() && !self.queue.lock().is_empty()
+    }
+}
+
+/// Logs a warning for each known publisher on the subscription's topic whose QoS profile is
+/// incompatible with `qos`.
+#[cfg(not(ros_distro = "foxy"))]
+fn warn_about_incompatible_publishers(
+    subscription_handle: &rcl_subscription_t,
+    node_handle: &rcl_node_t,
+    qos: QoSProfile,
+) -> Result<(), RclReturnCode> {
+    // SAFETY: The subscription handle is valid. The returned topic name is owned by the
+    // subscription and remains valid for the duration of this function.
+    let topic_name = unsafe { rcl_subscription_get_topic_name(subscription_handle as *const _) };
+    if topic_name.is_null() {
+        return Err(RclReturnCode::Error);
+    }
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut publishers_info = unsafe { rmw_get_zero_initialized_topic_endpoint_info_array() };
+    // SAFETY: No preconditions for this function.
+    let mut allocator = unsafe { rcutils_get_default_allocator() };
+    unsafe {
+        // SAFETY: The node handle and topic name are valid, and the endpoint info array is
+        // zero-initialized as expected by this function.
+        rcl_get_publishers_info_by_topic(
+            node_handle as *const _,
+            &mut allocator as *mut _,
+            topic_name,
+            false,
+            &mut publishers_info as *mut _,
+        )
+        .ok()?;
+    }
+    let publishers = if publishers_info.info_array.is_null() {
+        &[]
+    } else {
+        // SAFETY: The info array contains `size` initialized elements.
+        unsafe { std::slice::from_raw_parts(publishers_info.info_array, publishers_info.size) }
+    };
+    let mut result = Ok(());
+    for publisher_info in publishers {
+        let compatibility = match check_rmw_qos_compatible(
+            QoSProfile::from(&publisher_info.qos_profile).into(),
+            qos.into(),
+        ) {
+            Ok(compatibility) => compatibility,
+            Err(e) => {
+                result = Err(e);
+                break;
             }
-            Err(e) => return Err(e),
         };
-        (*self.callback.lock())(msg);
-        Ok(())
+        let reason = match compatibility {
+            QoSCompatibility::Ok => continue,
+            QoSCompatibility::Warning(reason) | QoSCompatibility::Error(reason) => reason,
+        };
+        // SAFETY: The strings in the endpoint info are valid and null-terminated.
+        let (node_namespace, node_name) = unsafe {
+            (
+                CStr::from_ptr(publisher_info.node_namespace).to_string_lossy(),
+                CStr::from_ptr(publisher_info.node_name).to_string_lossy(),
+            )
+        };
+        log_warning(&format!(
+            "Publisher of node '{}/{}' on topic '{}' has an incompatible QoS profile: {}",
+            node_namespace.trim_end_matches('/'),
+            node_name,
+            // SAFETY: The topic name was checked to be non-null above.
+            unsafe { CStr::from_ptr(topic_name) }.to_string_lossy(),
+            reason
+        ));
+    }
+    // SAFETY: The endpoint info array was initialized by rcl_get_publishers_info_by_topic().
+    unsafe {
+        rmw_topic_endpoint_info_array_fini(&mut publishers_info as *mut _, &mut allocator as *mut _)
     }
+    .ok()?;
+    result
 }
@@ -0,0 +1,209 @@
+use crate::qos::QoSProfile;
+use crate::{
+    CallbackErrorPolicy, Node, Publisher, QueueOverflowPolicy, RclReturnCode, Subscription,
+    SubscriptionOptions,
+};
+
+use std::sync::Arc;
+
+use rosidl_runtime_rs::Message;
+
+/// A chain of processing steps for the messages received on a topic.
+///
+/// A pipeline starts with a topic, is extended with [`map`][1], [`filter`][2] and
+/// [`filter_map`][3], and is finished with [`publish_to`][4] or [`for_each`][5], which create the
+/// [`Subscription`] that drives it. The steps are executed in the subscription callback, i.e. by
+/// [`spin_once`][6] and [`spin`][7].
+///
+/// # Backpressure
+/// By default, the queue in front of the pipeline is the history of the subscription, which is
+/// bounded by the depth of its [`QoSProfile`]. When the pipeline does not keep up, the
+/// `KeepLast` history policy drops the oldest messages. With the `KeepAll` history policy and
+/// reliable communication, the middleware instead makes the publishers wait, up to its resource
+/// limits.
+///
+/// With [`queue`][8], the messages are instead taken into a bounded queue of the pipeline, and
+/// the [`QueueOverflowPolicy`] decides which messages are dropped when it is full, or whether the
+/// messages are left in the history of the subscription.
+///
+/// # Example
+/// ```ignore
+/// let publisher = node.create_publisher::<std_msgs::msg::Float64>("scaled", QOS_PROFILE_DEFAULT)?;
+/// let _subscription = Pipeline::<std_msgs::msg::Float64, _>::new("raw", QOS_PROFILE_DEFAULT)
+///     .filter(|msg| msg.data.is_finite())
+///     .map(|msg| std_msgs::msg::Float64 { data: msg.data * 2.0 })
+///     .publish_to(&mut node, publisher, CallbackErrorPolicy::LogAndContinue)?;
+/// ```
+///
+/// [1]: Pipeline::map
+/// [2]: Pipeline::filter
+/// [3]: Pipeline::filter_map
+/// [4]: Pipeline::publish_to
+/// [5]: Pipeline::for_each
+/// [6]: crate::spin_once
+/// [7]: crate::spin
+/// [8]: Pipeline::queue
+pub struct Pipeline<T, U> {
+    topic: String,
+    qos: QoSProfile,
+    options: SubscriptionOptions,
+    stages: Box<dyn FnMut(T) -> Option<U>>,
+}
+
+impl<T> Pipeline<T, T>
+where
+    T: 'static,
+{
+    /// Creates a new pipeline for the messages on the given topic.
+    pub fn new(topic: &str, qos: QoSProfile) -> Self {
+        Self {
+            topic: topic.to_owned(),
+            qos,
+            options: SubscriptionOptions::default(),
+            stages: Box::new(Some),
+        }
+    }
+}
+
+impl<T, U> Pipeline<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
+    /// Takes the received messages into a queue with the given capacity, and processes them
+    /// from there.
+    ///
+    /// When the queue is full, the `overflow_policy` decides what happens with new messages. See
+    /// [`SubscriptionOptions::queue_capacity`] for details.
+    pub fn queue(mut self, capacity: usize, overflow_policy: QueueOverflowPolicy) -> Self {
+        self.options.queue_capacity = capacity;
+        self.options.queue_overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Transforms each message with the given function.
+    pub fn map<V, F>(self, mut f: F) -> Pipeline<T, V>
+    where
+        F: FnMut(U) -> V + 'static,
+    {
+        self.filter_map(move |msg| Some(f(msg)))
+    }
+
+    /// Drops the messages for which the given predicate returns `false`.
+    pub fn filter<F>(self, mut predicate: F) -> Self
+    where
+        F: FnMut(&U) -> bool + 'static,
+    {
+        self.filter_map(move |msg| if predicate(&msg) { Some(msg) } else { None })
+    }
+
+    /// Transforms each message with the given function, and drops it when the result is `None`.
+    pub fn filter_map<V, F>(self, mut f: F) -> Pipeline<T, V>
+    where
+        F: FnMut(U) -> Option<V> + 'static,
+    {
+        let mut stages = self.stages;
+        Pipeline {
+            topic: self.topic,
+            qos: self.qos,
+            options: self.options,
+            stages: Box::new(move |msg| stages(msg).and_then(&mut f)),
+        }
+    }
+
+    /// Finishes the pipeline by passing each resulting message to the given function.
+    ///
+    /// The pipeline runs as long as the returned subscription is alive.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn for_each<F>(
+        self,
+        node: &mut Node,
+        mut f: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        F: FnMut(U) + 'static,
+    {
+        let mut stages = self.stages;
+        node.create_subscription_with_options(&self.topic, self.qos, self.options, move |msg: T| {
+            if let Some(output) = stages(msg) {
+                f(output);
+            }
+        })
+    }
+
+    /// Finishes the pipeline by publishing each resulting message with the given publisher.
+    ///
+    /// Errors while publishing are handled according to the `error_policy`, see
+    /// [`Subscription::new_fallible`]. The pipeline runs as long as the returned subscription is
+    /// alive.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn publish_to(
+        self,
+        node: &mut Node,
+        publisher: Publisher<U>,
+        error_policy: CallbackErrorPolicy<RclReturnCode>,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        T: Message,
+        U: Message,
+    {
+        let mut stages = self.stages;
+        node.create_fallible_subscription_with_options(
+            &self.topic,
+            self.qos,
+            self.options,
+            error_policy,
+            move |msg: T| match stages(msg) {
+                Some(output) => publisher.publish(output),
+                None => Ok(()),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pipeline;
+    use crate::{QueueOverflowPolicy, QOS_PROFILE_DEFAULT};
+
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_pipeline_stages() {
+        let mut pipeline = Pipeline::<i32, i32>::new("numbers", QOS_PROFILE_DEFAULT)
+            .filter(|x| *x > 0)
+            .map(|x| x * 2)
+            .filter_map(|x| if x < 10 { Some(x.to_string()) } else { None });
+        assert_eq!((pipeline.stages)(-1), None);
+        assert_eq!((pipeline.stages)(2), Some(String::from("4")));
+        assert_eq!((pipeline.stages)(5), None);
+    }
+
+    #[test]
+    fn test_pipeline_queue_overflow() {
+        let pipeline = Pipeline::<i32, i32>::new("numbers", QOS_PROFILE_DEFAULT)
+            .queue(2, QueueOverflowPolicy::DropNewest)
+            .map(|x| x * 2);
+        assert_eq!(pipeline.options.queue_capacity, 2);
+        assert_eq!(
+            pipeline.options.queue_overflow_policy,
+            QueueOverflowPolicy::DropNewest
+        );
+
+        let overflow = |policy: QueueOverflowPolicy| {
+            let mut queue = VecDeque::new();
+            for msg in 1..=4 {
+                policy.push(&mut queue, 2, msg);
+            }
+            queue
+        };
+        assert_eq!(overflow(QueueOverflowPolicy::DropOldest), [3, 4]);
+        assert_eq!(overflow(QueueOverflowPolicy::DropNewest), [1, 2]);
+        assert_eq!(overflow(QueueOverflowPolicy::Block), [1, 2]);
+    }
+}
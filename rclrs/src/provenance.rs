@@ -0,0 +1,153 @@
+use crate::rcl_bindings::*;
+
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use parking_lot::{const_rwlock, Mutex, RwLock};
+
+/// Identifies the message that started a chain of callbacks.
+///
+/// While a subscription callback runs, the provenance ID of the received message is the
+/// [current provenance][1] of the thread. Messages that are published from within the callback
+/// carry the ID on to the subscriptions in the same process that receive them. This way, all
+/// callbacks in a pipeline of nodes that is spun in one process see the ID of the message that
+/// entered the pipeline, which allows attributing the end-to-end latency of the pipeline to its
+/// stages, e.g. in the `callback` and `publish` spans of the `tracing` feature.
+///
+/// A message that was published without a current provenance, e.g. by a driver or by another
+/// process, starts a new chain. Its ID is derived from the publisher and the source timestamp
+/// of the message, so all subscriptions of the message agree on it.
+///
+/// The propagation tracks the last message of each publisher, so when a publisher publishes
+/// messages with different provenances faster than they are taken, some messages may be
+/// attributed to a later chain.
+///
+/// [1]: current_provenance
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProvenanceId(u64);
+
+impl ProvenanceId {
+    /// Creates a provenance ID from its raw value, e.g. to start a chain for data from hardware.
+    pub fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw value of the ID.
+    pub fn as_raw(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for ProvenanceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+thread_local! {
+    static CURRENT_PROVENANCE: Cell<Option<ProvenanceId>> = const { Cell::new(None) };
+}
+
+// The publishers in this process, keyed by their GIDs, for looking up the provenance of received
+// messages. This is only written to when publishers are created and dropped.
+static PUBLISHERS: RwLock<BTreeMap<u64, Arc<PublishedProvenance>>> = const_rwlock(BTreeMap::new());
+
+/// Returns the provenance ID of the callback chain that the current thread is in, if any.
+///
+/// This is set while subscription callbacks run, and by [`with_provenance`].
+pub fn current_provenance() -> Option<ProvenanceId> {
+    CURRENT_PROVENANCE.with(|current| current.get())
+}
+
+/// Runs a function with the given provenance ID as the current provenance of the thread.
+///
+/// Messages that are published from within the function are attributed to the provenance. The
+/// previous provenance is restored afterwards, also when the function panics.
+pub fn with_provenance<R>(provenance: ProvenanceId, f: impl FnOnce() -> R) -> R {
+    struct RestoreGuard(Option<ProvenanceId>);
+
+    impl Drop for RestoreGuard {
+        fn drop(&mut self) {
+            CURRENT_PROVENANCE.with(|current| current.set(self.0));
+        }
+    }
+
+    let previous = CURRENT_PROVENANCE.with(|current| current.replace(Some(provenance)));
+    let _guard = RestoreGuard(previous);
+    f()
+}
+
+// Returns a key that identifies a publisher by its GID.
+fn gid_key(gid: &rmw_gid_t) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    gid.data.hash(&mut hasher);
+    hasher.finish()
+}
+
+// The provenance of the last message of a publisher, which is owned by the publisher.
+pub(crate) struct PublishedProvenance {
+    gid_key: u64,
+    // `None` when the last message was published without a current provenance.
+    last: Mutex<Option<ProvenanceId>>,
+}
+
+impl PublishedProvenance {
+    // Registers the publisher with the given GID, so that its subscriptions in this process find
+    // the provenance of its messages.
+    pub(crate) fn register(gid: &rmw_gid_t) -> Arc<Self> {
+        let published = Arc::new(Self {
+            gid_key: gid_key(gid),
+            last: Mutex::new(None),
+        });
+        PUBLISHERS
+            .write()
+            .insert(published.gid_key, Arc::clone(&published));
+        published
+    }
+
+    // Forgets the publisher when it is dropped.
+    pub(crate) fn unregister(&self) {
+        PUBLISHERS.write().remove(&self.gid_key);
+    }
+
+    // Remembers the current provenance as the provenance of the publisher's last message.
+    pub(crate) fn record(&self) {
+        *self.last.lock() = current_provenance();
+    }
+}
+
+// Returns the provenance of a received message.
+pub(crate) fn received_provenance(message_info: &rmw_message_info_t) -> ProvenanceId {
+    let publisher_key = gid_key(&message_info.publisher_gid);
+    let published = PUBLISHERS.read().get(&publisher_key).cloned();
+    if let Some(provenance) = published.and_then(|published| *published.last.lock()) {
+        return provenance;
+    }
+    let mut hasher = DefaultHasher::new();
+    publisher_key.hash(&mut hasher);
+    message_info.source_timestamp.hash(&mut hasher);
+    ProvenanceId(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_provenance_restores_previous() {
+        assert_eq!(current_provenance(), None);
+        let outer = ProvenanceId::from_raw(1);
+        let inner = ProvenanceId::from_raw(2);
+        with_provenance(outer, || {
+            assert_eq!(current_provenance(), Some(outer));
+            with_provenance(inner, || assert_eq!(current_provenance(), Some(inner)));
+            assert_eq!(current_provenance(), Some(outer));
+        });
+        assert_eq!(current_provenance(), None);
+        assert_eq!(inner.to_string(), "0000000000000002");
+    }
+}
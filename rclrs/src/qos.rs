@@ -1,5 +1,11 @@
+#[cfg(not(ros_distro = "foxy"))]
+use crate::error::{RclReturnCode, ToResult};
 use crate::rcl_bindings::*;
 
+#[cfg(not(ros_distro = "foxy"))]
+use std::ffi::CStr;
+#[cfg(not(ros_distro = "foxy"))]
+use std::os::raw::c_char;
 use std::time::Duration;
 
 /// The `HISTORY` DDS QoS policy.
@@ -179,6 +185,40 @@ pub struct QoSProfile {
     pub avoid_ros_namespace_conventions: bool,
 }
 
+impl QoSProfile {
+    /// See [`QOS_PROFILE_DEFAULT`].
+    pub const DEFAULT: Self = QOS_PROFILE_DEFAULT;
+    /// See [`QOS_PROFILE_SENSOR_DATA`].
+    pub const SENSOR_DATA: Self = QOS_PROFILE_SENSOR_DATA;
+    /// See [`QOS_PROFILE_SERVICES_DEFAULT`].
+    pub const SERVICES_DEFAULT: Self = QOS_PROFILE_SERVICES_DEFAULT;
+    /// See [`QOS_PROFILE_PARAMETERS`].
+    pub const PARAMETERS_DEFAULT: Self = QOS_PROFILE_PARAMETERS;
+    /// See [`QOS_PROFILE_PARAMETER_EVENTS`].
+    pub const PARAMETER_EVENTS: Self = QOS_PROFILE_PARAMETER_EVENTS;
+    /// See [`QOS_PROFILE_SYSTEM_DEFAULT`].
+    pub const SYSTEM_DEFAULT: Self = QOS_PROFILE_SYSTEM_DEFAULT;
+    /// See [`QOS_PROFILE_CLOCK`].
+    pub const CLOCK: Self = QOS_PROFILE_CLOCK;
+
+    /// A profile for "latched" topics, on which late-joining subscriptions receive the last
+    /// `depth` messages that were published.
+    ///
+    /// This is the default profile with reliable delivery and transient local durability. For the
+    /// messages to be delivered to late joiners, the subscription needs to use transient local
+    /// durability as well. See also [`LatchedPublisher`][1].
+    ///
+    /// [1]: crate::LatchedPublisher
+    pub const fn transient_local_latched(depth: u32) -> Self {
+        Self {
+            history: QoSHistoryPolicy::KeepLast { depth },
+            reliability: QoSReliabilityPolicy::Reliable,
+            durability: QoSDurabilityPolicy::TransientLocal,
+            ..QOS_PROFILE_DEFAULT
+        }
+    }
+}
+
 impl From<QoSProfile> for rmw_qos_profile_t {
     fn from(qos: QoSProfile) -> Self {
         Self {
@@ -282,6 +322,161 @@ impl From<QoSDuration> for rmw_time_t {
     }
 }
 
+impl From<&rmw_qos_profile_t> for QoSProfile {
+    fn from(qos: &rmw_qos_profile_t) -> Self {
+        Self {
+            history: match qos.history {
+                rmw_qos_history_policy_t::RMW_QOS_POLICY_HISTORY_KEEP_LAST => {
+                    QoSHistoryPolicy::KeepLast {
+                        depth: qos.depth as u32,
+                    }
+                }
+                rmw_qos_history_policy_t::RMW_QOS_POLICY_HISTORY_KEEP_ALL => {
+                    QoSHistoryPolicy::KeepAll
+                }
+                _ => QoSHistoryPolicy::SystemDefault {
+                    depth: qos.depth as u32,
+                },
+            },
+            reliability: qos.reliability.into(),
+            durability: qos.durability.into(),
+            deadline: (&qos.deadline).into(),
+            lifespan: (&qos.lifespan).into(),
+            liveliness: qos.liveliness.into(),
+            liveliness_lease_duration: (&qos.liveliness_lease_duration).into(),
+            avoid_ros_namespace_conventions: qos.avoid_ros_namespace_conventions,
+        }
+    }
+}
+
+impl From<rmw_qos_reliability_policy_t> for QoSReliabilityPolicy {
+    fn from(policy: rmw_qos_reliability_policy_t) -> Self {
+        match policy {
+            rmw_qos_reliability_policy_t::RMW_QOS_POLICY_RELIABILITY_RELIABLE => Self::Reliable,
+            rmw_qos_reliability_policy_t::RMW_QOS_POLICY_RELIABILITY_BEST_EFFORT => {
+                Self::BestEffort
+            }
+            _ => Self::SystemDefault,
+        }
+    }
+}
+
+impl From<rmw_qos_durability_policy_t> for QoSDurabilityPolicy {
+    fn from(policy: rmw_qos_durability_policy_t) -> Self {
+        match policy {
+            rmw_qos_durability_policy_t::RMW_QOS_POLICY_DURABILITY_TRANSIENT_LOCAL => {
+                Self::TransientLocal
+            }
+            rmw_qos_durability_policy_t::RMW_QOS_POLICY_DURABILITY_VOLATILE => Self::Volatile,
+            _ => Self::SystemDefault,
+        }
+    }
+}
+
+impl From<rmw_qos_liveliness_policy_t> for QoSLivelinessPolicy {
+    fn from(policy: rmw_qos_liveliness_policy_t) -> Self {
+        match policy {
+            rmw_qos_liveliness_policy_t::RMW_QOS_POLICY_LIVELINESS_AUTOMATIC => Self::Automatic,
+            rmw_qos_liveliness_policy_t::RMW_QOS_POLICY_LIVELINESS_MANUAL_BY_TOPIC => {
+                Self::ManualByTopic
+            }
+            _ => Self::SystemDefault,
+        }
+    }
+}
+
+impl From<&rmw_time_t> for QoSDuration {
+    fn from(time: &rmw_time_t) -> Self {
+        match (time.sec, time.nsec) {
+            // See RMW_DURATION_DEFAULT
+            (0, 0) => Self::SystemDefault,
+            // See RMW_DURATION_INFINITE
+            (9223372036, 854775807) => Self::Infinite,
+            (sec, nsec) => Self::Custom(Duration::from_secs(sec) + Duration::from_nanos(nsec)),
+        }
+    }
+}
+
+/// The result of checking a pair of QoS profiles for compatibility, see
+/// [`qos_check_compatible`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum QoSCompatibility {
+    /// The profiles are compatible.
+    Ok,
+    /// The profiles may be incompatible, depending on the values the RMW layer chooses for
+    /// `SystemDefault` policies. Contains a description of the problem.
+    Warning(String),
+    /// The profiles are incompatible, so no messages will be delivered. Contains a description
+    /// of the problem.
+    Error(String),
+}
+
+/// Checks whether a publisher with `pub_qos` can deliver messages to a subscription with `sub_qos`.
+///
+/// This wraps `rmw_qos_profile_check_compatible()`, which is not available in Foxy.
+///
+/// # Example
+/// ```
+/// # #[cfg(not(ros_distro = "foxy"))] {
+/// # use rclrs::{qos_check_compatible, QoSCompatibility, QoSProfile, QoSReliabilityPolicy, QOS_PROFILE_DEFAULT};
+/// let best_effort = QoSProfile {
+///     reliability: QoSReliabilityPolicy::BestEffort,
+///     ..QOS_PROFILE_DEFAULT
+/// };
+/// let reliable = QoSProfile {
+///     reliability: QoSReliabilityPolicy::Reliable,
+///     ..QOS_PROFILE_DEFAULT
+/// };
+/// assert_eq!(qos_check_compatible(reliable, best_effort), Ok(QoSCompatibility::Ok));
+/// assert!(matches!(
+///     qos_check_compatible(best_effort, reliable),
+///     Ok(QoSCompatibility::Error(_))
+/// ));
+/// # }
+/// ```
+#[cfg(not(ros_distro = "foxy"))]
+pub fn qos_check_compatible(
+    pub_qos: QoSProfile,
+    sub_qos: QoSProfile,
+) -> Result<QoSCompatibility, RclReturnCode> {
+    check_rmw_qos_compatible(pub_qos.into(), sub_qos.into())
+}
+
+#[cfg(not(ros_distro = "foxy"))]
+pub(crate) fn check_rmw_qos_compatible(
+    pub_qos: rmw_qos_profile_t,
+    sub_qos: rmw_qos_profile_t,
+) -> Result<QoSCompatibility, RclReturnCode> {
+    let mut compatibility = rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_OK;
+    // The size of the reason buffer is the same as in rclcpp.
+    let mut reason = vec![0 as c_char; 2048];
+    // SAFETY: The reason buffer is valid for the given size, and will be null-terminated by
+    // this function.
+    unsafe {
+        rmw_qos_profile_check_compatible(
+            pub_qos,
+            sub_qos,
+            &mut compatibility as *mut _,
+            reason.as_mut_ptr(),
+            reason.len(),
+        )
+    }
+    .ok()?;
+    // SAFETY: The reason buffer is null-terminated.
+    let reason = unsafe { CStr::from_ptr(reason.as_ptr()) }
+        .to_string_lossy()
+        .into_owned();
+    Ok(match compatibility {
+        rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_OK => QoSCompatibility::Ok,
+        rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_WARNING => {
+            QoSCompatibility::Warning(reason)
+        }
+        rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_ERROR => {
+            QoSCompatibility::Error(reason)
+        }
+    })
+}
+
 /// Equivalent to `rmw_qos_profile_sensor_data` from the [`rmw` package][1].
 ///
 /// [1]: https://github.com/ros2/rmw/blob/master/rmw/include/rmw/qos_profiles.h
@@ -342,7 +537,7 @@ pub const QOS_PROFILE_SERVICES_DEFAULT: QoSProfile = QoSProfile {
 ///
 /// [1]: https://github.com/ros2/rmw/blob/master/rmw/include/rmw/qos_profiles.h
 pub const QOS_PROFILE_PARAMETER_EVENTS: QoSProfile = QoSProfile {
-    history: QoSHistoryPolicy::KeepAll,
+    history: QoSHistoryPolicy::KeepLast { depth: 1000 },
     reliability: QoSReliabilityPolicy::Reliable,
     durability: QoSDurabilityPolicy::Volatile,
     deadline: QoSDuration::SystemDefault,
@@ -365,3 +560,17 @@ pub const QOS_PROFILE_SYSTEM_DEFAULT: QoSProfile = QoSProfile {
     liveliness_lease_duration: QoSDuration::SystemDefault,
     avoid_ros_namespace_conventions: false,
 };
+
+/// Equivalent to `rclcpp::ClockQoS`, the profile used for the `/clock` topic.
+///
+/// There is no corresponding profile in the `rmw` package.
+pub const QOS_PROFILE_CLOCK: QoSProfile = QoSProfile {
+    history: QoSHistoryPolicy::KeepLast { depth: 1 },
+    reliability: QoSReliabilityPolicy::BestEffort,
+    durability: QoSDurabilityPolicy::Volatile,
+    deadline: QoSDuration::SystemDefault,
+    lifespan: QoSDuration::SystemDefault,
+    liveliness: QoSLivelinessPolicy::SystemDefault,
+    liveliness_lease_duration: QoSDuration::SystemDefault,
+    avoid_ros_namespace_conventions: false,
+};
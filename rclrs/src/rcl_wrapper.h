@@ -1,2 +1,11 @@
 #include <rcl/rcl.h>
-#include <rcutils/error_handling.h>
\ No newline at end of file
+#include <rcl/expand_topic_name.h>
+#include <rcl/logging.h>
+#if __has_include(<rcl/network_flow_endpoints.h>)
+#include <rcl/network_flow_endpoints.h>
+#endif
+#include <rcl/validate_topic_name.h>
+#include <rcutils/error_handling.h>
+#include <rcutils/logging.h>
+#include <rmw/validate_namespace.h>
+#include <rmw/validate_node_name.h>
\ No newline at end of file
@@ -0,0 +1,302 @@
+use crate::node::find_invalid_namespace;
+use crate::{Context, RclReturnCode};
+
+use std::error::Error;
+use std::ffi::CString;
+use std::fmt;
+
+/// The namespace of one robot in a multi-robot system, e.g. `robot1`.
+///
+/// When several robots share one ROS graph, each robot's topics, TF frames and parameters need
+/// a prefix, and every piece of code must apply it in the same way. `RobotNamespace` derives the
+/// prefix once, from a parameter override on the command line or an environment variable, and
+/// applies it to
+/// - topics: relative and absolute names are put into the namespace, e.g. `cmd_vel` and
+///   `/cmd_vel` become `/robot1/cmd_vel`, while private names like `~/status` are left alone,
+///   since they are already below the namespace of the node,
+/// - node namespaces, for [`NodeBuilder::namespace`][1], e.g. `/robot1/navigation`,
+/// - TF frames, with the `tf_prefix` convention, e.g. `robot1/base_link`,
+/// - parameter names, e.g. `robot1.max_speed`.
+///
+/// Topic names are made absolute, so that they do not depend on the namespace of the node. An
+/// empty namespace is the single-robot case, in which names get no prefix, so the same code runs
+/// with and without a robot namespace.
+///
+/// # Example
+/// ```
+/// # use rclrs::RobotNamespace;
+/// let robot = RobotNamespace::new("/robot1/")?;
+/// assert_eq!(robot.topic("cmd_vel"), "/robot1/cmd_vel");
+/// assert_eq!(robot.topic("/tf"), "/robot1/tf");
+/// assert_eq!(robot.frame("base_link"), "robot1/base_link");
+/// assert_eq!(robot.strip_frame("robot1/base_link"), Some("base_link"));
+/// assert_eq!(robot.node_namespace("navigation"), "/robot1/navigation");
+///
+/// let single = RobotNamespace::none();
+/// assert_eq!(single.topic("cmd_vel"), "/cmd_vel");
+/// assert_eq!(single.frame("base_link"), "base_link");
+/// # Ok::<(), rclrs::RobotNamespaceError>(())
+/// ```
+///
+/// [1]: crate::NodeBuilder::namespace
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RobotNamespace {
+    // Without leading and trailing slashes, empty for no namespace.
+    name: String,
+}
+
+/// The reason why a [`RobotNamespace`] could not be created.
+#[derive(Debug, PartialEq)]
+pub enum RobotNamespaceError {
+    /// The namespace is not a valid ROS namespace.
+    InvalidNamespace {
+        /// The invalid namespace.
+        namespace: String,
+        /// The index of the first invalid character in the namespace.
+        position: usize,
+        /// Why the namespace is invalid.
+        reason: String,
+    },
+    /// The command line arguments of the context could not be read.
+    Rcl(RclReturnCode),
+}
+
+impl fmt::Display for RobotNamespaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNamespace {
+                namespace,
+                position,
+                reason,
+            } => write!(
+                f,
+                "Invalid robot namespace '{}' at position {}: {}",
+                namespace, position, reason
+            ),
+            Self::Rcl(err) => write!(f, "Failed to read the robot namespace: {}", err),
+        }
+    }
+}
+
+impl Error for RobotNamespaceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Rcl(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<RclReturnCode> for RobotNamespaceError {
+    fn from(err: RclReturnCode) -> Self {
+        Self::Rcl(err)
+    }
+}
+
+impl RobotNamespace {
+    /// The parameter that [`RobotNamespace::resolve`] reads, e.g. with
+    /// `--ros-args -p robot_namespace:=robot1`.
+    pub const PARAMETER: &'static str = "robot_namespace";
+    /// The environment variable that [`RobotNamespace::resolve`] reads.
+    pub const ENV_VAR: &'static str = "ROBOT_NAMESPACE";
+
+    /// Creates a robot namespace.
+    ///
+    /// The namespace consists of one or more tokens separated by forward slashes, e.g. `robot1`
+    /// or `fleet_a/robot1`. Leading and trailing slashes are ignored, and an empty namespace is
+    /// the same as [`RobotNamespace::none`]. The namespace is validated like a node namespace:
+    /// the tokens must consist of alphanumeric characters and underscores, must not start with a
+    /// digit, and the namespace must not exceed the maximum length of the middleware.
+    pub fn new(namespace: &str) -> Result<Self, RobotNamespaceError> {
+        let trimmed = namespace.trim_matches('/');
+        let offset = namespace.len() - namespace.trim_start_matches('/').len();
+        // Positions in the absolute namespace, which has one leading slash, are mapped back to
+        // the namespace as it was given.
+        let invalid_namespace =
+            |position: usize, reason: &str| RobotNamespaceError::InvalidNamespace {
+                namespace: namespace.to_owned(),
+                position: offset + position.saturating_sub(1),
+                reason: reason.to_owned(),
+            };
+        let absolute = CString::new(format!("/{}", trimmed)).map_err(|err| {
+            invalid_namespace(err.nul_position(), "namespace must not contain null bytes")
+        })?;
+        if let Some((position, reason)) = find_invalid_namespace(&absolute)? {
+            return Err(invalid_namespace(position, &reason));
+        }
+        Ok(Self {
+            name: trimmed.to_owned(),
+        })
+    }
+
+    /// Returns the empty namespace, for single-robot systems.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Reads the namespace from an environment variable.
+    ///
+    /// Returns the empty namespace if the variable is not set.
+    pub fn from_env(var: &str) -> Result<Self, RobotNamespaceError> {
+        match std::env::var(var) {
+            Ok(namespace) => Self::new(&namespace),
+            Err(_) => Ok(Self::none()),
+        }
+    }
+
+    /// Reads the namespace from a global parameter override in the command line arguments of
+    /// the context, e.g. `--ros-args -p robot_namespace:=robot1`.
+    ///
+    /// Returns `None` if the parameter is not set.
+    pub fn from_parameter_override(
+        context: &Context,
+        parameter: &str,
+    ) -> Result<Option<Self>, RobotNamespaceError> {
+        let arguments = context.arguments()?;
+        // The last override wins, like in rcl.
+        let value = arguments
+            .parameter_overrides
+            .iter()
+            .rev()
+            .find_map(|rule| rule.strip_prefix(parameter)?.strip_prefix(":="));
+        value.map(|value| Self::new(unquote(value))).transpose()
+    }
+
+    /// Determines the namespace of the robot from the [`PARAMETER`][1] override, or else the
+    /// [`ENV_VAR`][2] environment variable, or else uses the empty namespace.
+    ///
+    /// [1]: RobotNamespace::PARAMETER
+    /// [2]: RobotNamespace::ENV_VAR
+    pub fn resolve(context: &Context) -> Result<Self, RobotNamespaceError> {
+        match Self::from_parameter_override(context, Self::PARAMETER)? {
+            Some(namespace) => Ok(namespace),
+            None => Self::from_env(Self::ENV_VAR),
+        }
+    }
+
+    /// Returns the namespace without leading and trailing slashes, e.g. `robot1`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` for the empty namespace.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty()
+    }
+
+    /// Returns a topic or service name in the robot namespace.
+    ///
+    /// Private names, which start with `~`, are returned unchanged.
+    pub fn topic(&self, topic: &str) -> String {
+        if topic.starts_with('~') {
+            return topic.to_owned();
+        }
+        let topic = topic.trim_start_matches('/');
+        if self.is_empty() {
+            format!("/{}", topic)
+        } else {
+            format!("/{}/{}", self.name, topic)
+        }
+    }
+
+    /// Returns a node namespace below the robot namespace, e.g. for the nodes of one
+    /// subsystem.
+    pub fn node_namespace(&self, namespace: &str) -> String {
+        let namespace = namespace.trim_matches('/');
+        match (self.is_empty(), namespace.is_empty()) {
+            (true, _) => format!("/{}", namespace),
+            (false, true) => format!("/{}", self.name),
+            (false, false) => format!("/{}/{}", self.name, namespace),
+        }
+    }
+
+    /// Returns a TF frame ID with the robot prefix.
+    ///
+    /// A leading slash, which tf2 ignores, is removed, and frames that already have the prefix
+    /// are not prefixed twice.
+    pub fn frame(&self, frame: &str) -> String {
+        let frame = frame.trim_start_matches('/');
+        if self.is_empty() || self.strip_frame(frame).is_some() {
+            frame.to_owned()
+        } else {
+            format!("{}/{}", self.name, frame)
+        }
+    }
+
+    /// Returns the frame ID without the robot prefix, or `None` if the frame belongs to another
+    /// robot.
+    ///
+    /// For the empty namespace, this returns the frame ID without a leading slash.
+    pub fn strip_frame<'a>(&self, frame: &'a str) -> Option<&'a str> {
+        let frame = frame.trim_start_matches('/');
+        if self.is_empty() {
+            return Some(frame);
+        }
+        frame.strip_prefix(self.name.as_str())?.strip_prefix('/')
+    }
+
+    /// Returns a parameter name with the robot prefix, e.g. for looking up the settings of this
+    /// robot in a parameter file that is shared by all robots.
+    ///
+    /// The tokens of the namespace are separated by dots, like the parts of parameter names.
+    pub fn parameter(&self, parameter: &str) -> String {
+        if self.is_empty() {
+            parameter.to_owned()
+        } else {
+            format!("{}.{}", self.name.replace('/', "."), parameter)
+        }
+    }
+}
+
+impl fmt::Display for RobotNamespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}", self.name)
+    }
+}
+
+// Removes the quotes around a YAML string, e.g. `"robot1"` or `'robot1'`.
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robot_namespace() {
+        let robot = RobotNamespace::new("fleet_a/robot1").unwrap();
+        assert_eq!(robot.to_string(), "/fleet_a/robot1");
+        assert_eq!(robot.topic("~/status"), "~/status");
+        assert_eq!(robot.node_namespace("/"), "/fleet_a/robot1");
+        assert_eq!(robot.frame("/fleet_a/robot1/odom"), "fleet_a/robot1/odom");
+        assert_eq!(robot.strip_frame("robot2/odom"), None);
+        assert_eq!(robot.parameter("max_speed"), "fleet_a.robot1.max_speed");
+        assert!(RobotNamespace::new("").unwrap().is_empty());
+
+        assert!(matches!(
+            RobotNamespace::new("/robot 1"),
+            Err(RobotNamespaceError::InvalidNamespace { namespace, position: 6, .. })
+                if namespace == "/robot 1"
+        ));
+        assert!(matches!(
+            RobotNamespace::new("robot\0_1"),
+            Err(RobotNamespaceError::InvalidNamespace { position: 5, .. })
+        ));
+        assert!(RobotNamespace::new("fleet//robot1").is_err());
+        assert!(RobotNamespace::new("1robot").is_err());
+        assert!(RobotNamespace::new(&"robot".repeat(100)).is_err());
+    }
+
+    #[test]
+    fn test_unquote() {
+        assert_eq!(unquote("robot1"), "robot1");
+        assert_eq!(unquote("\"robot1\""), "robot1");
+        assert_eq!(unquote("'robot1'"), "robot1");
+        assert_eq!(unquote("'robot1\""), "'robot1\"");
+        assert_eq!(unquote("\""), "\"");
+    }
+}
@@ -0,0 +1,174 @@
+use crate::error::{RclReturnCode, SubscriberErrorCode, ToResult};
+use crate::qos::QoSProfile;
+use crate::rcl_bindings::*;
+use crate::{Node, Publisher, Subscription, SubscriptionBase, SubscriptionHandle};
+
+use std::borrow::Borrow;
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+
+use rosidl_runtime_rs::Message;
+
+/// A message in the serialized format of the middleware.
+///
+/// Serialized messages are taken with [`Subscription::take_serialized`] and published with
+/// [`Publisher::publish_serialized`]. Passing them on without converting them to a message type
+/// is cheaper than deserializing and serializing them again, see also [`Relay`].
+///
+/// The buffer is reused when the message is taken again, so a single `SerializedMessage` only
+/// allocates when a larger message is received.
+pub struct SerializedMessage {
+    pub(crate) handle: rcl_serialized_message_t,
+}
+
+impl Drop for SerializedMessage {
+    fn drop(&mut self) {
+        // SAFETY: The array was initialized in the constructor, and uses its own allocator.
+        unsafe { rcutils_uint8_array_fini(&mut self.handle as *mut _) };
+    }
+}
+
+impl SerializedMessage {
+    /// Creates a new, empty serialized message.
+    pub fn new() -> Result<Self, RclReturnCode> {
+        Self::with_capacity(0)
+    }
+
+    /// Creates a new, empty serialized message with a buffer of the given capacity in bytes.
+    pub fn with_capacity(capacity: usize) -> Result<Self, RclReturnCode> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut handle = unsafe { rcutils_get_zero_initialized_uint8_array() };
+        // SAFETY: The array is zero-initialized as expected by this function, and the allocator
+        // is copied into it.
+        unsafe {
+            rcutils_uint8_array_init(
+                &mut handle as *mut _,
+                capacity,
+                &rcutils_get_default_allocator() as *const _,
+            )
+            .ok()?;
+        }
+        Ok(Self { handle })
+    }
+
+    /// Creates a serialized message from bytes in the serialized format of the middleware.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RclReturnCode> {
+        let mut message = Self::with_capacity(bytes.len())?;
+        if !bytes.is_empty() {
+            // SAFETY: The buffer has a capacity of at least `bytes.len()`, and does not overlap
+            // with `bytes`.
+            unsafe {
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), message.handle.buffer, bytes.len())
+            };
+        }
+        message.handle.buffer_length = bytes.len();
+        Ok(message)
+    }
+
+    /// Returns the serialized bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.handle.buffer.is_null() {
+            return &[];
+        }
+        // SAFETY: The buffer contains `buffer_length` initialized bytes.
+        unsafe { std::slice::from_raw_parts(self.handle.buffer, self.handle.buffer_length) }
+    }
+
+    /// Returns the length of the serialized message in bytes.
+    pub fn len(&self) -> usize {
+        self.handle.buffer_length
+    }
+
+    /// Returns `true` if the serialized message is empty.
+    pub fn is_empty(&self) -> bool {
+        self.handle.buffer_length == 0
+    }
+}
+
+/// Republishes the messages of one topic on another topic, without deserializing them.
+///
+/// This is the building block for relay, mux and demux nodes. The relay is executed by
+/// [`spin_once`][1] and [`spin`][2] like a subscription, and runs as long as it is alive.
+///
+/// [1]: crate::spin_once
+/// [2]: crate::spin
+pub struct Relay<T>
+where
+    T: Message,
+{
+    subscription: Subscription<T>,
+    publisher: Publisher<T>,
+    buffer: Mutex<SerializedMessage>,
+}
+
+impl<T> Relay<T>
+where
+    T: Message,
+{
+    /// Creates a relay from `input_topic` to `output_topic`, both with the given QoS profile.
+    ///
+    /// # Panics
+    /// When one of the topics contains interior null bytes.
+    pub fn new(
+        node: &mut Node,
+        input_topic: &str,
+        output_topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Arc<Self>, RclReturnCode> {
+        // The subscription is only used for taking serialized messages, so its callback is never
+        // invoked.
+        let relay = Arc::new(Self {
+            subscription: Subscription::new(node, input_topic, qos, |_msg: T| {})?,
+            publisher: Publisher::new(node, output_topic, qos)?,
+            buffer: Mutex::new(SerializedMessage::new()?),
+        });
+        node.subscriptions
+            .push(Arc::downgrade(&relay) as Weak<dyn SubscriptionBase>);
+        Ok(relay)
+    }
+
+    /// Returns the subscription to the input topic.
+    pub fn subscription(&self) -> &Subscription<T> {
+        &self.subscription
+    }
+
+    /// Returns the publisher on the output topic.
+    pub fn publisher(&self) -> &Publisher<T> {
+        &self.publisher
+    }
+}
+
+impl<T> SubscriptionBase for Relay<T>
+where
+    T: Message,
+{
+    fn handle(&self) -> &SubscriptionHandle {
+        self.subscription.handle.borrow()
+    }
+
+    fn execute(&self) -> Result<(), RclReturnCode> {
+        let buffer = &mut *self.buffer.lock();
+        match self.subscription.take_serialized(buffer) {
+            Ok(()) => self.publisher.publish_serialized(buffer),
+            // Spurious wakeup, as in `Subscription::execute()`.
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SerializedMessage;
+
+    #[test]
+    fn test_serialized_message_from_bytes() {
+        let message = SerializedMessage::from_bytes(&[0, 1, 0, 0, 42]).unwrap();
+        assert_eq!(message.as_bytes(), &[0, 1, 0, 0, 42]);
+        assert_eq!(message.len(), 5);
+        assert!(SerializedMessage::new().unwrap().is_empty());
+    }
+}
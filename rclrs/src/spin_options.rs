@@ -0,0 +1,197 @@
+use crate::logging::log_warning;
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Options for [`spin_once_with_options`][1] and [`spin_with_options`][2].
+///
+/// In each spin, the ready subscriptions are executed in round-robin order, starting with the
+/// subscription whose callback has run least recently. This way, a subscription on a high-rate
+/// topic cannot keep the other subscriptions from running.
+///
+/// [1]: crate::spin_once_with_options
+/// [2]: crate::spin_with_options
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpinOptions {
+    /// The maximum number of messages that are processed per subscription in one spin.
+    ///
+    /// After every ready subscription has processed one message, those that still have messages
+    /// get another turn, until this budget is used up. A budget of `0` is treated like `1`.
+    ///
+    /// The default is `1`.
+    pub budget_per_entity: usize,
+    /// Report a [`SpinWarning::Starvation`] when a subscription had to wait longer than this for
+    /// its callback to run, counted from the wait set returning it as ready.
+    ///
+    /// The warning is passed to the `warning_handler`. The default is `None`, which disables the
+    /// warning.
+    pub starvation_warning_threshold: Option<Duration>,
+    /// Report callbacks that run longer than this, while they are still running.
+    ///
+    /// A watchdog thread reports a [`SpinWarning::CallbackTimeout`] to the `warning_handler` once
+    /// per callback execution that exceeds the timeout. This helps to find accidental blocking
+    /// calls, e.g. waiting for a service response in a subscription callback, which keep all
+    /// other callbacks of the spin thread from running. The callback itself is not interrupted.
+    ///
+    /// The timeout applies to subscription callbacks and waitables. The default is `None`, which
+    /// disables the watchdog.
+    pub callback_timeout: Option<Duration>,
+    /// The handler for the [`SpinWarning`]s of the spin.
+    ///
+    /// The default is `None`, which logs the warnings through the ROS logging system, with the
+    /// logger `rclrs`.
+    pub warning_handler: Option<SpinWarningHandler>,
+}
+
+impl Default for SpinOptions {
+    fn default() -> Self {
+        Self {
+            budget_per_entity: 1,
+            starvation_warning_threshold: None,
+            callback_timeout: None,
+            warning_handler: None,
+        }
+    }
+}
+
+impl SpinOptions {
+    // Passes the warning to the handler, or logs it if there is none.
+    pub(crate) fn warn(&self, warning: SpinWarning) {
+        report_warning(self.warning_handler.as_ref(), warning);
+    }
+}
+
+// Passes the warning to the handler, or logs it without a handler.
+pub(crate) fn report_warning(handler: Option<&SpinWarningHandler>, warning: SpinWarning) {
+    match handler {
+        Some(handler) => (handler.0)(&warning),
+        None => log_warning(&warning.to_string()),
+    }
+}
+
+/// A warning about the timing of callbacks, see [`SpinOptions::warning_handler`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpinWarning {
+    /// A subscription had to wait longer than the [`starvation_warning_threshold`][1] for its
+    /// callback to run.
+    ///
+    /// [1]: SpinOptions::starvation_warning_threshold
+    Starvation {
+        /// The fully qualified topic name of the subscription.
+        topic_name: String,
+        /// How long the subscription waited, counted from the wait set returning it as ready.
+        wait_time: Duration,
+    },
+    /// A callback exceeded the [`callback_timeout`][1], and is still running.
+    ///
+    /// [1]: SpinOptions::callback_timeout
+    CallbackTimeout(CallbackTimeout),
+}
+
+impl fmt::Display for SpinWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Starvation {
+                topic_name,
+                wait_time,
+            } => write!(
+                f,
+                "Subscription on topic '{}' waited {:?} for its callback to run",
+                topic_name, wait_time
+            ),
+            Self::CallbackTimeout(timeout) => write!(
+                f,
+                "Callback of {} has been running for {:?}",
+                timeout.entity, timeout.elapsed
+            ),
+        }
+    }
+}
+
+/// A handler for the [`SpinWarning`]s of a spin, see [`SpinOptions::warning_handler`].
+///
+/// The handler is called on the spin thread, except for callback timeouts, which are reported
+/// by the watchdog thread while the callback is still running on the spin thread. Clones of a
+/// handler compare equal.
+///
+/// # Example
+/// ```
+/// # use rclrs::{SpinOptions, SpinWarning, SpinWarningHandler};
+/// # use std::time::Duration;
+/// let options = SpinOptions {
+///     starvation_warning_threshold: Some(Duration::from_millis(100)),
+///     callback_timeout: Some(Duration::from_millis(100)),
+///     warning_handler: Some(SpinWarningHandler::new(|warning| match warning {
+///         SpinWarning::CallbackTimeout(timeout) => {
+///             eprintln!("The {} is blocking the executor", timeout.entity);
+///         }
+///         warning => eprintln!("Spin warning: {}", warning),
+///     })),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct SpinWarningHandler(pub(crate) Arc<dyn Fn(&SpinWarning) + Send + Sync>);
+
+impl SpinWarningHandler {
+    /// Creates a handler from a function.
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&SpinWarning) + Send + Sync + 'static,
+    {
+        Self(Arc::new(handler))
+    }
+}
+
+impl fmt::Debug for SpinWarningHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SpinWarningHandler")
+    }
+}
+
+impl PartialEq for SpinWarningHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SpinWarningHandler {}
+
+/// The entity whose callback exceeded the [`callback_timeout`][1], see [`CallbackTimeout`].
+///
+/// [1]: SpinOptions::callback_timeout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallbackEntity {
+    /// A subscription on the given topic.
+    Subscription {
+        /// The fully qualified topic name of the subscription.
+        topic_name: String,
+    },
+    /// A [`Waitable`][1], e.g. a timer.
+    ///
+    /// [1]: crate::Waitable
+    Waitable,
+}
+
+impl fmt::Display for CallbackEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Subscription { topic_name } => {
+                write!(f, "subscription on topic '{}'", topic_name)
+            }
+            Self::Waitable => write!(f, "waitable"),
+        }
+    }
+}
+
+/// A callback that exceeded the [`callback_timeout`][1] of the [`SpinOptions`].
+///
+/// [1]: SpinOptions::callback_timeout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallbackTimeout {
+    /// The entity whose callback is running.
+    pub entity: CallbackEntity,
+    /// How long the callback has been running when it was reported.
+    pub elapsed: Duration,
+}
@@ -0,0 +1,364 @@
+//! Utilities for integration tests of code that uses `rclrs`.
+//!
+//! The [`TestFixture`] provides a publisher node and a subscriber node in a namespace that is
+//! unique to the fixture, and [`wait_for_message`] receives a single message with a timeout.
+//!
+//! For checking interoperability with other client libraries, [`ExternalNode`] runs a node of
+//! another package in the namespace of a fixture, and [`assert_rclcpp_round_trip`] checks that a
+//! message survives a round trip through an `rclcpp` node.
+//!
+//! # Example
+//! ```
+//! # use rclrs::RclReturnCode;
+//! use rclrs::testing::TestFixture;
+//!
+//! let fixture = TestFixture::new("my_test")?;
+//! assert!(fixture.namespace().starts_with("/my_test_"));
+//! # Ok::<(), RclReturnCode>(())
+//! ```
+
+use crate::error::SubscriberErrorCode;
+use crate::finalization::{LIVE_NODES, LIVE_PUBLISHERS, LIVE_SUBSCRIPTIONS};
+use crate::qos::{QoSProfile, QoSReliabilityPolicy, QOS_PROFILE_DEFAULT};
+use crate::{Context, Node, Publisher, RclReturnCode, Subscription, WaitSet};
+
+use std::fmt::Debug;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rosidl_runtime_rs::Message;
+
+/// A publisher node and a subscriber node that share a context.
+///
+/// Both nodes are created in a namespace that is unique within the process, e.g.
+/// `/my_test_1234_0`, so tests that use relative topic names do not receive each other's messages
+/// when they run in parallel. For complete isolation, pass a context with its own domain ID,
+/// created by [`Context::new_with_domain_id`], to [`new_with_context`][1].
+///
+/// The nodes are dropped before the context, so that dropping the fixture tears everything down
+/// in a deterministic order.
+///
+/// [1]: TestFixture::new_with_context
+pub struct TestFixture {
+    /// The node intended for creating publishers.
+    pub publisher_node: Node,
+    /// The node intended for creating subscriptions.
+    pub subscriber_node: Node,
+    /// The context of both nodes.
+    pub context: Context,
+    namespace: String,
+}
+
+impl TestFixture {
+    /// Creates a new fixture, without any ROS arguments.
+    ///
+    /// The `name` is used as a prefix of the namespace, and should be a valid namespace token,
+    /// e.g. the name of the test.
+    pub fn new(name: &str) -> Result<Self, RclReturnCode> {
+        Self::new_with_context(name, Context::new([])?)
+    }
+
+    /// Creates a new fixture that uses an existing context.
+    pub fn new_with_context(name: &str, context: Context) -> Result<Self, RclReturnCode> {
+        static FIXTURE_COUNT: AtomicUsize = AtomicUsize::new(0);
+        let namespace = format!(
+            "/{}_{}_{}",
+            name,
+            std::process::id(),
+            FIXTURE_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        Ok(Self {
+            publisher_node: Node::new_with_namespace("publisher", &namespace, &context)?,
+            subscriber_node: Node::new_with_namespace("subscriber", &namespace, &context)?,
+            context,
+            namespace,
+        })
+    }
+
+    /// Returns the namespace of both nodes.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Waits for a message on the given topic, using the subscriber node.
+    ///
+    /// See [`wait_for_message`].
+    pub fn wait_for_message<T>(
+        &self,
+        topic: &str,
+        qos: QoSProfile,
+        timeout: Duration,
+    ) -> Result<T, RclReturnCode>
+    where
+        T: Message,
+    {
+        wait_for_message(&self.subscriber_node, topic, qos, timeout)
+    }
+}
+
+/// Creates a temporary subscription and waits until it receives a message.
+///
+/// Only the temporary subscription is waited on, so no callbacks of the node are executed.
+/// Since the subscription is created by this function, messages that were published before it
+/// has been matched with the publisher are not received, unless both use a transient local
+/// [`QoSProfile`].
+///
+/// When no message is received within the `timeout`, [`RclReturnCode::Timeout`] is returned.
+///
+/// # Panics
+/// When the topic contains interior null bytes.
+pub fn wait_for_message<T>(
+    node: &Node,
+    topic: &str,
+    qos: QoSProfile,
+    timeout: Duration,
+) -> Result<T, RclReturnCode>
+where
+    T: Message,
+{
+    let subscription = Arc::new(Subscription::<T>::new(node, topic, qos, |_| {})?);
+    subscription.recv_timeout(timeout)
+}
+
+/// The number of `rcl` entities of each kind that currently exist in the process.
+///
+/// Returned by [`live_handles`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LiveHandles {
+    /// The number of nodes.
+    pub nodes: usize,
+    /// The number of publishers.
+    pub publishers: usize,
+    /// The number of subscriptions.
+    pub subscriptions: usize,
+}
+
+/// Returns how many nodes, publishers and subscriptions have been created and not finalized yet.
+///
+/// This is meant for leak checks: an entity is only finalized when the last value that co-owns
+/// it is dropped, e.g. a node is kept alive by its publishers. Since the counts are global, a
+/// test that compares them must not run in parallel with other tests that create entities.
+///
+/// # Example
+/// ```
+/// # use rclrs::{Context, Node, RclReturnCode};
+/// use rclrs::testing::live_handles;
+///
+/// let before = live_handles();
+/// let context = Context::new([])?;
+/// let node = Node::new("leak_check", &context)?;
+/// assert_eq!(live_handles().nodes, before.nodes + 1);
+/// drop(context);
+/// drop(node);
+/// assert_eq!(live_handles(), before);
+/// # Ok::<(), RclReturnCode>(())
+/// ```
+pub fn live_handles() -> LiveHandles {
+    LiveHandles {
+        nodes: LIVE_NODES.get(),
+        publishers: LIVE_PUBLISHERS.get(),
+        subscriptions: LIVE_SUBSCRIPTIONS.get(),
+    }
+}
+
+/// A ROS node that runs in a child process, e.g. a reference node of another client library.
+///
+/// The executable is looked up in the `lib/<package>` directories of the prefixes in
+/// `AMENT_PREFIX_PATH`, like `ros2 run` does, and started directly, so that the node is
+/// reliably killed when this value is dropped. Everything the node prints to stdout and stderr
+/// is collected line by line, see [`wait_for_output`][1].
+///
+/// [1]: ExternalNode::wait_for_output
+pub struct ExternalNode {
+    child: Child,
+    output: Receiver<String>,
+}
+
+impl ExternalNode {
+    /// Starts an executable of a ROS package in the given namespace.
+    ///
+    /// The `ros_args` are passed after `--ros-args`, e.g. `["-r", "chatter:=other"]`.
+    ///
+    /// Returns an error of kind [`ErrorKind::NotFound`] when the executable is not installed.
+    pub fn spawn(
+        package: &str,
+        executable: &str,
+        namespace: &str,
+        ros_args: &[&str],
+    ) -> io::Result<Self> {
+        let prefixes = std::env::var_os("AMENT_PREFIX_PATH").unwrap_or_default();
+        let path = std::env::split_paths(&prefixes)
+            .map(|prefix| prefix.join("lib").join(package).join(executable))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::NotFound,
+                    format!("Executable {} of package {} not found", executable, package),
+                )
+            })?;
+        let mut child = Command::new(path)
+            .arg("--ros-args")
+            .args(["-r", &format!("__ns:={}", namespace)])
+            .args(ros_args)
+            // Log lines would otherwise only be seen when the buffer is full
+            .env("RCUTILS_LOGGING_BUFFERED_STREAM", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let (sender, output) = mpsc::channel();
+        let stdout = child
+            .stdout
+            .take()
+            .map(|stdout| Box::new(stdout) as Box<dyn Read + Send>);
+        let stderr = child
+            .stderr
+            .take()
+            .map(|stderr| Box::new(stderr) as Box<dyn Read + Send>);
+        for stream in stdout.into_iter().chain(stderr) {
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(Self { child, output })
+    }
+
+    /// Waits until the node prints a line that contains `pattern`, and returns that line.
+    ///
+    /// Returns `None` when no such line is printed within the `timeout`. Lines that were printed
+    /// before the matching line are discarded.
+    pub fn wait_for_output(&self, pattern: &str, timeout: Duration) -> Option<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.output.recv_timeout(remaining) {
+                Ok(line) if line.contains(pattern) => return Some(line),
+                Ok(_) => {}
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Returns true if the process has not exited yet.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+}
+
+impl Drop for ExternalNode {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Publishes a message on `output_topic` and waits for it to come back on `input_topic`.
+///
+/// This is meant for checking that messages survive a round trip through a node of another
+/// client library, which relays them from one topic to the other. The message is published
+/// repeatedly until it comes back, so that no message is lost while the relay's publisher is
+/// still being discovered. Both topics are relative to the namespace of the fixture.
+///
+/// When no message comes back within the `timeout`, [`RclReturnCode::Timeout`] is returned.
+pub fn round_trip_through<T>(
+    fixture: &TestFixture,
+    output_topic: &str,
+    input_topic: &str,
+    message: &T,
+    timeout: Duration,
+) -> Result<T, RclReturnCode>
+where
+    T: Message,
+{
+    const REPUBLISH_INTERVAL: Duration = Duration::from_millis(100);
+    let deadline = Instant::now() + timeout;
+    let qos = QoSProfile {
+        reliability: QoSReliabilityPolicy::Reliable,
+        ..QOS_PROFILE_DEFAULT
+    };
+    let publisher = Publisher::<T>::new(&fixture.publisher_node, output_topic, qos)?;
+    let subscription = Arc::new(Subscription::<T>::new(
+        &fixture.subscriber_node,
+        input_topic,
+        qos,
+        |_| {},
+    )?);
+    let mut wait_set = WaitSet::new(1, &fixture.context)?;
+    loop {
+        if publisher.get_subscription_count()? > 0 {
+            publisher.publish(message)?;
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(RclReturnCode::Timeout);
+        }
+        wait_set.clear();
+        wait_set.add_subscription(subscription.clone())?;
+        match wait_set.wait(Some(remaining.min(REPUBLISH_INTERVAL))) {
+            Ok(_) | Err(RclReturnCode::Timeout) => {}
+            Err(e) => return Err(e),
+        }
+        match subscription.take() {
+            Ok(message) => return Ok(message),
+            Err(RclReturnCode::SubscriberError(SubscriberErrorCode::SubscriptionTakeFailed)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Asserts that a message is unchanged after a round trip through an `rclcpp` node.
+///
+/// The reference node is the `relay` node of the `topic_tools` package, which forwards
+/// serialized messages of any type, so custom message packages can be checked without writing
+/// any C++ code. It needs to be installed, and the message package needs to be available to it,
+/// i.e. be sourced.
+///
+/// The relay forwards from `<topic>` to `<topic>_relayed` in the namespace of the fixture.
+///
+/// # Panics
+/// When the relay can't be started, or the message does not come back unchanged within the
+/// `timeout`.
+///
+/// # Example
+/// ```ignore
+/// let fixture = TestFixture::new("conformance")?;
+/// let message = std_msgs::msg::String { data: "x".repeat(1 << 20) };
+/// assert_rclcpp_round_trip(&fixture, "large_string", &message, Duration::from_secs(10));
+/// ```
+pub fn assert_rclcpp_round_trip<T>(
+    fixture: &TestFixture,
+    topic: &str,
+    message: &T,
+    timeout: Duration,
+) where
+    T: Message + PartialEq + Debug,
+{
+    let relayed_topic = format!("{}_relayed", topic);
+    let _relay = ExternalNode::spawn(
+        "topic_tools",
+        "relay",
+        fixture.namespace(),
+        &[
+            "-p",
+            &format!("input_topic:={}", topic),
+            "-p",
+            &format!("output_topic:={}", relayed_topic),
+        ],
+    )
+    .unwrap_or_else(|e| panic!("Could not start the rclcpp relay: {}", e));
+    let received = round_trip_through(fixture, topic, &relayed_topic, message, timeout)
+        .unwrap_or_else(|e| panic!("No message came back from the rclcpp relay: {:?}", e));
+    assert_eq!(
+        &received, message,
+        "The message changed in the round trip through rclcpp"
+    );
+}
@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// Linear interpolation between two values, for [`TimedBuffer::lookup_at`].
+///
+/// # Example
+/// ```
+/// # use rclrs::Interpolate;
+/// struct Position {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// impl Interpolate for Position {
+///     fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+///         Position {
+///             x: self.x.interpolate(&other.x, ratio),
+///             y: self.y.interpolate(&other.y, ratio),
+///         }
+///     }
+/// }
+/// ```
+pub trait Interpolate: Sized {
+    /// Returns the value at `ratio` between `self` and `other`.
+    ///
+    /// The ratio is between `0.0`, for which the result should be `self`, and `1.0`, for which
+    /// the result should be `other`.
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        self + (other - self) * ratio
+    }
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        self + (other - self) * ratio as f32
+    }
+}
+
+/// A buffer of timestamped values that can be looked up at any time within its range.
+///
+/// This is the basic building block for fusing sensor data that arrives at different rates, e.g.
+/// for finding the odometry pose at the time of an IMU or laser scan message. Values between two
+/// samples are linearly interpolated with their [`Interpolate`] implementation.
+///
+/// Values are stamped with the time they are valid at, e.g. the stamp in the header of the
+/// message, which is converted with `UNIX_EPOCH + Duration::new(sec, nanosec)`. This works for
+/// simulated time as well, since the buffer only compares the stamps with each other. Values older
+/// than the retention duration, measured from the newest value, are dropped.
+///
+/// # Example
+/// ```
+/// # use rclrs::{TimedBuffer, TimedLookupError};
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// let mut buffer = TimedBuffer::new(Duration::from_secs(10));
+/// buffer.insert(UNIX_EPOCH + Duration::from_secs(1), 1.0);
+/// buffer.insert(UNIX_EPOCH + Duration::from_secs(3), 2.0);
+/// assert_eq!(buffer.lookup_at(UNIX_EPOCH + Duration::from_secs(2)), Ok(1.5));
+/// assert!(matches!(
+///     buffer.lookup_at(UNIX_EPOCH + Duration::from_secs(4)),
+///     Err(TimedLookupError::TooNew { .. })
+/// ));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimedBuffer<T> {
+    retention: Duration,
+    // Sorted by stamp, oldest first.
+    entries: VecDeque<(SystemTime, T)>,
+}
+
+/// The error returned by [`TimedBuffer::lookup_at`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimedLookupError {
+    /// The buffer contains no values.
+    Empty,
+    /// The requested time is before the oldest value in the buffer.
+    TooOld {
+        /// The stamp of the oldest value.
+        oldest: SystemTime,
+    },
+    /// The requested time is after the newest value in the buffer.
+    ///
+    /// This usually means that the value for that time has not arrived yet, so the lookup can be
+    /// retried later.
+    TooNew {
+        /// The stamp of the newest value.
+        newest: SystemTime,
+    },
+}
+
+impl<T> TimedBuffer<T> {
+    /// Creates an empty buffer that keeps values for the given duration.
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Returns the duration for which values are kept.
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Adds a value that is valid at the given time.
+    ///
+    /// Values may be inserted out of order. A value with the same stamp as an existing one
+    /// replaces it. Afterwards, values that are older than the retention duration are dropped.
+    pub fn insert(&mut self, stamp: SystemTime, value: T) {
+        match self.search(stamp) {
+            Ok(index) => self.entries[index].1 = value,
+            Err(index) => self.entries.insert(index, (stamp, value)),
+        }
+        let newest = self.entries.back().map(|(stamp, _)| *stamp);
+        if let Some(cutoff) = newest.and_then(|newest| newest.checked_sub(self.retention)) {
+            while matches!(self.entries.front(), Some((stamp, _)) if *stamp < cutoff) {
+                self.entries.pop_front();
+            }
+        }
+    }
+
+    /// Returns the value with the newest stamp, together with the stamp.
+    pub fn latest(&self) -> Option<(SystemTime, &T)> {
+        self.entries.back().map(|(stamp, value)| (*stamp, value))
+    }
+
+    /// Returns the range of stamps in the buffer, oldest first.
+    pub fn time_range(&self) -> Option<(SystemTime, SystemTime)> {
+        Some((self.entries.front()?.0, self.entries.back()?.0))
+    }
+
+    /// Returns the number of values in the buffer.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the buffer contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all values.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // Binary search for the stamp, like slice::binary_search.
+    fn search(&self, stamp: SystemTime) -> Result<usize, usize> {
+        self.entries
+            .binary_search_by(|(entry_stamp, _)| entry_stamp.cmp(&stamp))
+    }
+}
+
+impl<T> TimedBuffer<T>
+where
+    T: Clone + Interpolate,
+{
+    /// Returns the value at the given time.
+    ///
+    /// If there is no value with exactly this stamp, the two values around it are interpolated.
+    /// Times outside of the range of the buffer are an error, since extrapolating sensor data is
+    /// rarely safe.
+    pub fn lookup_at(&self, time: SystemTime) -> Result<T, TimedLookupError> {
+        let (oldest, newest) = self.time_range().ok_or(TimedLookupError::Empty)?;
+        if time < oldest {
+            return Err(TimedLookupError::TooOld { oldest });
+        }
+        if time > newest {
+            return Err(TimedLookupError::TooNew { newest });
+        }
+        let index = match self.search(time) {
+            Ok(index) => return Ok(self.entries[index].1.clone()),
+            Err(index) => index,
+        };
+        // The time is strictly between the oldest and newest stamps, so both neighbors exist.
+        let (before_stamp, before) = &self.entries[index - 1];
+        let (after_stamp, after) = &self.entries[index];
+        let span = after_stamp
+            .duration_since(*before_stamp)
+            .unwrap_or_default();
+        let offset = time.duration_since(*before_stamp).unwrap_or_default();
+        let ratio = offset.as_secs_f64() / span.as_secs_f64();
+        Ok(before.interpolate(after, ratio))
+    }
+}
+
+impl fmt::Display for TimedLookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "The buffer is empty"),
+            Self::TooOld { .. } => write!(f, "The requested time is before the oldest value"),
+            Self::TooNew { .. } => write!(f, "The requested time is after the newest value"),
+        }
+    }
+}
+
+impl Error for TimedLookupError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn at(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_timed_buffer() {
+        let mut buffer = TimedBuffer::new(Duration::from_millis(100));
+        assert_eq!(buffer.lookup_at(at(0)), Err(TimedLookupError::Empty));
+
+        // Out of order insertion
+        buffer.insert(at(40), 4.0);
+        buffer.insert(at(10), 1.0);
+        buffer.insert(at(20), 2.0);
+        assert_eq!(buffer.time_range(), Some((at(10), at(40))));
+        assert_eq!(buffer.lookup_at(at(20)), Ok(2.0));
+        assert_eq!(buffer.lookup_at(at(30)), Ok(3.0));
+        assert_eq!(buffer.lookup_at(at(15)), Ok(1.5));
+        assert_eq!(
+            buffer.lookup_at(at(5)),
+            Err(TimedLookupError::TooOld { oldest: at(10) })
+        );
+        assert_eq!(
+            buffer.lookup_at(at(45)),
+            Err(TimedLookupError::TooNew { newest: at(40) })
+        );
+
+        // Replacing a value with the same stamp
+        buffer.insert(at(20), 0.0);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.lookup_at(at(20)), Ok(0.0));
+
+        // Retention is measured from the newest value
+        buffer.insert(at(115), 11.5);
+        assert_eq!(buffer.time_range(), Some((at(20), at(115))));
+        assert_eq!(buffer.latest(), Some((at(115), &11.5)));
+    }
+}
@@ -0,0 +1,301 @@
+use crate::error::{RclReturnCode, ToResult};
+use crate::qos::QoSProfile;
+use crate::rcl_bindings::*;
+use crate::{Node, Publisher, Subscription};
+
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+use std::sync::Arc;
+
+use rosidl_runtime_rs::Message;
+
+/// A topic name together with the message type used on it.
+///
+/// Publishers and subscriptions created through a `Topic` always use the same name and type, so
+/// a typo in the topic name or a mismatched message type becomes a compile error instead of a
+/// silent failure to communicate.
+///
+/// Topics are usually declared with the [`topics!`] macro.
+pub struct Topic<T> {
+    name: &'static str,
+    message: PhantomData<fn() -> T>,
+}
+
+impl<T> Topic<T> {
+    /// Creates a new topic with the given name.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            message: PhantomData,
+        }
+    }
+
+    /// Returns the name of the topic.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Topic<T>
+where
+    T: Message,
+{
+    /// Creates a [`Publisher`] for this topic.
+    ///
+    /// See [`Node::create_publisher`].
+    pub fn create_publisher(
+        &self,
+        node: &Node,
+        qos: QoSProfile,
+    ) -> Result<Publisher<T>, RclReturnCode> {
+        node.create_publisher(self.name, qos)
+    }
+
+    /// Creates a [`Subscription`] for this topic.
+    ///
+    /// See [`Node::create_subscription`].
+    pub fn create_subscription<F>(
+        &self,
+        node: &mut Node,
+        qos: QoSProfile,
+        callback: F,
+    ) -> Result<Arc<Subscription<T>>, RclReturnCode>
+    where
+        F: FnMut(T) + Sized + 'static,
+    {
+        node.create_subscription(self.name, qos, callback)
+    }
+}
+
+// Implemented by hand, since deriving would require `T` to implement these traits too.
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Topic<T> {}
+
+impl<T> fmt::Debug for Topic<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Topic")
+            .field("name", &self.name)
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// Declares [`Topic`] constants, each with a name and a message type.
+///
+/// Each entry has the form `NAME: MessageType = "topic_name"`, optionally preceded by attributes
+/// such as doc comments and a visibility.
+///
+/// # Example
+/// ```ignore
+/// rclrs::topics! {
+///     /// Velocity commands for the base.
+///     pub CMD_VEL: geometry_msgs::msg::Twist = "/cmd_vel";
+///     ODOM: nav_msgs::msg::Odometry = "odom";
+/// }
+///
+/// let publisher = CMD_VEL.create_publisher(&node, rclrs::QOS_PROFILE_DEFAULT)?;
+/// ```
+#[macro_export]
+macro_rules! topics {
+    ($( $(#[$attr:meta])* $vis:vis $name:ident : $ty:ty = $topic:expr );* $(;)?) => {
+        $(
+            $(#[$attr])*
+            #[allow(non_upper_case_globals)]
+            $vis const $name: $crate::Topic<$ty> = $crate::Topic::new($topic);
+        )*
+    };
+}
+
+/// The reason why a topic name is invalid, returned by [`validate_topic_name`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TopicNameValidationError {
+    /// The index of the first invalid character in the topic name.
+    pub position: usize,
+    /// A description of the problem.
+    pub reason: &'static str,
+}
+
+impl fmt::Display for TopicNameValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid topic name at position {}: {}",
+            self.position, self.reason
+        )
+    }
+}
+
+impl Error for TopicNameValidationError {}
+
+/// Checks whether a topic name is valid, before expansion.
+///
+/// The topic name may be relative, and may contain substitutions such as `{node}` and the
+/// private namespace prefix `~`. See the [ROS 2 topic name design][1] for the rules.
+///
+/// # Panics
+/// When the topic name contains interior null bytes.
+///
+/// [1]: https://design.ros2.org/articles/topic_and_service_names.html
+pub fn validate_topic_name(topic_name: &str) -> Result<(), TopicNameValidationError> {
+    let topic_name_c_string = CString::new(topic_name).unwrap();
+    let mut validation_result: c_int = 0;
+    let mut invalid_index: usize = 0;
+    // SAFETY: All pointers are valid for the duration of the call. This function can only fail
+    // when passed null pointers.
+    unsafe {
+        rcl_validate_topic_name(
+            topic_name_c_string.as_ptr(),
+            &mut validation_result as *mut _,
+            &mut invalid_index as *mut _,
+        )
+        .unwrap();
+    }
+    // SAFETY: No preconditions for this function.
+    let reason = unsafe { rcl_topic_name_validation_result_string(validation_result) };
+    // A null pointer means that the name is valid.
+    if reason.is_null() {
+        return Ok(());
+    }
+    // SAFETY: The reason is a static null-terminated string.
+    let reason: &'static CStr = unsafe { CStr::from_ptr(reason) };
+    Err(TopicNameValidationError {
+        position: invalid_index,
+        reason: reason.to_str().unwrap_or("invalid topic name"),
+    })
+}
+
+/// Expands a topic name into a fully qualified topic name.
+///
+/// Relative names are prefixed with the node namespace, the private namespace prefix `~` is
+/// replaced with the fully qualified node name, and the `{node}` and `{ns}`/`{namespace}`
+/// substitutions are performed. Remapping rules are not applied.
+///
+/// An invalid topic name results in a [`TopicNameInvalid`][1] error. Use
+/// [`validate_topic_name`] for details on the problem.
+///
+/// # Panics
+/// When any of the arguments contain interior null bytes.
+///
+/// [1]: crate::RclErrorCode::TopicNameInvalid
+pub fn expand_topic_name(
+    topic_name: &str,
+    node_name: &str,
+    node_namespace: &str,
+) -> Result<String, RclReturnCode> {
+    let topic_name_c_string = CString::new(topic_name).unwrap();
+    let node_name_c_string = CString::new(node_name).unwrap();
+    let node_namespace_c_string = CString::new(node_namespace).unwrap();
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut substitutions = unsafe { rcutils_get_zero_initialized_string_map() };
+    unsafe {
+        // SAFETY: The string map is zero-initialized as expected by this function.
+        rcutils_string_map_init(
+            &mut substitutions as *mut _,
+            0,
+            rcutils_get_default_allocator(),
+        )
+        .ok()?;
+    }
+    let mut output_topic_name: *mut c_char = std::ptr::null_mut();
+    let result = unsafe {
+        // SAFETY: The string map has been initialized.
+        rcl_get_default_topic_name_substitutions(&mut substitutions as *mut _)
+            .ok()
+            .and_then(|()| {
+                // SAFETY: All strings are valid and null-terminated, and the string map has been
+                // initialized. The output is a valid pointer to a null pointer.
+                rcl_expand_topic_name(
+                    topic_name_c_string.as_ptr(),
+                    node_name_c_string.as_ptr(),
+                    node_namespace_c_string.as_ptr(),
+                    &substitutions as *const _,
+                    rcutils_get_default_allocator(),
+                    &mut output_topic_name as *mut _,
+                )
+                .ok()
+            })
+    };
+    // SAFETY: The string map has been initialized and is not used afterwards.
+    unsafe { rcutils_string_map_fini(&mut substitutions as *mut _).ok()? };
+    result?;
+    // SAFETY: On success, the output is a valid null-terminated string that was allocated with
+    // the default allocator. It is not used after being deallocated.
+    unsafe {
+        let expanded_topic_name = CStr::from_ptr(output_topic_name)
+            .to_string_lossy()
+            .into_owned();
+        let allocator = rcutils_get_default_allocator();
+        if let Some(deallocate) = allocator.deallocate {
+            deallocate(output_topic_name as *mut _, allocator.state);
+        }
+        Ok(expanded_topic_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RclErrorCode;
+
+    crate::topics! {
+        CHATTER: i32 = "/chatter";
+        pub(crate) cmd_vel: f64 = "cmd_vel";
+    }
+
+    #[test]
+    fn test_topics_macro() {
+        let chatter: Topic<i32> = CHATTER;
+        assert_eq!(chatter.name(), "/chatter");
+        assert_eq!(cmd_vel.name(), "cmd_vel");
+    }
+
+    #[test]
+    fn test_validate_topic_name() {
+        for topic_name in [
+            "chatter",
+            "/chatter",
+            "~/chatter",
+            "{node}/chatter",
+            "/ns/chatter_2",
+        ] {
+            assert_eq!(validate_topic_name(topic_name), Ok(()), "{}", topic_name);
+        }
+        for (topic_name, position) in [("", 0), ("1chatter", 0), ("chat ter", 4), ("/chatter/", 8)]
+        {
+            let err = validate_topic_name(topic_name).unwrap_err();
+            assert_eq!(err.position, position, "{}", topic_name);
+            assert!(!err.reason.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_expand_topic_name() {
+        for (topic_name, node_namespace, expanded) in [
+            ("chatter", "/", "/chatter"),
+            ("chatter", "/ns", "/ns/chatter"),
+            ("/chatter", "/ns", "/chatter"),
+            ("~/chatter", "/ns", "/ns/my_node/chatter"),
+            ("{node}/chatter", "/ns", "/ns/my_node/chatter"),
+            ("{ns}/chatter", "/ns", "/ns/chatter"),
+        ] {
+            assert_eq!(
+                expand_topic_name(topic_name, "my_node", node_namespace).unwrap(),
+                expanded
+            );
+        }
+        for topic_name in ["", "chat ter", "/chatter/"] {
+            assert_eq!(
+                expand_topic_name(topic_name, "my_node", "/ns"),
+                Err(RclReturnCode::RclError(RclErrorCode::TopicNameInvalid))
+            );
+        }
+    }
+}
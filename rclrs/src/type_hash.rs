@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use rosidl_runtime_rs::{Message, RmwMessage};
+
+/// The hash of a message type definition, in the ROS Interface Hashing Standard (RIHS).
+///
+/// Starting with Iron, endpoints advertise the hash of their message type, so that endpoints
+/// whose types have the same name but different definitions can be detected, e.g. after a
+/// message package was changed on only one machine. See [`Subscription::mismatched_publishers`][1]
+/// and [`Publisher::mismatched_subscriptions`][2].
+///
+/// The hash is formatted like `RIHS01_` followed by 64 hex digits, as in the output of
+/// `ros2 topic info --verbose`.
+///
+/// # Example
+/// ```
+/// # use rclrs::TypeHash;
+/// let text = "RIHS01_df668c740482bbd48fb39d76a70dfd4bd59db1288021743503259e948f6b1a18";
+/// let hash: TypeHash = text.parse().unwrap();
+/// assert_eq!(hash.version(), 1);
+/// assert_eq!(hash.to_string(), text);
+/// ```
+///
+/// [1]: crate::Subscription::mismatched_publishers
+/// [2]: crate::Publisher::mismatched_subscriptions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeHash {
+    pub(crate) version: u8,
+    pub(crate) value: [u8; 32],
+}
+
+/// The error returned when parsing an invalid [`TypeHash`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeHashParseError {
+    text: String,
+}
+
+impl TypeHash {
+    /// Returns the hash of the message type `T`, if it is known.
+    ///
+    /// The hash is generated by `rosidl_generator_rs`, so this is `None` for messages that were
+    /// generated for a distro before Iron, or by an older generator.
+    pub fn of<T: Message>() -> Option<Self> {
+        <T as Message>::RmwMsg::TYPE_HASH.and_then(|text| text.parse().ok())
+    }
+
+    /// Returns the version of the hashing standard, e.g. `1` for `RIHS01`.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the raw hash value.
+    pub fn value(&self) -> &[u8; 32] {
+        &self.value
+    }
+}
+
+impl fmt::Display for TypeHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RIHS{:02}_", self.version)?;
+        for byte in &self.value {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TypeHash {
+    type Err = TypeHashParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let err = || TypeHashParseError {
+            text: text.to_owned(),
+        };
+        let (version, value) = text
+            .strip_prefix("RIHS")
+            .and_then(|rest| rest.split_once('_'))
+            .ok_or_else(err)?;
+        if version.len() != 2
+            || !version.bytes().all(|b| b.is_ascii_digit())
+            || value.len() != 64
+            || !value.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            return Err(err());
+        }
+        let mut hash = Self {
+            // Only digits are left after the checks above, so the conversions can't fail.
+            version: version.parse().unwrap(),
+            value: [0; 32],
+        };
+        for (i, byte) in hash.value.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&value[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Ok(hash)
+    }
+}
+
+impl fmt::Display for TypeHashParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid type hash '{}'", self.text)
+    }
+}
+
+impl Error for TypeHashParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_type_hash() {
+        let text = "RIHS01_00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+        let hash: TypeHash = text.parse().unwrap();
+        assert_eq!(hash.version(), 1);
+        assert_eq!(hash.value()[..3], [0x00, 0x11, 0x22]);
+        assert_eq!(hash.to_string(), text);
+
+        assert!("RIHS01_0011".parse::<TypeHash>().is_err());
+        assert!(
+            "RIHS1_00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff"
+                .parse::<TypeHash>()
+                .is_err()
+        );
+        assert!(
+            "XXXX01_00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff"
+                .parse::<TypeHash>()
+                .is_err()
+        );
+        assert!(
+            "RIHS01_0011223344556677889gaabbccddeeff00112233445566778899aabbccddeeff"
+                .parse::<TypeHash>()
+                .is_err()
+        );
+    }
+}
@@ -16,8 +16,10 @@
 // OPSEC #4584.
 
 use crate::error::{to_rcl_result, RclReturnCode, ToResult};
+#[cfg(feature = "fault-injection")]
+use crate::fault_injection::FaultPoint;
 use crate::rcl_bindings::*;
-use crate::{Context, SubscriptionBase};
+use crate::{Context, GuardCondition, SubscriptionBase};
 
 use std::sync::Arc;
 use std::time::Duration;
@@ -29,17 +31,69 @@ use parking_lot::Mutex;
 pub struct WaitSet {
     handle: rcl_wait_set_t,
     // Used to ensure the context is alive while the wait set is alive.
-    _context_handle: Arc<Mutex<rcl_context_t>>,
+    context_handle: Arc<Mutex<rcl_context_t>>,
     // The subscriptions that are currently registered in the wait set.
     // This correspondence is an invariant that must be maintained by all functions,
     // even in the error case.
     subscriptions: Vec<Arc<dyn SubscriptionBase>>,
+    // The waitables that are currently registered in the wait set, with the same invariant.
+    waitables: Vec<Arc<dyn Waitable>>,
 }
 
 /// A list of entities that are ready, returned by [`WaitSet::wait`].
 pub struct ReadyEntities {
     /// A list of subscriptions that have potentially received messages.
     pub subscriptions: Vec<Arc<dyn SubscriptionBase>>,
+    /// A list of waitables whose guard condition has been triggered.
+    pub waitables: Vec<Arc<dyn Waitable>>,
+}
+
+/// An event source of user code that can be spun together with subscriptions.
+///
+/// A waitable owns a [`GuardCondition`], which it triggers when it has work to do, e.g. from a
+/// thread that reads from a serial port or from the sender of an in-process queue. After the
+/// guard condition has woken up the wait set, [`spin_once`][1] calls [`execute`][2] on the
+/// thread that spins. Waitables are added to a node with [`Node::add_waitable`][3].
+///
+/// Since several triggers only wake up the wait set once, `execute()` should process all
+/// pending work, e.g. drain the queue.
+///
+/// Subscriptions are not waitables, and are added to the wait set with their own handle instead
+/// of a guard condition. This lets `spin_once` schedule them: it wakes up for their message
+/// timeouts, takes their queued messages into account, lets them take turns within the budget of
+/// [`SpinOptions`][4] and records their metrics. A waitable is executed once per wake-up and has
+/// none of this state.
+///
+/// # Example
+/// ```ignore
+/// struct SerialPort {
+///     guard_condition: GuardCondition,
+///     lines: Mutex<Receiver<String>>,
+/// }
+///
+/// impl Waitable for SerialPort {
+///     fn guard_condition(&self) -> &GuardCondition {
+///         &self.guard_condition
+///     }
+///
+///     fn execute(&self) -> Result<(), RclReturnCode> {
+///         for line in self.lines.lock().try_iter() {
+///             println!("Received '{}'", line);
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// [1]: crate::spin_once
+/// [2]: Waitable::execute
+/// [3]: crate::Node::add_waitable
+/// [4]: crate::SpinOptions
+pub trait Waitable {
+    /// Returns the guard condition that is triggered when the waitable has work to do.
+    fn guard_condition(&self) -> &GuardCondition;
+    /// Processes the pending work of the waitable.
+    fn execute(&self) -> Result<(), RclReturnCode>;
 }
 
 impl Drop for rcl_wait_set_t {
@@ -58,6 +112,31 @@ impl WaitSet {
     /// The given number of subscriptions is a capacity, corresponding to how often
     /// [`WaitSet::add_subscription`] may be called.
     pub fn new(number_of_subscriptions: usize, context: &Context) -> Result<Self, RclReturnCode> {
+        Self::new_for_context_handle(number_of_subscriptions, 0, &context.handle)
+    }
+
+    /// Creates a new wait set that can also contain waitables.
+    ///
+    /// The given numbers are capacities, corresponding to how often
+    /// [`WaitSet::add_subscription`] and [`WaitSet::add_waitable`] may be called.
+    pub fn new_with_waitables(
+        number_of_subscriptions: usize,
+        number_of_waitables: usize,
+        context: &Context,
+    ) -> Result<Self, RclReturnCode> {
+        Self::new_for_context_handle(
+            number_of_subscriptions,
+            number_of_waitables,
+            &context.handle,
+        )
+    }
+
+    // Creates a wait set for the context of a node, which does not have a `Context` of its own.
+    pub(crate) fn new_for_context_handle(
+        number_of_subscriptions: usize,
+        number_of_waitables: usize,
+        context_handle: &Arc<Mutex<rcl_context_t>>,
+    ) -> Result<Self, RclReturnCode> {
         let rcl_wait_set = unsafe {
             // SAFETY: Getting a zero-initialized value is always safe
             let mut rcl_wait_set = rcl_get_zero_initialized_wait_set();
@@ -66,12 +145,12 @@ impl WaitSet {
             rcl_wait_set_init(
                 &mut rcl_wait_set as *mut _,
                 number_of_subscriptions,
+                number_of_waitables,
                 0,
                 0,
                 0,
                 0,
-                0,
-                &mut *context.handle.lock() as *mut _,
+                &mut *context_handle.lock() as *mut _,
                 rcutils_get_default_allocator(),
             )
             .ok()?;
@@ -79,8 +158,9 @@ impl WaitSet {
         };
         Ok(Self {
             handle: rcl_wait_set,
-            _context_handle: context.handle.clone(),
+            context_handle: context_handle.clone(),
             subscriptions: Vec::new(),
+            waitables: Vec::new(),
         })
     }
 
@@ -90,6 +170,7 @@ impl WaitSet {
     /// [`WaitSet::new`].
     pub fn clear(&mut self) {
         self.subscriptions.clear();
+        self.waitables.clear();
         // This cannot fail – the rcl_wait_set_clear function only checks that the input handle is
         // valid, which it always is in our case. Hence, only debug_assert instead of returning
         // Result.
@@ -107,10 +188,17 @@ impl WaitSet {
     ///
     /// The same subscription must not be added to multiple wait sets, because that would make it
     /// unsafe to simultaneously wait on those wait sets.
+    ///
+    /// This will return [`RclReturnCode::InvalidArgument`] if the subscription belongs to a node
+    /// from a different [`Context`] than the wait set.
     pub fn add_subscription(
         &mut self,
         subscription: Arc<dyn SubscriptionBase>,
     ) -> Result<(), RclReturnCode> {
+        let subscription_context = subscription.handle().node_handle.lock().context;
+        if !std::ptr::eq(subscription_context, &*self.context_handle.lock()) {
+            return Err(RclReturnCode::InvalidArgument);
+        }
         unsafe {
             // SAFETY: I'm not sure if it's required, but the subscription pointer will remain valid
             // for as long as the wait set exists, because it's stored in self.subscriptions.
@@ -126,6 +214,33 @@ impl WaitSet {
         Ok(())
     }
 
+    /// Adds a waitable to the wait set.
+    ///
+    /// This will return an error if the number of waitables in the wait set is larger than the
+    /// capacity set in [`WaitSet::new_with_waitables`].
+    ///
+    /// This will return [`RclReturnCode::InvalidArgument`] if the guard condition of the waitable
+    /// belongs to a different [`Context`] than the wait set.
+    pub fn add_waitable(&mut self, waitable: Arc<dyn Waitable>) -> Result<(), RclReturnCode> {
+        let guard_condition = waitable.guard_condition();
+        if !Arc::ptr_eq(&guard_condition.context_handle, &self.context_handle) {
+            return Err(RclReturnCode::InvalidArgument);
+        }
+        unsafe {
+            // SAFETY: The guard condition pointer will remain valid for as long as the wait set
+            // exists, because the waitable is stored in self.waitables.
+            // Passing in a null pointer for the third argument is explicitly allowed.
+            rcl_wait_set_add_guard_condition(
+                &mut self.handle as *mut _,
+                &*guard_condition.handle.lock() as *const _,
+                std::ptr::null_mut(),
+            )
+        }
+        .ok()?;
+        self.waitables.push(waitable);
+        Ok(())
+    }
+
     /// Blocks until the wait set is ready, or until the timeout has been exceeded.
     ///
     /// If the timeout is `None` then this function will block indefinitely until
@@ -162,9 +277,14 @@ impl WaitSet {
         // We cannot currently guarantee that the wait sets may not share content, but it is
         // mentioned in the doc comment for `add_subscription`.
         // Also, the handle is obviously valid.
+        #[cfg(feature = "fault-injection")]
+        if let Some(error_code) = crate::fault_injection::intercept(FaultPoint::Wait) {
+            return Err(RclReturnCode::from(error_code));
+        }
         unsafe { rcl_wait(&mut self.handle as *mut _, timeout_ns) }.ok()?;
         let mut ready_entities = ReadyEntities {
             subscriptions: Vec::new(),
+            waitables: Vec::new(),
         };
         for (i, subscription) in self.subscriptions.iter().enumerate() {
             // SAFETY: The `subscriptions` entry is an array of pointers, and this dereferencing is
@@ -175,6 +295,13 @@ impl WaitSet {
                 ready_entities.subscriptions.push(subscription.clone());
             }
         }
+        for (i, waitable) in self.waitables.iter().enumerate() {
+            // SAFETY: The `guard_conditions` entry is an array of pointers, like `subscriptions`.
+            let wait_set_entry = unsafe { *self.handle.guard_conditions.add(i) };
+            if !wait_set_entry.is_null() {
+                ready_entities.waitables.push(waitable.clone());
+            }
+        }
         Ok(ready_entities)
     }
 }
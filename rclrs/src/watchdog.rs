@@ -0,0 +1,140 @@
+use crate::spin_options::report_warning;
+use crate::{CallbackEntity, CallbackTimeout, SpinWarning, SpinWarningHandler};
+
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use parking_lot::{const_mutex, Condvar, Mutex};
+
+// The callbacks that are currently running with a timeout, shared by all spin threads.
+static WATCHES: Mutex<Watches> = const_mutex(Watches {
+    next_id: 0,
+    active: Vec::new(),
+});
+// Notified when a watch with a possibly earlier deadline is added.
+static WATCHES_CHANGED: Condvar = Condvar::new();
+static START_WATCHDOG: Once = Once::new();
+
+struct Watches {
+    next_id: u64,
+    active: Vec<Watch>,
+}
+
+struct Watch {
+    id: u64,
+    start: Instant,
+    deadline: Instant,
+    entity: CallbackEntity,
+    handler: Option<SpinWarningHandler>,
+}
+
+/// Reports the callback to the watchdog thread if it is still running after the timeout, i.e.
+/// until the guard is dropped.
+pub(crate) struct WatchGuard {
+    id: u64,
+}
+
+impl WatchGuard {
+    pub(crate) fn new(
+        timeout: Duration,
+        entity: CallbackEntity,
+        handler: Option<SpinWarningHandler>,
+    ) -> Self {
+        START_WATCHDOG.call_once(|| {
+            // Without the thread, timeouts are not reported, which does not affect the callbacks.
+            let _ = std::thread::Builder::new()
+                .name(String::from("rclrs_watchdog"))
+                .spawn(run_watchdog);
+        });
+        let start = Instant::now();
+        let watches = &mut *WATCHES.lock();
+        let id = watches.next_id;
+        watches.next_id += 1;
+        watches.active.push(Watch {
+            id,
+            start,
+            deadline: start + timeout,
+            entity,
+            handler,
+        });
+        WATCHES_CHANGED.notify_one();
+        Self { id }
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        // The watch is already gone if it has been reported.
+        WATCHES.lock().active.retain(|watch| watch.id != self.id);
+    }
+}
+
+// The loop of the watchdog thread, which reports each watch whose deadline has passed once.
+fn run_watchdog() {
+    let mut watches = WATCHES.lock();
+    loop {
+        let now = Instant::now();
+        let (expired, active): (Vec<_>, Vec<_>) = std::mem::take(&mut watches.active)
+            .into_iter()
+            .partition(|watch| watch.deadline <= now);
+        watches.active = active;
+        if !expired.is_empty() {
+            // Do not hold the lock while running user code.
+            drop(watches);
+            expired.into_iter().for_each(|watch| report(watch, now));
+            watches = WATCHES.lock();
+            continue;
+        }
+        match watches.active.iter().map(|watch| watch.deadline).min() {
+            Some(deadline) => {
+                WATCHES_CHANGED.wait_until(&mut watches, deadline);
+            }
+            None => WATCHES_CHANGED.wait(&mut watches),
+        }
+    }
+}
+
+fn report(watch: Watch, now: Instant) {
+    let timeout = CallbackTimeout {
+        entity: watch.entity,
+        elapsed: now - watch.start,
+    };
+    report_warning(
+        watch.handler.as_ref(),
+        SpinWarning::CallbackTimeout(timeout),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_watch_guard() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let handler = SpinWarningHandler::new(move |warning| {
+            if let SpinWarning::CallbackTimeout(timeout) = warning {
+                sender.lock().send(timeout.clone()).unwrap();
+            }
+        });
+        {
+            let _watch = WatchGuard::new(
+                Duration::from_millis(10),
+                CallbackEntity::Waitable,
+                Some(handler.clone()),
+            );
+            let timeout = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(timeout.entity, CallbackEntity::Waitable);
+            assert!(timeout.elapsed >= Duration::from_millis(10));
+        }
+        // A callback that finishes in time is not reported.
+        drop(WatchGuard::new(
+            Duration::from_millis(10),
+            CallbackEntity::Waitable,
+            Some(handler),
+        ));
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+    }
+}
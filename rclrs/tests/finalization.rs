@@ -0,0 +1,83 @@
+//! Entities must be finalized before their node, and nodes before their context, regardless of
+//! the order in which they are dropped.
+//!
+//! All checks are in a single test, since the live handle counts are global to the process.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use rclrs::testing::live_handles;
+use rclrs::{Context, Node, Publisher, Subscription, WaitSet, QOS_PROFILE_DEFAULT};
+use std_msgs::msg::String as StringMsg;
+
+struct Entities {
+    context: Context,
+    node: Node,
+    publisher: Publisher<StringMsg>,
+    subscription: Arc<Subscription<StringMsg>>,
+}
+
+fn create_entities() -> Entities {
+    let context = Context::new([]).unwrap();
+    let mut node = Node::new("finalization", &context).unwrap();
+    let publisher = node
+        .create_publisher("chatter", QOS_PROFILE_DEFAULT)
+        .unwrap();
+    let subscription = node
+        .create_subscription("chatter", QOS_PROFILE_DEFAULT, |_: StringMsg| {})
+        .unwrap();
+    Entities {
+        context,
+        node,
+        publisher,
+        subscription,
+    }
+}
+
+#[test]
+fn test_drop_order_does_not_matter() {
+    let before = live_handles();
+
+    // Context first, entities last
+    let entities = create_entities();
+    assert_eq!(live_handles().nodes, before.nodes + 1);
+    assert_eq!(live_handles().publishers, before.publishers + 1);
+    assert_eq!(live_handles().subscriptions, before.subscriptions + 1);
+    drop(entities.context);
+    drop(entities.node);
+    assert_eq!(live_handles().nodes, before.nodes + 1);
+    drop(entities.subscription);
+    drop(entities.publisher);
+    assert_eq!(live_handles(), before);
+
+    // Entities first, context last
+    let entities = create_entities();
+    drop(entities.publisher);
+    drop(entities.subscription);
+    drop(entities.node);
+    drop(entities.context);
+    assert_eq!(live_handles(), before);
+
+    // A wait set keeps the subscription alive after everything else has been dropped
+    let entities = create_entities();
+    let mut wait_set = WaitSet::new(1, &entities.context).unwrap();
+    wait_set
+        .add_subscription(entities.subscription.clone())
+        .unwrap();
+    drop(entities);
+    assert_eq!(live_handles().subscriptions, before.subscriptions + 1);
+    drop(wait_set);
+    assert_eq!(live_handles(), before);
+
+    // Unwinding drops the local variables in reverse order, i.e. the context first
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let entities = create_entities();
+        let _subscription = entities.subscription;
+        let _publisher = entities.publisher;
+        let _node = entities.node;
+        let _context = entities.context;
+        panic!("Unwinding with live entities");
+    }));
+    assert!(result.is_err());
+    assert_eq!(live_handles(), before);
+}
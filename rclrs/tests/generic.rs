@@ -0,0 +1,90 @@
+//! Forwarding a message whose type is looked up by name, between typed publishers and
+//! subscriptions.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rclrs::testing::TestFixture;
+use rclrs::{
+    GenericPublisher, GenericSubscription, Publisher, RclReturnCode, SerializedMessage,
+    Subscription, QOS_PROFILE_DEFAULT,
+};
+use std_msgs::msg::String as StringMsg;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn test_unknown_type_is_rejected() {
+    let fixture = TestFixture::new("generic_unknown").unwrap();
+    let result = GenericPublisher::new(
+        &fixture.publisher_node,
+        "unknown",
+        "no_msgs/msg/Nothing",
+        QOS_PROFILE_DEFAULT,
+    );
+    assert!(matches!(result, Err(RclReturnCode::InvalidArgument)));
+}
+
+#[test]
+fn test_generic_round_trip() {
+    let mut fixture = TestFixture::new("generic_round_trip").unwrap();
+    let taken: Arc<Mutex<Option<Vec<u8>>>> = Arc::new(Mutex::new(None));
+    let taken_in_callback = Arc::clone(&taken);
+    let generic_subscription = GenericSubscription::new(
+        &mut fixture.subscriber_node,
+        "generic_in",
+        "std_msgs/msg/String",
+        QOS_PROFILE_DEFAULT,
+        move |message: &SerializedMessage| {
+            *taken_in_callback.lock().unwrap() = Some(message.as_bytes().to_vec());
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert_eq!(generic_subscription.type_name(), "std_msgs/msg/String");
+    let received: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+    let received_in_callback = Arc::clone(&received);
+    let _subscription: Arc<Subscription<StringMsg>> = fixture
+        .subscriber_node
+        .create_subscription("generic_out", QOS_PROFILE_DEFAULT, move |msg: StringMsg| {
+            *received_in_callback.lock().unwrap() = Some(msg.data);
+        })
+        .unwrap();
+
+    let publisher: Publisher<StringMsg> =
+        Publisher::new(&fixture.publisher_node, "generic_in", QOS_PROFILE_DEFAULT).unwrap();
+    let generic_publisher = GenericPublisher::new(
+        &fixture.publisher_node,
+        "generic_out",
+        "std_msgs/msg/String",
+        QOS_PROFILE_DEFAULT,
+    )
+    .unwrap();
+    publisher.wait_for_subscribers(1, Some(TIMEOUT)).unwrap();
+    publisher
+        .publish(StringMsg {
+            data: String::from("forwarded"),
+        })
+        .unwrap();
+
+    // The bytes taken by the generic subscription are published as they are.
+    let deadline = Instant::now() + TIMEOUT;
+    let mut forwarded = false;
+    while received.lock().unwrap().is_none() {
+        assert!(Instant::now() < deadline, "the message was not forwarded");
+        let result = rclrs::spin_once(&fixture.subscriber_node, Some(Duration::from_millis(10)));
+        assert!(matches!(result, Ok(()) | Err(RclReturnCode::Timeout)));
+        if !forwarded {
+            if let Some(bytes) = taken.lock().unwrap().take() {
+                while generic_publisher.get_subscription_count().unwrap() == 0 {
+                    assert!(Instant::now() < deadline, "no subscription was matched");
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                let message = SerializedMessage::from_bytes(&bytes).unwrap();
+                generic_publisher.publish(&message).unwrap();
+                forwarded = true;
+            }
+        }
+    }
+    assert_eq!(received.lock().unwrap().as_deref(), Some("forwarded"));
+}
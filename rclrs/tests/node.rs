@@ -0,0 +1,55 @@
+//! Names of nodes and their discovery in the ROS graph.
+
+use std::time::{Duration, Instant};
+
+use rclrs::testing::TestFixture;
+use rclrs::{Context, Node};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn test_node_names() {
+    let context = Context::new([]).unwrap();
+    let node = Node::new_with_namespace("named", "my_ns", &context).unwrap();
+    assert_eq!(node.name(), "named");
+    // The missing leading slash is added.
+    assert_eq!(node.namespace(), "/my_ns");
+    assert_eq!(node.fully_qualified_name(), "/my_ns/named");
+
+    let node = Node::new("unnamespaced", &context).unwrap();
+    assert_eq!(node.namespace(), "/");
+    assert_eq!(node.fully_qualified_name(), "/unnamespaced");
+}
+
+#[test]
+fn test_node_names_are_remapped() {
+    let args = ["--ros-args", "-r", "__node:=remapped"].map(String::from);
+    let context = Context::new(args).unwrap();
+    let node = Node::new("original", &context).unwrap();
+    assert_eq!(node.name(), "remapped");
+    assert_eq!(node.fully_qualified_name(), "/remapped");
+}
+
+#[test]
+fn test_get_node_names_with_namespaces_contains_other_node() {
+    let fixture = TestFixture::new("node_names").unwrap();
+    let expected = ("subscriber".to_string(), fixture.namespace().to_string());
+    // Discovery is asynchronous, so the other node might not be listed immediately.
+    let start = Instant::now();
+    loop {
+        let names = fixture
+            .publisher_node
+            .get_node_names_with_namespaces()
+            .unwrap();
+        if names.contains(&expected) {
+            break;
+        }
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "{:?} not in {:?}",
+            expected,
+            names
+        );
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,87 @@
+//! Propagation of provenance IDs through a chain of callbacks within one process.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rclrs::testing::TestFixture;
+use rclrs::{
+    current_provenance, with_provenance, ProvenanceId, Publisher, RclReturnCode,
+    QOS_PROFILE_DEFAULT,
+};
+use std_msgs::msg::Float64;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Spins the node until the given number of provenances has been received.
+fn spin_until_received(
+    fixture: &TestFixture,
+    received: &Mutex<Vec<Option<ProvenanceId>>>,
+    count: usize,
+) {
+    let start = Instant::now();
+    while received.lock().unwrap().len() < count {
+        assert!(start.elapsed() < TIMEOUT, "Timed out waiting for messages");
+        match rclrs::spin_once(&fixture.subscriber_node, Some(Duration::from_millis(100))) {
+            Ok(()) | Err(RclReturnCode::Timeout) => {}
+            Err(e) => panic!("Spinning failed: {}", e),
+        }
+    }
+}
+
+#[test]
+fn test_provenance_is_propagated_through_relay() {
+    let mut fixture = TestFixture::new("provenance_relay").unwrap();
+    let input_publisher: Publisher<Float64> =
+        Publisher::new(&fixture.publisher_node, "input", QOS_PROFILE_DEFAULT).unwrap();
+    let relay_publisher: Publisher<Float64> =
+        Publisher::new(&fixture.subscriber_node, "output", QOS_PROFILE_DEFAULT).unwrap();
+
+    // The relay sees the provenance of the input, and publishes with it.
+    let relayed = Arc::new(Mutex::new(Vec::new()));
+    let relayed_in_callback = Arc::clone(&relayed);
+    let _relay = fixture
+        .subscriber_node
+        .create_subscription("input", QOS_PROFILE_DEFAULT, move |msg: Float64| {
+            relayed_in_callback
+                .lock()
+                .unwrap()
+                .push(current_provenance());
+            relay_publisher.publish(msg).unwrap();
+        })
+        .unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_in_callback = Arc::clone(&received);
+    let _output = fixture
+        .subscriber_node
+        .create_subscription("output", QOS_PROFILE_DEFAULT, move |_: Float64| {
+            received_in_callback
+                .lock()
+                .unwrap()
+                .push(current_provenance());
+        })
+        .unwrap();
+    input_publisher
+        .wait_for_subscribers(1, Some(TIMEOUT))
+        .unwrap();
+
+    let provenance = ProvenanceId::from_raw(42);
+    with_provenance(provenance, || {
+        input_publisher.publish(Float64 { data: 1.0 })
+    })
+    .unwrap();
+    spin_until_received(&fixture, &received, 1);
+    assert_eq!(*relayed.lock().unwrap(), [Some(provenance)]);
+    assert_eq!(*received.lock().unwrap(), [Some(provenance)]);
+    // The provenance is only current while the callbacks run.
+    assert_eq!(current_provenance(), None);
+
+    // A message without a provenance starts a new chain, which is also carried on.
+    input_publisher.publish(Float64 { data: 2.0 }).unwrap();
+    spin_until_received(&fixture, &received, 2);
+    let relayed = relayed.lock().unwrap();
+    let received = received.lock().unwrap();
+    let new_chain = relayed[1];
+    assert!(new_chain.is_some());
+    assert_ne!(new_chain, Some(provenance));
+    assert_eq!(received[1], new_chain);
+}
@@ -0,0 +1,113 @@
+//! Behaviour of the publisher wrappers when their messages are received.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rclrs::testing::TestFixture;
+use rclrs::{
+    LatchedPublisher, Publisher, PublisherWithBuffer, QoSProfile, QoSReliabilityPolicy,
+    QOS_PROFILE_DEFAULT, QOS_PROFILE_SYSTEM_DEFAULT,
+};
+use std_msgs::msg::Int32;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn test_publisher_with_buffer_publishes_modified_buffer() {
+    let mut fixture = TestFixture::new("publisher_with_buffer").unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let callback_received = Arc::clone(&received);
+    let _subscription = fixture
+        .subscriber_node
+        .create_subscription(
+            "buffered",
+            QOS_PROFILE_DEFAULT,
+            move |msg: std_msgs::msg::String| callback_received.lock().unwrap().push(msg.data),
+        )
+        .unwrap();
+    let mut publisher = PublisherWithBuffer::<std_msgs::msg::String>::new(
+        &fixture.publisher_node,
+        "buffered",
+        QOS_PROFILE_DEFAULT,
+    )
+    .unwrap();
+    publisher
+        .publisher()
+        .wait_for_subscribers(1, Some(TIMEOUT))
+        .unwrap();
+
+    publisher.buffer_mut().data = "first".into();
+    publisher.publish().unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+
+    // Publishing leaves the buffer unchanged, and a modification is sent on the next publish.
+    assert_eq!(publisher.buffer().data.to_string(), "first");
+    publisher.buffer_mut().data = "second".into();
+    publisher.publish().unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+
+    assert_eq!(*received.lock().unwrap(), ["first", "second"]);
+}
+
+#[test]
+fn test_latched_publisher_delivers_last_message_to_late_subscriber() {
+    let fixture = TestFixture::new("latched_late_subscriber").unwrap();
+    let publisher = LatchedPublisher::new(&fixture.publisher_node, "latched").unwrap();
+    publisher.publish(Int32 { data: 1 }).unwrap();
+    publisher.publish(Int32 { data: 2 }).unwrap();
+
+    // The subscription is created after publishing, and only receives the last message.
+    let msg: Int32 = fixture
+        .wait_for_message("latched", QoSProfile::transient_local_latched(1), TIMEOUT)
+        .unwrap();
+    assert_eq!(msg.data, 2);
+    assert_eq!(publisher.last_message(), Some(Int32 { data: 2 }));
+}
+
+#[test]
+fn test_latched_publisher_republishes_to_late_volatile_subscriber() {
+    let mut fixture = TestFixture::new("latched_republish").unwrap();
+    let publisher = LatchedPublisher::new(&fixture.publisher_node, "latched").unwrap();
+    // Nothing has been published yet, so there is nothing to republish.
+    assert!(!publisher.republish_to_new_subscribers().unwrap());
+    publisher.publish(Int32 { data: 3 }).unwrap();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let callback_received = Arc::clone(&received);
+    let _subscription = fixture
+        .subscriber_node
+        .create_subscription("latched", QOS_PROFILE_DEFAULT, move |msg: Int32| {
+            callback_received.lock().unwrap().push(msg.data)
+        })
+        .unwrap();
+    publisher
+        .publisher()
+        .wait_for_subscribers(1, Some(TIMEOUT))
+        .unwrap();
+
+    // The volatile subscription did not exist when the message was published, so it only
+    // receives the message that is published again.
+    assert!(publisher.republish_to_new_subscribers().unwrap());
+    assert!(!publisher.republish_to_new_subscribers().unwrap());
+    std::thread::sleep(Duration::from_millis(500));
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert_eq!(*received.lock().unwrap(), [3]);
+}
+
+#[test]
+fn test_actual_qos_replaces_system_defaults() {
+    let fixture = TestFixture::new("publisher_actual_qos").unwrap();
+    let publisher: Publisher<Int32> =
+        Publisher::new(&fixture.publisher_node, "qos", QOS_PROFILE_DEFAULT).unwrap();
+    let actual_qos = publisher.actual_qos().unwrap();
+    assert_eq!(actual_qos.history, QOS_PROFILE_DEFAULT.history);
+    assert_eq!(actual_qos.reliability, QOS_PROFILE_DEFAULT.reliability);
+    assert_eq!(actual_qos.durability, QOS_PROFILE_DEFAULT.durability);
+
+    let publisher: Publisher<Int32> =
+        Publisher::new(&fixture.publisher_node, "qos", QOS_PROFILE_SYSTEM_DEFAULT).unwrap();
+    let actual_qos = publisher.actual_qos().unwrap();
+    assert_ne!(actual_qos.reliability, QoSReliabilityPolicy::SystemDefault);
+}
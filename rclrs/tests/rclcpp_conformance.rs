@@ -0,0 +1,93 @@
+//! Round trips of messages between `rclrs` and `rclcpp`.
+//!
+//! These tests need the `topic_tools` and `demo_nodes_cpp` packages, so they are ignored by
+//! default. Run them with `cargo test --test rclcpp_conformance -- --ignored`.
+
+use std::time::Duration;
+
+use rclrs::testing::{assert_rclcpp_round_trip, ExternalNode, TestFixture};
+use rclrs::{Publisher, QOS_PROFILE_DEFAULT};
+use rclrs_example_msgs::msg::{NestedType, VariousTypes};
+use rosidl_runtime_rs::{seq, BoundedSequence};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+#[ignore = "needs the topic_tools package"]
+fn test_default_field_values_round_trip() {
+    let fixture = TestFixture::new("conformance_defaults").unwrap();
+    assert_rclcpp_round_trip(&fixture, "various_types", &VariousTypes::default(), TIMEOUT);
+}
+
+#[test]
+#[ignore = "needs the topic_tools package"]
+fn test_field_types_round_trip() {
+    let fixture = TestFixture::new("conformance_fields").unwrap();
+    let message = VariousTypes {
+        bool_member: false,
+        int8_member: i8::MIN,
+        uint8_member: u8::MAX,
+        float32_member: f32::MAX,
+        // Bounded sequences at their maximum size
+        float_seq_bounded: seq![3 # 1.0, 2.0, 3.0],
+        float_seq_unbounded: (0..100).map(|i| i as f32).collect(),
+        string_member: String::from("rclrs ↔ rclcpp"),
+        wstring_member: String::from("ταξίδι μετ' επιστροφής"),
+        nested_seq_unbounded: vec![
+            NestedType {
+                effect: String::from("round")
+            };
+            3
+        ],
+        ..Default::default()
+    };
+    assert_rclcpp_round_trip(&fixture, "various_types", &message, TIMEOUT);
+}
+
+#[test]
+#[ignore = "needs the topic_tools package"]
+fn test_large_payload_round_trip() {
+    let fixture = TestFixture::new("conformance_large").unwrap();
+    let message = std_msgs::msg::String {
+        data: "x".repeat(4 << 20),
+    };
+    assert_rclcpp_round_trip(&fixture, "large_payload", &message, TIMEOUT);
+}
+
+#[test]
+#[ignore = "needs the demo_nodes_cpp package"]
+fn test_receive_from_rclcpp_talker() {
+    let fixture = TestFixture::new("conformance_talker").unwrap();
+    let _talker =
+        ExternalNode::spawn("demo_nodes_cpp", "talker", fixture.namespace(), &[]).unwrap();
+    let message: std_msgs::msg::String = fixture
+        .wait_for_message("chatter", QOS_PROFILE_DEFAULT, TIMEOUT)
+        .unwrap();
+    assert!(message.data.starts_with("Hello World: "));
+}
+
+#[test]
+#[ignore = "needs the demo_nodes_cpp package"]
+fn test_send_to_rclcpp_listener() {
+    let fixture = TestFixture::new("conformance_listener").unwrap();
+    let listener =
+        ExternalNode::spawn("demo_nodes_cpp", "listener", fixture.namespace(), &[]).unwrap();
+    let publisher = Publisher::<std_msgs::msg::String>::new(
+        &fixture.publisher_node,
+        "chatter",
+        QOS_PROFILE_DEFAULT,
+    )
+    .unwrap();
+    let message = std_msgs::msg::String {
+        data: String::from("Hello from rclrs"),
+    };
+    // Publish until the listener has been discovered and prints the message.
+    let heard = (0..TIMEOUT.as_secs() * 10).find_map(|_| {
+        publisher.publish(&message).unwrap();
+        listener.wait_for_output("I heard: [Hello from rclrs]", Duration::from_millis(100))
+    });
+    assert!(
+        heard.is_some(),
+        "The rclcpp listener did not receive the message"
+    );
+}
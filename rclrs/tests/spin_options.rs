@@ -0,0 +1,135 @@
+//! Execution order and warnings of spinning with several ready subscriptions.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rclrs::testing::TestFixture;
+use rclrs::{
+    Publisher, SpinOptions, SpinWarning, SpinWarningHandler, Subscription, QOS_PROFILE_DEFAULT,
+};
+use std_msgs::msg::Int32;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Subscriptions that record their callbacks, and a publisher for each of them.
+struct Recorder {
+    _subscriptions: Vec<Arc<Subscription<Int32>>>,
+    publishers: Vec<Publisher<Int32>>,
+    executions: Arc<Mutex<Vec<(&'static str, i32)>>>,
+}
+
+impl Recorder {
+    // Creates a subscription on each topic, in this order. The callbacks record the topic and
+    // the message, and then take the given time.
+    fn new(fixture: &mut TestFixture, topics: &[&'static str], callback_time: Duration) -> Self {
+        let executions = Arc::new(Mutex::new(Vec::new()));
+        let mut subscriptions = Vec::new();
+        let mut publishers = Vec::new();
+        for &topic in topics {
+            let executions = Arc::clone(&executions);
+            subscriptions.push(
+                fixture
+                    .subscriber_node
+                    .create_subscription(topic, QOS_PROFILE_DEFAULT, move |msg: Int32| {
+                        executions.lock().unwrap().push((topic, msg.data));
+                        std::thread::sleep(callback_time);
+                    })
+                    .unwrap(),
+            );
+            let publisher: Publisher<Int32> =
+                Publisher::new(&fixture.publisher_node, topic, QOS_PROFILE_DEFAULT).unwrap();
+            publisher.wait_for_subscribers(1, Some(TIMEOUT)).unwrap();
+            publishers.push(publisher);
+        }
+        Self {
+            _subscriptions: subscriptions,
+            publishers,
+            executions,
+        }
+    }
+
+    // Publishes a message on each of the topics with the given indices.
+    fn publish(&self, topic_indices: &[usize], data: i32) {
+        for &index in topic_indices {
+            self.publishers[index].publish(Int32 { data }).unwrap();
+        }
+        // Give the middleware time to deliver the messages, so that they are ready in one spin.
+        std::thread::sleep(Duration::from_millis(500));
+    }
+
+    fn take_executions(&self) -> Vec<(&'static str, i32)> {
+        std::mem::take(&mut *self.executions.lock().unwrap())
+    }
+}
+
+#[test]
+fn test_budget_per_entity_takes_turns() {
+    let mut fixture = TestFixture::new("spin_budget").unwrap();
+    let recorder = Recorder::new(&mut fixture, &["first", "second"], Duration::ZERO);
+    for data in 0..3 {
+        recorder.publish(&[0, 1], data);
+    }
+    let options = SpinOptions {
+        budget_per_entity: 2,
+        ..Default::default()
+    };
+
+    // Each subscription processes up to two messages, taking turns with the other one.
+    rclrs::spin_once_with_options(&fixture.subscriber_node, Some(TIMEOUT), &options).unwrap();
+    assert_eq!(
+        recorder.take_executions(),
+        [("first", 0), ("second", 0), ("first", 1), ("second", 1)]
+    );
+
+    // The default budget processes one message per subscription.
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert_eq!(recorder.take_executions(), [("first", 2), ("second", 2)]);
+}
+
+#[test]
+fn test_least_recently_executed_subscription_runs_first() {
+    let mut fixture = TestFixture::new("spin_round_robin").unwrap();
+    let recorder = Recorder::new(&mut fixture, &["first", "second"], Duration::ZERO);
+    recorder.publish(&[0, 1], 0);
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert_eq!(recorder.take_executions(), [("first", 0), ("second", 0)]);
+    recorder.publish(&[0], 1);
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert_eq!(recorder.take_executions(), [("first", 1)]);
+
+    // The second subscription has waited longer, so it runs first.
+    recorder.publish(&[0, 1], 2);
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert_eq!(recorder.take_executions(), [("second", 2), ("first", 2)]);
+}
+
+#[test]
+fn test_starvation_warning_is_passed_to_handler() {
+    let mut fixture = TestFixture::new("spin_starvation").unwrap();
+    // The second subscription has to wait for the slow callback of the first one.
+    let recorder = Recorder::new(
+        &mut fixture,
+        &["first", "second"],
+        Duration::from_millis(100),
+    );
+    recorder.publish(&[0, 1], 0);
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let handler_warnings = Arc::clone(&warnings);
+    let options = SpinOptions {
+        starvation_warning_threshold: Some(Duration::from_millis(50)),
+        warning_handler: Some(SpinWarningHandler::new(move |warning| {
+            handler_warnings.lock().unwrap().push(warning.clone());
+        })),
+        ..Default::default()
+    };
+
+    rclrs::spin_once_with_options(&fixture.subscriber_node, Some(TIMEOUT), &options).unwrap();
+    assert_eq!(recorder.take_executions(), [("first", 0), ("second", 0)]);
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(
+        &warnings[0],
+        SpinWarning::Starvation { topic_name, wait_time }
+            if topic_name.ends_with("/second") && *wait_time >= Duration::from_millis(100)
+    ));
+}
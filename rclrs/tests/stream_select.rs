@@ -0,0 +1,76 @@
+//! Multiplexing several subscriptions and a timer in a single async task with `select!`, while
+//! spinning the node in the same task.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::{select, StreamExt};
+use rclrs::testing::TestFixture;
+use rclrs::{Publisher, RclReturnCode, Subscription, QOS_PROFILE_DEFAULT};
+use std_msgs::msg::{Float64, String as StringMsg};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn test_select_over_topics_and_timer() {
+    let mut fixture = TestFixture::new("stream_select").unwrap();
+    // The streams are woken up by spinning the node, so the subscriptions must belong to it.
+    let names: Arc<Subscription<StringMsg>> = fixture
+        .subscriber_node
+        .create_subscription("names", QOS_PROFILE_DEFAULT, |_| {})
+        .unwrap();
+    let values: Arc<Subscription<Float64>> = fixture
+        .subscriber_node
+        .create_subscription("values", QOS_PROFILE_DEFAULT, |_| {})
+        .unwrap();
+    let names_publisher: Publisher<StringMsg> =
+        Publisher::new(&fixture.publisher_node, "names", QOS_PROFILE_DEFAULT).unwrap();
+    let values_publisher: Publisher<Float64> =
+        Publisher::new(&fixture.publisher_node, "values", QOS_PROFILE_DEFAULT).unwrap();
+    names_publisher
+        .wait_for_subscribers(1, Some(TIMEOUT))
+        .unwrap();
+    values_publisher
+        .wait_for_subscribers(1, Some(TIMEOUT))
+        .unwrap();
+
+    names_publisher
+        .publish(StringMsg {
+            data: String::from("speed"),
+        })
+        .unwrap();
+    values_publisher.publish(Float64 { data: 4.2 }).unwrap();
+
+    // A timer of the async runtime, here a channel that is fed by a thread
+    let (mut tick_sender, ticks) = mpsc::channel(1);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_millis(50));
+        if tick_sender.try_send(()).is_err() && tick_sender.is_closed() {
+            break;
+        }
+    });
+
+    let (name, value, tick_count) = block_on(async {
+        let mut names = names.stream();
+        let mut values = values.stream();
+        let mut ticks = ticks.fuse();
+        let (mut name, mut value, mut tick_count) = (None, None, 0);
+        while name.is_none() || value.is_none() || tick_count == 0 {
+            select! {
+                message = names.next() => name = Some(message.unwrap().unwrap().data),
+                message = values.next() => value = Some(message.unwrap().unwrap().data),
+                _ = ticks.next() => {
+                    tick_count += 1;
+                    let result = rclrs::spin_once(&fixture.subscriber_node, Some(Duration::ZERO));
+                    assert!(matches!(result, Ok(()) | Err(RclReturnCode::Timeout)));
+                }
+            }
+        }
+        (name, value, tick_count)
+    });
+    assert_eq!(name.as_deref(), Some("speed"));
+    assert_eq!(value, Some(4.2));
+    assert!(tick_count > 0);
+}
@@ -0,0 +1,311 @@
+//! Behaviour of the subscription options and callbacks when spinning.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use rclrs::testing::TestFixture;
+use rclrs::{
+    Publisher, QoSDurabilityPolicy, QoSLivelinessPolicy, QueueOverflowPolicy, RclReturnCode,
+    RmwSpecificPayload, Subscription, SubscriptionOptions, QOS_PROFILE_DEFAULT,
+    QOS_PROFILE_SENSOR_DATA,
+};
+use std_msgs::msg::Int32;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// A subscription that records the data of the messages passed to its callback, and a publisher
+// that is matched with it.
+struct Recorder {
+    subscription: Arc<Subscription<Int32>>,
+    publisher: Publisher<Int32>,
+    received: Arc<Mutex<Vec<i32>>>,
+}
+
+impl Recorder {
+    fn new(fixture: &mut TestFixture, topic: &str, options: SubscriptionOptions) -> Self {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let callback_received = Arc::clone(&received);
+        let subscription = fixture
+            .subscriber_node
+            .create_subscription_with_options(
+                topic,
+                QOS_PROFILE_DEFAULT,
+                options,
+                move |msg: Int32| callback_received.lock().unwrap().push(msg.data),
+            )
+            .unwrap();
+        let publisher =
+            Publisher::new(&fixture.publisher_node, topic, QOS_PROFILE_DEFAULT).unwrap();
+        publisher.wait_for_subscribers(1, Some(TIMEOUT)).unwrap();
+        Self {
+            subscription,
+            publisher,
+            received,
+        }
+    }
+
+    // Publishes the messages in this order.
+    fn publish(&self, data: &[i32]) {
+        for &data in data {
+            self.publisher.publish(Int32 { data }).unwrap();
+        }
+        // Give the middleware time to deliver the messages, so that they are ready in one spin.
+        std::thread::sleep(Duration::from_millis(500));
+    }
+
+    // Spins until the callback has received the given number of messages in total.
+    fn spin_until_received(&self, fixture: &TestFixture, count: usize) {
+        while self.received.lock().unwrap().len() < count {
+            rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+        }
+    }
+
+    // Spins until there is nothing left to process.
+    fn spin_until_idle(&self, fixture: &TestFixture) {
+        loop {
+            match rclrs::spin_once(&fixture.subscriber_node, Some(Duration::from_millis(500))) {
+                Ok(()) => {}
+                Err(RclReturnCode::Timeout) => return,
+                Err(e) => panic!("Spinning failed: {:?}", e),
+            }
+        }
+    }
+
+    fn take_received(&self) -> Vec<i32> {
+        std::mem::take(&mut *self.received.lock().unwrap())
+    }
+}
+
+#[test]
+fn test_keep_latest_only_skips_older_messages() {
+    let mut fixture = TestFixture::new("keep_latest_only").unwrap();
+    let options = SubscriptionOptions {
+        keep_latest_only: true,
+        ..Default::default()
+    };
+    let recorder = Recorder::new(&mut fixture, "latest", options);
+    recorder.publish(&[1, 2, 3]);
+
+    // The callback runs once, with the newest message.
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert_eq!(recorder.take_received(), [3]);
+    assert!(recorder.subscription.take().is_err());
+}
+
+#[test]
+fn test_actual_qos_keeps_requested_policies() {
+    let mut fixture = TestFixture::new("subscription_actual_qos").unwrap();
+    let subscription = fixture
+        .subscriber_node
+        .create_subscription("qos", QOS_PROFILE_SENSOR_DATA, |_: Int32| {})
+        .unwrap();
+    let actual_qos = subscription.actual_qos().unwrap();
+    assert_eq!(actual_qos.history, QOS_PROFILE_SENSOR_DATA.history);
+    assert_eq!(actual_qos.reliability, QOS_PROFILE_SENSOR_DATA.reliability);
+    assert_eq!(actual_qos.durability, QoSDurabilityPolicy::Volatile);
+    // The liveliness policy was requested as a system default.
+    assert_ne!(actual_qos.liveliness, QoSLivelinessPolicy::SystemDefault);
+}
+
+#[test]
+fn test_history_keeps_newest_messages() {
+    let mut fixture = TestFixture::new("history_depth").unwrap();
+    let options = SubscriptionOptions {
+        history_depth: 2,
+        ..Default::default()
+    };
+    let recorder = Recorder::new(&mut fixture, "history", options);
+    assert!(recorder.subscription.history().is_empty());
+    recorder.publish(&[1, 2, 3]);
+    recorder.spin_until_received(&fixture, 3);
+
+    // Only the last two messages are kept, oldest first.
+    let history = recorder.subscription.history();
+    let data: Vec<i32> = history.iter().map(|entry| entry.message.data).collect();
+    assert_eq!(data, [2, 3]);
+    assert!(history[0].received_at <= history[1].received_at);
+}
+
+#[test]
+fn test_history_is_disabled_by_default() {
+    let mut fixture = TestFixture::new("history_disabled").unwrap();
+    let recorder = Recorder::new(&mut fixture, "history", SubscriptionOptions::default());
+    recorder.publish(&[1]);
+    recorder.spin_until_received(&fixture, 1);
+    assert!(recorder.subscription.history().is_empty());
+}
+
+#[test]
+fn test_message_timeout_fires_without_messages() {
+    let mut fixture = TestFixture::new("message_timeout").unwrap();
+    let recorder = Recorder::new(&mut fixture, "timeout", SubscriptionOptions::default());
+    let timeouts = Arc::new(AtomicUsize::new(0));
+    let callback_timeouts = Arc::clone(&timeouts);
+    let timeouts_after_message = Arc::clone(&timeouts);
+    recorder
+        .subscription
+        .set_message_timeout(Duration::from_millis(200), move || {
+            callback_timeouts.fetch_add(1, Ordering::SeqCst);
+        });
+
+    // Spinning wakes up for the timeout instead of waiting for the full spin timeout.
+    let start = Instant::now();
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert!(start.elapsed() < TIMEOUT);
+    assert_eq!(timeouts.load(Ordering::SeqCst), 1);
+
+    // A received message restarts the timeout, so it has not expired one second after it was
+    // set, but less than one second after the message.
+    recorder
+        .subscription
+        .set_message_timeout(Duration::from_secs(1), move || {
+            timeouts_after_message.fetch_add(1, Ordering::SeqCst);
+        });
+    recorder.publish(&[1]);
+    recorder.spin_until_received(&fixture, 1);
+    std::thread::sleep(Duration::from_millis(700));
+    assert!(matches!(
+        rclrs::spin_once(&fixture.subscriber_node, Some(Duration::ZERO)),
+        Err(RclReturnCode::Timeout)
+    ));
+    assert_eq!(timeouts.load(Ordering::SeqCst), 1);
+
+    // After clearing the timeout, spinning without messages times out as usual.
+    recorder.subscription.clear_message_timeout();
+    assert!(matches!(
+        rclrs::spin_once(&fixture.subscriber_node, Some(Duration::from_millis(500))),
+        Err(RclReturnCode::Timeout)
+    ));
+    assert_eq!(timeouts.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_callback_can_use_its_own_subscription() {
+    let mut fixture = TestFixture::new("callback_uses_subscription").unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let callback_received = Arc::clone(&received);
+    let own_subscription: Arc<Mutex<Weak<Subscription<Int32>>>> = Arc::default();
+    let callback_subscription = Arc::clone(&own_subscription);
+    let options = SubscriptionOptions {
+        history_depth: 1,
+        ..Default::default()
+    };
+    let subscription = fixture
+        .subscriber_node
+        .create_subscription_with_options(
+            "own_subscription",
+            QOS_PROFILE_DEFAULT,
+            options,
+            move |msg: Int32| {
+                let subscription = callback_subscription.lock().unwrap().upgrade().unwrap();
+                let received = &mut *callback_received.lock().unwrap();
+                received.push(msg.data);
+                assert_eq!(subscription.history().last().unwrap().message, msg);
+                // Taking the remaining messages does not deadlock while the callback runs.
+                while let Ok(msg) = subscription.take() {
+                    received.push(msg.data);
+                }
+            },
+        )
+        .unwrap();
+    *own_subscription.lock().unwrap() = Arc::downgrade(&subscription);
+    let publisher: Publisher<Int32> = Publisher::new(
+        &fixture.publisher_node,
+        "own_subscription",
+        QOS_PROFILE_DEFAULT,
+    )
+    .unwrap();
+    publisher.wait_for_subscribers(1, Some(TIMEOUT)).unwrap();
+    for data in [1, 2, 3] {
+        publisher.publish(Int32 { data }).unwrap();
+    }
+    std::thread::sleep(Duration::from_millis(500));
+
+    rclrs::spin_once(&fixture.subscriber_node, Some(TIMEOUT)).unwrap();
+    assert_eq!(*received.lock().unwrap(), [1, 2, 3]);
+}
+
+// Publishes four messages to a subscription with a queue of capacity 2, and returns the messages
+// that are passed to the callback.
+fn receive_with_queue_overflow_policy(name: &str, policy: QueueOverflowPolicy) -> Vec<i32> {
+    let mut fixture = TestFixture::new(name).unwrap();
+    let options = SubscriptionOptions {
+        queue_capacity: 2,
+        queue_overflow_policy: policy,
+        ..Default::default()
+    };
+    let recorder = Recorder::new(&mut fixture, "queue", options);
+    recorder.publish(&[1, 2, 3, 4]);
+    recorder.spin_until_idle(&fixture);
+    recorder.take_received()
+}
+
+#[test]
+fn test_queue_overflow_policies() {
+    assert_eq!(
+        receive_with_queue_overflow_policy("queue_drop_oldest", QueueOverflowPolicy::DropOldest),
+        [3, 4]
+    );
+    assert_eq!(
+        receive_with_queue_overflow_policy("queue_drop_newest", QueueOverflowPolicy::DropNewest),
+        [1, 2]
+    );
+    // The messages that do not fit into the queue stay in the middleware until there is room.
+    assert_eq!(
+        receive_with_queue_overflow_policy("queue_block", QueueOverflowPolicy::Block),
+        [1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_set_callback_from_running_callback() {
+    let mut fixture = TestFixture::new("set_callback").unwrap();
+    let recorder = Recorder::new(&mut fixture, "set_callback", SubscriptionOptions::default());
+    let subscription = Arc::downgrade(&recorder.subscription);
+    let received = Arc::clone(&recorder.received);
+    // Captured by the first callback, to check that it is dropped after being replaced.
+    let captured = Arc::new(());
+    let callback_captured = Arc::clone(&captured);
+    recorder.subscription.set_callback(move |msg: Int32| {
+        let _captured = &callback_captured;
+        received.lock().unwrap().push(-msg.data);
+        let received = Arc::clone(&received);
+        subscription
+            .upgrade()
+            .unwrap()
+            .set_callback(move |msg: Int32| received.lock().unwrap().push(msg.data));
+    });
+    recorder.publish(&[1, 2, 3]);
+    recorder.spin_until_idle(&fixture);
+
+    // The first message is passed to the running callback, and the later ones to its
+    // replacement, which does not get lost when the running callback is put back.
+    assert_eq!(recorder.take_received(), [-1, 2, 3]);
+    assert_eq!(Arc::strong_count(&captured), 1);
+}
+
+#[test]
+fn test_rmw_specific_payload() {
+    // SAFETY: A null pointer is never passed on to the middleware.
+    assert!(unsafe { RmwSpecificPayload::new(std::ptr::null_mut()) }.is_none());
+
+    // Only the middlewares that ignore the payload accept an arbitrary one.
+    let rmw_implementation = std::env::var("RMW_IMPLEMENTATION").unwrap_or_default();
+    if !["", "rmw_fastrtps_cpp", "rmw_cyclonedds_cpp"].contains(&rmw_implementation.as_str()) {
+        return;
+    }
+    let mut options_struct = [0u64; 8];
+    // SAFETY: The middleware ignores the payload, and the buffer outlives the subscription.
+    let payload = unsafe { RmwSpecificPayload::new(options_struct.as_mut_ptr().cast()) }.unwrap();
+    assert_eq!(payload.as_ptr(), options_struct.as_mut_ptr().cast());
+    let mut fixture = TestFixture::new("rmw_specific_payload").unwrap();
+    let options = SubscriptionOptions {
+        rmw_specific_payload: Some(payload),
+        ..Default::default()
+    };
+    let recorder = Recorder::new(&mut fixture, "payload", options);
+    recorder.publish(&[1]);
+    recorder.spin_until_received(&fixture, 1);
+    assert_eq!(recorder.take_received(), [1]);
+}
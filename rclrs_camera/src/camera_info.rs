@@ -0,0 +1,52 @@
+use sensor_msgs::msg::CameraInfo;
+use std_msgs::msg::Header;
+
+/// Keeps the calibration of a camera, as published on its `camera_info` topic.
+///
+/// An uncalibrated camera has a camera info with an all-zero camera matrix `k`. Its image size
+/// is filled in from the published images.
+#[derive(Clone, Debug, Default)]
+pub struct CameraInfoManager {
+    camera_info: CameraInfo,
+}
+
+impl CameraInfoManager {
+    /// Creates a manager for an uncalibrated camera.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a manager with an existing calibration.
+    pub fn from_camera_info(camera_info: CameraInfo) -> Self {
+        Self { camera_info }
+    }
+
+    /// Returns the current camera info.
+    pub fn camera_info(&self) -> &CameraInfo {
+        &self.camera_info
+    }
+
+    /// Replaces the camera info, e.g. after a new calibration.
+    pub fn set_camera_info(&mut self, camera_info: CameraInfo) {
+        self.camera_info = camera_info;
+    }
+
+    /// Returns `true` if the camera has a calibration, i.e. a non-zero camera matrix.
+    pub fn is_calibrated(&self) -> bool {
+        self.camera_info.k[0] != 0.0
+    }
+
+    /// Returns the camera info for an image with the given header and size.
+    ///
+    /// The header is copied, so that the camera info has the same stamp and frame as the image.
+    /// The image size is only filled in when the camera info does not specify one.
+    pub fn camera_info_for_image(&self, header: &Header, width: u32, height: u32) -> CameraInfo {
+        let mut camera_info = self.camera_info.clone();
+        camera_info.header = header.clone();
+        if camera_info.width == 0 && camera_info.height == 0 {
+            camera_info.width = width;
+            camera_info.height = height;
+        }
+        camera_info
+    }
+}
@@ -0,0 +1,61 @@
+use crate::CameraError;
+
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageOutputFormat};
+use sensor_msgs::msg::CompressedImage;
+
+/// The format of a [`CompressedImage`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompressionFormat {
+    /// Lossy JPEG compression with a quality between 1 and 100.
+    Jpeg {
+        /// The quality, where higher values give larger images with fewer artifacts.
+        quality: u8,
+    },
+    /// Lossless PNG compression.
+    Png,
+}
+
+impl CompressionFormat {
+    /// Returns the value of the `format` field of compressed images in this format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Jpeg { .. } => "jpeg",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// Compresses an image into a compressed image message.
+///
+/// The header of the message is left empty.
+pub fn compress(
+    image: &DynamicImage,
+    format: CompressionFormat,
+) -> Result<CompressedImage, CameraError> {
+    let output_format = match format {
+        CompressionFormat::Jpeg { quality } => ImageOutputFormat::Jpeg(quality.clamp(1, 100)),
+        CompressionFormat::Png => ImageOutputFormat::Png,
+    };
+    let mut data = Cursor::new(Vec::new());
+    match (format, image) {
+        (CompressionFormat::Png, _)
+        | (_, DynamicImage::ImageLuma8(_))
+        | (_, DynamicImage::ImageRgb8(_)) => image.write_to(&mut data, output_format)?,
+        // JPEG supports neither an alpha channel nor 16-bit images.
+        _ => DynamicImage::ImageRgb8(image.to_rgb8()).write_to(&mut data, output_format)?,
+    }
+    Ok(CompressedImage {
+        format: format.name().to_owned(),
+        data: data.into_inner(),
+        ..Default::default()
+    })
+}
+
+/// Decompresses a compressed image message.
+///
+/// The format is detected from the data, so the `format` field of the message is not used.
+pub fn decompress(msg: &CompressedImage) -> Result<DynamicImage, CameraError> {
+    Ok(image::load_from_memory(&msg.data)?)
+}
@@ -0,0 +1,163 @@
+use crate::CameraError;
+
+use image::{DynamicImage, ImageBuffer};
+use sensor_msgs::msg::Image;
+
+/// Converts an image of the `image` crate into an image message.
+///
+/// 8-bit grayscale, 16-bit grayscale, RGB and RGBA images are converted to the `mono8`,
+/// `mono16`, `rgb8` and `rgba8` encodings, respectively. All other images are converted to
+/// `rgb8`. The header of the message is left empty.
+pub fn to_image_msg(image: &DynamicImage) -> Image {
+    let (encoding, bytes_per_pixel, width, height, data) = match image {
+        DynamicImage::ImageLuma8(buffer) => (
+            "mono8",
+            1,
+            buffer.width(),
+            buffer.height(),
+            buffer.as_raw().clone(),
+        ),
+        DynamicImage::ImageLuma16(buffer) => (
+            "mono16",
+            2,
+            buffer.width(),
+            buffer.height(),
+            buffer
+                .as_raw()
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        ),
+        DynamicImage::ImageRgb8(buffer) => (
+            "rgb8",
+            3,
+            buffer.width(),
+            buffer.height(),
+            buffer.as_raw().clone(),
+        ),
+        DynamicImage::ImageRgba8(buffer) => (
+            "rgba8",
+            4,
+            buffer.width(),
+            buffer.height(),
+            buffer.as_raw().clone(),
+        ),
+        _ => {
+            let buffer = image.to_rgb8();
+            (
+                "rgb8",
+                3,
+                buffer.width(),
+                buffer.height(),
+                buffer.into_raw(),
+            )
+        }
+    };
+    Image {
+        height,
+        width,
+        encoding: encoding.to_owned(),
+        is_bigendian: 0,
+        step: width * bytes_per_pixel,
+        data,
+        ..Default::default()
+    }
+}
+
+/// Converts an image message into an image of the `image` crate.
+///
+/// The supported encodings are `mono8`, `mono16`, `rgb8`, `rgba8`, `bgr8` and `bgra8`. Images
+/// in the BGR encodings are converted to RGB. Row padding, i.e. a `step` larger than the row
+/// size, is removed.
+pub fn from_image_msg(msg: &Image) -> Result<DynamicImage, CameraError> {
+    let bytes_per_pixel = match msg.encoding.as_str() {
+        "mono8" => 1,
+        "mono16" => 2,
+        "rgb8" | "bgr8" => 3,
+        "rgba8" | "bgra8" => 4,
+        encoding => return Err(CameraError::UnsupportedEncoding(encoding.to_owned())),
+    };
+    let (width, height, step) = (msg.width as usize, msg.height as usize, msg.step as usize);
+    let row_size = width * bytes_per_pixel;
+    if step < row_size || msg.data.len() < step * height {
+        return Err(CameraError::InvalidImage(format!(
+            "{} bytes with step {} are too few for a {}x{} {} image",
+            msg.data.len(),
+            msg.step,
+            msg.width,
+            msg.height,
+            msg.encoding
+        )));
+    }
+    let mut pixels = Vec::with_capacity(row_size * height);
+    if row_size > 0 {
+        for row in msg.data.chunks(step).take(height) {
+            pixels.extend_from_slice(&row[..row_size]);
+        }
+    }
+    if msg.encoding.starts_with("bgr") {
+        for pixel in pixels.chunks_mut(bytes_per_pixel) {
+            pixel.swap(0, 2);
+        }
+    }
+    let image = match msg.encoding.as_str() {
+        "mono8" => {
+            ImageBuffer::from_raw(msg.width, msg.height, pixels).map(DynamicImage::ImageLuma8)
+        }
+        "mono16" => {
+            let values = pixels
+                .chunks(2)
+                .map(|bytes| {
+                    let bytes = [bytes[0], bytes[1]];
+                    if msg.is_bigendian != 0 {
+                        u16::from_be_bytes(bytes)
+                    } else {
+                        u16::from_le_bytes(bytes)
+                    }
+                })
+                .collect();
+            ImageBuffer::from_raw(msg.width, msg.height, values).map(DynamicImage::ImageLuma16)
+        }
+        "rgb8" | "bgr8" => {
+            ImageBuffer::from_raw(msg.width, msg.height, pixels).map(DynamicImage::ImageRgb8)
+        }
+        _ => ImageBuffer::from_raw(msg.width, msg.height, pixels).map(DynamicImage::ImageRgba8),
+    };
+    // The buffer size has been checked above, so this cannot fail.
+    Ok(image.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bgr8_with_padding() {
+        let msg = Image {
+            height: 2,
+            width: 1,
+            encoding: "bgr8".to_owned(),
+            step: 4,
+            data: vec![1, 2, 3, 0, 4, 5, 6, 0],
+            ..Default::default()
+        };
+        let image = from_image_msg(&msg).unwrap();
+        assert_eq!(image.to_rgb8().into_raw(), vec![3, 2, 1, 6, 5, 4]);
+        let converted = to_image_msg(&image);
+        assert_eq!(converted.encoding, "rgb8");
+        assert_eq!(converted.step, 3);
+        assert_eq!(converted.data, vec![3, 2, 1, 6, 5, 4]);
+    }
+
+    #[test]
+    fn test_unsupported_encoding() {
+        let msg = Image {
+            encoding: "yuv422".to_owned(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            from_image_msg(&msg),
+            Err(CameraError::UnsupportedEncoding(_))
+        ));
+    }
+}
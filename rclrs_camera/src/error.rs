@@ -0,0 +1,46 @@
+use std::fmt::{self, Display};
+
+use rclrs::RclReturnCode;
+
+/// An error in the conversion or publishing of camera images.
+#[derive(Debug)]
+pub enum CameraError {
+    /// Publishing failed.
+    Rcl(RclReturnCode),
+    /// The encoding of an image message is not supported.
+    UnsupportedEncoding(String),
+    /// The size of an image message does not match its dimensions.
+    InvalidImage(String),
+    /// Encoding or decoding a compressed image failed.
+    Image(image::ImageError),
+    /// No transport with the given name is registered.
+    UnknownTransport(String),
+}
+
+impl Display for CameraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rcl(error) => write!(f, "{}", error),
+            Self::UnsupportedEncoding(encoding) => {
+                write!(f, "Unsupported image encoding '{}'", encoding)
+            }
+            Self::InvalidImage(reason) => write!(f, "Invalid image: {}", reason),
+            Self::Image(error) => write!(f, "{}", error),
+            Self::UnknownTransport(name) => write!(f, "Unknown image transport '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for CameraError {}
+
+impl From<RclReturnCode> for CameraError {
+    fn from(error: RclReturnCode) -> Self {
+        Self::Rcl(error)
+    }
+}
+
+impl From<image::ImageError> for CameraError {
+    fn from(error: image::ImageError) -> Self {
+        Self::Image(error)
+    }
+}
@@ -0,0 +1,24 @@
+#![warn(missing_docs)]
+//! Helpers for camera drivers written with `rclrs`.
+//!
+//! This crate converts between [`sensor_msgs::msg::Image`] and the images of the [`image`]
+//! crate, manages the [`sensor_msgs::msg::CameraInfo`] of a camera, and provides a
+//! [`CameraPublisher`] that publishes an image together with its camera info.
+//!
+//! Like `image_transport`, images can be published in several representations at once, e.g. raw
+//! and compressed. Each representation is a [`TransportPlugin`], and subscribers choose the one
+//! they want by name from a [`TransportRegistry`].
+
+mod camera_info;
+mod compression;
+mod conversion;
+mod error;
+mod publisher;
+mod transport;
+
+pub use camera_info::*;
+pub use compression::*;
+pub use conversion::*;
+pub use error::*;
+pub use publisher::*;
+pub use transport::*;
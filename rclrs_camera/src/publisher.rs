@@ -0,0 +1,152 @@
+use crate::{
+    to_image_msg, CameraError, CameraInfoManager, CompressedTransport, CompressionFormat,
+    TransportPlugin, TransportPublisher,
+};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use builtin_interfaces::msg::Time;
+use image::DynamicImage;
+use rclrs::{Node, Publisher, QoSProfile};
+use sensor_msgs::msg::{CameraInfo, Image};
+use std_msgs::msg::Header;
+
+/// Publishes the images of a camera together with its camera info.
+///
+/// Following the `image_transport` conventions, the camera info is published on the
+/// `camera_info` topic next to the image topic, e.g. `camera/camera_info` for the image topic
+/// `camera/image_raw`. Additional transports publish on their own subtopics, e.g.
+/// `camera/image_raw/compressed`, see [`TransportPlugin`].
+///
+/// Each image and its camera info get the same header, which is stamped with the current system
+/// time.
+///
+/// # Example
+/// ```ignore
+/// let mut publisher = CameraPublisher::new(&node, "camera/image_raw", "camera_optical_frame", QOS_PROFILE_SENSOR_DATA)?
+///     .with_compression(&node, CompressionFormat::Jpeg { quality: 80 })?;
+/// publisher.publish(&image::open("frame.png")?)?;
+/// ```
+pub struct CameraPublisher {
+    image_publisher: Publisher<Image>,
+    camera_info_publisher: Publisher<CameraInfo>,
+    transport_publishers: Vec<Box<dyn TransportPublisher>>,
+    camera_info_manager: CameraInfoManager,
+    image_topic: String,
+    qos: QoSProfile,
+    frame_id: String,
+}
+
+impl CameraPublisher {
+    /// Creates a new camera publisher for an uncalibrated camera.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new(
+        node: &Node,
+        image_topic: &str,
+        frame_id: &str,
+        qos: QoSProfile,
+    ) -> Result<Self, CameraError> {
+        Ok(Self {
+            image_publisher: node.create_publisher(image_topic, qos)?,
+            camera_info_publisher: node.create_publisher(&camera_info_topic(image_topic), qos)?,
+            transport_publishers: Vec::new(),
+            camera_info_manager: CameraInfoManager::new(),
+            image_topic: image_topic.to_owned(),
+            qos,
+            frame_id: frame_id.to_owned(),
+        })
+    }
+
+    /// Additionally publishes each image compressed in the given format.
+    ///
+    /// This is a shorthand for [`with_transport`][1] with a [`CompressedTransport`].
+    ///
+    /// [1]: CameraPublisher::with_transport
+    pub fn with_compression(
+        self,
+        node: &Node,
+        format: CompressionFormat,
+    ) -> Result<Self, CameraError> {
+        self.with_transport(node, &CompressedTransport::new(format))
+    }
+
+    /// Additionally publishes each image with the given transport.
+    pub fn with_transport(
+        mut self,
+        node: &Node,
+        transport: &dyn TransportPlugin,
+    ) -> Result<Self, CameraError> {
+        let publisher = transport.create_publisher(node, &self.image_topic, self.qos)?;
+        self.transport_publishers.push(publisher);
+        Ok(self)
+    }
+
+    /// Returns the camera info manager, e.g. for setting a calibration.
+    pub fn camera_info_manager(&mut self) -> &mut CameraInfoManager {
+        &mut self.camera_info_manager
+    }
+
+    /// Converts an image to a message and publishes it, see [`to_image_msg`].
+    pub fn publish(&self, image: &DynamicImage) -> Result<(), CameraError> {
+        self.publish_msg(to_image_msg(image))
+    }
+
+    /// Publishes an image message, e.g. one that is already in an encoding of the camera.
+    ///
+    /// The header of the message is replaced. Additional transports only support the encodings
+    /// of [`from_image_msg`][1].
+    ///
+    /// The raw image and the camera info are published first, so that a failing transport does
+    /// not hold them back. All transports are tried, and the first error of a transport is
+    /// returned afterwards.
+    ///
+    /// [1]: crate::from_image_msg
+    pub fn publish_msg(&self, image: Image) -> Result<(), CameraError> {
+        let header = self.header();
+        let camera_info =
+            self.camera_info_manager
+                .camera_info_for_image(&header, image.width, image.height);
+        let image = Image { header, ..image };
+        self.image_publisher.publish(&image)?;
+        self.camera_info_publisher.publish(camera_info)?;
+        self.transport_publishers
+            .iter()
+            .map(|publisher| publisher.publish(&image))
+            .fold(Ok(()), Result::and)
+    }
+
+    fn header(&self) -> Header {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Header {
+            stamp: Time {
+                sec: since_epoch.as_secs() as i32,
+                nanosec: since_epoch.subsec_nanos(),
+            },
+            frame_id: self.frame_id.clone(),
+        }
+    }
+}
+
+// Returns the camera info topic for an image topic.
+fn camera_info_topic(image_topic: &str) -> String {
+    match image_topic.rsplit_once('/') {
+        Some((namespace, _)) => format!("{}/camera_info", namespace),
+        None => String::from("camera_info"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::camera_info_topic;
+
+    #[test]
+    fn test_camera_info_topic() {
+        assert_eq!(camera_info_topic("camera/image_raw"), "camera/camera_info");
+        assert_eq!(camera_info_topic("/image"), "/camera_info");
+        assert_eq!(camera_info_topic("image"), "camera_info");
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{compress, decompress, from_image_msg, to_image_msg, CameraError, CompressionFormat};
+
+use std::sync::Arc;
+
+use rclrs::{CallbackErrorPolicy, Node, Publisher, QoSProfile, SubscriptionBase};
+use sensor_msgs::msg::{CompressedImage, Image};
+
+/// The callback of a transport subscriber, which receives the decoded images.
+pub type ImageCallback = Box<dyn FnMut(Image) + 'static>;
+
+/// A way of transporting images, e.g. raw or compressed.
+///
+/// Following the `image_transport` conventions, each transport except `raw` publishes on a
+/// subtopic of the base image topic that is named after the transport, e.g.
+/// `camera/image_raw/compressed` for the `compressed` transport. A transport encodes the
+/// [`Image`] messages on the publisher side and decodes them again on the subscriber side, so
+/// that both sides only deal with [`Image`] messages.
+///
+/// New transports, e.g. for video codecs, are added by implementing this trait and registering
+/// the implementation in a [`TransportRegistry`].
+pub trait TransportPlugin {
+    /// Returns the name of the transport, which is also the name of its subtopic.
+    fn name(&self) -> &str;
+
+    /// Returns the topic on which this transport publishes images of the given base topic.
+    fn topic(&self, base_topic: &str) -> String {
+        format!("{}/{}", base_topic, self.name())
+    }
+
+    /// Creates a publisher that encodes images with this transport.
+    fn create_publisher(
+        &self,
+        node: &Node,
+        base_topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Box<dyn TransportPublisher>, CameraError>;
+
+    /// Creates a subscription that decodes images of this transport and passes them to the
+    /// callback.
+    ///
+    /// The subscription is spun like any other subscription of the node. When a message cannot
+    /// be decoded, it is dropped and [`spin_once`][1] returns an
+    /// [`RclReturnCode::CallbackError`][2] with the description of the [`CameraError`].
+    ///
+    /// [1]: rclrs::spin_once
+    /// [2]: rclrs::RclReturnCode::CallbackError
+    fn create_subscription(
+        &self,
+        node: &mut Node,
+        base_topic: &str,
+        qos: QoSProfile,
+        callback: ImageCallback,
+    ) -> Result<Arc<dyn SubscriptionBase>, CameraError>;
+}
+
+/// The publisher side of a [`TransportPlugin`].
+pub trait TransportPublisher {
+    /// Encodes an image and publishes it.
+    ///
+    /// The header of the image is kept.
+    fn publish(&self, image: &Image) -> Result<(), CameraError>;
+}
+
+/// The `raw` transport, which publishes [`Image`] messages unchanged on the base topic.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawTransport;
+
+impl TransportPlugin for RawTransport {
+    fn name(&self) -> &str {
+        "raw"
+    }
+
+    fn topic(&self, base_topic: &str) -> String {
+        base_topic.to_owned()
+    }
+
+    fn create_publisher(
+        &self,
+        node: &Node,
+        base_topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Box<dyn TransportPublisher>, CameraError> {
+        let publisher: Publisher<Image> = node.create_publisher(&self.topic(base_topic), qos)?;
+        Ok(Box::new(publisher))
+    }
+
+    fn create_subscription(
+        &self,
+        node: &mut Node,
+        base_topic: &str,
+        qos: QoSProfile,
+        callback: ImageCallback,
+    ) -> Result<Arc<dyn SubscriptionBase>, CameraError> {
+        let subscription = node.create_subscription(&self.topic(base_topic), qos, callback)?;
+        Ok(subscription)
+    }
+}
+
+impl TransportPublisher for Publisher<Image> {
+    fn publish(&self, image: &Image) -> Result<(), CameraError> {
+        Ok(Publisher::publish(self, image)?)
+    }
+}
+
+/// The `compressed` transport, which publishes [`CompressedImage`] messages in JPEG or PNG.
+///
+/// The format is only used by the publisher, since subscribers detect it from the data.
+#[derive(Clone, Copy, Debug)]
+pub struct CompressedTransport {
+    format: CompressionFormat,
+}
+
+impl CompressedTransport {
+    /// Creates a compressed transport that publishes in the given format.
+    pub fn new(format: CompressionFormat) -> Self {
+        Self { format }
+    }
+
+    /// Returns the format in which images are published.
+    pub fn format(&self) -> CompressionFormat {
+        self.format
+    }
+}
+
+impl Default for CompressedTransport {
+    /// Creates a compressed transport that publishes in JPEG with a quality of 95, like the
+    /// default of `image_transport`.
+    fn default() -> Self {
+        Self::new(CompressionFormat::Jpeg { quality: 95 })
+    }
+}
+
+impl TransportPlugin for CompressedTransport {
+    fn name(&self) -> &str {
+        "compressed"
+    }
+
+    fn create_publisher(
+        &self,
+        node: &Node,
+        base_topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Box<dyn TransportPublisher>, CameraError> {
+        Ok(Box::new(CompressedPublisher {
+            publisher: node.create_publisher(&self.topic(base_topic), qos)?,
+            format: self.format,
+        }))
+    }
+
+    fn create_subscription(
+        &self,
+        node: &mut Node,
+        base_topic: &str,
+        qos: QoSProfile,
+        mut callback: ImageCallback,
+    ) -> Result<Arc<dyn SubscriptionBase>, CameraError> {
+        let subscription = node.create_fallible_subscription(
+            &self.topic(base_topic),
+            qos,
+            CallbackErrorPolicy::StopSpinning,
+            move |msg: CompressedImage| {
+                let image = decompress(&msg)?;
+                callback(Image {
+                    header: msg.header,
+                    ..to_image_msg(&image)
+                });
+                Ok::<(), CameraError>(())
+            },
+        )?;
+        Ok(subscription)
+    }
+}
+
+struct CompressedPublisher {
+    publisher: Publisher<CompressedImage>,
+    format: CompressionFormat,
+}
+
+impl TransportPublisher for CompressedPublisher {
+    fn publish(&self, image: &Image) -> Result<(), CameraError> {
+        let compressed = compress(&from_image_msg(image)?, self.format)?;
+        self.publisher.publish(CompressedImage {
+            header: image.header.clone(),
+            ..compressed
+        })?;
+        Ok(())
+    }
+}
+
+/// A set of transports, looked up by name.
+///
+/// A new registry contains the `raw` and `compressed` transports.
+pub struct TransportRegistry {
+    transports: Vec<Box<dyn TransportPlugin>>,
+}
+
+impl Default for TransportRegistry {
+    fn default() -> Self {
+        Self {
+            transports: vec![
+                Box::new(RawTransport),
+                Box::new(CompressedTransport::default()),
+            ],
+        }
+    }
+}
+
+impl TransportRegistry {
+    /// Creates a registry with the built-in transports.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a transport, replacing any transport with the same name.
+    pub fn register(&mut self, transport: Box<dyn TransportPlugin>) {
+        self.transports
+            .retain(|other| other.name() != transport.name());
+        self.transports.push(transport);
+    }
+
+    /// Returns the transport with the given name, if it is registered.
+    pub fn get(&self, name: &str) -> Option<&dyn TransportPlugin> {
+        self.transports
+            .iter()
+            .find(|transport| transport.name() == name)
+            .map(|transport| transport.as_ref())
+    }
+
+    /// Returns the names of all registered transports.
+    pub fn names(&self) -> Vec<&str> {
+        self.transports
+            .iter()
+            .map(|transport| transport.name())
+            .collect()
+    }
+
+    /// Creates a publisher for each registered transport.
+    ///
+    /// Each image passed to [`ImagePublisher::publish`] is published with all transports, so
+    /// that subscribers can choose the representation they want.
+    pub fn create_publisher(
+        &self,
+        node: &Node,
+        base_topic: &str,
+        qos: QoSProfile,
+    ) -> Result<ImagePublisher, CameraError> {
+        let publishers = self
+            .transports
+            .iter()
+            .map(|transport| transport.create_publisher(node, base_topic, qos))
+            .collect::<Result<_, _>>()?;
+        Ok(ImagePublisher { publishers })
+    }
+
+    /// Subscribes to the images of a base topic with the transport of the given name.
+    pub fn create_subscription<F>(
+        &self,
+        node: &mut Node,
+        base_topic: &str,
+        transport: &str,
+        qos: QoSProfile,
+        callback: F,
+    ) -> Result<Arc<dyn SubscriptionBase>, CameraError>
+    where
+        F: FnMut(Image) + 'static,
+    {
+        self.get(transport)
+            .ok_or_else(|| CameraError::UnknownTransport(transport.to_owned()))?
+            .create_subscription(node, base_topic, qos, Box::new(callback))
+    }
+}
+
+/// Publishes images with several transports at once.
+///
+/// Created by [`TransportRegistry::create_publisher`].
+pub struct ImagePublisher {
+    publishers: Vec<Box<dyn TransportPublisher>>,
+}
+
+impl ImagePublisher {
+    /// Publishes an image with all transports.
+    ///
+    /// Publishing continues with the remaining transports when one of them fails, and the first
+    /// error is returned.
+    pub fn publish(&self, image: &Image) -> Result<(), CameraError> {
+        let mut result = Ok(());
+        for publisher in &self.publishers {
+            let published = publisher.publish(image);
+            if result.is_ok() {
+                result = published;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lookup() {
+        let mut registry = TransportRegistry::new();
+        assert_eq!(registry.names(), vec!["raw", "compressed"]);
+        assert_eq!(
+            registry.get("raw").unwrap().topic("camera/image"),
+            "camera/image"
+        );
+        assert_eq!(
+            registry.get("compressed").unwrap().topic("camera/image"),
+            "camera/image/compressed"
+        );
+        assert!(registry.get("theora").is_none());
+        registry.register(Box::new(CompressedTransport::new(CompressionFormat::Png)));
+        assert_eq!(registry.names(), vec!["raw", "compressed"]);
+    }
+}
@@ -0,0 +1,13 @@
+#![warn(missing_docs)]
+//! Statistics of `rclrs` nodes as diagnostic messages for dashboards.
+//!
+//! The [`StatisticsPublisher`] periodically turns the [`rclrs::CallbackMetrics`] of a node into
+//! `diagnostic_msgs/DiagnosticArray` messages on `/diagnostics`, with the message rate and
+//! callback timing of each subscribed topic. This makes the health of Rust nodes visible in the
+//! same tools as that of other ROS nodes.
+
+mod publisher;
+mod status;
+
+pub use publisher::*;
+pub use status::*;
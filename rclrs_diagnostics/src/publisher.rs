@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use builtin_interfaces::msg::Time;
+use diagnostic_msgs::msg::DiagnosticArray;
+use rclrs::{Node, Publisher, RclReturnCode, QOS_PROFILE_DEFAULT};
+use std_msgs::msg::Header;
+
+use crate::{snapshot_by_topic, topic_status, TopicSnapshot};
+
+/// The topic that diagnostics are published on by convention.
+pub const DIAGNOSTICS_TOPIC: &str = "/diagnostics";
+
+/// Publishes the callback statistics of a node as `diagnostic_msgs/DiagnosticArray` messages.
+///
+/// Each report contains one status per subscribed topic, with the message rate and the mean
+/// callback duration and latency since the previous report, see [`topic_status`]. The messages
+/// can be shown by fleet dashboards and `rqt_robot_monitor`, or be aggregated with
+/// `diagnostic_aggregator`, without changing the code of the node.
+///
+/// The statistics are collected by [`rclrs::spin_once`] anyway, so the publisher only reads
+/// them when a report is due. [`update`][1] must be called periodically for this, e.g. after each
+/// call to `spin_once` with a timeout. Reports can be switched off and on at runtime with
+/// [`set_enabled`][2], or from the callback of a subscription through [`enabled_flag`][3].
+///
+/// # Example
+/// ```ignore
+/// let mut statistics = StatisticsPublisher::new(&node, Duration::from_secs(1))?;
+/// while context.ok() {
+///     rclrs::spin_once(&node, Some(Duration::from_millis(100)))?;
+///     statistics.update(&node)?;
+/// }
+/// ```
+///
+/// [1]: StatisticsPublisher::update
+/// [2]: StatisticsPublisher::set_enabled
+/// [3]: StatisticsPublisher::enabled_flag
+pub struct StatisticsPublisher {
+    publisher: Publisher<DiagnosticArray>,
+    period: Duration,
+    enabled: Arc<AtomicBool>,
+    slow_callback_threshold: Option<Duration>,
+    last_report: Option<(Instant, BTreeMap<String, TopicSnapshot>)>,
+}
+
+impl StatisticsPublisher {
+    /// Creates a publisher on [`DIAGNOSTICS_TOPIC`] that reports once per `period`.
+    pub fn new(node: &Node, period: Duration) -> Result<Self, RclReturnCode> {
+        Self::new_with_topic(node, DIAGNOSTICS_TOPIC, period)
+    }
+
+    /// Creates a publisher on the given topic that reports once per `period`.
+    pub fn new_with_topic(
+        node: &Node,
+        topic: &str,
+        period: Duration,
+    ) -> Result<Self, RclReturnCode> {
+        Ok(Self {
+            publisher: node.create_publisher(topic, QOS_PROFILE_DEFAULT)?,
+            period,
+            enabled: Arc::new(AtomicBool::new(true)),
+            slow_callback_threshold: None,
+            last_report: None,
+        })
+    }
+
+    /// Sets the mean callback duration above which a topic is reported with the `WARN` level.
+    ///
+    /// The default is `None`, for which all topics are reported as `OK`.
+    pub fn set_slow_callback_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_callback_threshold = threshold;
+    }
+
+    /// Switches the reports on or off. Reports are enabled by default.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if reports are enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the flag that enables reports, for switching them from elsewhere, e.g. from the
+    /// callback of a subscription.
+    pub fn enabled_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.enabled)
+    }
+
+    /// Publishes a report if reports are enabled and the period has elapsed since the last
+    /// report.
+    ///
+    /// The first call after creating the publisher or enabling reports only starts the first
+    /// interval, so the first report is published one period later.
+    pub fn update(&mut self, node: &Node) -> Result<(), RclReturnCode> {
+        if !self.is_enabled() {
+            // Do not report the interval in which reports were disabled when they are enabled
+            // again.
+            self.last_report = None;
+            return Ok(());
+        }
+        let now = Instant::now();
+        let (last_time, previous) = match self.last_report.take() {
+            Some(last_report) => last_report,
+            None => {
+                self.last_report = Some((now, snapshot_by_topic(&node.callback_metrics())));
+                return Ok(());
+            }
+        };
+        if now.duration_since(last_time) < self.period {
+            self.last_report = Some((last_time, previous));
+            return Ok(());
+        }
+        let snapshots = snapshot_by_topic(&node.callback_metrics());
+        let result = self.publish(node, &snapshots, &previous, now.duration_since(last_time));
+        self.last_report = Some((now, snapshots));
+        result
+    }
+
+    fn publish(
+        &self,
+        node: &Node,
+        snapshots: &BTreeMap<String, TopicSnapshot>,
+        previous: &BTreeMap<String, TopicSnapshot>,
+        interval: Duration,
+    ) -> Result<(), RclReturnCode> {
+        let node_name = node.fully_qualified_name();
+        let status = snapshots
+            .iter()
+            .map(|(topic, current)| {
+                topic_status(
+                    &node_name,
+                    topic,
+                    current,
+                    previous.get(topic),
+                    interval,
+                    self.slow_callback_threshold,
+                )
+            })
+            .collect();
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.publisher.publish(DiagnosticArray {
+            header: Header {
+                stamp: Time {
+                    sec: since_epoch.as_secs() as i32,
+                    nanosec: since_epoch.subsec_nanos(),
+                },
+                frame_id: String::new(),
+            },
+            status,
+        })
+    }
+}
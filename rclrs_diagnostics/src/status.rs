@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use diagnostic_msgs::msg::{DiagnosticStatus, KeyValue};
+use rclrs::CallbackMetrics;
+
+/// The level of a [`DiagnosticStatus`], which the generated message type has no constants for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiagnosticLevel {
+    /// Everything is working as expected.
+    Ok = 0,
+    /// Something may be wrong.
+    Warn = 1,
+    /// Something is wrong.
+    Error = 2,
+    /// No information has been received for a while.
+    Stale = 3,
+}
+
+impl DiagnosticLevel {
+    /// Returns the value of the `level` field of a [`DiagnosticStatus`].
+    pub fn as_byte(self) -> u8 {
+        self as u8
+    }
+}
+
+/// The callback statistics of one topic at one point in time, summed over the subscriptions of
+/// the topic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TopicSnapshot {
+    /// The number of messages that have been processed.
+    pub messages: u64,
+    /// The total time spent in callbacks.
+    pub callback_time: Duration,
+    /// The longest callback.
+    pub max_callback_duration: Duration,
+    /// The total time that messages waited for their callback to run.
+    pub latency_time: Duration,
+}
+
+/// Groups the callback metrics of a node by topic.
+pub fn snapshot_by_topic(metrics: &[CallbackMetrics]) -> BTreeMap<String, TopicSnapshot> {
+    let mut snapshots = BTreeMap::<String, TopicSnapshot>::new();
+    for entity in metrics {
+        let snapshot = snapshots.entry(entity.topic.clone()).or_default();
+        snapshot.messages += entity.duration.count;
+        snapshot.callback_time += entity.duration.sum;
+        snapshot.max_callback_duration = snapshot.max_callback_duration.max(entity.duration.max);
+        snapshot.latency_time += entity.latency.sum;
+    }
+    snapshots
+}
+
+/// Creates the diagnostic status of a topic for the interval between two snapshots.
+///
+/// The status is named `"<node name>: <topic>"`, as expected by `diagnostic_aggregator`. Its
+/// level is [`Warn`][1] if the mean callback duration in the interval exceeds the
+/// `slow_callback_threshold`. Rates and means refer to the interval, or to the whole lifetime of
+/// the subscriptions if there is no previous snapshot. The rate is `0` for an empty interval.
+///
+/// [1]: DiagnosticLevel::Warn
+pub fn topic_status(
+    node_name: &str,
+    topic: &str,
+    current: &TopicSnapshot,
+    previous: Option<&TopicSnapshot>,
+    interval: Duration,
+    slow_callback_threshold: Option<Duration>,
+) -> DiagnosticStatus {
+    let previous = previous.copied().unwrap_or_default();
+    let messages = current.messages.saturating_sub(previous.messages);
+    let mean = |total: Duration, previous_total: Duration| {
+        if messages == 0 {
+            Duration::ZERO
+        } else {
+            total.saturating_sub(previous_total) / messages as u32
+        }
+    };
+    let mean_callback_duration = mean(current.callback_time, previous.callback_time);
+    let mean_latency = mean(current.latency_time, previous.latency_time);
+    let rate = if interval.is_zero() {
+        0.0
+    } else {
+        messages as f64 / interval.as_secs_f64()
+    };
+    let slow = slow_callback_threshold.is_some_and(|threshold| mean_callback_duration > threshold);
+    let (level, message) = if slow {
+        (DiagnosticLevel::Warn, String::from("Slow callbacks"))
+    } else {
+        (DiagnosticLevel::Ok, format!("{:.1} Hz", rate))
+    };
+    let milliseconds = |duration: Duration| format!("{:.3}", duration.as_secs_f64() * 1e3);
+    let values = [
+        ("rate_hz", format!("{:.2}", rate)),
+        ("messages", current.messages.to_string()),
+        (
+            "mean_callback_duration_ms",
+            milliseconds(mean_callback_duration),
+        ),
+        (
+            "max_callback_duration_ms",
+            milliseconds(current.max_callback_duration),
+        ),
+        ("mean_latency_ms", milliseconds(mean_latency)),
+    ];
+    DiagnosticStatus {
+        level: level.as_byte(),
+        name: format!("{}: {}", node_name, topic),
+        message,
+        hardware_id: String::new(),
+        values: values
+            .into_iter()
+            .map(|(key, value)| KeyValue {
+                key: key.to_owned(),
+                value,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_status() {
+        let previous = TopicSnapshot {
+            messages: 10,
+            callback_time: Duration::from_millis(10),
+            max_callback_duration: Duration::from_millis(2),
+            latency_time: Duration::from_millis(5),
+        };
+        let current = TopicSnapshot {
+            messages: 30,
+            callback_time: Duration::from_millis(50),
+            max_callback_duration: Duration::from_millis(4),
+            latency_time: Duration::from_millis(25),
+        };
+        let status = topic_status(
+            "/robot/driver",
+            "/cmd_vel",
+            &current,
+            Some(&previous),
+            Duration::from_secs(2),
+            None,
+        );
+        assert_eq!(status.level, DiagnosticLevel::Ok.as_byte());
+        assert_eq!(status.name, "/robot/driver: /cmd_vel");
+        assert_eq!(status.message, "10.0 Hz");
+        let value = |key: &str| {
+            status
+                .values
+                .iter()
+                .find(|value| value.key == key)
+                .map(|value| value.value.as_str())
+        };
+        assert_eq!(value("messages"), Some("30"));
+        assert_eq!(value("mean_callback_duration_ms"), Some("2.000"));
+        assert_eq!(value("mean_latency_ms"), Some("1.000"));
+
+        let status = topic_status(
+            "/robot/driver",
+            "/cmd_vel",
+            &current,
+            Some(&previous),
+            Duration::from_secs(2),
+            Some(Duration::from_millis(1)),
+        );
+        assert_eq!(status.level, DiagnosticLevel::Warn.as_byte());
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt::{self, Display};
+
+/// The reason why a joint state or joint trajectory message is not valid.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JointError {
+    /// A joint name is empty.
+    EmptyName,
+    /// A joint name appears more than once.
+    DuplicateName(String),
+    /// A joint is not in the message.
+    UnknownJoint(String),
+    /// An array does not have one value per joint.
+    LengthMismatch {
+        /// The name of the field.
+        field: &'static str,
+        /// The number of joints.
+        expected: usize,
+        /// The length of the array.
+        actual: usize,
+    },
+    /// A trajectory has no points.
+    EmptyTrajectory,
+    /// The `time_from_start` of a trajectory point is negative or not after the previous point.
+    InvalidTime {
+        /// The index of the point.
+        index: usize,
+    },
+}
+
+impl Display for JointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "A joint name is empty"),
+            Self::DuplicateName(name) => write!(f, "The joint '{}' appears twice", name),
+            Self::UnknownJoint(name) => write!(f, "The joint '{}' is unknown", name),
+            Self::LengthMismatch {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "The field '{}' has {} values for {} joints",
+                field, actual, expected
+            ),
+            Self::EmptyTrajectory => write!(f, "The trajectory has no points"),
+            Self::InvalidTime { index } => write!(
+                f,
+                "The time from start of point {} is negative or not after the previous point",
+                index
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JointError {}
+
+// Checks that the names are not empty and unique.
+pub(crate) fn check_names(names: &[String]) -> Result<(), JointError> {
+    for (index, name) in names.iter().enumerate() {
+        if name.is_empty() {
+            return Err(JointError::EmptyName);
+        }
+        if names[..index].contains(name) {
+            return Err(JointError::DuplicateName(name.clone()));
+        }
+    }
+    Ok(())
+}
+
+// Checks that an array has one value per joint. Optional arrays may also be empty.
+pub(crate) fn check_length(
+    field: &'static str,
+    values: &[f64],
+    joint_count: usize,
+    optional: bool,
+) -> Result<(), JointError> {
+    if values.len() == joint_count || (optional && values.is_empty()) {
+        Ok(())
+    } else {
+        Err(JointError::LengthMismatch {
+            field,
+            expected: joint_count,
+            actual: values.len(),
+        })
+    }
+}
@@ -0,0 +1,18 @@
+#![warn(missing_docs)]
+//! Helpers for joint states and joint trajectories in controllers written with `rclrs`.
+//!
+//! The arrays of [`sensor_msgs::msg::JointState`] and [`trajectory_msgs::msg::JointTrajectory`]
+//! messages are indexed by the position of the joint in the list of names, and the lengths of the
+//! arrays are only fixed by convention. This crate checks those conventions, looks up values by
+//! joint name, and interpolates joint states and trajectories in time.
+//!
+//! [`JointStates`] implement [`rclrs::Interpolate`], so they can be kept in an
+//! [`rclrs::TimedBuffer`] to look up the state of the joints at the stamp of another message.
+
+mod error;
+mod state;
+mod trajectory;
+
+pub use error::*;
+pub use state::*;
+pub use trajectory::*;
@@ -0,0 +1,309 @@
+use crate::{check_length, check_names, JointError};
+
+use rclrs::Interpolate;
+use sensor_msgs::msg::JointState;
+use std_msgs::msg::Header;
+
+/// Checks a joint state message before its values are used.
+///
+/// The message is valid if its joint names are not empty and unique, and each of `position`,
+/// `velocity` and `effort` is either empty or has one value per joint.
+pub fn validate_joint_state(joint_state: &JointState) -> Result<(), JointError> {
+    check_names(&joint_state.name)?;
+    let joint_count = joint_state.name.len();
+    check_length("position", &joint_state.position, joint_count, true)?;
+    check_length("velocity", &joint_state.velocity, joint_count, true)?;
+    check_length("effort", &joint_state.effort, joint_count, true)
+}
+
+/// The positions, velocities and efforts of a set of joints, looked up by joint name.
+///
+/// Each of the arrays is either empty, when the source does not report that quantity, or has one
+/// value per joint.
+///
+/// Joint states can be kept in an [`rclrs::TimedBuffer`]. Joints are matched by name when they
+/// are interpolated, so the joints may be in a different order in each message:
+///
+/// ```
+/// # use rclrs::TimedBuffer;
+/// # use rclrs_joints::JointStates;
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// let names = vec![String::from("shoulder"), String::from("elbow")];
+/// let mut states = TimedBuffer::new(Duration::from_secs(1));
+/// let first = JointStates::new(names.clone(), vec![0.0, 1.0], vec![], vec![]).unwrap();
+/// let second = JointStates::new(names, vec![1.0, 1.0], vec![], vec![]).unwrap();
+/// states.insert(UNIX_EPOCH, first);
+/// states.insert(UNIX_EPOCH + Duration::from_millis(100), second);
+/// let state = states.lookup_at(UNIX_EPOCH + Duration::from_millis(25)).unwrap();
+/// assert_eq!(state.position("shoulder"), Some(0.25));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JointStates {
+    names: Vec<String>,
+    positions: Vec<f64>,
+    velocities: Vec<f64>,
+    efforts: Vec<f64>,
+}
+
+impl JointStates {
+    /// Creates joint states, with the same checks as [`validate_joint_state`].
+    pub fn new(
+        names: Vec<String>,
+        positions: Vec<f64>,
+        velocities: Vec<f64>,
+        efforts: Vec<f64>,
+    ) -> Result<Self, JointError> {
+        check_names(&names)?;
+        check_length("position", &positions, names.len(), true)?;
+        check_length("velocity", &velocities, names.len(), true)?;
+        check_length("effort", &efforts, names.len(), true)?;
+        Ok(Self {
+            names,
+            positions,
+            velocities,
+            efforts,
+        })
+    }
+
+    /// Returns the joint states of a message, see [`validate_joint_state`].
+    pub fn from_message(joint_state: &JointState) -> Result<Self, JointError> {
+        Self::new(
+            joint_state.name.clone(),
+            joint_state.position.clone(),
+            joint_state.velocity.clone(),
+            joint_state.effort.clone(),
+        )
+    }
+
+    /// Returns the joint states as a message with the given header.
+    pub fn to_message(&self, header: Header) -> JointState {
+        JointState {
+            header,
+            name: self.names.clone(),
+            position: self.positions.clone(),
+            velocity: self.velocities.clone(),
+            effort: self.efforts.clone(),
+        }
+    }
+
+    /// Returns the names of the joints.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the number of joints.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns true if there are no joints.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Returns the index of a joint in the arrays.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|joint| joint == name)
+    }
+
+    /// Returns the positions of all joints, or an empty slice if they are not reported.
+    pub fn positions(&self) -> &[f64] {
+        &self.positions
+    }
+
+    /// Returns the velocities of all joints, or an empty slice if they are not reported.
+    pub fn velocities(&self) -> &[f64] {
+        &self.velocities
+    }
+
+    /// Returns the efforts of all joints, or an empty slice if they are not reported.
+    pub fn efforts(&self) -> &[f64] {
+        &self.efforts
+    }
+
+    /// Returns the position of a joint, if the joint is known and positions are reported.
+    pub fn position(&self, name: &str) -> Option<f64> {
+        Self::value(&self.positions, self.index_of(name)?)
+    }
+
+    /// Returns the velocity of a joint, if the joint is known and velocities are reported.
+    pub fn velocity(&self, name: &str) -> Option<f64> {
+        Self::value(&self.velocities, self.index_of(name)?)
+    }
+
+    /// Returns the effort of a joint, if the joint is known and efforts are reported.
+    pub fn effort(&self, name: &str) -> Option<f64> {
+        Self::value(&self.efforts, self.index_of(name)?)
+    }
+
+    /// Returns the positions of the given joints, in the order of `names`.
+    ///
+    /// This is how a controller gets the values of its joints from a message that contains the
+    /// joints in a different order, or other joints as well.
+    pub fn positions_of(&self, names: &[&str]) -> Result<Vec<f64>, JointError> {
+        self.values_of("position", &self.positions, names)
+    }
+
+    /// Returns the velocities of the given joints, in the order of `names`.
+    pub fn velocities_of(&self, names: &[&str]) -> Result<Vec<f64>, JointError> {
+        self.values_of("velocity", &self.velocities, names)
+    }
+
+    /// Returns the efforts of the given joints, in the order of `names`.
+    pub fn efforts_of(&self, names: &[&str]) -> Result<Vec<f64>, JointError> {
+        self.values_of("effort", &self.efforts, names)
+    }
+
+    fn value(values: &[f64], index: usize) -> Option<f64> {
+        values.get(index).copied()
+    }
+
+    fn values_of(
+        &self,
+        field: &'static str,
+        values: &[f64],
+        names: &[&str],
+    ) -> Result<Vec<f64>, JointError> {
+        check_length(field, values, self.len(), false)?;
+        names
+            .iter()
+            .map(|&name| {
+                self.index_of(name)
+                    .map(|index| values[index])
+                    .ok_or_else(|| JointError::UnknownJoint(name.to_owned()))
+            })
+            .collect()
+    }
+}
+
+impl Interpolate for JointStates {
+    /// Interpolates the values of each joint of `self` with the values of the joint with the same
+    /// name in `other`.
+    ///
+    /// Joints that are not in `other`, and quantities that `other` does not report, keep the
+    /// values of `self`.
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        let interpolate_values = |values: &[f64], other_values: &[f64]| -> Vec<f64> {
+            values
+                .iter()
+                .zip(&self.names)
+                .map(|(value, name)| {
+                    match other
+                        .index_of(name)
+                        .and_then(|index| other_values.get(index))
+                    {
+                        Some(other_value) => value.interpolate(other_value, ratio),
+                        None => *value,
+                    }
+                })
+                .collect()
+        };
+        Self {
+            names: self.names.clone(),
+            positions: interpolate_values(&self.positions, &other.positions),
+            velocities: interpolate_values(&self.velocities, &other.velocities),
+            efforts: interpolate_values(&self.efforts, &other.efforts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_validate_joint_state() {
+        let mut joint_state = JointState {
+            name: names(&["a", "b"]),
+            position: vec![1.0, 2.0],
+            ..Default::default()
+        };
+        assert_eq!(validate_joint_state(&joint_state), Ok(()));
+        joint_state.effort = vec![0.5];
+        assert_eq!(
+            validate_joint_state(&joint_state),
+            Err(JointError::LengthMismatch {
+                field: "effort",
+                expected: 2,
+                actual: 1
+            })
+        );
+        joint_state.effort.clear();
+        joint_state.name = names(&["a", "a"]);
+        assert_eq!(
+            validate_joint_state(&joint_state),
+            Err(JointError::DuplicateName(String::from("a")))
+        );
+        joint_state.name = names(&["a", ""]);
+        assert_eq!(
+            validate_joint_state(&joint_state),
+            Err(JointError::EmptyName)
+        );
+    }
+
+    #[test]
+    fn test_lookup_by_name() {
+        let states = JointStates::new(
+            names(&["wrist", "elbow", "shoulder"]),
+            vec![3.0, 2.0, 1.0],
+            vec![0.3, 0.2, 0.1],
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(states.index_of("elbow"), Some(1));
+        assert_eq!(states.position("shoulder"), Some(1.0));
+        assert_eq!(states.velocity("wrist"), Some(0.3));
+        assert_eq!(states.effort("wrist"), None);
+        assert_eq!(states.position("gripper"), None);
+        assert_eq!(
+            states.positions_of(&["shoulder", "elbow"]),
+            Ok(vec![1.0, 2.0])
+        );
+        assert_eq!(
+            states.positions_of(&["gripper"]),
+            Err(JointError::UnknownJoint(String::from("gripper")))
+        );
+        assert!(matches!(
+            states.efforts_of(&["wrist"]),
+            Err(JointError::LengthMismatch {
+                field: "effort",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        let joint_state = JointState {
+            name: names(&["a"]),
+            position: vec![1.0],
+            velocity: vec![2.0],
+            effort: vec![3.0],
+            ..Default::default()
+        };
+        let states = JointStates::from_message(&joint_state).unwrap();
+        assert_eq!(states.to_message(Header::default()), joint_state);
+    }
+
+    #[test]
+    fn test_interpolate_by_name() {
+        let start = JointStates::new(
+            names(&["a", "b", "c"]),
+            vec![0.0, 10.0, 5.0],
+            vec![1.0, 1.0, 1.0],
+            vec![],
+        )
+        .unwrap();
+        // A different order, without joint "c" and without velocities
+        let end = JointStates::new(names(&["b", "a"]), vec![20.0, 4.0], vec![], vec![]).unwrap();
+        let middle = start.interpolate(&end, 0.25);
+        assert_eq!(middle.names(), start.names());
+        assert_eq!(middle.positions(), &[1.0, 12.5, 5.0]);
+        assert_eq!(middle.velocities(), &[1.0, 1.0, 1.0]);
+        assert!(middle.efforts().is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{check_length, check_names, JointError, JointStates};
+
+use std::time::Duration;
+
+use rclrs::Interpolate;
+use trajectory_msgs::msg::{JointTrajectory, JointTrajectoryPoint};
+
+/// Checks a joint trajectory message before it is executed.
+///
+/// The message is valid if
+/// - its joint names are not empty and unique,
+/// - it has at least one point,
+/// - the `positions` of each point have one value per joint,
+/// - the `velocities`, `accelerations` and `effort` of each point are either empty or have one
+///   value per joint,
+/// - the `time_from_start` of the points is not negative and strictly increasing.
+pub fn validate_joint_trajectory(trajectory: &JointTrajectory) -> Result<(), JointError> {
+    check_names(&trajectory.joint_names)?;
+    if trajectory.points.is_empty() {
+        return Err(JointError::EmptyTrajectory);
+    }
+    let joint_count = trajectory.joint_names.len();
+    let mut previous = None;
+    for (index, point) in trajectory.points.iter().enumerate() {
+        check_length("positions", &point.positions, joint_count, false)?;
+        check_length("velocities", &point.velocities, joint_count, true)?;
+        check_length("accelerations", &point.accelerations, joint_count, true)?;
+        check_length("effort", &point.effort, joint_count, true)?;
+        let time = time_from_start(point).ok_or(JointError::InvalidTime { index })?;
+        if matches!(previous, Some(previous) if time <= previous) {
+            return Err(JointError::InvalidTime { index });
+        }
+        previous = Some(time);
+    }
+    Ok(())
+}
+
+// Returns the time from start of a point, or None if it is negative.
+fn time_from_start(point: &JointTrajectoryPoint) -> Option<Duration> {
+    let sec = u64::try_from(point.time_from_start.sec).ok()?;
+    Some(Duration::new(sec, point.time_from_start.nanosec))
+}
+
+/// A joint trajectory that can be sampled at any time from its start.
+///
+/// Positions, velocities and efforts between two points are linearly interpolated, with the
+/// [`Interpolate`] implementation of [`JointStates`]. Accelerations are checked, but not sampled.
+///
+/// ```
+/// # use builtin_interfaces::msg::Duration as DurationMsg;
+/// # use rclrs_joints::Trajectory;
+/// # use std::time::Duration;
+/// # use trajectory_msgs::msg::{JointTrajectory, JointTrajectoryPoint};
+/// let point = |position, sec| JointTrajectoryPoint {
+///     positions: vec![position],
+///     time_from_start: DurationMsg { sec, nanosec: 0 },
+///     ..Default::default()
+/// };
+/// let message = JointTrajectory {
+///     joint_names: vec![String::from("elbow")],
+///     points: vec![point(0.0, 1), point(2.0, 3)],
+///     ..Default::default()
+/// };
+/// let trajectory = Trajectory::from_message(&message).unwrap();
+/// assert_eq!(trajectory.sample(Duration::from_secs(2)).position("elbow"), Some(1.0));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trajectory {
+    // Sorted by time from start, with at least one point.
+    points: Vec<(Duration, JointStates)>,
+}
+
+impl Trajectory {
+    /// Returns the trajectory of a message, see [`validate_joint_trajectory`].
+    pub fn from_message(trajectory: &JointTrajectory) -> Result<Self, JointError> {
+        validate_joint_trajectory(trajectory)?;
+        let points = trajectory
+            .points
+            .iter()
+            .map(|point| {
+                // The point has been validated, so neither the time nor the states can fail.
+                let time = time_from_start(point).unwrap_or_default();
+                let states = JointStates::new(
+                    trajectory.joint_names.clone(),
+                    point.positions.clone(),
+                    point.velocities.clone(),
+                    point.effort.clone(),
+                )?;
+                Ok((time, states))
+            })
+            .collect::<Result<_, JointError>>()?;
+        Ok(Self { points })
+    }
+
+    /// Returns the names of the joints.
+    pub fn joint_names(&self) -> &[String] {
+        self.points[0].1.names()
+    }
+
+    /// Returns the time from start of the last point.
+    pub fn duration(&self) -> Duration {
+        self.points[self.points.len() - 1].0
+    }
+
+    /// Returns the joint states at the given time from the start of the trajectory.
+    ///
+    /// Before the first point, the trajectory holds the first point, and after the last point it
+    /// holds the last point. A controller that should move smoothly from the current state to the
+    /// first point can insert the current state as a point at zero.
+    pub fn sample(&self, time_from_start: Duration) -> JointStates {
+        let index = self
+            .points
+            .partition_point(|(time, _)| *time <= time_from_start);
+        if index == 0 {
+            return self.points[0].1.clone();
+        }
+        let (before_time, before) = &self.points[index - 1];
+        let (after_time, after) = match self.points.get(index) {
+            Some(point) => point,
+            None => return before.clone(),
+        };
+        let ratio = (time_from_start - *before_time).as_secs_f64()
+            / (*after_time - *before_time).as_secs_f64();
+        before.interpolate(after, ratio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use builtin_interfaces::msg::Duration as DurationMsg;
+
+    fn point(positions: Vec<f64>, sec: i32, nanosec: u32) -> JointTrajectoryPoint {
+        JointTrajectoryPoint {
+            positions,
+            time_from_start: DurationMsg { sec, nanosec },
+            ..Default::default()
+        }
+    }
+
+    fn trajectory(points: Vec<JointTrajectoryPoint>) -> JointTrajectory {
+        JointTrajectory {
+            joint_names: vec![String::from("a"), String::from("b")],
+            points,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_joint_trajectory() {
+        assert_eq!(
+            validate_joint_trajectory(&trajectory(vec![])),
+            Err(JointError::EmptyTrajectory)
+        );
+        assert_eq!(
+            validate_joint_trajectory(&trajectory(vec![point(vec![0.0], 1, 0)])),
+            Err(JointError::LengthMismatch {
+                field: "positions",
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            validate_joint_trajectory(&trajectory(vec![point(vec![0.0, 0.0], -1, 0)])),
+            Err(JointError::InvalidTime { index: 0 })
+        );
+        assert_eq!(
+            validate_joint_trajectory(&trajectory(vec![
+                point(vec![0.0, 0.0], 1, 0),
+                point(vec![1.0, 1.0], 1, 0),
+            ])),
+            Err(JointError::InvalidTime { index: 1 })
+        );
+        let mut with_velocities = trajectory(vec![point(vec![0.0, 0.0], 0, 0)]);
+        with_velocities.points[0].velocities = vec![1.0];
+        assert!(matches!(
+            validate_joint_trajectory(&with_velocities),
+            Err(JointError::LengthMismatch {
+                field: "velocities",
+                ..
+            })
+        ));
+        assert_eq!(
+            validate_joint_trajectory(&trajectory(vec![
+                point(vec![0.0, 0.0], 0, 500_000_000),
+                point(vec![1.0, 1.0], 1, 0),
+            ])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sample() {
+        let trajectory = Trajectory::from_message(&trajectory(vec![
+            point(vec![0.0, 10.0], 1, 0),
+            point(vec![1.0, 20.0], 2, 0),
+            point(vec![3.0, 20.0], 4, 0),
+        ]))
+        .unwrap();
+        assert_eq!(trajectory.joint_names(), &["a", "b"]);
+        assert_eq!(trajectory.duration(), Duration::from_secs(4));
+        let positions = |millis| {
+            trajectory
+                .sample(Duration::from_millis(millis))
+                .positions()
+                .to_vec()
+        };
+        assert_eq!(positions(0), [0.0, 10.0]);
+        assert_eq!(positions(1000), [0.0, 10.0]);
+        assert_eq!(positions(1500), [0.5, 15.0]);
+        assert_eq!(positions(2000), [1.0, 20.0]);
+        assert_eq!(positions(3000), [2.0, 20.0]);
+        assert_eq!(positions(5000), [3.0, 20.0]);
+    }
+}
@@ -0,0 +1,105 @@
+// The tolerance for the symmetry of a covariance matrix, relative to its largest element.
+const SYMMETRY_TOLERANCE: f64 = 1e-9;
+
+/// Returns whether a covariance is marked as unknown.
+///
+/// By the convention of `sensor_msgs/Imu`, a covariance whose first element is `-1` means that
+/// the sensor does not provide the corresponding value at all, e.g. IMUs without an orientation
+/// estimate. Such values must be ignored by estimators.
+pub fn is_covariance_unknown(covariance: &[f64]) -> bool {
+    covariance.first() == Some(&-1.0)
+}
+
+/// Returns whether all elements of a covariance are zero.
+///
+/// A zero covariance usually means that the publisher did not fill it in, rather than that the
+/// value is perfectly certain.
+pub fn is_covariance_zero(covariance: &[f64]) -> bool {
+    covariance.iter().all(|&value| value == 0.0)
+}
+
+/// Checks that a row-major covariance matrix is symmetric with non-negative, finite variances.
+///
+/// The matrix is square, e.g. 9 elements for the 3x3 covariances of `sensor_msgs/Imu` or 36
+/// elements for the 6x6 covariances of `nav_msgs/Odometry`.
+pub fn check_covariance(covariance: &[f64]) -> Result<(), &'static str> {
+    let dimension = covariance_dimension(covariance).ok_or("The matrix is not square")?;
+    if !covariance.iter().all(|value| value.is_finite()) {
+        return Err("The matrix has non-finite elements");
+    }
+    let scale = covariance
+        .iter()
+        .fold(0.0_f64, |max, value| max.max(value.abs()));
+    for row in 0..dimension {
+        if covariance[row * dimension + row] < 0.0 {
+            return Err("The matrix has negative variances");
+        }
+        for column in (row + 1)..dimension {
+            let upper = covariance[row * dimension + column];
+            let lower = covariance[column * dimension + row];
+            if (upper - lower).abs() > SYMMETRY_TOLERANCE * scale {
+                return Err("The matrix is not symmetric");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the variances, i.e. the diagonal, of a row-major covariance matrix.
+///
+/// # Panics
+/// When the number of elements is not a square number.
+pub fn covariance_diagonal(covariance: &[f64]) -> Vec<f64> {
+    let dimension = covariance_dimension(covariance).expect("The covariance matrix is not square");
+    (0..dimension)
+        .map(|i| covariance[i * dimension + i])
+        .collect()
+}
+
+/// Returns a 3x3 covariance matrix with the given variances, e.g. for the fields of an IMU
+/// message.
+pub fn diagonal_covariance3(variances: [f64; 3]) -> [f64; 9] {
+    let mut covariance = [0.0; 9];
+    for (i, variance) in variances.into_iter().enumerate() {
+        covariance[i * 3 + i] = variance;
+    }
+    covariance
+}
+
+/// Returns a 6x6 covariance matrix with the given variances, e.g. for the pose or twist of an
+/// odometry message.
+///
+/// The variances are in the order x, y, z, rotation about x, rotation about y, rotation about z.
+pub fn diagonal_covariance6(variances: [f64; 6]) -> [f64; 36] {
+    let mut covariance = [0.0; 36];
+    for (i, variance) in variances.into_iter().enumerate() {
+        covariance[i * 6 + i] = variance;
+    }
+    covariance
+}
+
+fn covariance_dimension(covariance: &[f64]) -> Option<usize> {
+    let dimension = (covariance.len() as f64).sqrt().round() as usize;
+    (dimension * dimension == covariance.len()).then_some(dimension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_covariance() {
+        let mut covariance = diagonal_covariance3([1.0, 2.0, 3.0]);
+        assert_eq!(check_covariance(&covariance), Ok(()));
+        assert_eq!(covariance_diagonal(&covariance), [1.0, 2.0, 3.0]);
+        covariance[1] = 0.5;
+        assert!(check_covariance(&covariance).is_err());
+        covariance[3] = 0.5;
+        assert_eq!(check_covariance(&covariance), Ok(()));
+        covariance[0] = -1.0;
+        assert!(check_covariance(&covariance).is_err());
+        assert!(is_covariance_unknown(&covariance));
+        assert!(check_covariance(&[1.0, 2.0]).is_err());
+        assert!(is_covariance_zero(&[0.0; 36]));
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt::{self, Display};
+
+/// The reason why an odometry or IMU message is not valid, see [`validate_odometry`][1] and
+/// [`validate_imu`][2].
+///
+/// [1]: crate::validate_odometry
+/// [2]: crate::validate_imu
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// A frame ID is empty.
+    EmptyFrameId(&'static str),
+    /// A frame ID is not the expected one.
+    UnexpectedFrameId {
+        /// The name of the field.
+        field: &'static str,
+        /// The frame ID in the message.
+        actual: String,
+        /// The expected frame ID.
+        expected: String,
+    },
+    /// A value is NaN or infinite.
+    NonFinite(&'static str),
+    /// An orientation quaternion does not have unit length.
+    UnnormalizedQuaternion {
+        /// The name of the field.
+        field: &'static str,
+        /// The length of the quaternion.
+        norm: f64,
+    },
+    /// A covariance matrix is not a valid covariance matrix.
+    InvalidCovariance {
+        /// The name of the field.
+        field: &'static str,
+        /// A description of the problem.
+        reason: &'static str,
+    },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyFrameId(field) => write!(f, "The frame ID '{}' is empty", field),
+            Self::UnexpectedFrameId {
+                field,
+                actual,
+                expected,
+            } => write!(
+                f,
+                "The frame ID '{}' is '{}', but '{}' was expected",
+                field, actual, expected
+            ),
+            Self::NonFinite(field) => write!(f, "The field '{}' is not finite", field),
+            Self::UnnormalizedQuaternion { field, norm } => write!(
+                f,
+                "The quaternion '{}' has a length of {} instead of 1",
+                field, norm
+            ),
+            Self::InvalidCovariance { field, reason } => {
+                write!(f, "The covariance '{}' is invalid: {}", field, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
@@ -0,0 +1,133 @@
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use crate::angle_difference;
+
+/// A first-order low-pass filter, e.g. for smoothing noisy angular velocities of an IMU.
+///
+/// The filter is parameterized with a time constant rather than a fixed smoothing factor, so
+/// that it behaves the same regardless of the rate of the messages, and when messages are
+/// dropped.
+#[derive(Clone, Debug)]
+pub struct LowPassFilter {
+    time_constant: Duration,
+    value: Option<f64>,
+}
+
+impl LowPassFilter {
+    /// Creates a filter with the given time constant.
+    ///
+    /// After a step in the input, the output reaches about 63% of the step after one time
+    /// constant. A time constant of zero disables the filter.
+    pub fn new(time_constant: Duration) -> Self {
+        Self {
+            time_constant,
+            value: None,
+        }
+    }
+
+    /// Adds a sample that was taken `elapsed` after the previous one, and returns the filtered
+    /// value.
+    ///
+    /// The first sample is returned unchanged.
+    pub fn update(&mut self, sample: f64, elapsed: Duration) -> f64 {
+        let value = match self.value {
+            Some(value) if !self.time_constant.is_zero() => {
+                let alpha = 1.0 - (-elapsed.as_secs_f64() / self.time_constant.as_secs_f64()).exp();
+                value + alpha * (sample - value)
+            }
+            _ => sample,
+        };
+        self.value = Some(value);
+        value
+    }
+
+    /// Returns the current filtered value, or `None` before the first sample.
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    /// Forgets the filtered value, so that the next sample is taken as is.
+    pub fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+/// Turns a sequence of yaw angles in `(-π, π]` into a continuous angle.
+///
+/// The yaw of an orientation jumps by 2π when a robot turns past π. Estimators that integrate
+/// or differentiate the yaw need a continuous angle instead, which counts full turns.
+///
+/// # Example
+/// ```
+/// # use rclrs_odometry::YawUnwrapper;
+/// let mut unwrapper = YawUnwrapper::new();
+/// assert_eq!(unwrapper.update(3.0), 3.0);
+/// let unwrapped = unwrapper.update(-3.0);
+/// assert!((unwrapped - (2.0 * std::f64::consts::PI - 3.0)).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct YawUnwrapper {
+    unwrapped: Option<f64>,
+}
+
+impl YawUnwrapper {
+    /// Creates an unwrapper without a previous angle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next yaw, and returns the continuous angle.
+    ///
+    /// Consecutive yaws are assumed to differ by less than π, i.e. the robot turns by less than
+    /// half a turn between two messages.
+    pub fn update(&mut self, yaw: f64) -> f64 {
+        let unwrapped = match self.unwrapped {
+            Some(previous) => previous + angle_difference(previous, yaw),
+            None => yaw,
+        };
+        self.unwrapped = Some(unwrapped);
+        unwrapped
+    }
+
+    /// Returns the number of full turns since the first yaw, counterclockwise being positive.
+    pub fn turns(&self) -> i64 {
+        self.unwrapped
+            .map(|unwrapped| ((unwrapped + PI) / (2.0 * PI)).floor() as i64)
+            .unwrap_or(0)
+    }
+
+    /// Forgets the previous angle.
+    pub fn reset(&mut self) {
+        self.unwrapped = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_pass_filter() {
+        let mut filter = LowPassFilter::new(Duration::from_secs(1));
+        assert_eq!(filter.update(1.0, Duration::ZERO), 1.0);
+        let value = filter.update(0.0, Duration::from_secs(1));
+        assert!((value - (-1.0_f64).exp()).abs() < 1e-12);
+        filter.reset();
+        assert_eq!(filter.value(), None);
+
+        let mut disabled = LowPassFilter::new(Duration::ZERO);
+        disabled.update(1.0, Duration::ZERO);
+        assert_eq!(disabled.update(5.0, Duration::from_millis(10)), 5.0);
+    }
+
+    #[test]
+    fn test_yaw_unwrapper() {
+        let mut unwrapper = YawUnwrapper::new();
+        for i in 0..40 {
+            unwrapper.update(crate::normalize_angle(i as f64 * 0.5));
+        }
+        assert!((unwrapper.update(crate::normalize_angle(20.0)) - 20.0).abs() < 1e-9);
+        assert_eq!(unwrapper.turns(), 3);
+    }
+}
@@ -0,0 +1,27 @@
+#![warn(missing_docs)]
+//! Helpers for odometry and IMU data in state estimators written with `rclrs`.
+//!
+//! This crate extracts angles from the quaternions in [`nav_msgs::msg::Odometry`] and
+//! [`sensor_msgs::msg::Imu`] messages, checks the messages for the conventions of
+//! [REP 103][1] and [REP 105][2] before they are fused, and provides small filters for the
+//! values in them.
+//!
+//! A [`Pose2D`] taken from odometry messages can be kept in an [`rclrs::TimedBuffer`], to look
+//! up the pose of a mobile robot at the stamp of another sensor reading.
+//!
+//! [1]: https://www.ros.org/reps/rep-0103.html
+//! [2]: https://www.ros.org/reps/rep-0105.html
+
+mod covariance;
+mod error;
+mod filter;
+mod orientation;
+mod pose;
+mod validation;
+
+pub use covariance::*;
+pub use error::*;
+pub use filter::*;
+pub use orientation::*;
+pub use pose::*;
+pub use validation::*;
@@ -0,0 +1,93 @@
+use std::f64::consts::PI;
+
+use geometry_msgs::msg::Quaternion;
+
+/// Returns the yaw of an orientation, i.e. the rotation around the z axis, in radians.
+///
+/// This is the heading of a mobile robot in its odometry frame. The result is in `[-π, π]`.
+///
+/// # Example
+/// ```
+/// # use rclrs_odometry::{quaternion_from_yaw, yaw_from_quaternion};
+/// let q = quaternion_from_yaw(1.0);
+/// assert!((yaw_from_quaternion(&q) - 1.0).abs() < 1e-12);
+/// ```
+pub fn yaw_from_quaternion(q: &Quaternion) -> f64 {
+    roll_pitch_yaw_from_quaternion(q).2
+}
+
+/// Returns the roll, pitch and yaw of an orientation, in radians.
+///
+/// The angles are the extrinsic rotations around the fixed x, y and z axes, in that order, as in
+/// `tf2::Matrix3x3::getRPY()`.
+pub fn roll_pitch_yaw_from_quaternion(q: &Quaternion) -> (f64, f64, f64) {
+    let roll = (2.0 * (q.w * q.x + q.y * q.z)).atan2(1.0 - 2.0 * (q.x * q.x + q.y * q.y));
+    // Clamped, since rounding can push the argument slightly out of the domain of asin
+    let pitch = (2.0 * (q.w * q.y - q.z * q.x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (q.w * q.z + q.x * q.y)).atan2(1.0 - 2.0 * (q.y * q.y + q.z * q.z));
+    (roll, pitch, yaw)
+}
+
+/// Returns the orientation with the given yaw, and zero roll and pitch.
+pub fn quaternion_from_yaw(yaw: f64) -> Quaternion {
+    quaternion_from_roll_pitch_yaw(0.0, 0.0, yaw)
+}
+
+/// Returns the orientation with the given roll, pitch and yaw, in radians.
+///
+/// This is the inverse of [`roll_pitch_yaw_from_quaternion`].
+pub fn quaternion_from_roll_pitch_yaw(roll: f64, pitch: f64, yaw: f64) -> Quaternion {
+    let (sr, cr) = (roll / 2.0).sin_cos();
+    let (sp, cp) = (pitch / 2.0).sin_cos();
+    let (sy, cy) = (yaw / 2.0).sin_cos();
+    Quaternion {
+        x: sr * cp * cy - cr * sp * sy,
+        y: cr * sp * cy + sr * cp * sy,
+        z: cr * cp * sy - sr * sp * cy,
+        w: cr * cp * cy + sr * sp * sy,
+    }
+}
+
+/// Returns the length of a quaternion, which is 1 for valid orientations.
+pub fn quaternion_norm(q: &Quaternion) -> f64 {
+    (q.x * q.x + q.y * q.y + q.z * q.z + q.w * q.w).sqrt()
+}
+
+/// Wraps an angle into `(-π, π]`.
+pub fn normalize_angle(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(2.0 * PI);
+    if wrapped > PI {
+        wrapped - 2.0 * PI
+    } else {
+        wrapped
+    }
+}
+
+/// Returns the shortest signed rotation from the angle `from` to the angle `to`, in `(-π, π]`.
+pub fn angle_difference(from: f64, to: f64) -> f64 {
+    normalize_angle(to - from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roll_pitch_yaw_round_trip() {
+        for &(roll, pitch, yaw) in &[(0.0, 0.0, 0.0), (0.1, -0.2, 3.0), (-1.0, 0.5, -2.5)] {
+            let q = quaternion_from_roll_pitch_yaw(roll, pitch, yaw);
+            assert!((quaternion_norm(&q) - 1.0).abs() < 1e-12);
+            let (r, p, y) = roll_pitch_yaw_from_quaternion(&q);
+            assert!((r - roll).abs() < 1e-12);
+            assert!((p - pitch).abs() < 1e-12);
+            assert!((y - yaw).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_normalize_angle() {
+        assert!((normalize_angle(3.0 * PI) - PI).abs() < 1e-12);
+        assert!((normalize_angle(-0.5) + 0.5).abs() < 1e-12);
+        assert!((angle_difference(3.0, -3.0) - (2.0 * PI - 6.0)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use builtin_interfaces::msg::Time;
+use geometry_msgs::msg::Pose;
+use nav_msgs::msg::Odometry;
+use rclrs::Interpolate;
+
+use crate::{angle_difference, normalize_angle, quaternion_from_yaw, yaw_from_quaternion};
+
+/// The pose of a mobile robot in the plane.
+///
+/// Poses can be kept in an [`rclrs::TimedBuffer`], which interpolates the yaw along the shorter
+/// direction of rotation:
+///
+/// ```
+/// # use rclrs::TimedBuffer;
+/// # use rclrs_odometry::Pose2D;
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// let mut poses = TimedBuffer::new(Duration::from_secs(5));
+/// poses.insert(UNIX_EPOCH + Duration::from_secs(1), Pose2D::new(0.0, 0.0, 3.0));
+/// poses.insert(UNIX_EPOCH + Duration::from_secs(2), Pose2D::new(1.0, 0.0, -3.0));
+/// let pose = poses.lookup_at(UNIX_EPOCH + Duration::from_millis(1500)).unwrap();
+/// assert!((pose.x - 0.5).abs() < 1e-12);
+/// assert!(pose.yaw.abs() > 3.0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pose2D {
+    /// The x coordinate, in meters.
+    pub x: f64,
+    /// The y coordinate, in meters.
+    pub y: f64,
+    /// The heading, in radians in `(-π, π]`.
+    pub yaw: f64,
+}
+
+impl Pose2D {
+    /// Creates a pose, normalizing the yaw.
+    pub fn new(x: f64, y: f64, yaw: f64) -> Self {
+        Self {
+            x,
+            y,
+            yaw: normalize_angle(yaw),
+        }
+    }
+
+    /// Returns the planar part of a pose, dropping the z coordinate, roll and pitch.
+    pub fn from_pose(pose: &Pose) -> Self {
+        Self::new(
+            pose.position.x,
+            pose.position.y,
+            yaw_from_quaternion(&pose.orientation),
+        )
+    }
+
+    /// Returns the planar pose of an odometry message.
+    pub fn from_odometry(odometry: &Odometry) -> Self {
+        Self::from_pose(&odometry.pose.pose)
+    }
+
+    /// Returns the pose as a `geometry_msgs/Pose`, with zero z coordinate, roll and pitch.
+    pub fn to_pose(&self) -> Pose {
+        let mut pose = Pose::default();
+        pose.position.x = self.x;
+        pose.position.y = self.y;
+        pose.orientation = quaternion_from_yaw(self.yaw);
+        pose
+    }
+}
+
+impl Interpolate for Pose2D {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        Self::new(
+            self.x.interpolate(&other.x, ratio),
+            self.y.interpolate(&other.y, ratio),
+            self.yaw + angle_difference(self.yaw, other.yaw) * ratio,
+        )
+    }
+}
+
+/// Converts the stamp of a message to a [`SystemTime`], e.g. for an [`rclrs::TimedBuffer`].
+///
+/// Stamps before the epoch, which are invalid in ROS, are clamped to the epoch.
+pub fn stamp_to_system_time(stamp: &Time) -> SystemTime {
+    match u64::try_from(stamp.sec) {
+        Ok(sec) => UNIX_EPOCH + Duration::new(sec, stamp.nanosec),
+        Err(_) => UNIX_EPOCH,
+    }
+}
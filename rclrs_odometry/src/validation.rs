@@ -0,0 +1,188 @@
+use crate::{check_covariance, is_covariance_unknown, quaternion_norm, ValidationError};
+
+use geometry_msgs::msg::{Quaternion, Vector3};
+use nav_msgs::msg::Odometry;
+use sensor_msgs::msg::Imu;
+
+// How far the length of an orientation quaternion may be from 1. This is the same tolerance
+// as tf2 uses for warning about unnormalized quaternions.
+const QUATERNION_NORM_TOLERANCE: f64 = 1e-2;
+
+/// Checks an odometry message before it is used by an estimator.
+///
+/// The message is valid if
+/// - its `header.frame_id` and `child_frame_id` are not empty,
+/// - all of its values are finite,
+/// - its orientation is a unit quaternion,
+/// - its covariances are symmetric with non-negative variances.
+///
+/// To also check that the message is in the expected frames, see [`check_odometry_frames`].
+pub fn validate_odometry(odometry: &Odometry) -> Result<(), ValidationError> {
+    if odometry.header.frame_id.is_empty() {
+        return Err(ValidationError::EmptyFrameId("header.frame_id"));
+    }
+    if odometry.child_frame_id.is_empty() {
+        return Err(ValidationError::EmptyFrameId("child_frame_id"));
+    }
+    let position = &odometry.pose.pose.position;
+    if ![position.x, position.y, position.z]
+        .iter()
+        .all(|value| value.is_finite())
+    {
+        return Err(ValidationError::NonFinite("pose.pose.position"));
+    }
+    check_orientation(&odometry.pose.pose.orientation, "pose.pose.orientation")?;
+    check_vector(&odometry.twist.twist.linear, "twist.twist.linear")?;
+    check_vector(&odometry.twist.twist.angular, "twist.twist.angular")?;
+    check_covariance_field(&odometry.pose.covariance, "pose.covariance")?;
+    check_covariance_field(&odometry.twist.covariance, "twist.covariance")?;
+    Ok(())
+}
+
+/// Checks that an odometry message is in the given frames, e.g. `odom` and `base_link`.
+///
+/// Odometry from the wrong source, e.g. a second robot in the same namespace, is a common cause
+/// of estimators that jump.
+pub fn check_odometry_frames(
+    odometry: &Odometry,
+    frame_id: &str,
+    child_frame_id: &str,
+) -> Result<(), ValidationError> {
+    check_frame_id(&odometry.header.frame_id, frame_id, "header.frame_id")?;
+    check_frame_id(&odometry.child_frame_id, child_frame_id, "child_frame_id")
+}
+
+/// Checks an IMU message before it is used by an estimator.
+///
+/// The message is valid if
+/// - its `header.frame_id` is not empty,
+/// - all of its values are finite,
+/// - its orientation is a unit quaternion, unless it is marked as unknown,
+/// - its covariances are symmetric with non-negative variances, unless they are marked as
+///   unknown.
+///
+/// Values are marked as unknown with a `-1` in the first element of their covariance, see
+/// [`is_covariance_unknown`].
+pub fn validate_imu(imu: &Imu) -> Result<(), ValidationError> {
+    if imu.header.frame_id.is_empty() {
+        return Err(ValidationError::EmptyFrameId("header.frame_id"));
+    }
+    if !is_covariance_unknown(&imu.orientation_covariance) {
+        check_orientation(&imu.orientation, "orientation")?;
+        check_covariance_field(&imu.orientation_covariance, "orientation_covariance")?;
+    }
+    if !is_covariance_unknown(&imu.angular_velocity_covariance) {
+        check_vector(&imu.angular_velocity, "angular_velocity")?;
+        check_covariance_field(
+            &imu.angular_velocity_covariance,
+            "angular_velocity_covariance",
+        )?;
+    }
+    if !is_covariance_unknown(&imu.linear_acceleration_covariance) {
+        check_vector(&imu.linear_acceleration, "linear_acceleration")?;
+        check_covariance_field(
+            &imu.linear_acceleration_covariance,
+            "linear_acceleration_covariance",
+        )?;
+    }
+    Ok(())
+}
+
+fn check_frame_id(
+    actual: &str,
+    expected: &str,
+    field: &'static str,
+) -> Result<(), ValidationError> {
+    // tf2 ignores a leading slash in frame IDs
+    if actual.trim_start_matches('/') == expected.trim_start_matches('/') {
+        Ok(())
+    } else {
+        Err(ValidationError::UnexpectedFrameId {
+            field,
+            actual: actual.to_owned(),
+            expected: expected.to_owned(),
+        })
+    }
+}
+
+fn check_vector(vector: &Vector3, field: &'static str) -> Result<(), ValidationError> {
+    if [vector.x, vector.y, vector.z]
+        .iter()
+        .all(|value| value.is_finite())
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::NonFinite(field))
+    }
+}
+
+fn check_orientation(orientation: &Quaternion, field: &'static str) -> Result<(), ValidationError> {
+    let norm = quaternion_norm(orientation);
+    if !norm.is_finite() {
+        return Err(ValidationError::NonFinite(field));
+    }
+    if (norm - 1.0).abs() > QUATERNION_NORM_TOLERANCE {
+        return Err(ValidationError::UnnormalizedQuaternion { field, norm });
+    }
+    Ok(())
+}
+
+fn check_covariance_field(covariance: &[f64], field: &'static str) -> Result<(), ValidationError> {
+    check_covariance(covariance)
+        .map_err(|reason| ValidationError::InvalidCovariance { field, reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quaternion_from_yaw;
+
+    fn odometry() -> Odometry {
+        let mut odometry = Odometry::default();
+        odometry.header.frame_id = "odom".into();
+        odometry.child_frame_id = "base_link".into();
+        odometry.pose.pose.orientation = quaternion_from_yaw(0.5);
+        odometry
+    }
+
+    #[test]
+    fn test_validate_odometry() {
+        let mut odometry = odometry();
+        assert_eq!(validate_odometry(&odometry), Ok(()));
+        assert_eq!(
+            check_odometry_frames(&odometry, "/odom", "base_link"),
+            Ok(())
+        );
+        assert!(check_odometry_frames(&odometry, "map", "base_link").is_err());
+
+        odometry.twist.twist.angular.z = f64::NAN;
+        assert_eq!(
+            validate_odometry(&odometry),
+            Err(ValidationError::NonFinite("twist.twist.angular"))
+        );
+
+        let mut odometry = self::odometry();
+        odometry.pose.pose.orientation.w = 2.0;
+        assert!(matches!(
+            validate_odometry(&odometry),
+            Err(ValidationError::UnnormalizedQuaternion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_imu() {
+        let mut imu = Imu::default();
+        imu.header.frame_id = "imu_link".into();
+        // An IMU without an orientation estimate
+        imu.orientation = Quaternion::default();
+        imu.orientation.w = 0.0;
+        imu.orientation_covariance[0] = -1.0;
+        assert_eq!(validate_imu(&imu), Ok(()));
+
+        imu.angular_velocity_covariance[0] = -0.1;
+        assert!(matches!(
+            validate_imu(&imu),
+            Err(ValidationError::InvalidCovariance { .. })
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt::{self, Display};
+
+/// The reason why a URDF could not be parsed, see [`Robot::from_urdf`][1].
+///
+/// [1]: crate::Robot::from_urdf
+#[derive(Clone, Debug, PartialEq)]
+pub enum UrdfError {
+    /// The document is not well-formed XML.
+    Xml(String),
+    /// The root element is not `<robot>`.
+    NotARobot,
+    /// A required attribute is missing.
+    MissingAttribute {
+        /// The name of the element.
+        element: &'static str,
+        /// The name of the attribute.
+        attribute: &'static str,
+    },
+    /// An attribute does not have a valid value.
+    InvalidAttribute {
+        /// The name of the element.
+        element: &'static str,
+        /// The name of the attribute.
+        attribute: &'static str,
+        /// The value of the attribute.
+        value: String,
+    },
+    /// A revolute or prismatic joint has no `<limit>` element.
+    MissingLimit(String),
+    /// Two links or two joints have the same name.
+    DuplicateName(String),
+    /// A joint refers to a link that does not exist.
+    UnknownLink {
+        /// The name of the joint.
+        joint: String,
+        /// The name of the link.
+        link: String,
+    },
+    /// A link is the child of more than one joint.
+    MultipleParents(String),
+    /// The links do not form a tree with a single root.
+    NotATree,
+}
+
+impl Display for UrdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(error) => write!(f, "The URDF is not valid XML: {}", error),
+            Self::NotARobot => write!(f, "The root element of the URDF is not <robot>"),
+            Self::MissingAttribute { element, attribute } => write!(
+                f,
+                "The attribute '{}' of a <{}> element is missing",
+                attribute, element
+            ),
+            Self::InvalidAttribute {
+                element,
+                attribute,
+                value,
+            } => write!(
+                f,
+                "The attribute '{}' of a <{}> element has the invalid value '{}'",
+                attribute, element, value
+            ),
+            Self::MissingLimit(joint) => write!(f, "The joint '{}' has no <limit> element", joint),
+            Self::DuplicateName(name) => write!(f, "The name '{}' is used twice", name),
+            Self::UnknownLink { joint, link } => write!(
+                f,
+                "The joint '{}' refers to the unknown link '{}'",
+                joint, link
+            ),
+            Self::MultipleParents(link) => {
+                write!(f, "The link '{}' is the child of more than one joint", link)
+            }
+            Self::NotATree => write!(f, "The links do not form a tree with a single root"),
+        }
+    }
+}
+
+impl std::error::Error for UrdfError {}
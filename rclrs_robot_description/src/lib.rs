@@ -0,0 +1,21 @@
+#![warn(missing_docs)]
+//! Receives the robot description and parses its URDF into typed structures.
+//!
+//! Drivers and controllers of manipulators need the joints of the robot and their limits, which
+//! are described by the [URDF][1] that `robot_state_publisher` publishes on the latched
+//! `robot_description` topic. [`RobotDescriptionSubscription`] receives it, also when it was
+//! published before the subscription was created, and [`Robot::from_urdf`] parses it.
+//!
+//! Reading the URDF from the `robot_description` parameter is not supported yet, since `rclrs`
+//! has no parameters. A URDF that is read from a file can be passed to [`Robot::from_urdf`]
+//! directly.
+//!
+//! [1]: http://wiki.ros.org/urdf/XML
+
+mod error;
+mod subscription;
+mod urdf;
+
+pub use error::*;
+pub use subscription::*;
+pub use urdf::*;
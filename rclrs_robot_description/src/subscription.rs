@@ -0,0 +1,76 @@
+use crate::{Robot, UrdfError};
+
+use std::sync::{Arc, Mutex};
+
+use rclrs::{CallbackErrorPolicy, Node, QoSProfile, RclReturnCode, Subscription};
+use std_msgs::msg::String as StringMsg;
+
+/// The topic on which `robot_state_publisher` publishes the URDF, relative to the namespace of
+/// the node.
+pub const ROBOT_DESCRIPTION_TOPIC: &str = "robot_description";
+
+/// A subscription to the robot description, which parses each URDF that is received.
+///
+/// The topic is latched, so the subscription receives the robot description even if it was
+/// published before the subscription was created. It uses
+/// [`QoSProfile::transient_local_latched`], which works with publishers that use transient local
+/// durability, such as `robot_state_publisher` and [`rclrs::LatchedPublisher`].
+///
+/// A URDF that cannot be parsed is logged as a warning and does not replace the last robot.
+pub struct RobotDescriptionSubscription {
+    subscription: Arc<Subscription<StringMsg>>,
+    robot: Arc<Mutex<Option<Arc<Robot>>>>,
+}
+
+impl RobotDescriptionSubscription {
+    /// Creates a subscription to the [`ROBOT_DESCRIPTION_TOPIC`].
+    ///
+    /// The callback is called with each robot that is received, after it has been parsed.
+    pub fn new<F>(node: &mut Node, callback: F) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut(&Robot) + 'static,
+    {
+        Self::new_on_topic(node, ROBOT_DESCRIPTION_TOPIC, callback)
+    }
+
+    /// Creates a subscription to a robot description on another topic.
+    ///
+    /// # Panics
+    /// When the topic contains interior null bytes.
+    pub fn new_on_topic<F>(
+        node: &mut Node,
+        topic: &str,
+        mut callback: F,
+    ) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut(&Robot) + 'static,
+    {
+        let robot = Arc::new(Mutex::new(None));
+        let robot_in_callback = Arc::clone(&robot);
+        let subscription = node.create_fallible_subscription(
+            topic,
+            QoSProfile::transient_local_latched(1),
+            CallbackErrorPolicy::LogAndContinue,
+            move |message: StringMsg| -> Result<(), UrdfError> {
+                let parsed = Arc::new(Robot::from_urdf(&message.data)?);
+                *robot_in_callback.lock().unwrap() = Some(Arc::clone(&parsed));
+                callback(&parsed);
+                Ok(())
+            },
+        )?;
+        Ok(Self {
+            subscription,
+            robot,
+        })
+    }
+
+    /// Returns the last robot that was received, if any.
+    pub fn robot(&self) -> Option<Arc<Robot>> {
+        self.robot.lock().unwrap().clone()
+    }
+
+    /// Returns the underlying subscription.
+    pub fn subscription(&self) -> &Subscription<StringMsg> {
+        &self.subscription
+    }
+}
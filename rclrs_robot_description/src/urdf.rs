@@ -0,0 +1,569 @@
+use crate::UrdfError;
+
+use std::str::FromStr;
+
+use roxmltree::{Document, Node};
+
+/// A robot, as described by a URDF.
+///
+/// Only the kinematic structure is parsed: links, joints and their limits. Visual, collision and
+/// simulator specific elements are ignored.
+///
+/// ```
+/// # use rclrs_robot_description::{JointType, Robot};
+/// let robot = Robot::from_urdf(
+///     r#"<robot name="arm">
+///          <link name="base"/>
+///          <link name="upper_arm"/>
+///          <joint name="shoulder" type="revolute">
+///            <parent link="base"/>
+///            <child link="upper_arm"/>
+///            <axis xyz="0 0 1"/>
+///            <limit lower="-1.5" upper="1.5" effort="10" velocity="2"/>
+///          </joint>
+///        </robot>"#,
+/// )
+/// .unwrap();
+/// assert_eq!(robot.root_link().unwrap().name, "base");
+/// let shoulder = robot.joint("shoulder").unwrap();
+/// assert_eq!(shoulder.joint_type, JointType::Revolute);
+/// assert_eq!(shoulder.limit.unwrap().upper, 1.5);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Robot {
+    /// The name of the robot.
+    pub name: String,
+    /// The links, in the order of the URDF.
+    pub links: Vec<Link>,
+    /// The joints, in the order of the URDF.
+    pub joints: Vec<Joint>,
+}
+
+/// A rigid body of a robot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    /// The name of the link.
+    pub name: String,
+}
+
+/// A joint, which connects a parent link to a child link.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Joint {
+    /// The name of the joint.
+    pub name: String,
+    /// The type of the joint.
+    pub joint_type: JointType,
+    /// The name of the parent link.
+    pub parent: String,
+    /// The name of the child link.
+    pub child: String,
+    /// The transform from the parent link to the joint frame.
+    pub origin: Origin,
+    /// The axis of rotation or translation, in the joint frame. It defaults to `[1, 0, 0]`.
+    pub axis: [f64; 3],
+    /// The limits of the joint, which are required for revolute and prismatic joints.
+    pub limit: Option<JointLimits>,
+}
+
+/// The type of a [`Joint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JointType {
+    /// A hinge joint that rotates along the axis, within the limits.
+    Revolute,
+    /// A hinge joint that rotates along the axis without limits.
+    Continuous,
+    /// A sliding joint that moves along the axis, within the limits.
+    Prismatic,
+    /// A joint that cannot move.
+    Fixed,
+    /// A joint that allows motion in all six degrees of freedom.
+    Floating,
+    /// A joint that allows motion in the plane perpendicular to the axis.
+    Planar,
+}
+
+impl JointType {
+    /// Returns true for joints that move, i.e. all but fixed joints.
+    pub fn is_movable(&self) -> bool {
+        *self != Self::Fixed
+    }
+}
+
+impl FromStr for JointType {
+    type Err = UrdfError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "revolute" => Ok(Self::Revolute),
+            "continuous" => Ok(Self::Continuous),
+            "prismatic" => Ok(Self::Prismatic),
+            "fixed" => Ok(Self::Fixed),
+            "floating" => Ok(Self::Floating),
+            "planar" => Ok(Self::Planar),
+            _ => Err(UrdfError::InvalidAttribute {
+                element: "joint",
+                attribute: "type",
+                value: s.to_owned(),
+            }),
+        }
+    }
+}
+
+/// A transform, given by a translation and roll, pitch and yaw angles.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Origin {
+    /// The translation, in meters.
+    pub xyz: [f64; 3],
+    /// The rotation around the fixed x, y and z axes, in radians.
+    pub rpy: [f64; 3],
+}
+
+/// The limits of a [`Joint`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct JointLimits {
+    /// The lower position limit, in radians or meters. It defaults to zero.
+    pub lower: f64,
+    /// The upper position limit, in radians or meters. It defaults to zero.
+    pub upper: f64,
+    /// The maximum effort, in newton meters or newtons.
+    pub effort: f64,
+    /// The maximum velocity, in radians or meters per second.
+    pub velocity: f64,
+}
+
+impl JointLimits {
+    /// Returns the position clamped to the lower and upper limits.
+    pub fn clamp_position(&self, position: f64) -> f64 {
+        position.max(self.lower).min(self.upper)
+    }
+}
+
+impl Robot {
+    /// Parses a URDF.
+    ///
+    /// Besides the required elements and attributes, this checks that the names of links and of
+    /// joints are unique, and that the joints connect the links to a tree.
+    pub fn from_urdf(urdf: &str) -> Result<Self, UrdfError> {
+        let document = Document::parse(urdf).map_err(|error| UrdfError::Xml(error.to_string()))?;
+        let root = document.root_element();
+        if !root.has_tag_name("robot") {
+            return Err(UrdfError::NotARobot);
+        }
+        let mut robot = Robot {
+            name: required_attribute(root, "robot", "name")?.to_owned(),
+            links: Vec::new(),
+            joints: Vec::new(),
+        };
+        for element in root.children().filter(|node| node.is_element()) {
+            match element.tag_name().name() {
+                "link" => robot.links.push(Link {
+                    name: required_attribute(element, "link", "name")?.to_owned(),
+                }),
+                "joint" => robot.joints.push(parse_joint(element)?),
+                _ => {}
+            }
+        }
+        robot.check_tree()?;
+        Ok(robot)
+    }
+
+    /// Returns the link with the given name.
+    pub fn link(&self, name: &str) -> Option<&Link> {
+        self.links.iter().find(|link| link.name == name)
+    }
+
+    /// Returns the joint with the given name.
+    pub fn joint(&self, name: &str) -> Option<&Joint> {
+        self.joints.iter().find(|joint| joint.name == name)
+    }
+
+    /// Returns the link that is not the child of any joint.
+    ///
+    /// This is only `None` for a robot without links, since parsed robots are checked to be
+    /// trees.
+    pub fn root_link(&self) -> Option<&Link> {
+        self.links
+            .iter()
+            .find(|link| self.parent_joint(&link.name).is_none())
+    }
+
+    /// Returns the joint whose child is the given link, or `None` for the root link.
+    pub fn parent_joint(&self, link: &str) -> Option<&Joint> {
+        self.joints.iter().find(|joint| joint.child == link)
+    }
+
+    /// Returns the joints whose parent is the given link.
+    pub fn child_joints<'a>(&'a self, link: &'a str) -> impl Iterator<Item = &'a Joint> + 'a {
+        self.joints.iter().filter(move |joint| joint.parent == link)
+    }
+
+    /// Returns the joints that are not fixed, in the order of the URDF.
+    pub fn movable_joints(&self) -> impl Iterator<Item = &Joint> {
+        self.joints
+            .iter()
+            .filter(|joint| joint.joint_type.is_movable())
+    }
+
+    fn check_tree(&self) -> Result<(), UrdfError> {
+        for (index, link) in self.links.iter().enumerate() {
+            if self.links[..index]
+                .iter()
+                .any(|other| other.name == link.name)
+            {
+                return Err(UrdfError::DuplicateName(link.name.clone()));
+            }
+        }
+        for (index, joint) in self.joints.iter().enumerate() {
+            if self.joints[..index]
+                .iter()
+                .any(|other| other.name == joint.name)
+            {
+                return Err(UrdfError::DuplicateName(joint.name.clone()));
+            }
+            for link in [&joint.parent, &joint.child] {
+                if self.link(link).is_none() {
+                    return Err(UrdfError::UnknownLink {
+                        joint: joint.name.clone(),
+                        link: link.clone(),
+                    });
+                }
+            }
+            if self.joints[..index]
+                .iter()
+                .any(|other| other.child == joint.child)
+            {
+                return Err(UrdfError::MultipleParents(joint.child.clone()));
+            }
+        }
+        // With at most one parent per link, the links form a tree if a single root reaches all
+        // of them.
+        let mut roots = self
+            .links
+            .iter()
+            .filter(|link| self.parent_joint(&link.name).is_none());
+        let root = match (roots.next(), roots.next()) {
+            (Some(root), None) => root,
+            _ => return Err(UrdfError::NotATree),
+        };
+        let mut reached = vec![root.name.as_str()];
+        let mut index = 0;
+        while let Some(&link) = reached.get(index) {
+            reached.extend(self.child_joints(link).map(|joint| joint.child.as_str()));
+            index += 1;
+        }
+        if reached.len() != self.links.len() {
+            return Err(UrdfError::NotATree);
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Robot {
+    type Err = UrdfError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_urdf(s)
+    }
+}
+
+fn parse_joint(element: Node<'_, '_>) -> Result<Joint, UrdfError> {
+    let name = required_attribute(element, "joint", "name")?.to_owned();
+    let joint_type: JointType = required_attribute(element, "joint", "type")?.parse()?;
+    let mut parent = None;
+    let mut child = None;
+    let mut origin = Origin::default();
+    let mut axis = [1.0, 0.0, 0.0];
+    let mut limit = None;
+    for child_element in element.children().filter(|node| node.is_element()) {
+        match child_element.tag_name().name() {
+            "parent" => {
+                parent = Some(required_attribute(child_element, "parent", "link")?.to_owned())
+            }
+            "child" => child = Some(required_attribute(child_element, "child", "link")?.to_owned()),
+            "origin" => {
+                origin = Origin {
+                    xyz: optional_vector(child_element, "origin", "xyz")?.unwrap_or_default(),
+                    rpy: optional_vector(child_element, "origin", "rpy")?.unwrap_or_default(),
+                }
+            }
+            "axis" => {
+                axis = optional_vector(child_element, "axis", "xyz")?.unwrap_or([1.0, 0.0, 0.0])
+            }
+            "limit" => {
+                limit = Some(JointLimits {
+                    lower: optional_number(child_element, "limit", "lower")?.unwrap_or_default(),
+                    upper: optional_number(child_element, "limit", "upper")?.unwrap_or_default(),
+                    effort: required_number(child_element, "limit", "effort")?,
+                    velocity: required_number(child_element, "limit", "velocity")?,
+                })
+            }
+            _ => {}
+        }
+    }
+    let missing_link = |element| UrdfError::MissingAttribute {
+        element,
+        attribute: "link",
+    };
+    let parent = parent.ok_or_else(|| missing_link("parent"))?;
+    let child = child.ok_or_else(|| missing_link("child"))?;
+    let limited = matches!(joint_type, JointType::Revolute | JointType::Prismatic);
+    if limited && limit.is_none() {
+        return Err(UrdfError::MissingLimit(name));
+    }
+    Ok(Joint {
+        name,
+        joint_type,
+        parent,
+        child,
+        origin,
+        axis,
+        limit,
+    })
+}
+
+fn required_attribute<'a>(
+    element: Node<'a, '_>,
+    element_name: &'static str,
+    attribute: &'static str,
+) -> Result<&'a str, UrdfError> {
+    element
+        .attribute(attribute)
+        .ok_or(UrdfError::MissingAttribute {
+            element: element_name,
+            attribute,
+        })
+}
+
+fn invalid_attribute(element: &'static str, attribute: &'static str, value: &str) -> UrdfError {
+    UrdfError::InvalidAttribute {
+        element,
+        attribute,
+        value: value.to_owned(),
+    }
+}
+
+fn optional_number(
+    element: Node<'_, '_>,
+    element_name: &'static str,
+    attribute: &'static str,
+) -> Result<Option<f64>, UrdfError> {
+    element
+        .attribute(attribute)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| invalid_attribute(element_name, attribute, value))
+        })
+        .transpose()
+}
+
+fn required_number(
+    element: Node<'_, '_>,
+    element_name: &'static str,
+    attribute: &'static str,
+) -> Result<f64, UrdfError> {
+    optional_number(element, element_name, attribute)?.ok_or(UrdfError::MissingAttribute {
+        element: element_name,
+        attribute,
+    })
+}
+
+// Parses a vector of three numbers that are separated by whitespace, e.g. "0 0 1".
+fn optional_vector(
+    element: Node<'_, '_>,
+    element_name: &'static str,
+    attribute: &'static str,
+) -> Result<Option<[f64; 3]>, UrdfError> {
+    let value = match element.attribute(attribute) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let invalid = || invalid_attribute(element_name, attribute, value);
+    let mut vector = [0.0; 3];
+    let mut components = value.split_whitespace();
+    for component in vector.iter_mut() {
+        *component = components
+            .next()
+            .and_then(|component| component.parse().ok())
+            .ok_or_else(invalid)?;
+    }
+    if components.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(Some(vector))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARM: &str = r#"<?xml version="1.0"?>
+<robot name="arm">
+  <!-- A base with a two-joint arm and a fixed tool -->
+  <material name="grey"><color rgba="0.5 0.5 0.5 1"/></material>
+  <link name="base">
+    <visual><geometry><box size="0.1 0.1 0.1"/></geometry></visual>
+  </link>
+  <link name="upper_arm"/>
+  <link name="forearm"/>
+  <link name="tool"/>
+  <joint name="shoulder" type="revolute">
+    <parent link="base"/>
+    <child link="upper_arm"/>
+    <origin xyz="0 0 0.1" rpy="0 0 1.25"/>
+    <axis xyz="0 0 1"/>
+    <limit lower="-2.5" upper="2.5" effort="50" velocity="1.5"/>
+  </joint>
+  <joint name="elbow" type="prismatic">
+    <parent link="upper_arm"/>
+    <child link="forearm"/>
+    <limit upper="0.2" effort="20" velocity="0.1"/>
+  </joint>
+  <joint name="tool_mount" type="fixed">
+    <parent link="forearm"/>
+    <child link="tool"/>
+  </joint>
+</robot>"#;
+
+    #[test]
+    fn test_parse_arm() {
+        let robot: Robot = ARM.parse().unwrap();
+        assert_eq!(robot.name, "arm");
+        assert_eq!(robot.links.len(), 4);
+        assert_eq!(robot.root_link().unwrap().name, "base");
+
+        let shoulder = robot.joint("shoulder").unwrap();
+        assert_eq!(shoulder.joint_type, JointType::Revolute);
+        assert_eq!(shoulder.parent, "base");
+        assert_eq!(shoulder.child, "upper_arm");
+        assert_eq!(shoulder.origin.xyz, [0.0, 0.0, 0.1]);
+        assert_eq!(shoulder.origin.rpy, [0.0, 0.0, 1.25]);
+        assert_eq!(shoulder.axis, [0.0, 0.0, 1.0]);
+        assert_eq!(
+            shoulder.limit,
+            Some(JointLimits {
+                lower: -2.5,
+                upper: 2.5,
+                effort: 50.0,
+                velocity: 1.5,
+            })
+        );
+
+        let elbow = robot.joint("elbow").unwrap();
+        assert_eq!(elbow.axis, [1.0, 0.0, 0.0]);
+        assert_eq!(elbow.origin, Origin::default());
+        let elbow_limit = elbow.limit.unwrap();
+        assert_eq!((elbow_limit.lower, elbow_limit.upper), (0.0, 0.2));
+        assert_eq!(elbow_limit.clamp_position(0.5), 0.2);
+
+        let movable: Vec<&str> = robot
+            .movable_joints()
+            .map(|joint| joint.name.as_str())
+            .collect();
+        assert_eq!(movable, ["shoulder", "elbow"]);
+        assert_eq!(robot.parent_joint("tool").unwrap().name, "tool_mount");
+        assert_eq!(robot.child_joints("base").count(), 1);
+    }
+
+    #[test]
+    fn test_invalid_documents() {
+        assert!(matches!(
+            Robot::from_urdf("<robot name=\"r\">"),
+            Err(UrdfError::Xml(_))
+        ));
+        assert_eq!(
+            Robot::from_urdf("<model name=\"r\"/>"),
+            Err(UrdfError::NotARobot)
+        );
+        assert_eq!(
+            Robot::from_urdf("<robot/>"),
+            Err(UrdfError::MissingAttribute {
+                element: "robot",
+                attribute: "name"
+            })
+        );
+    }
+
+    fn robot_with_joint(joint: &str) -> Result<Robot, UrdfError> {
+        Robot::from_urdf(&format!(
+            r#"<robot name="r"><link name="a"/><link name="b"/>{}</robot>"#,
+            joint
+        ))
+    }
+
+    #[test]
+    fn test_invalid_joints() {
+        assert_eq!(
+            robot_with_joint(
+                r#"<joint name="j" type="ball"><parent link="a"/><child link="b"/></joint>"#
+            ),
+            Err(UrdfError::InvalidAttribute {
+                element: "joint",
+                attribute: "type",
+                value: String::from("ball")
+            })
+        );
+        assert_eq!(
+            robot_with_joint(
+                r#"<joint name="j" type="revolute"><parent link="a"/><child link="b"/></joint>"#
+            ),
+            Err(UrdfError::MissingLimit(String::from("j")))
+        );
+        assert_eq!(
+            robot_with_joint(
+                r#"<joint name="j" type="continuous"><parent link="a"/><child link="c"/></joint>"#
+            ),
+            Err(UrdfError::UnknownLink {
+                joint: String::from("j"),
+                link: String::from("c")
+            })
+        );
+        assert_eq!(
+            robot_with_joint(
+                r#"<joint name="j" type="fixed"><parent link="a"/><child link="b"/>
+                   <axis xyz="0 1"/></joint>"#
+            ),
+            Err(UrdfError::InvalidAttribute {
+                element: "axis",
+                attribute: "xyz",
+                value: String::from("0 1")
+            })
+        );
+        assert_eq!(
+            robot_with_joint(
+                r#"<joint name="j" type="prismatic"><parent link="a"/><child link="b"/>
+                   <limit upper="1" effort="1"/></joint>"#
+            ),
+            Err(UrdfError::MissingAttribute {
+                element: "limit",
+                attribute: "velocity"
+            })
+        );
+    }
+
+    #[test]
+    fn test_not_a_tree() {
+        // Two roots
+        assert_eq!(robot_with_joint(""), Err(UrdfError::NotATree));
+        // A cycle
+        assert_eq!(
+            robot_with_joint(
+                r#"<joint name="j1" type="fixed"><parent link="a"/><child link="b"/></joint>
+                   <joint name="j2" type="fixed"><parent link="b"/><child link="a"/></joint>"#
+            ),
+            Err(UrdfError::NotATree)
+        );
+        assert_eq!(
+            robot_with_joint(
+                r#"<joint name="j1" type="fixed"><parent link="a"/><child link="b"/></joint>
+                   <joint name="j2" type="fixed"><parent link="a"/><child link="b"/></joint>"#
+            ),
+            Err(UrdfError::MultipleParents(String::from("b")))
+        );
+        assert_eq!(
+            Robot::from_urdf(r#"<robot name="r"><link name="a"/><link name="a"/></robot>"#),
+            Err(UrdfError::DuplicateName(String::from("a")))
+        );
+    }
+}
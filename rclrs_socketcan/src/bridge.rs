@@ -0,0 +1,132 @@
+use crate::{CanError, CanSocket};
+
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use builtin_interfaces::msg::Time;
+use can_msgs::msg::Frame;
+use rclrs::{
+    CallbackErrorPolicy, FdWaitable, Node, Publisher, RclReturnCode, Subscription,
+    QOS_PROFILE_DEFAULT,
+};
+use std_msgs::msg::Header;
+
+/// Forwards frames between a CAN interface and ROS topics.
+///
+/// Like the bridge of `ros2_socketcan`, received frames are published on the `from_can_bus`
+/// topic, and frames received on the `to_can_bus` topic are written to the interface. Frames are
+/// read in the spin loop of the node, through an [`FdWaitable`], and stamped with the current
+/// system time.
+///
+/// When reading from or writing to the interface fails, [`spin_once`][1] and [`spin`][2] return
+/// an [`RclReturnCode::CallbackError`] with the description of the [`CanError`]. Forwarding
+/// continues when the node is spun again. A frame that cannot be written because the transmit
+/// queue of the interface is full is dropped with a warning through the [`log`] crate instead,
+/// like a frame that is lost on the bus.
+///
+/// The bridge stops forwarding when it is dropped.
+///
+/// # Example
+/// ```ignore
+/// let bridge = SocketCanBridge::new(&mut node, "can0", "can")?;
+/// bridge.socket().set_filters(&[CanFilter::standard(0x123)])?;
+/// rclrs::spin(&node)?;
+/// ```
+///
+/// [1]: rclrs::spin_once
+/// [2]: rclrs::spin
+pub struct SocketCanBridge {
+    socket: Arc<CanSocket>,
+    _sender: Arc<Subscription<Frame>>,
+    _receiver: Arc<FdWaitable>,
+}
+
+impl SocketCanBridge {
+    /// Opens the CAN interface and starts forwarding frames.
+    ///
+    /// The `frame_id` is used in the header of the published frames.
+    pub fn new(node: &mut Node, interface: &str, frame_id: &str) -> Result<Self, CanError> {
+        let socket = Arc::new(CanSocket::open(interface)?);
+        socket.set_nonblocking(true)?;
+        let publisher: Publisher<Frame> =
+            node.create_publisher("from_can_bus", QOS_PROFILE_DEFAULT)?;
+        let receiver = {
+            let socket = Arc::clone(&socket);
+            let frame_id = frame_id.to_owned();
+            Arc::new(FdWaitable::new_for_node(
+                node,
+                socket.as_raw_fd(),
+                move || {
+                    forward_received_frames(&socket, &publisher, &frame_id)
+                        .map_err(RclReturnCode::from)
+                },
+            )?)
+        };
+        node.add_waitable(&receiver)?;
+        let sender = {
+            let socket = Arc::clone(&socket);
+            node.create_fallible_subscription(
+                "to_can_bus",
+                QOS_PROFILE_DEFAULT,
+                CallbackErrorPolicy::StopSpinning,
+                move |frame: Frame| match socket.write(&frame) {
+                    Err(CanError::Io(error)) if is_transmit_queue_full(&error) => {
+                        log::warn!("Dropped a frame for the CAN bus: {}", error);
+                        Ok(())
+                    }
+                    result => result,
+                },
+            )?
+        };
+        Ok(Self {
+            socket,
+            _sender: sender,
+            _receiver: receiver,
+        })
+    }
+
+    /// Returns the socket, e.g. for setting filters.
+    pub fn socket(&self) -> &CanSocket {
+        &self.socket
+    }
+}
+
+// Publishes all frames that are available without blocking.
+fn forward_received_frames(
+    socket: &CanSocket,
+    publisher: &Publisher<Frame>,
+    frame_id: &str,
+) -> Result<(), CanError> {
+    loop {
+        let frame = match socket.read() {
+            Ok(frame) => frame,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        publisher.publish(Frame {
+            header: header(frame_id),
+            ..frame
+        })?;
+    }
+}
+
+// Whether a write failed only because the transmit queue of the nonblocking socket is full.
+fn is_transmit_queue_full(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::WouldBlock || error.raw_os_error() == Some(libc::ENOBUFS)
+}
+
+fn header(frame_id: &str) -> Header {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Header {
+        stamp: Time {
+            // Saturates in 2038, when the seconds no longer fit into the message.
+            sec: i32::try_from(since_epoch.as_secs()).unwrap_or(i32::MAX),
+            nanosec: since_epoch.subsec_nanos(),
+        },
+        frame_id: frame_id.to_owned(),
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt::{self, Display};
+use std::io;
+
+use rclrs::RclReturnCode;
+
+/// An error in the communication with a CAN interface.
+#[derive(Debug)]
+pub enum CanError {
+    /// Creating or using a publisher, subscription or waitable failed.
+    Rcl(RclReturnCode),
+    /// Reading from or writing to the CAN socket failed.
+    Io(io::Error),
+    /// A frame message cannot be sent, e.g. because its ID or length is out of range.
+    InvalidFrame(String),
+}
+
+impl Display for CanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rcl(error) => write!(f, "{}", error),
+            Self::Io(error) => write!(f, "{}", error),
+            Self::InvalidFrame(reason) => write!(f, "Invalid CAN frame: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CanError {}
+
+impl From<RclReturnCode> for CanError {
+    fn from(error: RclReturnCode) -> Self {
+        Self::Rcl(error)
+    }
+}
+
+// Used to return errors from the callbacks of the bridge, which are reported by the spin loop.
+impl From<CanError> for RclReturnCode {
+    fn from(error: CanError) -> Self {
+        match error {
+            CanError::Rcl(error) => error,
+            error => RclReturnCode::CallbackError(error.to_string()),
+        }
+    }
+}
+
+impl From<io::Error> for CanError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::CanError;
+
+use can_msgs::msg::Frame;
+
+// The flags and masks of `can_id`, from `linux/can.h`.
+pub(crate) const CAN_EFF_FLAG: u32 = 0x8000_0000;
+pub(crate) const CAN_RTR_FLAG: u32 = 0x4000_0000;
+pub(crate) const CAN_ERR_FLAG: u32 = 0x2000_0000;
+pub(crate) const CAN_SFF_MASK: u32 = 0x0000_07ff;
+pub(crate) const CAN_EFF_MASK: u32 = 0x1fff_ffff;
+
+// A classic CAN frame in the layout of `struct can_frame` from `linux/can.h`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RawCanFrame {
+    pub(crate) can_id: u32,
+    pub(crate) can_dlc: u8,
+    pad: u8,
+    res0: u8,
+    res1: u8,
+    pub(crate) data: [u8; 8],
+}
+
+// Converts a frame from the kernel into a message. The header is left empty.
+pub(crate) fn frame_to_msg(raw: &RawCanFrame) -> Frame {
+    let is_extended = raw.can_id & CAN_EFF_FLAG != 0;
+    Frame {
+        id: raw.can_id
+            & if is_extended {
+                CAN_EFF_MASK
+            } else {
+                CAN_SFF_MASK
+            },
+        is_rtr: raw.can_id & CAN_RTR_FLAG != 0,
+        is_extended,
+        is_error: raw.can_id & CAN_ERR_FLAG != 0,
+        dlc: raw.can_dlc,
+        data: raw.data,
+        ..Default::default()
+    }
+}
+
+// Converts a message into a frame for the kernel.
+pub(crate) fn msg_to_frame(msg: &Frame) -> Result<RawCanFrame, CanError> {
+    let id_mask = if msg.is_extended {
+        CAN_EFF_MASK
+    } else {
+        CAN_SFF_MASK
+    };
+    if msg.id & !id_mask != 0 {
+        return Err(CanError::InvalidFrame(format!(
+            "ID {:#x} does not fit into a {} frame",
+            msg.id,
+            if msg.is_extended {
+                "extended"
+            } else {
+                "standard"
+            }
+        )));
+    }
+    if msg.dlc > 8 {
+        return Err(CanError::InvalidFrame(format!(
+            "length {} is larger than 8",
+            msg.dlc
+        )));
+    }
+    let mut can_id = msg.id;
+    if msg.is_extended {
+        can_id |= CAN_EFF_FLAG;
+    }
+    if msg.is_rtr {
+        can_id |= CAN_RTR_FLAG;
+    }
+    if msg.is_error {
+        can_id |= CAN_ERR_FLAG;
+    }
+    Ok(RawCanFrame {
+        can_id,
+        can_dlc: msg.dlc,
+        data: msg.data,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_frame_round_trip() {
+        let msg = Frame {
+            id: 0x1234_5678,
+            is_extended: true,
+            is_rtr: true,
+            dlc: 2,
+            data: [1, 2, 0, 0, 0, 0, 0, 0],
+            ..Default::default()
+        };
+        let raw = msg_to_frame(&msg).unwrap();
+        assert_eq!(raw.can_id, 0x1234_5678 | CAN_EFF_FLAG | CAN_RTR_FLAG);
+        assert_eq!(frame_to_msg(&raw), msg);
+    }
+
+    #[test]
+    fn test_invalid_frames() {
+        let msg = Frame {
+            id: 0x800,
+            ..Default::default()
+        };
+        assert!(matches!(msg_to_frame(&msg), Err(CanError::InvalidFrame(_))));
+        let msg = Frame {
+            dlc: 9,
+            ..Default::default()
+        };
+        assert!(matches!(msg_to_frame(&msg), Err(CanError::InvalidFrame(_))));
+    }
+}
@@ -0,0 +1,18 @@
+#![warn(missing_docs)]
+//! SocketCAN integration for `rclrs`.
+//!
+//! This crate converts between the frames of the Linux SocketCAN interface and
+//! [`can_msgs::msg::Frame`] messages. The [`SocketCanBridge`] forwards frames between a CAN
+//! interface and the `from_can_bus` and `to_can_bus` topics, like the bridge of
+//! `ros2_socketcan`, and reads from the interface in the spin loop of its node.
+//!
+//! This crate only works on Linux.
+
+mod bridge;
+mod error;
+mod frame;
+mod socket;
+
+pub use bridge::*;
+pub use error::*;
+pub use socket::*;
@@ -0,0 +1,204 @@
+use crate::frame::{frame_to_msg, msg_to_frame, RawCanFrame};
+use crate::frame::{CAN_EFF_FLAG, CAN_EFF_MASK, CAN_RTR_FLAG, CAN_SFF_MASK};
+use crate::CanError;
+
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem::size_of;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use can_msgs::msg::Frame;
+
+// The constants of the raw CAN protocol, from `linux/can.h` and `linux/can/raw.h`.
+const AF_CAN: libc::c_int = 29;
+const CAN_RAW: libc::c_int = 1;
+const SOL_CAN_RAW: libc::c_int = 100 + CAN_RAW;
+const CAN_RAW_FILTER: libc::c_int = 1;
+
+// The layout of `struct sockaddr_can` from `linux/can.h`. The address union is only used by
+// other CAN protocols.
+#[repr(C)]
+struct SockaddrCan {
+    can_family: libc::sa_family_t,
+    can_ifindex: libc::c_int,
+    can_addr: [u8; 16],
+}
+
+/// A receive filter of a [`CanSocket`].
+///
+/// A received frame passes the filter when `can_id & mask == id & mask`, where `can_id` contains
+/// the flags for extended and remote frames in its upper bits, as in the SocketCAN API.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CanFilter {
+    /// The ID to compare with, including the flags.
+    pub id: u32,
+    /// The bits of the ID and flags that are compared.
+    pub mask: u32,
+}
+
+impl CanFilter {
+    /// Creates a filter with the given ID and mask in the format of the SocketCAN API.
+    pub fn new(id: u32, mask: u32) -> Self {
+        Self { id, mask }
+    }
+
+    /// Creates a filter that only passes standard data frames with exactly this 11-bit ID.
+    pub fn standard(id: u32) -> Self {
+        Self::new(
+            id & CAN_SFF_MASK,
+            CAN_SFF_MASK | CAN_EFF_FLAG | CAN_RTR_FLAG,
+        )
+    }
+
+    /// Creates a filter that only passes extended data frames with exactly this 29-bit ID.
+    pub fn extended(id: u32) -> Self {
+        Self::new(
+            (id & CAN_EFF_MASK) | CAN_EFF_FLAG,
+            CAN_EFF_MASK | CAN_EFF_FLAG | CAN_RTR_FLAG,
+        )
+    }
+}
+
+/// A raw SocketCAN socket that is bound to a CAN interface, e.g. `can0` or `vcan0`.
+///
+/// Frames are read and written as [`Frame`] messages. The socket is closed when it is dropped.
+pub struct CanSocket {
+    file: File,
+}
+
+impl CanSocket {
+    /// Opens a socket on the CAN interface with the given name.
+    ///
+    /// The socket receives all frames until [`set_filters`][1] is called.
+    ///
+    /// [1]: CanSocket::set_filters
+    pub fn open(interface: &str) -> io::Result<Self> {
+        let name = CString::new(interface)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "interior null byte"))?;
+        // SAFETY: The name is a valid null-terminated string.
+        let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if ifindex == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: No preconditions for this function.
+        let fd = unsafe { libc::socket(AF_CAN, libc::SOCK_RAW | libc::SOCK_CLOEXEC, CAN_RAW) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: The file descriptor is valid and owned by nothing else, so the file may close
+        // it.
+        let file = unsafe { File::from_raw_fd(fd) };
+        let address = SockaddrCan {
+            can_family: AF_CAN as libc::sa_family_t,
+            can_ifindex: ifindex as libc::c_int,
+            can_addr: [0; 16],
+        };
+        // SAFETY: The address is a valid sockaddr_can, and its size is passed along.
+        let ret = unsafe {
+            libc::bind(
+                fd,
+                &address as *const SockaddrCan as *const libc::sockaddr,
+                size_of::<SockaddrCan>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { file })
+    }
+
+    /// Only receives frames that pass at least one of the filters.
+    ///
+    /// An empty list of filters means that no frames are received.
+    pub fn set_filters(&self, filters: &[CanFilter]) -> io::Result<()> {
+        // SAFETY: The filters have the layout of `struct can_filter`, and their total size is
+        // passed along. The kernel copies them.
+        let ret = unsafe {
+            libc::setsockopt(
+                self.as_raw_fd(),
+                SOL_CAN_RAW,
+                CAN_RAW_FILTER,
+                filters.as_ptr() as *const libc::c_void,
+                std::mem::size_of_val(filters) as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Sets whether reads return an error of kind [`WouldBlock`][1] instead of blocking when no
+    /// frame is available.
+    ///
+    /// [1]: std::io::ErrorKind::WouldBlock
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        let fd = self.as_raw_fd();
+        // SAFETY: The file descriptor is valid.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let flags = if nonblocking {
+            flags | libc::O_NONBLOCK
+        } else {
+            flags & !libc::O_NONBLOCK
+        };
+        // SAFETY: The file descriptor is valid.
+        if unsafe { libc::fcntl(fd, libc::F_SETFL, flags) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Reads the next frame.
+    ///
+    /// The header of the message is left empty.
+    pub fn read(&self) -> io::Result<Frame> {
+        let mut raw = RawCanFrame::default();
+        // SAFETY: The frame is plain data without padding bytes, so it can be viewed and written
+        // as bytes.
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(
+                &mut raw as *mut RawCanFrame as *mut u8,
+                size_of::<RawCanFrame>(),
+            )
+        };
+        let n = (&self.file).read(bytes)?;
+        if n != bytes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete CAN frame",
+            ));
+        }
+        Ok(frame_to_msg(&raw))
+    }
+
+    /// Writes a frame.
+    pub fn write(&self, frame: &Frame) -> Result<(), CanError> {
+        let raw = msg_to_frame(frame)?;
+        // SAFETY: The frame is plain data without padding bytes, so it can be viewed as bytes.
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &raw as *const RawCanFrame as *const u8,
+                size_of::<RawCanFrame>(),
+            )
+        };
+        let n = (&self.file).write(bytes)?;
+        if n != bytes.len() {
+            return Err(CanError::Io(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "incomplete CAN frame",
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl AsRawFd for CanSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
@@ -0,0 +1,39 @@
+use rclrs::{GenericPublisher, GenericSubscription, Node, QoSProfile, RclReturnCode};
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The subscription and publisher of a tool that forwards messages from one topic to another.
+pub(crate) struct Forward {
+    pub(crate) subscription: Arc<GenericSubscription>,
+    pub(crate) publisher: Rc<GenericPublisher>,
+}
+
+impl Forward {
+    /// Forwards the messages on `input_topic` for which `filter` returns true to `output_topic`.
+    pub(crate) fn new<F>(
+        node: &mut Node,
+        type_name: &str,
+        input_topic: &str,
+        output_topic: &str,
+        qos: QoSProfile,
+        mut filter: F,
+    ) -> Result<Self, RclReturnCode>
+    where
+        F: FnMut(&[u8]) -> bool + 'static,
+    {
+        let publisher = Rc::new(GenericPublisher::new(node, output_topic, type_name, qos)?);
+        let callback_publisher = Rc::clone(&publisher);
+        let subscription =
+            GenericSubscription::new(node, input_topic, type_name, qos, move |message| {
+                if filter(message.as_bytes()) {
+                    callback_publisher.publish(message)?;
+                }
+                Ok(())
+            })?;
+        Ok(Self {
+            subscription,
+            publisher,
+        })
+    }
+}
@@ -0,0 +1,21 @@
+#![warn(missing_docs)]
+//! Relay, throttle and drop for topics whose message type is only known at runtime.
+//!
+//! These are the tools of the `topic_tools` package of ROS 2, written with
+//! [`rclrs::GenericSubscription`] and [`rclrs::GenericPublisher`]. Messages are forwarded in the
+//! serialized format of the middleware and never deserialized, so the same code works for any
+//! message type. The type is given by its full name, e.g. `sensor_msgs/msg/Image`, and must be
+//! registered with the registry of `rosidl_runtime_rs`, which means that the crate of the message
+//! type is linked into the binary with its `registry` feature enabled.
+//!
+//! A mux, which switches between input topics when a service is called, is not provided yet
+//! because `rclrs` does not support services.
+
+mod forward;
+mod random_drop;
+mod relay;
+mod throttle;
+
+pub use random_drop::*;
+pub use relay::*;
+pub use throttle::*;
@@ -0,0 +1,126 @@
+use crate::forward::Forward;
+
+use rclrs::{GenericPublisher, GenericSubscription, Node, QoSProfile, RclReturnCode};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Decides at random which messages are dropped, with a xorshift64* generator.
+///
+/// The generator is not suitable for cryptography, but it is good enough for simulating a lossy
+/// link without pulling in a dependency.
+struct DropFilter {
+    probability: f64,
+    state: u64,
+}
+
+impl DropFilter {
+    fn new(probability: f64, seed: u64) -> Self {
+        Self {
+            probability,
+            // The state of a xorshift generator must not be zero.
+            state: seed.max(1),
+        }
+    }
+
+    /// Returns a number that is uniformly distributed in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let value = self.state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        // The upper 53 bits fill the mantissa of the float.
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns whether the next message is forwarded.
+    fn pass(&mut self) -> bool {
+        self.next_f64() >= self.probability
+    }
+}
+
+/// Forwards messages from one topic to another, dropping each one with a fixed probability.
+///
+/// This is useful for testing how a system copes with a lossy link. The messages are dropped
+/// independently of each other. The tool forwards messages as long as it and the node are alive.
+pub struct RandomDrop {
+    forward: Forward,
+    probability: f64,
+}
+
+impl RandomDrop {
+    /// Creates a random drop from `input_topic` to `output_topic`, both with the given QoS
+    /// profile.
+    ///
+    /// Returns [`RclReturnCode::InvalidArgument`] when the message type is not registered, or
+    /// when the probability is not between 0 and 1.
+    ///
+    /// # Panics
+    /// When one of the topics contains interior null bytes.
+    pub fn new(
+        node: &mut Node,
+        type_name: &str,
+        input_topic: &str,
+        output_topic: &str,
+        qos: QoSProfile,
+        probability: f64,
+    ) -> Result<Self, RclReturnCode> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(RclReturnCode::InvalidArgument);
+        }
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+        let mut filter = DropFilter::new(probability, seed);
+        let forward = Forward::new(node, type_name, input_topic, output_topic, qos, move |_| {
+            filter.pass()
+        })?;
+        Ok(Self {
+            forward,
+            probability,
+        })
+    }
+
+    /// Returns the probability with which a message is dropped.
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// Returns the subscription on the input topic.
+    pub fn subscription(&self) -> &GenericSubscription {
+        &self.forward.subscription
+    }
+
+    /// Returns the publisher on the output topic.
+    pub fn publisher(&self) -> &GenericPublisher {
+        &self.forward.publisher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extreme_probabilities() {
+        let mut never = DropFilter::new(0.0, 42);
+        let mut always = DropFilter::new(1.0, 42);
+        assert!((0..1000).all(|_| never.pass()));
+        assert!((0..1000).all(|_| !always.pass()));
+    }
+
+    #[test]
+    fn test_drop_rate() {
+        let mut filter = DropFilter::new(0.3, 42);
+        let passed = (0..10_000).filter(|_| filter.pass()).count();
+        assert!((6_700..7_300).contains(&passed), "{} passed", passed);
+    }
+
+    #[test]
+    fn test_zero_seed() {
+        let mut filter = DropFilter::new(0.5, 0);
+        let values: Vec<f64> = (0..4).map(|_| filter.next_f64()).collect();
+        assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+        assert!(values.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+}
@@ -0,0 +1,41 @@
+use crate::forward::Forward;
+
+use rclrs::{GenericPublisher, GenericSubscription, Node, QoSProfile, RclReturnCode};
+
+/// Forwards all messages from one topic to another.
+///
+/// Unlike [`rclrs::Relay`], the message type is given by its name when the relay is created.
+/// The relay forwards messages as long as it and the node are alive.
+pub struct TopicRelay {
+    forward: Forward,
+}
+
+impl TopicRelay {
+    /// Creates a relay from `input_topic` to `output_topic`, both with the given QoS profile.
+    ///
+    /// Returns [`RclReturnCode::InvalidArgument`] when the message type is not registered.
+    ///
+    /// # Panics
+    /// When one of the topics contains interior null bytes.
+    pub fn new(
+        node: &mut Node,
+        type_name: &str,
+        input_topic: &str,
+        output_topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Self, RclReturnCode> {
+        Ok(Self {
+            forward: Forward::new(node, type_name, input_topic, output_topic, qos, |_| true)?,
+        })
+    }
+
+    /// Returns the subscription on the input topic.
+    pub fn subscription(&self) -> &GenericSubscription {
+        &self.forward.subscription
+    }
+
+    /// Returns the publisher on the output topic.
+    pub fn publisher(&self) -> &GenericPublisher {
+        &self.forward.publisher
+    }
+}
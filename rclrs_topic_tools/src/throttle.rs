@@ -0,0 +1,205 @@
+use crate::forward::Forward;
+
+use rclrs::{GenericPublisher, GenericSubscription, Node, QoSProfile, RclReturnCode};
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The limit that a [`Throttle`] enforces on the forwarded messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThrottleLimit {
+    /// Forwards at most this many messages per second.
+    ///
+    /// A message is forwarded when at least `1 / rate` seconds have passed since the last
+    /// forwarded message.
+    MessageRate(f64),
+    /// Forwards at most this many bytes per second, averaged over the window.
+    ///
+    /// A message is forwarded when the serialized messages forwarded within the last `window`,
+    /// together with this one, do not exceed `bytes_per_second * window`.
+    Bandwidth {
+        /// The number of bytes per second.
+        bytes_per_second: f64,
+        /// The length of the window over which the bandwidth is averaged.
+        window: Duration,
+    },
+}
+
+impl ThrottleLimit {
+    fn is_valid(&self) -> bool {
+        match *self {
+            Self::MessageRate(rate) => rate.is_finite() && rate > 0.0,
+            Self::Bandwidth {
+                bytes_per_second,
+                window,
+            } => bytes_per_second.is_finite() && bytes_per_second > 0.0 && !window.is_zero(),
+        }
+    }
+}
+
+/// Decides which messages pass a [`ThrottleLimit`].
+struct Limiter {
+    limit: ThrottleLimit,
+    last: Option<Instant>,
+    // The times and sizes of the messages passed within the window of a bandwidth limit.
+    passed: VecDeque<(Instant, usize)>,
+    passed_bytes: usize,
+}
+
+impl Limiter {
+    fn new(limit: ThrottleLimit) -> Self {
+        Self {
+            limit,
+            last: None,
+            passed: VecDeque::new(),
+            passed_bytes: 0,
+        }
+    }
+
+    /// Returns whether a message of `len` bytes that arrives at `now` is forwarded.
+    fn pass(&mut self, now: Instant, len: usize) -> bool {
+        match self.limit {
+            ThrottleLimit::MessageRate(rate) => {
+                let period = Duration::from_secs_f64(1.0 / rate);
+                match self.last {
+                    Some(last) if now.saturating_duration_since(last) < period => false,
+                    _ => {
+                        self.last = Some(now);
+                        true
+                    }
+                }
+            }
+            ThrottleLimit::Bandwidth {
+                bytes_per_second,
+                window,
+            } => {
+                while let Some(&(time, size)) = self.passed.front() {
+                    if now.saturating_duration_since(time) < window {
+                        break;
+                    }
+                    self.passed.pop_front();
+                    self.passed_bytes -= size;
+                }
+                let budget = bytes_per_second * window.as_secs_f64();
+                if (self.passed_bytes + len) as f64 > budget {
+                    return false;
+                }
+                self.passed.push_back((now, len));
+                self.passed_bytes += len;
+                true
+            }
+        }
+    }
+}
+
+/// Forwards messages from one topic to another, limited by their rate or bandwidth.
+///
+/// Messages over the limit are dropped, not delayed. The throttle forwards messages as long as it
+/// and the node are alive.
+pub struct Throttle {
+    forward: Forward,
+    limit: ThrottleLimit,
+}
+
+impl Throttle {
+    /// Creates a throttle from `input_topic` to `output_topic`, both with the given QoS profile.
+    ///
+    /// Returns [`RclReturnCode::InvalidArgument`] when the message type is not registered, or
+    /// when the limit is not positive and finite.
+    ///
+    /// # Panics
+    /// When one of the topics contains interior null bytes.
+    pub fn new(
+        node: &mut Node,
+        type_name: &str,
+        input_topic: &str,
+        output_topic: &str,
+        qos: QoSProfile,
+        limit: ThrottleLimit,
+    ) -> Result<Self, RclReturnCode> {
+        if !limit.is_valid() {
+            return Err(RclReturnCode::InvalidArgument);
+        }
+        let mut limiter = Limiter::new(limit);
+        let forward = Forward::new(
+            node,
+            type_name,
+            input_topic,
+            output_topic,
+            qos,
+            move |bytes| limiter.pass(Instant::now(), bytes.len()),
+        )?;
+        Ok(Self { forward, limit })
+    }
+
+    /// Returns the limit of the throttle.
+    pub fn limit(&self) -> ThrottleLimit {
+        self.limit
+    }
+
+    /// Returns the subscription on the input topic.
+    pub fn subscription(&self) -> &GenericSubscription {
+        &self.forward.subscription
+    }
+
+    /// Returns the publisher on the output topic.
+    pub fn publisher(&self) -> &GenericPublisher {
+        &self.forward.publisher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_limits() {
+        assert!(ThrottleLimit::MessageRate(10.0).is_valid());
+        assert!(!ThrottleLimit::MessageRate(0.0).is_valid());
+        assert!(!ThrottleLimit::MessageRate(f64::INFINITY).is_valid());
+        assert!(!ThrottleLimit::MessageRate(f64::NAN).is_valid());
+        assert!(!ThrottleLimit::Bandwidth {
+            bytes_per_second: 100.0,
+            window: Duration::ZERO,
+        }
+        .is_valid());
+        assert!(!ThrottleLimit::Bandwidth {
+            bytes_per_second: -1.0,
+            window: Duration::from_secs(1),
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn test_message_rate() {
+        let start = Instant::now();
+        let mut limiter = Limiter::new(ThrottleLimit::MessageRate(10.0));
+        // Messages every 30 ms, of which every fourth is at least 100 ms after the last one.
+        let passed: Vec<bool> = (0..9)
+            .map(|i| limiter.pass(start + Duration::from_millis(30 * i), 1))
+            .collect();
+        assert_eq!(
+            passed,
+            [true, false, false, false, true, false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_bandwidth() {
+        let start = Instant::now();
+        let mut limiter = Limiter::new(ThrottleLimit::Bandwidth {
+            bytes_per_second: 100.0,
+            window: Duration::from_secs(1),
+        });
+        assert!(limiter.pass(start, 60));
+        assert!(limiter.pass(start + Duration::from_millis(100), 40));
+        // The budget of 100 bytes within one second is used up.
+        assert!(!limiter.pass(start + Duration::from_millis(200), 1));
+        // The first message has left the window, so 60 bytes are available again.
+        let later = start + Duration::from_millis(1050);
+        assert!(!limiter.pass(later, 61));
+        assert!(limiter.pass(later, 60));
+        // A message that is too large on its own is never forwarded.
+        assert!(!limiter.pass(start + Duration::from_secs(10), 101));
+    }
+}
@@ -102,6 +102,18 @@ foreach(_pkg_name ${rosidl_generate_interfaces_DEPENDENCY_PACKAGE_NAMES})
   endforeach()
 endforeach()
 
+# An interface package can add derives and attributes to the generated types with a generator
+# configuration file, see read_generator_config() in the generator for the format.
+if(NOT DEFINED rosidl_generator_rs_CONFIG_FILE)
+  set(rosidl_generator_rs_CONFIG_FILE "${CMAKE_CURRENT_SOURCE_DIR}/rosidl_generator_rs.json")
+endif()
+set(_generator_config_args "")
+set(_generator_config_files "")
+if(EXISTS "${rosidl_generator_rs_CONFIG_FILE}")
+  set(_generator_config_args --generator-config-file "${rosidl_generator_rs_CONFIG_FILE}")
+  set(_generator_config_files "${rosidl_generator_rs_CONFIG_FILE}")
+endif()
+
 set(target_dependencies
   "${rosidl_generator_rs_BIN}"
   ${rosidl_generator_rs_GENERATOR_FILES}
@@ -109,13 +121,27 @@ set(target_dependencies
   "${rosidl_generator_rs_TEMPLATE_DIR}/srv.rs.em"
   ${rosidl_generate_interfaces_ABS_IDL_FILES}
   ${_idl_file_without_actions}
-  ${_dependency_files})
+  ${_dependency_files}
+  ${_generator_config_files})
 foreach(dep ${target_dependencies})
   if(NOT EXISTS "${dep}")
     message(FATAL_ERROR "Target dependency '${dep}' does not exist")
   endif()
 endforeach()
 
+# Type descriptions, which contain the type hashes, are only generated since Iron
+set(_type_description_tuples_argument "")
+if(DEFINED ${rosidl_generate_interfaces_TARGET}__DESCRIPTION_TUPLES)
+  set(_type_description_tuples_argument
+    TYPE_DESCRIPTION_TUPLES "${${rosidl_generate_interfaces_TARGET}__DESCRIPTION_TUPLES}")
+  # The JSON files are generated at build time, so they are added after the existence check.
+  # Each tuple has the form <idl file>:<JSON file>.
+  foreach(_type_description_tuple ${${rosidl_generate_interfaces_TARGET}__DESCRIPTION_TUPLES})
+    string(REGEX REPLACE "^[^:]*:" "" _type_description_file "${_type_description_tuple}")
+    list(APPEND target_dependencies "${_type_description_file}")
+  endforeach()
+endif()
+
 set(generator_arguments_file "${CMAKE_CURRENT_BINARY_DIR}/rosidl_generator_rs__arguments.json")
 rosidl_write_generator_arguments(
   "${generator_arguments_file}"
@@ -126,6 +152,7 @@ rosidl_write_generator_arguments(
   OUTPUT_DIR "${_output_path}"
   TEMPLATE_DIR "${rosidl_generator_rs_TEMPLATE_DIR}"
   TARGET_DEPENDENCIES ${target_dependencies}
+  ${_type_description_tuples_argument}
 )
 
 file(MAKE_DIRECTORY "${_output_path}")
@@ -133,10 +160,32 @@ file(MAKE_DIRECTORY "${_output_path}")
 set(_target_suffix "__rs")
 
 set(CRATES_DEPENDENCIES "rosidl_runtime_rs = \"*\"")
+set(CRATES_ARBITRARY_FEATURES "\"dep:arbitrary\", \"rosidl_runtime_rs/arbitrary\"")
+set(CRATES_SERDE_FEATURES "\"dep:serde\", \"rosidl_runtime_rs/serde\"")
+set(CRATES_REGISTRY_FEATURES "\"rosidl_runtime_rs/registry\"")
 foreach(_pkg_name ${rosidl_generate_interfaces_DEPENDENCY_PACKAGE_NAMES})
   find_package(${_pkg_name} REQUIRED)
   set(CRATES_DEPENDENCIES "${CRATES_DEPENDENCIES}\n${_pkg_name} = \"*\"")
+  set(CRATES_ARBITRARY_FEATURES "${CRATES_ARBITRARY_FEATURES}, \"${_pkg_name}/arbitrary\"")
+  set(CRATES_SERDE_FEATURES "${CRATES_SERDE_FEATURES}, \"${_pkg_name}/serde\"")
+  set(CRATES_REGISTRY_FEATURES "${CRATES_REGISTRY_FEATURES}, \"${_pkg_name}/registry\"")
 endforeach()
+if(NOT _generator_config_args STREQUAL "")
+  execute_process(
+    COMMAND ${PYTHON_EXECUTABLE} ${rosidl_generator_rs_BIN}
+    ${_generator_config_args} --print-cargo-dependencies
+    OUTPUT_VARIABLE _extra_crates_dependencies
+    OUTPUT_STRIP_TRAILING_WHITESPACE
+    RESULT_VARIABLE _result)
+  if(NOT _result EQUAL 0)
+    message(FATAL_ERROR "Invalid Rust generator config file: ${rosidl_generator_rs_CONFIG_FILE}")
+  endif()
+  if(NOT _extra_crates_dependencies STREQUAL "")
+    set(CRATES_DEPENDENCIES "${CRATES_DEPENDENCIES}\n${_extra_crates_dependencies}")
+  endif()
+  # Re-run CMake when the dependencies change
+  set_property(DIRECTORY APPEND PROPERTY CMAKE_CONFIGURE_DEPENDS "${rosidl_generator_rs_CONFIG_FILE}")
+endif()
 ament_index_register_resource("rust_packages")
 
 
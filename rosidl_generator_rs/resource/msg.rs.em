@@ -31,9 +31,17 @@ extern "C" {
 @# it just calls the drop/fini functions of all fields
 // Corresponds to @(package_name)__@(subfolder)__@(type_name)
 #[repr(C)]
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd@(get_extra_derives(msg_spec)))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+@[for attribute in get_custom_attributes(subfolder, msg_spec, 'rmw')]@
+@(attribute)
+@[end for]@
 pub struct @(type_name) {
 @[for member in msg_spec.structure.members]@
+@[if needs_serde_array(member.type)]@
+    #[cfg_attr(feature = "serde", serde(with = "rosidl_runtime_rs::serde_array"))]
+@[end if]@
     pub @(get_rs_name(member.name)): @(get_rmw_rs_type(member.type)),
 @[end for]@
 }
@@ -72,12 +80,27 @@ impl rosidl_runtime_rs::Message for @(type_name) {
   type RmwMsg = Self;
   fn into_rmw_message(msg_cow: std::borrow::Cow<'_, Self>) -> std::borrow::Cow<'_, Self::RmwMsg> { msg_cow }
   fn from_rmw_message(msg: Self::RmwMsg) -> Self { msg }
+@{size_bound = get_serialized_size_bound(msg_spec)}@
+@[if size_bound]@
+  fn serialized_size_bound() -> Option<usize> { Some(@(size_bound)) }
+@[end if]@
 }
 
 impl rosidl_runtime_rs::RmwMessage for @(type_name) where Self: Sized {
   fn get_type_support() -> libc::uintptr_t {
     unsafe { rosidl_typesupport_c__get_message_type_support_handle__@(package_name)__@(subfolder)__@(type_name)() }
   }
+@[if get_type_hash(subfolder, type_name)]@
+  const TYPE_HASH: Option<&'static str> = Some("@(get_type_hash(subfolder, type_name))");
+@[end if]@
+}
+
+#[cfg(feature = "registry")]
+rosidl_runtime_rs::inventory::submit! {
+  rosidl_runtime_rs::MessageTypeSupport {
+    type_name: "@(package_name)/@(subfolder)/@(type_name)",
+    get_type_support: <@(type_name) as rosidl_runtime_rs::RmwMessage>::get_type_support,
+  }
 }
 
 @[end for]
@@ -92,9 +115,17 @@ impl rosidl_runtime_rs::RmwMessage for @(type_name) where Self: Sized {
 type_name = msg_spec.structure.namespaced_type.name
 }@
 
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd@(get_extra_derives(msg_spec)))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+@[for attribute in get_custom_attributes(subfolder, msg_spec, 'idiomatic')]@
+@(attribute)
+@[end for]@
 pub struct @(type_name) {
 @[for member in msg_spec.structure.members]@
+@[if needs_serde_array(member.type)]@
+    #[cfg_attr(feature = "serde", serde(with = "rosidl_runtime_rs::serde_array"))]
+@[end if]@
     pub @(get_rs_name(member.name)): @(get_idiomatic_rs_type(member.type)),
 @[end for]@
 }
@@ -278,6 +309,10 @@ impl rosidl_runtime_rs::Message for @(type_name) {
 @[end for]@
     }
   }
+
+  fn serialized_size_bound() -> Option<usize> {
+    <Self::RmwMsg as rosidl_runtime_rs::Message>::serialized_size_bound()
+  }
 }
 
 @[end for]
@@ -12,6 +12,7 @@
 # See the License for the specific language governing permissions and
 # limitations under the License.
 
+import json
 import os
 import pathlib
 import subprocess
@@ -52,9 +53,10 @@ def convert_lower_case_underscore_to_camel_case(word):
     return ''.join(x.capitalize() or '_' for x in word.split('_'))
 
 
-def generate_rs(generator_arguments_file, typesupport_impls):
+def generate_rs(generator_arguments_file, typesupport_impls, generator_config_file=None):
     args = read_generator_arguments(generator_arguments_file)
     package_name = args['package_name']
+    generator_config = read_generator_config(generator_config_file)
 
     # expand init modules for each directory
     modules = {}
@@ -95,6 +97,11 @@ def generate_rs(generator_arguments_file, typesupport_impls):
     data = {
         'get_rmw_rs_type': make_get_rmw_rs_type(args['package_name']),
         'get_rs_name': get_rs_name,
+        'get_extra_derives': get_extra_derives,
+        'get_custom_attributes': make_get_custom_attributes(generator_config),
+        'get_type_hash': make_get_type_hash(package_name, read_type_hashes(args)),
+        'get_serialized_size_bound': make_get_serialized_size_bound(args['package_name']),
+        'needs_serde_array': needs_serde_array,
         'get_idiomatic_rs_type': make_get_idiomatic_rs_type(args['package_name']),
         'constant_value_to_rs': constant_value_to_rs,
         'value_to_rs': value_to_rs,
@@ -147,6 +154,105 @@ def generate_rs(generator_arguments_file, typesupport_impls):
 
     return 0
 
+# The representations of a message type that custom attributes can be added to.
+REPRESENTATIONS = ['idiomatic', 'rmw']
+
+
+def read_generator_config(generator_config_file):
+    """Read the generator configuration file of an interface package.
+
+    The configuration is a JSON object with the following optional keys:
+
+    - "dependencies": extra dependencies of the generated crate, as an object
+      that maps crate names to version strings, or to objects that are written
+      as inline tables into the Cargo.toml, e.g.
+      {"zerocopy": {"version": "0.7", "features": ["derive"]}}
+    - "idiomatic" and "rmw": the custom attributes of all idiomatic or rmw
+      message types, as an object with the keys "derives" (a list of paths of
+      derive macros) and "attributes" (a list of complete outer attributes)
+    - "types": custom attributes of single message types, as an object that
+      maps type names like "msg/Frame" to objects with the keys "idiomatic"
+      and "rmw" described above
+
+    Returns an empty configuration when no file is given.
+    """
+    if not generator_config_file:
+        return {}
+    with open(generator_config_file, 'r') as f:
+        config = json.load(f)
+    unknown_keys = set(config) - {'dependencies', 'types', *REPRESENTATIONS}
+    assert not unknown_keys, \
+        'Unknown keys in generator config %s: %s' % (generator_config_file, sorted(unknown_keys))
+    for type_config in [config, *config.get('types', {}).values()]:
+        for representation in REPRESENTATIONS:
+            unknown_keys = set(type_config.get(representation, {})) - {'derives', 'attributes'}
+            assert not unknown_keys, \
+                'Unknown keys in generator config %s: %s' % (generator_config_file, sorted(unknown_keys))
+    return config
+
+
+def render_cargo_dependencies(generator_config):
+    """Return the extra dependencies of a generator config as Cargo.toml lines."""
+    def to_toml(value):
+        if isinstance(value, bool):
+            return 'true' if value else 'false'
+        elif isinstance(value, str):
+            return json.dumps(value)
+        elif isinstance(value, list):
+            return '[%s]' % ', '.join(to_toml(elem) for elem in value)
+        elif isinstance(value, dict):
+            return '{ %s }' % ', '.join('%s = %s' % (key, to_toml(elem)) for key, elem in value.items())
+        assert False, "unsupported dependency value '%s'" % value
+
+    return '\n'.join(
+        '%s = %s' % (name, to_toml(spec))
+        for name, spec in generator_config.get('dependencies', {}).items())
+
+
+def make_get_custom_attributes(generator_config):
+    def get_custom_attributes(subfolder, msg_spec, representation):
+        """Return the custom attribute lines of a message type in the given representation.
+
+        The attributes for all types come first, followed by those for this type.
+        """
+        assert representation in REPRESENTATIONS
+        type_name = '%s/%s' % (subfolder, msg_spec.structure.namespaced_type.name)
+        type_config = generator_config.get('types', {}).get(type_name, {})
+        derives = []
+        attributes = []
+        for config in [generator_config, type_config]:
+            derives += config.get(representation, {}).get('derives', [])
+            attributes += config.get(representation, {}).get('attributes', [])
+        lines = ['#[derive(%s)]' % ', '.join(derives)] if derives else []
+        return lines + attributes
+    return get_custom_attributes
+
+
+def read_type_hashes(args):
+    """Read the type hashes of the interfaces from their type description files.
+
+    Returns a dict that maps type names like "std_msgs/msg/String" to hash
+    strings like "RIHS01_df66...". Type descriptions are only generated since
+    Iron, so the dict is empty for older distros.
+    """
+    type_hashes = {}
+    for description_tuple in args.get('type_description_tuples', []):
+        description_parts = description_tuple.split(':', 1)
+        assert len(description_parts) == 2
+        with open(description_parts[1], 'r') as f:
+            type_description = json.load(f)
+        for type_hash in type_description.get('type_hashes', []):
+            type_hashes[type_hash['type_name']] = type_hash['hash_string']
+    return type_hashes
+
+
+def make_get_type_hash(package_name, type_hashes):
+    def get_type_hash(subfolder, type_name):
+        """Return the hash string of a message type, or None if it is unknown."""
+        return type_hashes.get('%s/%s/%s' % (package_name, subfolder, type_name))
+    return get_type_hash
+
+
 def get_rs_name(name):
     keywords = [
         # strict keywords
@@ -262,6 +368,103 @@ def constant_value_to_rs(type_, value):
 #     - BoundedSequence
 #     - UnboundedSequence
 
+def is_hashable(type_):
+    """Return whether the Rust type of a member can implement Eq and Hash.
+
+    Floating point members can't, and nested messages are conservatively
+    assumed to contain them.
+    """
+    if isinstance(type_, BasicType):
+        return type_.typename not in ['float', 'double', 'long double']
+    elif isinstance(type_, (Array, AbstractSequence)):
+        return is_hashable(type_.value_type)
+    elif isinstance(type_, AbstractGenericString):
+        return True
+    return False
+
+def get_extra_derives(msg_spec):
+    """Return the derives that depend on the member types, with a leading comma."""
+    if all(is_hashable(member.type) for member in msg_spec.structure.members):
+        return ', Eq, Hash'
+    return ''
+
+def needs_serde_array(type_):
+    """Return whether a member is an array that is too large for serde's own implementation."""
+    return isinstance(type_, Array) and type_.size > 32
+
+# The sizes of the basic types in the CDR serialization format
+CDR_SIZES = {
+    'boolean': 1, 'byte': 1, 'octet': 1, 'char': 1, 'int8': 1, 'uint8': 1,
+    'wchar': 2, 'int16': 2, 'uint16': 2,
+    'float': 4, 'int32': 4, 'uint32': 4,
+    'double': 8, 'int64': 8, 'uint64': 8,
+    'long double': 16,
+}
+
+# The largest alignment in CDR, which bounds the padding before any member
+CDR_MAX_PADDING = 7
+
+# The encapsulation header that precedes every serialized message
+CDR_ENCAPSULATION_HEADER_SIZE = 4
+
+def make_get_serialized_size_bound(package_name):
+    """Return a function for the serialized size bound of a message.
+
+    The function returns a Rust expression of type usize for the upper bound
+    of the CDR size of the message, or None if the message contains unbounded
+    members. The expression uses the `?` operator to propagate the bounds of
+    nested messages, so it must be used in a function returning an Option.
+    """
+    get_rmw_rs_type = make_get_rmw_rs_type(package_name)
+
+    # Returns the bound as a pair of a constant and a list of Rust expressions,
+    # which are summed up, or None for unbounded types.
+    def get_bound(type_):
+        if isinstance(type_, BasicType):
+            size = CDR_SIZES[type_.typename]
+            return (size + size - 1, [])
+        elif isinstance(type_, NamespacedType):
+            return (CDR_MAX_PADDING, [
+                '<{} as rosidl_runtime_rs::Message>::serialized_size_bound()?'.format(
+                    get_rmw_rs_type(type_))])
+        elif isinstance(type_, BoundedString):
+            # Length, padding before the length, characters and null terminator
+            return (4 + 3 + type_.maximum_size + 1, [])
+        elif isinstance(type_, BoundedWString):
+            return (4 + 3 + 4 * (type_.maximum_size + 1), [])
+        elif isinstance(type_, Array):
+            return get_elements_bound(type_.value_type, type_.size)
+        elif isinstance(type_, BoundedSequence):
+            elements_bound = get_elements_bound(type_.value_type, type_.maximum_size)
+            if elements_bound is None:
+                return None
+            return (4 + 3 + elements_bound[0], elements_bound[1])
+        return None
+
+    # Returns the bound of a number of consecutive elements, which are only
+    # padded once if they are of a basic type.
+    def get_elements_bound(value_type, count):
+        if isinstance(value_type, BasicType):
+            size = CDR_SIZES[value_type.typename]
+            return (count * size + size - 1, [])
+        element_bound = get_bound(value_type)
+        if element_bound is None:
+            return None
+        constant, expressions = element_bound
+        return (count * constant, ['{} * {}'.format(count, e) for e in expressions])
+
+    def get_serialized_size_bound(msg_spec):
+        constant = CDR_ENCAPSULATION_HEADER_SIZE
+        expressions = []
+        for member in msg_spec.structure.members:
+            bound = get_bound(member.type)
+            if bound is None:
+                return None
+            constant += bound[0]
+            expressions += bound[1]
+        return ' + '.join([str(constant)] + expressions)
+    return get_serialized_size_bound
+
 def make_get_idiomatic_rs_type(package_name):
     get_rmw_rs_type = make_get_rmw_rs_type(package_name)
     def get_idiomatic_rs_type(type_):
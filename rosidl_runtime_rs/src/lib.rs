@@ -10,3 +10,20 @@ pub use string::{BoundedString, BoundedWString, String, StringExceedsBoundsError
 
 mod traits;
 pub use traits::{Message, RmwMessage, SequenceAlloc};
+
+#[cfg(feature = "serde")]
+pub mod serde_array;
+
+#[cfg(feature = "yaml")]
+mod yaml;
+#[cfg(feature = "yaml")]
+pub use yaml::YamlMessage;
+
+#[cfg(feature = "registry")]
+mod registry;
+#[cfg(feature = "registry")]
+pub use registry::{get_message_type_support, registered_message_types, MessageTypeSupport};
+// Used by generated message crates, which do not depend on `inventory` directly.
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub use inventory;
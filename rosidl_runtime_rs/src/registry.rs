@@ -0,0 +1,56 @@
+/// An entry in the registry of message types, which maps a type name to its type support.
+///
+/// Generated message crates submit one entry per message when their `registry` feature is
+/// enabled, which also enables the `registry` feature of this crate. The entries of all crates
+/// that are linked into the final binary are collected at startup, without any `dlopen`.
+///
+/// There is usually no need to submit entries by hand.
+pub struct MessageTypeSupport {
+    /// The full name of the message type, e.g. `std_msgs/msg/String`.
+    pub type_name: &'static str,
+    /// Returns a pointer to the `rosidl_message_type_support_t` structure of the message type.
+    pub get_type_support: fn() -> libc::uintptr_t,
+}
+
+inventory::collect!(MessageTypeSupport);
+
+/// Looks up the type support of a message type by its full name, e.g. `std_msgs/msg/String`.
+///
+/// Returns `None` if no linked message crate has registered the type, see
+/// [`MessageTypeSupport`].
+pub fn get_message_type_support(type_name: &str) -> Option<libc::uintptr_t> {
+    inventory::iter::<MessageTypeSupport>
+        .into_iter()
+        .find(|entry| entry.type_name == type_name)
+        .map(|entry| (entry.get_type_support)())
+}
+
+/// Returns the full names of all registered message types, in no particular order.
+pub fn registered_message_types() -> impl Iterator<Item = &'static str> {
+    inventory::iter::<MessageTypeSupport>
+        .into_iter()
+        .map(|entry| entry.type_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_dummy_type_support() -> libc::uintptr_t {
+        42
+    }
+
+    inventory::submit! {
+        MessageTypeSupport {
+            type_name: "test_msgs/msg/Dummy",
+            get_type_support: get_dummy_type_support,
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(get_message_type_support("test_msgs/msg/Dummy"), Some(42));
+        assert_eq!(get_message_type_support("test_msgs/msg/Missing"), None);
+        assert!(registered_message_types().any(|type_name| type_name == "test_msgs/msg/Dummy"));
+    }
+}
@@ -84,6 +84,42 @@ pub struct SequenceIterator<T: SequenceAlloc> {
 
 // ========================= impl for Sequence =========================
 
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for Sequence<T>
+where
+    T: SequenceAlloc + arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Sequence<T>
+where
+    T: SequenceAlloc + serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Self::from(Vec::<T>::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Sequence<T>
+where
+    T: SequenceAlloc + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.as_slice())
+    }
+}
+
 impl<T: SequenceAlloc> Clone for Sequence<T> {
     fn clone(&self) -> Self {
         let mut seq = Self::default();
@@ -245,10 +281,19 @@ where
         seq
     }
 
+    /// Returns the number of elements the sequence can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
     /// Extracts a slice containing the entire sequence.
     ///
     /// Equivalent to `&seq[..]`.
     pub fn as_slice(&self) -> &[T] {
+        // An empty sequence may have a null data pointer, which slices must not have.
+        if self.data.is_null() {
+            return &[];
+        }
         // SAFETY: self.data points to self.size consecutive, initialized elements and
         // isn't modified externally.
         unsafe { std::slice::from_raw_parts(self.data, self.size) }
@@ -258,6 +303,9 @@ where
     ///
     /// Equivalent to `&mut seq[..]`.
     pub fn as_mut_slice(&mut self) -> &mut [T] {
+        if self.data.is_null() {
+            return &mut [];
+        }
         // SAFETY: self.data points to self.size consecutive, initialized elements and
         // isn't modified externally.
         unsafe { std::slice::from_raw_parts_mut(self.data, self.size) }
@@ -265,31 +313,147 @@ where
 }
 
 impl<T: Default + SequenceAlloc> Sequence<T> {
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// Like [`Vec::reserve()`], this may reserve more space to avoid frequent reallocations.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self
+            .size
+            .checked_add(additional)
+            .expect("Sequence capacity overflow");
+        if required > self.capacity {
+            self.grow_to(required.max(self.capacity.saturating_mul(2)));
+        }
+    }
+
+    /// Appends an element to the end of the sequence.
+    ///
+    /// The capacity grows geometrically, so repeated pushes take amortized constant time.
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+        // SAFETY: The size is smaller than the capacity, and the element is initialized.
+        unsafe { *self.data.add(self.size) = value };
+        self.size += 1;
+    }
+
+    /// Appends clones of all elements in a slice to the end of the sequence.
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(other.len());
+        for elem in other {
+            self.push(elem.clone());
+        }
+    }
+
+    /// Changes the length of the sequence, filling new elements with clones of `value`.
+    ///
+    /// Unlike assigning a new sequence, this keeps the allocation when the sequence shrinks, and
+    /// only reallocates when it grows beyond its capacity.
+    pub fn resize(&mut self, new_len: usize, value: T)
+    where
+        T: Clone,
+    {
+        if new_len > self.size {
+            self.reserve(new_len - self.size);
+            while self.size < new_len {
+                self.push(value.clone());
+            }
+        } else {
+            self.truncate(new_len);
+        }
+    }
+
+    /// Shortens the sequence to `len` elements, keeping its capacity.
+    ///
+    /// This has no effect if the sequence is not longer than `len`.
+    pub fn truncate(&mut self, len: usize) {
+        while self.size > len {
+            self.size -= 1;
+            // The C functions for sequences of messages finalize all elements up to the capacity,
+            // so the removed elements must stay initialized. They are reset to release their
+            // resources.
+            // SAFETY: The element is within the capacity and initialized.
+            unsafe { *self.data.add(self.size) = T::default() };
+        }
+    }
+
+    /// Removes all elements, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
     /// Internal function for the sequence_copy impl. To be removed when rosidl#650 is backported and released.
     pub fn resize_to_at_least(&mut self, len: usize) {
-        let allocation_size = std::mem::size_of::<Self>() * len;
         if self.capacity < len {
-            // SAFETY: The memory in self.data is owned by C.
-            let data = unsafe { libc::realloc(self.data as *mut _, allocation_size) } as *mut T;
-            if data.is_null() {
-                panic!("realloc failed");
-            }
-            // Initialize the new memory
-            for i in self.capacity..len {
-                // SAFETY: i is in bounds, and write() is appropriate for initializing uninitialized memory
-                unsafe {
-                    data.add(i).write(T::default());
-                }
-            }
-            self.data = data;
+            self.grow_to(len);
             self.size = len;
-            self.capacity = len;
         }
     }
+
+    // Reallocates the data to the new capacity, which must not be smaller than the current one,
+    // and initializes the new elements.
+    fn grow_to(&mut self, capacity: usize) {
+        let allocation_size = std::mem::size_of::<T>()
+            .checked_mul(capacity)
+            .expect("Sequence capacity overflow");
+        // SAFETY: The memory in self.data is owned by C.
+        let data = unsafe { libc::realloc(self.data as *mut _, allocation_size) } as *mut T;
+        if data.is_null() {
+            panic!("realloc failed");
+        }
+        // Initialize the new memory
+        for i in self.capacity..capacity {
+            // SAFETY: i is in bounds, and write() is appropriate for initializing uninitialized memory
+            unsafe {
+                data.add(i).write(T::default());
+            }
+        }
+        self.data = data;
+        self.capacity = capacity;
+    }
 }
 
 // ========================= impl for BoundedSequence =========================
 
+#[cfg(feature = "arbitrary")]
+impl<'a, T, const N: usize> arbitrary::Arbitrary<'a> for BoundedSequence<T, N>
+where
+    T: SequenceAlloc + arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(0..=N)?;
+        (0..len).map(|_| T::arbitrary(u)).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const N: usize> serde::Deserialize<'de> for BoundedSequence<T, N>
+where
+    T: SequenceAlloc + serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Self::try_from(Vec::<T>::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize> serde::Serialize for BoundedSequence<T, N>
+where
+    T: SequenceAlloc + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.as_slice())
+    }
+}
+
 impl<T: Debug + SequenceAlloc, const N: usize> Debug for BoundedSequence<T, N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         self.as_slice().fmt(f)
@@ -702,4 +866,29 @@ mod tests {
             seq_1 == seq_2
         }
     }
+
+    quickcheck! {
+        fn test_push_and_resize(xs: Vec<i32>, len: u8) -> bool {
+            let mut seq = Sequence::default();
+            for x in &xs {
+                seq.push(*x);
+            }
+            if seq[..] != xs[..] || seq.capacity() < xs.len() {
+                return false;
+            }
+            let capacity = seq.capacity();
+            let mut vec = xs;
+            vec.resize(len as usize, 7);
+            seq.resize(len as usize, 7);
+            seq[..] == vec[..] && (vec.len() > capacity || seq.capacity() == capacity)
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_reserve_overflow() {
+        // The number of elements fits into a usize, but their size in bytes does not.
+        let mut seq = Sequence::<i32>::default();
+        seq.reserve(usize::MAX / 2);
+    }
 }
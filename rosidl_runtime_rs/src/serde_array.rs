@@ -0,0 +1,32 @@
+//! Serialization of arrays with more than 32 elements, for which serde has no implementation.
+//!
+//! Generated messages use this module for such array members, e.g. the covariance matrices in
+//! `geometry_msgs`, via `#[serde(with = "rosidl_runtime_rs::serde_array")]`.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes an array as a sequence.
+pub fn serialize<S, T, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    serializer.collect_seq(array.iter())
+}
+
+/// Deserializes an array from a sequence of exactly `N` elements.
+pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let elements = Vec::<T>::deserialize(deserializer)?;
+    let len = elements.len();
+    elements.try_into().map_err(|_| {
+        D::Error::custom(format!(
+            "Expected an array with {} elements, got {} elements",
+            N, len
+        ))
+    })
+}
@@ -1,8 +1,10 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::ffi::CStr;
 use std::fmt::{self, Debug, Display};
 use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
+use std::str::Utf8Error;
 
 use crate::sequence::Sequence;
 use crate::traits::SequenceAlloc;
@@ -132,6 +134,34 @@ macro_rules! string_impl {
             }
         }
 
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $string {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                Ok(Self::from(<&str as arbitrary::Arbitrary>::arbitrary(u)?))
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $string {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let s = std::string::String::deserialize(deserializer)?;
+                Ok(Self::from(s.as_str()))
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $string {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
         impl Clone for $string {
             fn clone(&self) -> Self {
                 let mut msg = Self::default();
@@ -279,6 +309,38 @@ impl String {
         // Also, the lifetime of the CStr is the same as self, which is correct.
         unsafe { CStr::from_ptr(self.data as *const _) }
     }
+
+    /// Returns the bytes of this String, without the null byte at the end.
+    ///
+    /// Equivalent to `&s[..]`.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.deref()
+    }
+
+    /// Returns a `&str` view of this String, or an error if it is not valid UTF-8.
+    ///
+    /// Unlike [`ToString::to_string()`], this does not copy the string, which matters for
+    /// messages with large string fields, e.g. logs, when they are read in their RMW
+    /// representation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rosidl_runtime_rs::String;
+    /// let s = String::from("Grüß Gott!");
+    /// assert_eq!(s.as_str(), Ok("Grüß Gott!"));
+    /// ```
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.as_bytes())
+    }
+
+    /// Like [`as_str()`](String::as_str), but replaces invalid UTF-8 sequences with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// This only copies the string if it is not valid UTF-8.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        std::string::String::from_utf8_lossy(self.as_bytes())
+    }
 }
 
 impl From<&str> for WString {
@@ -306,6 +368,37 @@ impl From<&str> for WString {
 
 // ========================= impl for BoundedString =========================
 
+#[cfg(feature = "arbitrary")]
+impl<'a, const N: usize> arbitrary::Arbitrary<'a> for BoundedString<N> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let s = <&str as arbitrary::Arbitrary>::arbitrary(u)?;
+        // Truncate to the bound instead of rejecting long strings
+        let truncated: std::string::String = s.chars().take(N).collect();
+        Ok(Self::try_from(truncated.as_str()).unwrap())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for BoundedString<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = std::string::String::deserialize(deserializer)?;
+        Self::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for BoundedString<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl<const N: usize> Debug for BoundedString<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         Debug::fmt(&self.inner, f)
@@ -353,6 +446,23 @@ impl<const N: usize> SequenceAlloc for BoundedString<N> {
     }
 }
 
+impl<const N: usize> BoundedString<N> {
+    /// Returns the bytes of this string, see [`String::as_bytes()`].
+    pub fn as_bytes(&self) -> &[u8] {
+        self.inner.as_bytes()
+    }
+
+    /// Returns a `&str` view of this string, see [`String::as_str()`].
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        self.inner.as_str()
+    }
+
+    /// Returns this string with invalid UTF-8 replaced, see [`String::to_string_lossy()`].
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        self.inner.to_string_lossy()
+    }
+}
+
 impl<const N: usize> TryFrom<&str> for BoundedString<N> {
     type Error = StringExceedsBoundsError;
     fn try_from(s: &str) -> Result<Self, Self::Error> {
@@ -372,6 +482,37 @@ impl<const N: usize> TryFrom<&str> for BoundedString<N> {
 
 // ========================= impl for BoundedWString =========================
 
+#[cfg(feature = "arbitrary")]
+impl<'a, const N: usize> arbitrary::Arbitrary<'a> for BoundedWString<N> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let s = <&str as arbitrary::Arbitrary>::arbitrary(u)?;
+        // Truncate to the bound instead of rejecting long strings
+        let truncated: std::string::String = s.chars().take(N).collect();
+        Ok(Self::try_from(truncated.as_str()).unwrap())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for BoundedWString<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = std::string::String::deserialize(deserializer)?;
+        Self::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for BoundedWString<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl<const N: usize> Debug for BoundedWString<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         Debug::fmt(&self.inner, f)
@@ -449,3 +590,20 @@ impl Display for StringExceedsBoundsError {
 }
 
 impl std::error::Error for StringExceedsBoundsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_string_views() {
+        let mut s = BoundedString::<8>::try_from("Grüß").unwrap();
+        assert_eq!(s.as_bytes(), "Grüß".as_bytes());
+        assert_eq!(s.as_str(), Ok("Grüß"));
+
+        // Overwrite the first byte of the 'ü', which leaves an invalid UTF-8 sequence.
+        s[2] = b'!';
+        assert!(s.as_str().is_err());
+        assert_eq!(s.to_string_lossy(), "Gr!\u{FFFD}ß");
+    }
+}
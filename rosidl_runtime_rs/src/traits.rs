@@ -40,6 +40,11 @@ pub trait SequenceAlloc: Sized {
 pub trait RmwMessage: Clone + Debug + Default {
     /// Get a pointer to the correct `rosidl_message_type_support_t` structure.
     fn get_type_support() -> libc::uintptr_t;
+
+    /// The hash of the type definition, e.g. `RIHS01_df66…`, if it was known to the generator.
+    ///
+    /// Type hashes exist since Iron. Code generated for older distros doesn't set this.
+    const TYPE_HASH: Option<&'static str> = None;
 }
 
 /// Trait for types that can be used in a `rclrs::Subscription` and a `rclrs::Publisher`.
@@ -142,4 +147,17 @@ pub trait Message: Clone + Debug + Default + 'static {
 
     /// Converts the RMW-compatible message into an idiomatic message.
     fn from_rmw_message(msg: Self::RmwMsg) -> Self;
+
+    /// Returns an upper bound for the size of the message in the CDR serialization format, in
+    /// bytes, including the encapsulation header.
+    ///
+    /// The bound takes the worst case for alignment padding into account, so actual messages are
+    /// usually smaller. It can be used for pre-allocating buffers or for estimating the bandwidth
+    /// of a topic.
+    ///
+    /// This is `None` for messages with an unbounded string or sequence, and for code generated by
+    /// an older generator.
+    fn serialized_size_bound() -> Option<usize> {
+        None
+    }
 }
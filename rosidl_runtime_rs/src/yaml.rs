@@ -0,0 +1,130 @@
+use crate::Message;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Conversion of messages from and to YAML, in the syntax of `ros2 topic pub` and `ros2 topic echo`.
+///
+/// This trait is implemented for all messages that implement serde's traits, which generated
+/// messages do when the `serde` feature of their crate is enabled. It requires the `yaml` feature
+/// of this crate.
+///
+/// Fields that are missing in the YAML are set to their default values, as with
+/// `ros2 topic pub`. Nested messages are written as nested mappings and sequences and arrays as
+/// lists.
+///
+/// # Example
+/// ```ignore
+/// use rosidl_runtime_rs::YamlMessage;
+///
+/// let pose = geometry_msgs::msg::Pose::from_yaml("{position: {x: 1.0, y: 2.0}}")?;
+/// assert_eq!(pose.position.z, 0.0);
+/// println!("{}", pose.to_yaml()?);
+/// ```
+pub trait YamlMessage: Sized {
+    /// Parses a message from YAML.
+    fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error>;
+
+    /// Formats the message as YAML.
+    fn to_yaml(&self) -> Result<std::string::String, serde_yaml::Error>;
+}
+
+impl<T> YamlMessage for T
+where
+    T: Message + Serialize + DeserializeOwned,
+{
+    fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+
+    fn to_yaml(&self) -> Result<std::string::String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundedSequence, BoundedString, RmwMessage, Sequence};
+
+    use serde::Deserialize;
+    use std::borrow::Cow;
+
+    // A message with the kinds of members that need the serde support of this crate.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(default)]
+    struct TestMessage {
+        name: crate::String,
+        frame_id: BoundedString<8>,
+        values: Sequence<i32>,
+        limits: BoundedSequence<f64, 2>,
+        #[serde(with = "crate::serde_array")]
+        covariance: [f64; 36],
+    }
+
+    // Arrays with more than 32 elements don't implement Default.
+    impl Default for TestMessage {
+        fn default() -> Self {
+            Self {
+                name: Default::default(),
+                frame_id: Default::default(),
+                values: Default::default(),
+                limits: Default::default(),
+                covariance: [0.0; 36],
+            }
+        }
+    }
+
+    impl RmwMessage for TestMessage {
+        fn get_type_support() -> libc::uintptr_t {
+            0
+        }
+    }
+
+    impl Message for TestMessage {
+        type RmwMsg = Self;
+
+        fn into_rmw_message(msg_cow: Cow<'_, Self>) -> Cow<'_, Self::RmwMsg> {
+            msg_cow
+        }
+
+        fn from_rmw_message(msg: Self::RmwMsg) -> Self {
+            msg
+        }
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let mut covariance = [0.0; 36];
+        covariance[35] = 0.5;
+        let msg = TestMessage {
+            name: crate::String::from("robot"),
+            frame_id: BoundedString::try_from("base").unwrap(),
+            values: Sequence::from(vec![1, -2, 3]),
+            limits: BoundedSequence::try_from(vec![-1.5, 1.5]).unwrap(),
+            covariance,
+        };
+        let yaml = msg.to_yaml().unwrap();
+        assert_eq!(TestMessage::from_yaml(&yaml).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_from_yaml_uses_defaults_for_missing_fields() {
+        let msg = TestMessage::from_yaml("{name: robot, values: [1, 2]}").unwrap();
+        assert_eq!(
+            msg,
+            TestMessage {
+                name: crate::String::from("robot"),
+                values: Sequence::from(vec![1, 2]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_yaml_rejects_out_of_bounds_values() {
+        assert!(TestMessage::from_yaml("{frame_id: base_footprint}").is_err());
+        assert!(TestMessage::from_yaml("{limits: [1.0, 2.0, 3.0]}").is_err());
+        assert!(TestMessage::from_yaml("{covariance: [1.0, 2.0]}").is_err());
+    }
+}